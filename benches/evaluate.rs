@@ -46,12 +46,12 @@ fn bench_evaluate(c: &mut Criterion) {
 
     for &n in &[5, 20, 50] {
         let (ruleset, ctx) = build_ruleset(n);
-        group.bench_function(&format!("{n}_rules_context"), |b| {
+        group.bench_function(format!("{n}_rules_context"), |b| {
             b.iter(|| ruleset.evaluate(black_box(&ctx)));
         });
 
         let (ruleset, indexed) = build_ruleset_indexed(n);
-        group.bench_function(&format!("{n}_rules_indexed"), |b| {
+        group.bench_function(format!("{n}_rules_indexed"), |b| {
             b.iter(|| ruleset.evaluate_indexed(black_box(&indexed)));
         });
     }
@@ -65,7 +65,7 @@ fn bench_context_construction(c: &mut Criterion) {
     for &n in &[5, 20, 50] {
         let (ruleset, _) = build_ruleset(n);
 
-        group.bench_function(&format!("{n}_fields_indexed"), |b| {
+        group.bench_function(format!("{n}_fields_indexed"), |b| {
             b.iter(|| {
                 let mut cb = ruleset.context_builder();
                 for i in 0..n {
@@ -83,7 +83,7 @@ fn bench_compilation(c: &mut Criterion) {
     let mut group = c.benchmark_group("compilation");
 
     for &n in &[5, 20, 50] {
-        group.bench_function(&format!("{n}_rules"), |b| {
+        group.bench_function(format!("{n}_rules"), |b| {
             b.iter(|| {
                 let mut builder = RuleSetBuilder::new();
                 for i in 0..n {
@@ -0,0 +1,66 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ooroo::{field, rule_ref, Context, RuleSet, RuleSetBuilder, RuleSetView};
+
+fn build_ruleset(n: usize) -> RuleSet {
+    let mut builder = RuleSetBuilder::new();
+    for i in 0..n {
+        let field_name = format!("f{}", i % 100);
+        let tag = format!("t{i}");
+        builder = builder.rule(&format!("r{i}"), move |r| {
+            r.when(
+                field(&field_name)
+                    .gte(i64::try_from(i).unwrap())
+                    .and(field("user.tier").is_in(vec!["gold", "silver"]))
+                    .or(field("user.tags").contains(tag.as_str())),
+            )
+        });
+    }
+    let mut any_rule = rule_ref("r0");
+    for i in (1..n).step_by(n / 10) {
+        any_rule = any_rule.or(rule_ref(&format!("r{i}")));
+    }
+    builder
+        .rule("final", move |r| r.when(any_rule))
+        .terminal("final", 0)
+        .compile()
+        .unwrap()
+}
+
+fn bench_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load");
+    for n in [1_000, 50_000] {
+        let ruleset = build_ruleset(n);
+        let blob = ruleset.to_bytes(None).unwrap();
+        let view_bytes = ruleset.to_view_bytes();
+
+        group.bench_with_input(BenchmarkId::new("from_bytes", n), &blob, |b, blob| {
+            b.iter(|| RuleSet::from_bytes(blob).unwrap());
+        });
+        group.bench_with_input(
+            BenchmarkId::new("view_from_bytes", n),
+            &view_bytes,
+            |b, bytes| {
+                b.iter(|| RuleSetView::from_bytes(bytes).unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_view_evaluate(c: &mut Criterion) {
+    let ruleset = build_ruleset(1_000);
+    let view_bytes = ruleset.to_view_bytes();
+    let view = RuleSetView::from_bytes(&view_bytes).unwrap();
+    let ctx = Context::new()
+        .set("f1", 0_i64)
+        .set("user.tier", "bronze")
+        .set("user.tags", ooroo::Value::List(vec![]));
+
+    let mut group = c.benchmark_group("evaluate_1000");
+    group.bench_function("ruleset", |b| b.iter(|| ruleset.evaluate(&ctx)));
+    group.bench_function("view", |b| b.iter(|| view.evaluate(&ctx)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_load, bench_view_evaluate);
+criterion_main!(benches);
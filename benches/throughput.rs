@@ -44,7 +44,7 @@ fn bench_throughput(c: &mut Criterion) {
     for &threads in &thread_counts {
         let (ruleset, ctx) = build_shared_ruleset();
 
-        group.bench_function(&format!("{threads}_threads"), |b| {
+        group.bench_function(format!("{threads}_threads"), |b| {
             b.iter_custom(|iters| {
                 let per_thread = iters / threads as u64;
                 let handles: Vec<_> = (0..threads)
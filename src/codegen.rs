@@ -0,0 +1,1160 @@
+//! Ahead-of-time compilation of a ruleset into Rust source.
+//!
+//! [`Codegen`] emits a self-contained module: an input struct with one field
+//! per context field the rules read, and an `evaluate` function that runs the
+//! rules as straight-line Rust. Value semantics are shared with the
+//! interpreter through the hidden [`runtime`] module.
+
+#[doc(hidden)]
+pub mod runtime;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::types::{CompiledBound, CompiledExpr, CompiledRule, CompiledStep};
+use crate::{CompareOp, Context, FieldType, RuleSet, SetOp, ToDslError, Value};
+
+/// Number of sampled contexts the generated self-test checks.
+const TEST_SAMPLES: usize = 512;
+
+/// Generates Rust source evaluating a [`RuleSet`] without the interpreter,
+/// for use from a `build.rs`.
+///
+/// The generated module contains:
+///
+/// - `SOURCE`, the ruleset in DSL form;
+/// - an input struct (named `Input` by default) with one `Option` field per
+///   field the rules read, sorted by path. Fields declared `int`, `float`,
+///   `bool` or `string` in the schema are held natively; all others as a
+///   [`Value`];
+/// - `Input::from_context`, which returns `None` if a declared field holds a
+///   value of another type;
+/// - `evaluate(&Input) -> Option<&'static str>`, returning the terminal
+///   [`RuleSet::evaluate`] would return for the same context, without
+///   overrides;
+/// - unless disabled with [`tests`](Self::tests), a `#[cfg(test)]` module
+///   checking `evaluate` against the interpreter on
+///   [`sampled contexts`](RuleSet::sample_contexts).
+///
+/// `int` values given for a `float` field are converted to `f64` by
+/// `from_context`, so integers beyond 2<sup>53</sup> compared against
+/// integer literals may differ from the interpreter.
+///
+/// # Example
+///
+/// In `build.rs`:
+///
+/// ```no_run
+/// use ooroo::{Codegen, RuleSet};
+///
+/// let source = std::fs::read_to_string("rules/policy.ooroo").unwrap();
+/// let ruleset = RuleSet::from_dsl(&source).unwrap();
+/// let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("policy.rs");
+/// std::fs::write(out, Codegen::new(&ruleset).generate().unwrap()).unwrap();
+/// println!("cargo::rerun-if-changed=rules/policy.ooroo");
+/// ```
+///
+/// and in the crate:
+///
+/// ```ignore
+/// mod policy {
+///     include!(concat!(env!("OUT_DIR"), "/policy.rs"));
+/// }
+///
+/// let input = policy::Input { user_age: Some(30), ..Default::default() };
+/// assert_eq!(policy::evaluate(&input), Some("allow"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Codegen<'r> {
+    ruleset: &'r RuleSet,
+    struct_name: String,
+    crate_path: String,
+    tests: bool,
+}
+
+impl<'r> Codegen<'r> {
+    /// A generator for `ruleset` with the default options.
+    #[must_use]
+    pub fn new(ruleset: &'r RuleSet) -> Self {
+        Self {
+            ruleset,
+            struct_name: "Input".to_owned(),
+            crate_path: "::ooroo".to_owned(),
+            tests: true,
+        }
+    }
+
+    /// Name the input struct. Defaults to `Input`.
+    #[must_use]
+    pub fn struct_name(mut self, name: impl Into<String>) -> Self {
+        self.struct_name = name.into();
+        self
+    }
+
+    /// The path the generated code uses to reach this crate. Defaults to
+    /// `::ooroo`; set it when the crate is renamed or re-exported.
+    #[must_use]
+    pub fn crate_path(mut self, path: impl Into<String>) -> Self {
+        self.crate_path = path.into();
+        self
+    }
+
+    /// Whether to emit the `#[cfg(test)]` equivalence check. Defaults to `true`.
+    #[must_use]
+    pub fn tests(mut self, tests: bool) -> Self {
+        self.tests = tests;
+        self
+    }
+
+    /// Generate the module source. The output has no inner attributes, so it
+    /// can be brought in with `include!`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ToDslError`] if the ruleset cannot be written as the DSL
+    /// `SOURCE`; see [`RuleSet::to_dsl`].
+    pub fn generate(&self) -> Result<String, ToDslError> {
+        let fields = Field::all(self.ruleset);
+        let mut sorted: Vec<&Field> = fields.iter().flatten().collect();
+        sorted.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+        let mut out = String::new();
+        let krate = &self.crate_path;
+        let name = &self.struct_name;
+
+        out.push_str("// @generated by ooroo::Codegen. Do not edit.\n\n");
+        out.push_str("/// The ruleset this module was generated from.\n");
+        let _ = writeln!(
+            out,
+            "pub const SOURCE: &str = {:?};\n",
+            self.ruleset.to_dsl()?
+        );
+
+        out.push_str("/// The context fields read by the ruleset.\n");
+        out.push_str("#[derive(Debug, Clone, Default, PartialEq)]\n");
+        let _ = writeln!(out, "pub struct {name} {{");
+        for field in &sorted {
+            let _ = writeln!(out, "    /// `{}`", field.path);
+            let _ = writeln!(
+                out,
+                "    pub {}: Option<{}>,",
+                field.ident,
+                field.rust_type(krate)
+            );
+        }
+        out.push_str("}\n\n");
+
+        let _ = writeln!(out, "impl {name} {{");
+        out.push_str(
+            "    /// Read every field from `ctx`. Returns `None` if a field declared in\n",
+        );
+        out.push_str("    /// the schema holds a value of another type.\n");
+        out.push_str("    #[must_use]\n");
+        let _ = writeln!(
+            out,
+            "    pub fn from_context(ctx: &{krate}::Context) -> Option<Self> {{"
+        );
+        out.push_str("        Some(Self {\n");
+        for field in &sorted {
+            let read = format!("ctx.get({:?})", field.path);
+            let convert = match field.ty {
+                Some(FieldType::Int) => format!("{krate}::__codegen::int({read})"),
+                Some(FieldType::Float) => format!("{krate}::__codegen::float({read})"),
+                Some(FieldType::Bool) => format!("{krate}::__codegen::bool({read})"),
+                Some(FieldType::String) => format!("{krate}::__codegen::string({read})"),
+                Some(ty @ (FieldType::List | FieldType::Map)) => {
+                    format!("{krate}::__codegen::value({read}, Some({krate}::FieldType::{ty:?}))")
+                }
+                None => format!("{krate}::__codegen::value({read}, None)"),
+            };
+            let _ = writeln!(out, "            {}: {convert}?,", field.ident);
+        }
+        out.push_str("        })\n    }\n}\n\n");
+
+        let rules = self.rules_in_order();
+        let mut rule_vars = vec![String::new(); self.ruleset.rules.len()];
+        let mut taken = BTreeSet::new();
+        for rule in &rules {
+            rule_vars[rule.index] = unique(&mut taken, format!("r_{}", snake_ident(&rule.name)));
+        }
+        let mut emitter = Emitter {
+            fields: &fields,
+            rule_vars: &rule_vars,
+            generic: BTreeSet::new(),
+            literals: Vec::new(),
+            locals: HashMap::new(),
+            next_local: 0,
+        };
+        let mut body = String::new();
+        for rule in &rules {
+            emitter.locals.clear();
+            emitter.next_local = 0;
+            let condition = if rule.metadata.enabled {
+                emitter.expr(&rule.condition)
+            } else {
+                "false".to_owned()
+            };
+            let _ = writeln!(body, "    let {} = {condition};", rule_vars[rule.index]);
+        }
+        for (terminal, &idx) in self
+            .ruleset
+            .terminals
+            .iter()
+            .zip(&self.ruleset.terminal_indices)
+        {
+            let _ = writeln!(
+                body,
+                "    if {} {{\n        return Some({:?});\n    }}",
+                rule_vars[idx], terminal.rule_name
+            );
+        }
+        body.push_str("    None\n");
+
+        out.push_str("/// Evaluate the ruleset, returning the name of the highest-priority\n");
+        out.push_str("/// terminal that holds.\n");
+        out.push_str("#[must_use]\n");
+        out.push_str(
+            "#[allow(clippy::all, clippy::pedantic, unused_imports, unused_parens, \
+             unused_variables)]\n",
+        );
+        let _ = writeln!(
+            out,
+            "pub fn evaluate(input: &{name}) -> Option<&'static str> {{"
+        );
+        let _ = writeln!(out, "    use {krate}::__codegen as rt;");
+        let _ = writeln!(out, "    use {krate}::{{CompareOp, SetOp, Value}};");
+        if !emitter.literals.is_empty() {
+            let _ = writeln!(
+                out,
+                "    static LITERALS: ::std::sync::LazyLock<[Value; {}]> = \
+                 ::std::sync::LazyLock::new(|| {{\n        [",
+                emitter.literals.len()
+            );
+            for literal in &emitter.literals {
+                let _ = writeln!(out, "            {},", value_expr(literal));
+            }
+            out.push_str("        ]\n    });\n");
+        }
+        for field in sorted.iter().filter(|f| emitter.generic.contains(&f.slot)) {
+            let read = match field.ty {
+                Some(FieldType::Int) => ".map(Value::Int)",
+                Some(FieldType::Float) => ".map(Value::Float)",
+                Some(FieldType::Bool) => ".map(Value::Bool)",
+                _ => ".as_deref()",
+            };
+            let _ = writeln!(out, "    let f_{0} = input.{0}{read};", field.ident);
+        }
+        out.push_str(&body);
+        out.push_str("}\n");
+
+        if self.tests {
+            out.push('\n');
+            out.push_str("#[cfg(test)]\nmod generated_tests {\n");
+            out.push_str("    /// `evaluate` agrees with the interpreter on sampled contexts.\n");
+            out.push_str("    #[test]\n    fn evaluate_matches_interpreter() {\n");
+            let _ = writeln!(
+                out,
+                "        let ruleset = {krate}::RuleSet::from_dsl(super::SOURCE).unwrap();"
+            );
+            let _ = writeln!(
+                out,
+                "        for ctx in ruleset.sample_contexts({TEST_SAMPLES}, 0) {{"
+            );
+            let _ = writeln!(
+                out,
+                "            let input = super::{name}::from_context(&ctx).unwrap();"
+            );
+            out.push_str(
+                "            let expected = ruleset.evaluate(&ctx);\n            \
+                 assert_eq!(\n                super::evaluate(&input),\n                \
+                 expected.as_ref().map(|v| v.terminal()),\n                \
+                 \"{ctx:?}\"\n            );\n        }\n    }\n}\n",
+            );
+        }
+        Ok(out)
+    }
+}
+
+impl Codegen<'_> {
+    /// The rules in dependency order, ties broken by name, so the output
+    /// does not depend on how the ruleset was compiled.
+    fn rules_in_order(&self) -> Vec<&CompiledRule> {
+        let by_name: HashMap<&str, &CompiledRule> = self
+            .ruleset
+            .rules
+            .iter()
+            .map(|rule| (rule.name.as_str(), rule))
+            .collect();
+        crate::decompile::decompile(self.ruleset)
+            .iter()
+            .map(|rule| by_name[rule.name.as_str()])
+            .collect()
+    }
+}
+
+/// A context field of the generated input struct.
+#[derive(Debug)]
+struct Field {
+    slot: usize,
+    path: String,
+    ident: String,
+    /// The declared type, if any.
+    ty: Option<FieldType>,
+}
+
+impl Field {
+    /// The fields of `ruleset` indexed by slot, with identifiers assigned in
+    /// path order.
+    fn all(ruleset: &RuleSet) -> Vec<Option<Field>> {
+        let mut paths: Vec<(&str, usize)> = ruleset
+            .field_registry
+            .iter()
+            .map(|(path, &index)| (path, index))
+            .collect();
+        paths.sort_unstable();
+        let mut fields: Vec<Option<Field>> =
+            (0..ruleset.field_registry.len()).map(|_| None).collect();
+        let mut taken = BTreeSet::new();
+        for (path, index) in paths {
+            let ident = unique(&mut taken, snake_ident(path));
+            let ty = ruleset
+                .schema
+                .as_ref()
+                .and_then(|schema| schema.resolve(path))
+                .flatten();
+            fields[index] = Some(Field {
+                slot: index,
+                path: path.to_owned(),
+                ident,
+                ty,
+            });
+        }
+        fields
+    }
+
+    /// Whether the field is held natively rather than as a [`Value`].
+    fn native(&self) -> Option<FieldType> {
+        self.ty.filter(|ty| {
+            matches!(
+                ty,
+                FieldType::Int | FieldType::Float | FieldType::Bool | FieldType::String
+            )
+        })
+    }
+
+    fn rust_type(&self, krate: &str) -> String {
+        match self.native() {
+            Some(FieldType::Int) => "i64".to_owned(),
+            Some(FieldType::Float) => "f64".to_owned(),
+            Some(FieldType::Bool) => "bool".to_owned(),
+            Some(FieldType::String) => "String".to_owned(),
+            _ => format!("{krate}::Value"),
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// A snake-case Rust identifier for a field path or rule name: `user.age`
+/// becomes `user_age`. Keywords get a trailing underscore.
+fn snake_ident(name: &str) -> String {
+    let mut ident = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            ident.push(c.to_ascii_lowercase());
+        } else if !ident.ends_with('_') {
+            ident.push('_');
+        }
+    }
+    let ident = ident.trim_matches('_');
+    if ident.is_empty() {
+        "field".to_owned()
+    } else if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("f{ident}")
+    } else if KEYWORDS.contains(&ident) {
+        format!("{ident}_")
+    } else {
+        ident.to_owned()
+    }
+}
+
+/// `ident`, numbered if already in `taken`.
+fn unique(taken: &mut BTreeSet<String>, ident: String) -> String {
+    let mut candidate = ident.clone();
+    let mut n = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{ident}_{n}");
+        n += 1;
+    }
+    candidate
+}
+
+/// Writes compiled expressions as Rust expressions over `input`.
+///
+/// Slots are read generically as runtime operands: fields held as values by
+/// reference, native strings borrowed with `as_deref`, other natively held
+/// fields through a `Value` copy made once per call (recorded in `generic`),
+/// and quantifier and lookup locals as `s0`, `s1`, ... numbered within each
+/// rule. Comparisons, `LIKE` and null
+/// checks of natively held fields are written directly.
+struct Emitter<'f> {
+    fields: &'f [Option<Field>],
+    rule_vars: &'f [String],
+    generic: BTreeSet<usize>,
+    /// Literals too costly to build per call, held in a `LITERALS` static.
+    literals: Vec<Value>,
+    /// Local slot to its number in the current rule.
+    locals: HashMap<usize, usize>,
+    next_local: usize,
+}
+
+impl<'f> Emitter<'f> {
+    fn field(&self, index: usize) -> Option<&'f Field> {
+        self.fields.get(index).and_then(Option::as_ref)
+    }
+
+    /// The slot as an `Option<&Value>` expression, or `Option<&str>` for a
+    /// native string.
+    fn slot(&mut self, index: usize) -> String {
+        match self.field(index) {
+            Some(field) if field.native() == Some(FieldType::String) => {
+                self.generic.insert(index);
+                format!("f_{}", field.ident)
+            }
+            Some(field) if field.native().is_some() => {
+                self.generic.insert(index);
+                format!("f_{}.as_ref()", field.ident)
+            }
+            Some(field) => format!("input.{}.as_ref()", field.ident),
+            None => format!("s{}", self.locals[&index]),
+        }
+    }
+
+    /// Number a local slot about to be bound.
+    fn bind(&mut self, slot: usize) -> usize {
+        let n = self.next_local;
+        self.next_local += 1;
+        self.locals.insert(slot, n);
+        n
+    }
+
+    /// A literal as an `Option<&Value>` expression.
+    fn literal(&mut self, value: &Value) -> String {
+        match value {
+            Value::Int(_) | Value::Float(_) | Value::Bool(_) => {
+                format!("Some(&{})", value_expr(value))
+            }
+            _ => {
+                let index = match self.literals.iter().position(|v| v == value) {
+                    Some(index) => index,
+                    None => {
+                        self.literals.push(value.clone());
+                        self.literals.len() - 1
+                    }
+                };
+                format!("Some(&LITERALS[{index}])")
+            }
+        }
+    }
+
+    fn bound(&mut self, bound: &CompiledBound) -> String {
+        match bound {
+            CompiledBound::Literal(value) => self.literal(value),
+            CompiledBound::FieldIndex(index) => self.slot(*index),
+        }
+    }
+
+    fn expr(&mut self, expr: &CompiledExpr) -> String {
+        match expr {
+            CompiledExpr::And(..) => self.chain(expr, " && "),
+            CompiledExpr::Or(..) => self.chain(expr, " || "),
+            CompiledExpr::Not(inner) => format!("!({})", self.expr(inner)),
+            CompiledExpr::RuleRef(index) => self.rule_vars[*index].clone(),
+            CompiledExpr::AtLeast { n, exprs } => {
+                if *n == 0 {
+                    return "true".to_owned();
+                }
+                if exprs.is_empty() {
+                    return "false".to_owned();
+                }
+                let terms: Vec<String> = exprs
+                    .iter()
+                    .map(|e| format!("usize::from({})", self.expr(e)))
+                    .collect();
+                format!("({} >= {n})", terms.join(" + "))
+            }
+            CompiledExpr::Any {
+                list_index,
+                bindings,
+                predicate,
+            } => self.quantifier("any", *list_index, bindings, predicate),
+            CompiledExpr::All {
+                list_index,
+                bindings,
+                predicate,
+            } => self.quantifier("all", *list_index, bindings, predicate),
+            CompiledExpr::Lookup { lookups, body } => {
+                let mut out = String::from("{ ");
+                let mut bound = Vec::new();
+                for lookup in lookups {
+                    let steps: Vec<String> = lookup
+                        .steps
+                        .iter()
+                        .map(|step| match step {
+                            CompiledStep::Key(key) => format!("rt::Step::Key({key:?})"),
+                            CompiledStep::Index(index) => format!("rt::Step::Index({index})"),
+                            CompiledStep::Dynamic(slot) => {
+                                format!("rt::dynamic({})", self.slot(*slot))
+                            }
+                            CompiledStep::Wildcard => "rt::Step::Wildcard".to_owned(),
+                        })
+                        .collect();
+                    let base = self.slot(lookup.base);
+                    let n = self.bind(lookup.slot);
+                    let _ = write!(
+                        out,
+                        "let l{n} = rt::lookup({base}, &[{}]); ",
+                        steps.join(", ")
+                    );
+                    bound.push(n);
+                }
+                for n in bound {
+                    let _ = write!(out, "let s{n} = l{n}.as_deref(); ");
+                }
+                let _ = write!(out, "{} }}", self.expr(body));
+                out
+            }
+            leaf => self.leaf(leaf),
+        }
+    }
+
+    /// A flattened `&&` or `||` chain.
+    fn chain(&mut self, expr: &CompiledExpr, op: &str) -> String {
+        let mut operands = Vec::new();
+        collect_chain(expr, op == " && ", &mut operands);
+        let operands: Vec<String> = operands.into_iter().map(|e| self.expr(e)).collect();
+        format!("({})", operands.join(op))
+    }
+
+    fn quantifier(
+        &mut self,
+        method: &str,
+        list_index: usize,
+        bindings: &[(usize, Vec<String>)],
+        predicate: &CompiledExpr,
+    ) -> String {
+        let list = self.slot(list_index);
+        let mut out =
+            format!("rt::list({list}).is_some_and(|items| items.iter().{method}(|item| {{ ");
+        for (slot, path) in bindings {
+            let path: Vec<String> = path.iter().map(|key| format!("{key:?}")).collect();
+            let n = self.bind(*slot);
+            let _ = write!(
+                out,
+                "let s{n} = rt::element(item, &[{}]); ",
+                path.join(", ")
+            );
+        }
+        let _ = write!(out, "{} }}))", self.expr(predicate));
+        out
+    }
+
+    fn leaf(&mut self, expr: &CompiledExpr) -> String {
+        if let Some(native) = self.native_leaf(expr) {
+            return native;
+        }
+        match expr {
+            CompiledExpr::Compare {
+                field_index,
+                op,
+                value,
+            } => {
+                let field = self.slot(*field_index);
+                let value = self.literal(value);
+                format!("rt::compare({field}, CompareOp::{op:?}, {value})")
+            }
+            CompiledExpr::CompareFields {
+                left_index,
+                op,
+                right_index,
+            } => {
+                let left = self.slot(*left_index);
+                let right = self.slot(*right_index);
+                format!("rt::compare({left}, CompareOp::{op:?}, {right})")
+            }
+            CompiledExpr::In {
+                field_index,
+                members,
+            }
+            | CompiledExpr::NotIn {
+                field_index,
+                members,
+            } => {
+                let function = if matches!(expr, CompiledExpr::In { .. }) {
+                    "is_in"
+                } else {
+                    "not_in"
+                };
+                let field = self.slot(*field_index);
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|m| format!("{}.into()", self.bound(m)))
+                    .collect();
+                format!("rt::{function}({field}, &[{}])", members.join(", "))
+            }
+            CompiledExpr::Between {
+                field_index,
+                low,
+                high,
+            } => {
+                let field = self.slot(*field_index);
+                let low = self.bound(low);
+                let high = self.bound(high);
+                format!("rt::between({field}, {low}, {high})")
+            }
+            CompiledExpr::Like {
+                field_index,
+                pattern,
+            } => format!("rt::like({}, {pattern:?})", self.slot(*field_index)),
+            CompiledExpr::NotLike {
+                field_index,
+                pattern,
+            } => format!("rt::not_like({}, {pattern:?})", self.slot(*field_index)),
+            CompiledExpr::IsNull(index) => format!("{}.is_none()", self.slot(*index)),
+            CompiledExpr::IsNotNull(index) => format!("{}.is_some()", self.slot(*index)),
+            CompiledExpr::Contains { field_index, value } => {
+                let field = self.slot(*field_index);
+                format!("rt::contains({field}, {})", self.bound(value))
+            }
+            CompiledExpr::SetCompare {
+                field_index,
+                op,
+                value,
+            } => {
+                let field = self.slot(*field_index);
+                let op = set_op(*op);
+                format!(
+                    "rt::set_compare({field}, SetOp::{op}, {})",
+                    self.bound(value)
+                )
+            }
+            CompiledExpr::Len {
+                field_index,
+                op,
+                value,
+            } => {
+                let field = self.slot(*field_index);
+                format!("rt::len({field}, CompareOp::{op:?}, {})", self.bound(value))
+            }
+            CompiledExpr::HasKey { field_index, key } => {
+                let field = self.slot(*field_index);
+                format!("rt::has_key({field}, {})", self.bound(key))
+            }
+            _ => unreachable!("not a leaf expression"),
+        }
+    }
+
+    /// Leaves over a natively held field written without going through
+    /// [`Value`]: comparisons with a literal, `LIKE` and null checks.
+    fn native_leaf(&self, expr: &CompiledExpr) -> Option<String> {
+        let index = match expr {
+            CompiledExpr::Compare { field_index, .. }
+            | CompiledExpr::Like { field_index, .. }
+            | CompiledExpr::NotLike { field_index, .. }
+            | CompiledExpr::IsNull(field_index)
+            | CompiledExpr::IsNotNull(field_index) => *field_index,
+            _ => return None,
+        };
+        let field = self.field(index)?;
+        let ty = field.native()?;
+        let access = format!("input.{}", field.ident);
+        Some(match expr {
+            CompiledExpr::IsNull(_) => format!("{access}.is_none()"),
+            CompiledExpr::IsNotNull(_) => format!("{access}.is_some()"),
+            CompiledExpr::Like { pattern, .. } | CompiledExpr::NotLike { pattern, .. } => {
+                if ty != FieldType::String {
+                    return Some("false".to_owned());
+                }
+                let negate = if matches!(expr, CompiledExpr::NotLike { .. }) {
+                    "!"
+                } else {
+                    ""
+                };
+                format!("matches!(&{access}, Some(v) if {negate}rt::like_str(v, {pattern:?}))")
+            }
+            CompiledExpr::Compare { op, value, .. } => native_compare(&access, ty, *op, value),
+            _ => unreachable!(),
+        })
+    }
+}
+
+/// Compare a natively held field with a literal, following
+/// [`Value::compare`]: numbers compare as `f64` when either side is a float,
+/// and mismatched types never compare.
+fn native_compare(access: &str, ty: FieldType, op: CompareOp, value: &Value) -> String {
+    let token = match op {
+        CompareOp::Eq => "==",
+        CompareOp::Neq => "!=",
+        CompareOp::Gt => ">",
+        CompareOp::Gte => ">=",
+        CompareOp::Lt => "<",
+        CompareOp::Lte => "<=",
+    };
+    let float = |left: &str, right: f64| {
+        format!(
+            "matches!({access}, Some(v) if rt::holds({left}.partial_cmp(&{}), CompareOp::{op:?}))",
+            float_expr(right)
+        )
+    };
+    match (ty, value) {
+        (FieldType::Int, Value::Int(l)) => {
+            format!("matches!({access}, Some(v) if v {token} {})", int_expr(*l))
+        }
+        (FieldType::Int, Value::Float(l)) => float("(v as f64)", *l),
+        (FieldType::Float, Value::Int(l)) => float("v", *l as f64),
+        (FieldType::Float, Value::Float(l)) => float("v", *l),
+        (FieldType::Bool, Value::Bool(l)) => {
+            format!("matches!({access}, Some(v) if v {token} {l})")
+        }
+        (FieldType::String, Value::String(l)) => {
+            format!("matches!(&{access}, Some(v) if v.as_str() {token} {l:?})")
+        }
+        _ => "false".to_owned(),
+    }
+}
+
+/// Flatten nested `And` (or `Or`) nodes into their operands.
+fn collect_chain<'e>(expr: &'e CompiledExpr, and: bool, out: &mut Vec<&'e CompiledExpr>) {
+    match expr {
+        CompiledExpr::And(a, b) if and => {
+            collect_chain(a, and, out);
+            collect_chain(b, and, out);
+        }
+        CompiledExpr::Or(a, b) if !and => {
+            collect_chain(a, and, out);
+            collect_chain(b, and, out);
+        }
+        _ => out.push(expr),
+    }
+}
+
+fn set_op(op: SetOp) -> &'static str {
+    match op {
+        SetOp::Overlaps => "Overlaps",
+        SetOp::SubsetOf => "SubsetOf",
+        SetOp::SupersetOf => "SupersetOf",
+        SetOp::DisjointFrom => "DisjointFrom",
+    }
+}
+
+fn int_expr(i: i64) -> String {
+    if i == i64::MIN {
+        "i64::MIN".to_owned()
+    } else {
+        i.to_string()
+    }
+}
+
+/// An `f64` expression for exactly `f`.
+fn float_expr(f: f64) -> String {
+    if f.is_nan() {
+        "f64::NAN".to_owned()
+    } else if f.is_infinite() {
+        if f > 0.0 {
+            "f64::INFINITY"
+        } else {
+            "f64::NEG_INFINITY"
+        }
+        .to_owned()
+    } else {
+        format!("{f:?}_f64")
+    }
+}
+
+/// A Rust expression constructing `value`.
+fn value_expr(value: &Value) -> String {
+    match value {
+        Value::Int(i) => format!("Value::Int({})", int_expr(*i)),
+        Value::Float(f) => format!("Value::Float({})", float_expr(*f)),
+        Value::Bool(b) => format!("Value::Bool({b})"),
+        Value::String(s) => format!("Value::String({s:?}.to_owned())"),
+        Value::List(items) => {
+            let items: Vec<String> = items.iter().map(value_expr).collect();
+            format!("Value::List(vec![{}])", items.join(", "))
+        }
+        Value::Map(entries) if entries.is_empty() => {
+            "Value::Map(::std::collections::BTreeMap::new())".to_owned()
+        }
+        Value::Map(entries) => {
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|(k, v)| format!("({k:?}.to_owned(), {})", value_expr(v)))
+                .collect();
+            format!(
+                "Value::Map(::std::collections::BTreeMap::from([{}]))",
+                entries.join(", ")
+            )
+        }
+    }
+}
+
+impl RuleSet {
+    /// Generate `count` contexts for exercising the ruleset, deterministically
+    /// from `seed`.
+    ///
+    /// Values are drawn from the literals the rules test against and their
+    /// neighbours, so most rules come out both ways; lists and maps are built
+    /// from the same values and the keys the rules look up. Fields are
+    /// sometimes missing, and fields declared in the schema always hold the
+    /// declared type. Used by the test [`Codegen`] emits.
+    ///
+    /// # Example
+    /// ```
+    /// use ooroo::RuleSet;
+    ///
+    /// let ruleset = RuleSet::from_dsl("rule adult (priority 0):\n    user.age >= 18").unwrap();
+    /// let contexts = ruleset.sample_contexts(100, 7);
+    /// assert_eq!(contexts.len(), 100);
+    /// assert!(contexts.iter().any(|ctx| ruleset.evaluate(ctx).is_some()));
+    /// assert!(contexts.iter().any(|ctx| ruleset.evaluate(ctx).is_none()));
+    /// ```
+    #[must_use]
+    pub fn sample_contexts(&self, count: usize, seed: u64) -> Vec<Context> {
+        let mut rules: Vec<&CompiledRule> = self.rules.iter().collect();
+        rules.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        let mut pool = Pool::default();
+        for rule in rules {
+            pool.visit(&rule.condition);
+        }
+        pool.finish();
+        let mut paths: Vec<&str> = self.field_registry.iter().map(|(p, _)| p).collect();
+        paths.sort_unstable();
+        let types: HashMap<&str, FieldType> = paths
+            .iter()
+            .filter_map(|&path| {
+                let ty = self.schema.as_ref()?.resolve(path)??;
+                Some((path, ty))
+            })
+            .collect();
+        let shapes: HashMap<&str, FieldType> = self
+            .field_registry
+            .iter()
+            .filter_map(|(path, index)| Some((path, *pool.shapes.get(index)?)))
+            .collect();
+
+        let mut rng = Rng::new(seed);
+        (0..count)
+            .map(|_| {
+                let mut ctx = Context::new();
+                // Ancestors sort first, so a field set under another replaces it
+                // rather than being hidden by it.
+                for &path in &paths {
+                    if rng.below(100) < 15 {
+                        continue;
+                    }
+                    // Undeclared fields mostly take the shape the rules use them in.
+                    let ty = types
+                        .get(path)
+                        .copied()
+                        .or_else(|| shapes.get(path).copied().filter(|_| rng.below(4) > 0));
+                    ctx.insert(path, pool.sample(&mut rng, ty, 0));
+                }
+                ctx
+            })
+            .collect()
+    }
+}
+
+/// Values and keys a ruleset tests against, for [`RuleSet::sample_contexts`].
+#[derive(Default)]
+struct Pool {
+    scalars: Vec<Value>,
+    lists: Vec<Value>,
+    keys: BTreeSet<String>,
+    lens: BTreeSet<usize>,
+    /// Slots the rules use as a list or map, such as quantified lists.
+    shapes: HashMap<usize, FieldType>,
+}
+
+/// Longest list `sample_contexts` generates.
+const MAX_SAMPLE_LEN: usize = 6;
+
+impl Pool {
+    fn visit(&mut self, expr: &CompiledExpr) {
+        match expr {
+            CompiledExpr::Compare { value, .. } => self.literal(value),
+            CompiledExpr::And(a, b) | CompiledExpr::Or(a, b) => {
+                self.visit(a);
+                self.visit(b);
+            }
+            CompiledExpr::Not(inner) => self.visit(inner),
+            CompiledExpr::In { members, .. } | CompiledExpr::NotIn { members, .. } => {
+                members.iter().for_each(|m| self.bound(m));
+            }
+            CompiledExpr::Between { low, high, .. } => {
+                self.bound(low);
+                self.bound(high);
+            }
+            CompiledExpr::Like { pattern, .. } | CompiledExpr::NotLike { pattern, .. } => {
+                self.scalars
+                    .push(Value::String(pattern.replace('%', "").replace('_', "x")));
+                self.scalars
+                    .push(Value::String(pattern.replace('%', "zz").replace('_', "y")));
+            }
+            CompiledExpr::AtLeast { exprs, .. } => exprs.iter().for_each(|e| self.visit(e)),
+            CompiledExpr::Any {
+                list_index,
+                bindings,
+                predicate,
+            }
+            | CompiledExpr::All {
+                list_index,
+                bindings,
+                predicate,
+            } => {
+                self.shapes.insert(*list_index, FieldType::List);
+                for (_, path) in bindings {
+                    self.keys.extend(path.iter().cloned());
+                }
+                self.visit(predicate);
+            }
+            CompiledExpr::Contains { field_index, value }
+            | CompiledExpr::SetCompare {
+                field_index, value, ..
+            } => {
+                self.shapes.insert(*field_index, FieldType::List);
+                self.bound(value);
+            }
+            CompiledExpr::HasKey { field_index, key } => {
+                self.shapes.insert(*field_index, FieldType::Map);
+                self.bound(key);
+            }
+            CompiledExpr::Len {
+                field_index, value, ..
+            } => {
+                self.shapes.insert(*field_index, FieldType::List);
+                if let CompiledBound::Literal(Value::Int(n)) = value {
+                    for n in [n.saturating_sub(1), *n, n.saturating_add(1)] {
+                        if let Ok(n) = usize::try_from(n) {
+                            self.lens.insert(n);
+                        }
+                    }
+                }
+                self.bound(value);
+            }
+            CompiledExpr::Lookup { lookups, body } => {
+                for lookup in lookups {
+                    let shape = match lookup.steps.first() {
+                        Some(CompiledStep::Key(_) | CompiledStep::Dynamic(_)) => FieldType::Map,
+                        _ => FieldType::List,
+                    };
+                    self.shapes.insert(lookup.base, shape);
+                }
+                for step in lookups.iter().flat_map(|l| &l.steps) {
+                    match step {
+                        CompiledStep::Key(key) => {
+                            self.keys.insert(key.clone());
+                        }
+                        CompiledStep::Index(index) => {
+                            if let Ok(n) = usize::try_from(index.unsigned_abs()) {
+                                self.lens.insert(n);
+                                self.lens.insert(n.saturating_add(1));
+                            }
+                        }
+                        CompiledStep::Dynamic(_) | CompiledStep::Wildcard => {}
+                    }
+                }
+                self.visit(body);
+            }
+            CompiledExpr::RuleRef(_)
+            | CompiledExpr::IsNull(_)
+            | CompiledExpr::IsNotNull(_)
+            | CompiledExpr::CompareFields { .. } => {}
+        }
+    }
+
+    fn bound(&mut self, bound: &CompiledBound) {
+        if let CompiledBound::Literal(value) = bound {
+            self.literal(value);
+        }
+    }
+
+    /// Add a literal and values either side of it.
+    fn literal(&mut self, value: &Value) {
+        match value {
+            Value::Int(i) => self
+                .scalars
+                .extend([i.saturating_sub(1), *i, i.saturating_add(1)].map(Value::Int)),
+            Value::Float(f) => {
+                self.scalars.push(Value::Float(*f));
+                if f.is_finite() {
+                    self.scalars.push(Value::Float(f - 0.5));
+                    self.scalars.push(Value::Float(f + 0.5));
+                }
+            }
+            Value::Bool(_) => {}
+            Value::String(s) => {
+                self.scalars.push(value.clone());
+                self.scalars.push(Value::String(format!("{s}x")));
+                self.keys.insert(s.clone());
+            }
+            Value::List(items) => {
+                self.lists.push(value.clone());
+                items.iter().for_each(|item| self.literal(item));
+            }
+            Value::Map(entries) => {
+                self.keys.extend(entries.keys().cloned());
+                entries.values().for_each(|v| self.literal(v));
+            }
+        }
+    }
+
+    /// Add the defaults every pool has and drop duplicates.
+    fn finish(&mut self) {
+        self.scalars.extend([
+            Value::Int(0),
+            Value::Float(0.5),
+            Value::Bool(true),
+            Value::Bool(false),
+            Value::String(String::new()),
+        ]);
+        let mut seen = Vec::new();
+        self.scalars.retain(|v| {
+            let new = !seen.contains(v);
+            if new {
+                seen.push(v.clone());
+            }
+            new
+        });
+        self.keys.insert("key".to_owned());
+        self.lens.extend(0..=3);
+        self.lens.retain(|&n| n <= MAX_SAMPLE_LEN);
+    }
+
+    /// A value of type `ty`, or of any type.
+    fn sample(&self, rng: &mut Rng, ty: Option<FieldType>, depth: usize) -> Value {
+        let ty = ty.unwrap_or_else(|| match rng.below(10) {
+            _ if depth >= 2 => FieldType::of(rng.pick(&self.scalars)),
+            0 | 1 => FieldType::List,
+            2 | 3 => FieldType::Map,
+            _ => FieldType::of(rng.pick(&self.scalars)),
+        });
+        match ty {
+            FieldType::List if !self.lists.is_empty() && rng.below(3) == 0 => {
+                rng.pick(&self.lists).clone()
+            }
+            FieldType::List => {
+                let lens: Vec<usize> = self.lens.iter().copied().collect();
+                let len = *rng.pick(&lens);
+                Value::List(
+                    (0..len)
+                        .map(|_| self.sample(rng, None, depth + 1))
+                        .collect(),
+                )
+            }
+            FieldType::Map => {
+                let keys: Vec<&String> = self.keys.iter().collect();
+                let entries: BTreeMap<String, Value> = (0..=rng.below(3))
+                    .map(|_| {
+                        let key = (*rng.pick(&keys)).clone();
+                        (key, self.sample(rng, None, depth + 1))
+                    })
+                    .collect();
+                Value::Map(entries)
+            }
+            scalar => {
+                let candidates: Vec<&Value> =
+                    self.scalars.iter().filter(|v| scalar.accepts(v)).collect();
+                (*rng.pick(&candidates)).clone()
+            }
+        }
+    }
+}
+
+/// A small xorshift generator; sampling needs to be reproducible, not strong.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed ^ 0x9E37_79B9_7F4A_7C15 | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_idents_are_snake_case() {
+        assert_eq!(snake_ident("user.age"), "user_age");
+        assert_eq!(snake_ident("User.firstName"), "user_firstname");
+        assert_eq!(snake_ident("a..b--c"), "a_b_c");
+    }
+
+    #[test]
+    fn field_idents_avoid_keywords_and_digits() {
+        assert_eq!(snake_ident("type"), "type_");
+        assert_eq!(snake_ident("self"), "self_");
+        assert_eq!(snake_ident("2fa.enabled"), "f2fa_enabled");
+        assert_eq!(snake_ident("_"), "field");
+    }
+
+    #[test]
+    fn colliding_idents_are_numbered() {
+        let ruleset = RuleSet::from_dsl(
+            "rule a (priority 0):\n    user.age > 1 AND user_age > 2 AND user.age_ > 3",
+        )
+        .unwrap();
+        let idents: Vec<String> = Field::all(&ruleset)
+            .into_iter()
+            .flatten()
+            .map(|f| f.ident)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        assert_eq!(idents, ["user_age", "user_age_2", "user_age_3"]);
+    }
+
+    #[test]
+    fn float_literals_are_exact() {
+        assert_eq!(float_expr(0.1), "0.1_f64");
+        assert_eq!(float_expr(1e300), "1e300_f64");
+        assert_eq!(float_expr(f64::NEG_INFINITY), "f64::NEG_INFINITY");
+        assert_eq!(int_expr(i64::MIN), "i64::MIN");
+    }
+
+    #[test]
+    fn sampling_is_deterministic() {
+        let ruleset = RuleSet::from_dsl("rule a (priority 0):\n    x > 1").unwrap();
+        let render = |seed| {
+            ruleset
+                .sample_contexts(20, seed)
+                .iter()
+                .map(|ctx| format!("{:?}", ctx.get("x")))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(render(3), render(3));
+        assert_ne!(render(3), render(4));
+    }
+}
@@ -0,0 +1,273 @@
+//! Support functions called by code emitted by [`Codegen`](crate::Codegen).
+//!
+//! Generated modules go through these instead of restating the value
+//! semantics, so every test evaluates exactly as it does in the interpreter.
+//! Each test function returns the two-valued result: `false` whenever the
+//! interpreter's leaf would be unknown. Not a stable API; only generated
+//! code should call it.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use crate::evaluate;
+use crate::types::value::like_match;
+use crate::{CompareOp, FieldType, SetOp, Value};
+
+/// A value read by generated code, borrowed: a context value, or a string
+/// field held natively. Built from `Option<&Value>` or `Option<&str>`.
+#[derive(Debug, Clone, Copy)]
+pub enum Operand<'a> {
+    /// The field is missing.
+    Missing,
+    /// A value held as a [`Value`].
+    Value(&'a Value),
+    /// A string field held natively.
+    Str(&'a str),
+}
+
+impl<'a> From<Option<&'a Value>> for Operand<'a> {
+    fn from(value: Option<&'a Value>) -> Self {
+        value.map_or(Self::Missing, Self::Value)
+    }
+}
+
+impl<'a> From<Option<&'a str>> for Operand<'a> {
+    fn from(value: Option<&'a str>) -> Self {
+        value.map_or(Self::Missing, Self::Str)
+    }
+}
+
+impl<'a> Operand<'a> {
+    fn value(self) -> Option<&'a Value> {
+        match self {
+            Self::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn str(self) -> Option<&'a str> {
+        match self {
+            Self::Value(Value::String(s)) => Some(s),
+            Self::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// [`Value::compare`], with native strings comparing as strings do.
+    fn compare(self, op: CompareOp, other: Self) -> Option<bool> {
+        match (self, other) {
+            (Self::Value(value), Self::Value(other)) => value.compare(op, other),
+            _ => Some(op.holds(self.str()?.cmp(other.str()?))),
+        }
+    }
+}
+
+/// Whether `op` holds for operands ordered as `ord`; `false` if unordered.
+#[must_use]
+pub fn holds(ord: Option<Ordering>, op: CompareOp) -> bool {
+    ord.is_some_and(|ord| op.holds(ord))
+}
+
+/// `value op other`.
+#[must_use]
+pub fn compare<'a>(
+    value: impl Into<Operand<'a>>,
+    op: CompareOp,
+    other: impl Into<Operand<'a>>,
+) -> bool {
+    value.into().compare(op, other.into()) == Some(true)
+}
+
+/// Whether `value` equals `member`, or an element of it if it is a list.
+fn member_matches(value: Operand<'_>, member: Operand<'_>) -> bool {
+    match member {
+        Operand::Value(Value::List(items)) => items
+            .iter()
+            .any(|item| value.compare(CompareOp::Eq, Operand::Value(item)) == Some(true)),
+        Operand::Missing => false,
+        member => value.compare(CompareOp::Eq, member) == Some(true),
+    }
+}
+
+/// `value IN (members...)`.
+#[must_use]
+pub fn is_in<'a>(value: impl Into<Operand<'a>>, members: &[Operand<'_>]) -> bool {
+    let value = value.into();
+    !matches!(value, Operand::Missing) && members.iter().any(|m| member_matches(value, *m))
+}
+
+/// `value NOT IN (members...)`.
+#[must_use]
+pub fn not_in<'a>(value: impl Into<Operand<'a>>, members: &[Operand<'_>]) -> bool {
+    let value = value.into();
+    !matches!(value, Operand::Missing) && !members.iter().any(|m| member_matches(value, *m))
+}
+
+/// `value BETWEEN low AND high`.
+#[must_use]
+pub fn between<'a>(
+    value: impl Into<Operand<'a>>,
+    low: impl Into<Operand<'a>>,
+    high: impl Into<Operand<'a>>,
+) -> bool {
+    let value = value.into();
+    value.compare(CompareOp::Gte, low.into()) == Some(true)
+        && value.compare(CompareOp::Lte, high.into()) == Some(true)
+}
+
+/// `value LIKE pattern`.
+#[must_use]
+pub fn like<'a>(value: impl Into<Operand<'a>>, pattern: &str) -> bool {
+    value.into().str().is_some_and(|s| like_match(s, pattern))
+}
+
+/// `value NOT LIKE pattern`.
+#[must_use]
+pub fn not_like<'a>(value: impl Into<Operand<'a>>, pattern: &str) -> bool {
+    value.into().str().is_some_and(|s| !like_match(s, pattern))
+}
+
+/// `LIKE` on a string field held natively.
+#[must_use]
+pub fn like_str(value: &str, pattern: &str) -> bool {
+    like_match(value, pattern)
+}
+
+/// `value CONTAINS item`.
+#[must_use]
+pub fn contains<'a>(value: impl Into<Operand<'a>>, item: impl Into<Operand<'a>>) -> bool {
+    let item = item.into();
+    match value.into() {
+        Operand::Value(Value::List(items)) => items
+            .iter()
+            .any(|v| Operand::Value(v).compare(CompareOp::Eq, item) == Some(true)),
+        _ => false,
+    }
+}
+
+/// `value op other` for a set relation.
+#[must_use]
+pub fn set_compare<'a>(
+    value: impl Into<Operand<'a>>,
+    op: SetOp,
+    other: impl Into<Operand<'a>>,
+) -> bool {
+    match (value.into().value(), other.into().value()) {
+        (Some(value), Some(other)) => value.set_compare(op, other) == Some(true),
+        _ => false,
+    }
+}
+
+/// `len(value) op n`.
+#[must_use]
+pub fn len<'a>(value: impl Into<Operand<'a>>, op: CompareOp, n: impl Into<Operand<'a>>) -> bool {
+    match (value.into().value(), n.into().value()) {
+        (Some(value), Some(n)) => evaluate::len_compare(value, op, n) == Some(true),
+        _ => false,
+    }
+}
+
+/// `value HAS_KEY key`.
+#[must_use]
+pub fn has_key<'a>(value: impl Into<Operand<'a>>, key: impl Into<Operand<'a>>) -> bool {
+    match (value.into(), key.into().str()) {
+        (Operand::Value(Value::Map(entries)), Some(key)) => entries.contains_key(key),
+        _ => false,
+    }
+}
+
+/// The elements of a quantified list; `None` if `value` is not a list.
+#[must_use]
+pub fn list<'a>(value: impl Into<Operand<'a>>) -> Option<&'a [Value]> {
+    match value.into() {
+        Operand::Value(Value::List(items)) => Some(items),
+        _ => None,
+    }
+}
+
+/// The value at `path` within a quantifier element.
+#[must_use]
+pub fn element<'a>(item: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    evaluate::element_path(item, path)
+}
+
+/// One step of a path lookup such as `limits[user.country].daily`, as
+/// passed to [`lookup`].
+pub use crate::evaluate::Step;
+
+/// A `[field]` step keyed by `key`: a native string names a map entry as a
+/// `Value::String` would.
+#[must_use]
+pub fn dynamic<'a>(key: impl Into<Operand<'a>>) -> Step<'a> {
+    match key.into() {
+        Operand::Missing => Step::Dynamic(None),
+        Operand::Value(value) => Step::Dynamic(Some(value)),
+        Operand::Str(key) => Step::Key(key),
+    }
+}
+
+/// Apply lookup `steps` to `base`. A lookup with a wildcard step resolves
+/// to a list of every value reached.
+#[must_use]
+pub fn lookup<'a>(base: impl Into<Operand<'a>>, steps: &[Step<'_>]) -> Option<Cow<'a, Value>> {
+    match base.into() {
+        Operand::Value(base) => evaluate::apply_steps(base, steps, |step| *step),
+        // A string has no entries: only a wildcard reaches anything, and
+        // it reaches nothing.
+        Operand::Str(_) => steps
+            .iter()
+            .any(|step| matches!(step, Step::Wildcard))
+            .then(|| Cow::Owned(Value::List(Vec::new()))),
+        Operand::Missing => None,
+    }
+}
+
+/// Read an `int` field. The outer `None` means the value has another type.
+#[must_use]
+pub fn int(value: Option<&Value>) -> Option<Option<i64>> {
+    match value {
+        None => Some(None),
+        Some(Value::Int(i)) => Some(Some(*i)),
+        Some(_) => None,
+    }
+}
+
+/// Read a `float` field, converting integers.
+#[must_use]
+pub fn float(value: Option<&Value>) -> Option<Option<f64>> {
+    match value {
+        None => Some(None),
+        Some(Value::Float(f)) => Some(Some(*f)),
+        Some(Value::Int(i)) => Some(Some(*i as f64)),
+        Some(_) => None,
+    }
+}
+
+/// Read a `bool` field.
+#[must_use]
+pub fn bool(value: Option<&Value>) -> Option<Option<bool>> {
+    match value {
+        None => Some(None),
+        Some(Value::Bool(b)) => Some(Some(*b)),
+        Some(_) => None,
+    }
+}
+
+/// Read a `string` field.
+#[must_use]
+pub fn string(value: Option<&Value>) -> Option<Option<String>> {
+    match value {
+        None => Some(None),
+        Some(Value::String(s)) => Some(Some(s.clone())),
+        Some(_) => None,
+    }
+}
+
+/// Read a field held as a [`Value`], checking it against `declared`.
+#[must_use]
+pub fn value(value: Option<&Value>, declared: Option<FieldType>) -> Option<Option<Value>> {
+    match (value, declared) {
+        (Some(v), Some(ty)) if !ty.accepts(v) => None,
+        _ => Some(value.cloned()),
+    }
+}
@@ -1,11 +1,16 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use crate::types::{CompiledBound, CompiledExpr, CompiledRule};
-use crate::{Bound, CompileError, Expr, FieldRegistry, Rule, RuleSet, Terminal};
+use crate::types::path::{FieldPath, PathStep};
+use crate::types::{CompiledBound, CompiledExpr, CompiledLookup, CompiledRule, CompiledStep};
+use crate::{
+    Bound, CompareOp, CompileError, Expr, FieldRegistry, FieldType, Rule, RuleSet, RuleTest,
+    Schema, Terminal, Value,
+};
 
 pub(crate) fn compile(
     rules: &[Rule],
     mut terminals: Vec<Terminal>,
+    schema: Option<Schema>,
 ) -> Result<RuleSet, CompileError> {
     check_missing_conditions(rules)?;
     check_duplicates(rules)?;
@@ -15,6 +20,10 @@ pub(crate) fn compile(
     let rule_map: HashMap<&str, &Rule> = rules.iter().map(|r| (r.name.as_str(), r)).collect();
 
     check_references(rules, &rule_map)?;
+    check_field_paths(rules)?;
+    if let Some(schema) = &schema {
+        check_schema(rules, schema)?;
+    }
 
     let sorted_names = topological_sort(rules, &rule_map)?;
 
@@ -36,8 +45,13 @@ pub(crate) fn compile(
             let rule = rule_map[name.as_str()];
             CompiledRule {
                 name: rule.name.clone(),
-                condition: compile_expr(condition_of(rule), &field_registry, &rule_indices),
+                condition: compile_expr(
+                    condition_of(rule),
+                    &mut LocalScope::new(&field_registry),
+                    &rule_indices,
+                ),
                 index: i,
+                metadata: rule.metadata.clone(),
             }
         })
         .collect();
@@ -54,6 +68,8 @@ pub(crate) fn compile(
         terminals,
         field_registry,
         terminal_indices,
+        schema,
+        tests: Vec::new(),
     })
 }
 
@@ -115,6 +131,27 @@ fn check_terminals(terminals: &[Terminal], rules: &[Rule]) -> Result<(), Compile
     Ok(())
 }
 
+/// Check that test names are unique and each `expect` names a terminal.
+pub(crate) fn check_tests(tests: &[RuleTest], terminals: &[Terminal]) -> Result<(), CompileError> {
+    let mut seen = HashSet::new();
+    for test in tests {
+        if !seen.insert(&test.name) {
+            return Err(CompileError::DuplicateTest {
+                name: test.name.clone(),
+            });
+        }
+        if let Some(expect) = &test.expect {
+            if !terminals.iter().any(|t| t.rule_name == *expect) {
+                return Err(CompileError::UndefinedTestTerminal {
+                    test: test.name.clone(),
+                    terminal: expect.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
 fn check_references(rules: &[Rule], rule_map: &HashMap<&str, &Rule>) -> Result<(), CompileError> {
     for rule in rules {
         collect_and_check_refs(condition_of(rule), &rule.name, rule_map)?;
@@ -142,7 +179,13 @@ fn collect_and_check_refs(
             collect_and_check_refs(b, rule_name, rule_map)?;
             Ok(())
         }
-        Expr::Not(inner) => collect_and_check_refs(inner, rule_name, rule_map),
+        Expr::Not(inner)
+        | Expr::Any {
+            predicate: inner, ..
+        }
+        | Expr::All {
+            predicate: inner, ..
+        } => collect_and_check_refs(inner, rule_name, rule_map),
         Expr::AtLeast { exprs, .. } => {
             for e in exprs {
                 collect_and_check_refs(e, rule_name, rule_map)?;
@@ -157,7 +200,11 @@ fn collect_and_check_refs(
         | Expr::NotLike { .. }
         | Expr::IsNull(_)
         | Expr::IsNotNull(_)
-        | Expr::CompareFields { .. } => Ok(()),
+        | Expr::CompareFields { .. }
+        | Expr::Contains { .. }
+        | Expr::SetCompare { .. }
+        | Expr::Len { .. }
+        | Expr::HasKey { .. } => Ok(()),
     }
 }
 
@@ -220,7 +267,7 @@ fn topological_sort(
     Ok(sorted)
 }
 
-fn collect_rule_refs(expr: &Expr) -> Vec<String> {
+pub(crate) fn collect_rule_refs(expr: &Expr) -> Vec<String> {
     let mut refs = Vec::new();
     collect_rule_refs_inner(expr, &mut refs);
     refs
@@ -233,7 +280,13 @@ fn collect_rule_refs_inner(expr: &Expr, refs: &mut Vec<String>) {
             collect_rule_refs_inner(a, refs);
             collect_rule_refs_inner(b, refs);
         }
-        Expr::Not(inner) => collect_rule_refs_inner(inner, refs),
+        Expr::Not(inner)
+        | Expr::Any {
+            predicate: inner, ..
+        }
+        | Expr::All {
+            predicate: inner, ..
+        } => collect_rule_refs_inner(inner, refs),
         Expr::AtLeast { exprs, .. } => {
             for e in exprs {
                 collect_rule_refs_inner(e, refs);
@@ -247,7 +300,11 @@ fn collect_rule_refs_inner(expr: &Expr, refs: &mut Vec<String>) {
         | Expr::NotLike { .. }
         | Expr::IsNull(_)
         | Expr::IsNotNull(_)
-        | Expr::CompareFields { .. } => {}
+        | Expr::CompareFields { .. }
+        | Expr::Contains { .. }
+        | Expr::SetCompare { .. }
+        | Expr::Len { .. }
+        | Expr::HasKey { .. } => {}
     }
 }
 
@@ -325,153 +382,611 @@ fn dfs<'a>(
     None
 }
 
-fn collect_fields(expr: &Expr, registry: &mut FieldRegistry) {
+/// Call `f` with every field path referenced by `expr`, along with the
+/// quantifier element variables in scope where the path appears.
+fn visit_paths<'e>(
+    expr: &'e Expr,
+    vars: &mut Vec<&'e str>,
+    f: &mut impl FnMut(&'e str, &[&'e str]),
+) {
+    let bound_path = |b: &'e Bound| match b {
+        Bound::Field(path) => Some(path.as_str()),
+        Bound::Literal(_) => None,
+    };
     match expr {
         Expr::Compare { field, .. }
         | Expr::Like { field, .. }
         | Expr::NotLike { field, .. }
         | Expr::IsNull(field)
-        | Expr::IsNotNull(field) => {
-            registry.register(field);
-        }
+        | Expr::IsNotNull(field) => f(field, vars),
         Expr::In { field, members } | Expr::NotIn { field, members } => {
-            registry.register(field);
-            for m in members {
-                if let crate::Bound::Field(path) = m {
-                    registry.register(path);
-                }
+            f(field, vars);
+            for path in members.iter().filter_map(bound_path) {
+                f(path, vars);
             }
         }
         Expr::Between { field, low, high } => {
-            registry.register(field);
-            if let crate::Bound::Field(path) = low {
-                registry.register(path);
+            f(field, vars);
+            for path in [low, high].into_iter().filter_map(bound_path) {
+                f(path, vars);
             }
-            if let crate::Bound::Field(path) = high {
-                registry.register(path);
+        }
+        Expr::Contains { field, value }
+        | Expr::SetCompare { field, value, .. }
+        | Expr::Len { field, value, .. }
+        | Expr::HasKey { field, key: value } => {
+            f(field, vars);
+            if let Some(path) = bound_path(value) {
+                f(path, vars);
             }
         }
         Expr::CompareFields { left, right, .. } => {
-            registry.register(left);
-            registry.register(right);
+            f(left, vars);
+            f(right, vars);
         }
         Expr::And(a, b) | Expr::Or(a, b) => {
-            collect_fields(a, registry);
-            collect_fields(b, registry);
+            visit_paths(a, vars, f);
+            visit_paths(b, vars, f);
         }
-        Expr::Not(inner) => collect_fields(inner, registry),
+        Expr::Not(inner) => visit_paths(inner, vars, f),
         Expr::AtLeast { exprs, .. } => {
             for e in exprs {
-                collect_fields(e, registry);
+                visit_paths(e, vars, f);
             }
         }
+        Expr::Any {
+            list,
+            var,
+            predicate,
+        }
+        | Expr::All {
+            list,
+            var,
+            predicate,
+        } => {
+            f(list, vars);
+            vars.push(var);
+            visit_paths(predicate, vars, f);
+            vars.pop();
+        }
         Expr::RuleRef(_) => {}
     }
 }
 
-pub(crate) fn compile_bound(bound: &Bound, field_registry: &FieldRegistry) -> CompiledBound {
-    match bound {
-        Bound::Literal(v) => CompiledBound::Literal(v.clone()),
-        Bound::Field(path) => CompiledBound::FieldIndex(
-            field_registry
-                .get(path)
-                .expect("bound field should be registered"),
-        ),
+fn check_field_paths(rules: &[Rule]) -> Result<(), CompileError> {
+    for rule in rules {
+        let mut invalid = None;
+        visit_paths(condition_of(rule), &mut Vec::new(), &mut |path, _| {
+            if invalid.is_none() {
+                if let Err(reason) = FieldPath::parse(path) {
+                    invalid = Some((path.to_owned(), reason));
+                }
+            }
+        });
+        if let Some((path, reason)) = invalid {
+            return Err(CompileError::InvalidFieldPath {
+                rule: rule.name.clone(),
+                path,
+                reason,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check every rule against `schema`: each referenced context field must be
+/// declared, and each comparison must be between compatible types.
+fn check_schema(rules: &[Rule], schema: &Schema) -> Result<(), CompileError> {
+    for rule in rules {
+        let mut unknown = None;
+        visit_paths(condition_of(rule), &mut Vec::new(), &mut |path, vars| {
+            if unknown.is_none() {
+                unknown = undeclared_path(schema, path, vars).map(str::to_owned);
+            }
+        });
+        if let Some(path) = unknown {
+            return Err(CompileError::UnknownField {
+                rule: rule.name.clone(),
+                path,
+            });
+        }
+        check_types(condition_of(rule), &mut Vec::new(), &rule.name, schema)?;
+    }
+    Ok(())
+}
+
+/// The first context field within `path` (its root or a field-sourced key)
+/// that `schema` does not declare.
+fn undeclared_path<'p>(schema: &Schema, path: &'p str, vars: &[&str]) -> Option<&'p str> {
+    let path = FieldPath::parse(path).expect("paths validated by check_field_paths");
+    let keys = path.steps.iter().filter_map(|step| match step {
+        PathStep::Dynamic(key) => Some(*key),
+        PathStep::Key(_) | PathStep::Index(_) | PathStep::Wildcard => None,
+    });
+    std::iter::once(path.root)
+        .chain(keys)
+        .find(|plain| !vars.contains(&root_segment(plain)) && schema.resolve(plain).is_none())
+}
+
+/// The type `path` resolves to, where the schema determines it. Wildcard
+/// paths always produce a list; element variables, other indexed paths and
+/// paths inside a declared `map` are untyped.
+fn path_type(schema: &Schema, path: &str, vars: &[&str]) -> Option<FieldType> {
+    let path = FieldPath::parse(path).expect("paths validated by check_field_paths");
+    if path.steps.contains(&PathStep::Wildcard) {
+        return Some(FieldType::List);
+    }
+    if path.is_indexed() || vars.contains(&root_segment(path.root)) {
+        return None;
+    }
+    schema.resolve(path.root).flatten()
+}
+
+fn check_types<'e>(
+    expr: &'e Expr,
+    vars: &mut Vec<&'e str>,
+    rule: &str,
+    schema: &Schema,
+) -> Result<(), CompileError> {
+    let typed = |path: &str, vars: &[&str]| path_type(schema, path, vars);
+    let comparable = |path: &str, field_type: FieldType, other: FieldType, op: CompareOp| {
+        if field_type.comparable(other, op) {
+            return Ok(());
+        }
+        let reason = match op {
+            CompareOp::Eq | CompareOp::Neq => format!("cannot be compared with {other}"),
+            _ => format!("cannot be ordered against {other}"),
+        };
+        Err(CompileError::TypeMismatch {
+            rule: rule.to_owned(),
+            path: path.to_owned(),
+            field_type,
+            reason,
+        })
+    };
+    // Operators that only apply to a list (or map) fail on any other
+    // declared type.
+    let collection = |path: &str, vars: &[&str], allowed: &[FieldType], reason: String| match typed(
+        path, vars,
+    ) {
+        Some(ty) if !allowed.contains(&ty) => Err(CompileError::TypeMismatch {
+            rule: rule.to_owned(),
+            path: path.to_owned(),
+            field_type: ty,
+            reason,
+        }),
+        _ => Ok(()),
+    };
+    let list = [FieldType::List];
+    match expr {
+        Expr::Compare { field, op, value } => {
+            if let Some(ty) = typed(field, vars) {
+                comparable(field, ty, FieldType::of(value), *op)?;
+            }
+        }
+        Expr::Contains { field, .. } => {
+            collection(
+                field,
+                vars,
+                &list,
+                "cannot be searched with CONTAINS".to_owned(),
+            )?;
+        }
+        Expr::SetCompare { field, op, value } => {
+            let reason = || format!("cannot be compared as a set with {op}");
+            collection(field, vars, &list, reason())?;
+            match value {
+                Bound::Field(path) => collection(path, vars, &list, reason())?,
+                Bound::Literal(value) if !matches!(value, Value::List(_)) => {
+                    if let Some(ty) = typed(field, vars) {
+                        return Err(CompileError::TypeMismatch {
+                            rule: rule.to_owned(),
+                            path: field.clone(),
+                            field_type: ty,
+                            reason: format!(
+                                "cannot be compared as a set with {}",
+                                FieldType::of(value)
+                            ),
+                        });
+                    }
+                }
+                Bound::Literal(_) => {}
+            }
+        }
+        Expr::Len { field, .. } => {
+            collection(
+                field,
+                vars,
+                &[FieldType::List, FieldType::Map],
+                "has no len()".to_owned(),
+            )?;
+        }
+        Expr::HasKey { field, .. } => {
+            collection(
+                field,
+                vars,
+                &[FieldType::Map],
+                "cannot be tested with HAS_KEY".to_owned(),
+            )?;
+        }
+        Expr::Between { field, low, high } => {
+            let Some(ty) = typed(field, vars) else {
+                return Ok(());
+            };
+            for bound in [low, high] {
+                let other = match bound {
+                    Bound::Literal(value) => Some(FieldType::of(value)),
+                    Bound::Field(path) => typed(path, vars),
+                };
+                if let Some(other) = other {
+                    comparable(field, ty, other, CompareOp::Lte)?;
+                }
+            }
+        }
+        Expr::In { field, members } | Expr::NotIn { field, members } => {
+            let Some(ty) = typed(field, vars) else {
+                return Ok(());
+            };
+            for member in members {
+                // List members are searched element by element.
+                match member {
+                    Bound::Literal(Value::List(items)) => {
+                        for item in items {
+                            comparable(field, ty, FieldType::of(item), CompareOp::Eq)?;
+                        }
+                    }
+                    Bound::Literal(value) => {
+                        comparable(field, ty, FieldType::of(value), CompareOp::Eq)?;
+                    }
+                    Bound::Field(path) => match typed(path, vars) {
+                        Some(FieldType::List) | None => {}
+                        Some(other) => comparable(field, ty, other, CompareOp::Eq)?,
+                    },
+                }
+            }
+        }
+        Expr::Like { field, .. } | Expr::NotLike { field, .. } => {
+            if let Some(ty) = typed(field, vars) {
+                if ty != FieldType::String {
+                    return Err(CompileError::TypeMismatch {
+                        rule: rule.to_owned(),
+                        path: field.clone(),
+                        field_type: ty,
+                        reason: "cannot be matched with LIKE".to_owned(),
+                    });
+                }
+            }
+        }
+        Expr::CompareFields { left, op, right } => {
+            if let (Some(ty), Some(other)) = (typed(left, vars), typed(right, vars)) {
+                comparable(left, ty, other, *op)?;
+            }
+        }
+        Expr::And(a, b) | Expr::Or(a, b) => {
+            check_types(a, vars, rule, schema)?;
+            check_types(b, vars, rule, schema)?;
+        }
+        Expr::Not(inner) => check_types(inner, vars, rule, schema)?,
+        Expr::AtLeast { exprs, .. } => {
+            for e in exprs {
+                check_types(e, vars, rule, schema)?;
+            }
+        }
+        Expr::Any {
+            list: path,
+            var,
+            predicate,
+        }
+        | Expr::All {
+            list: path,
+            var,
+            predicate,
+        } => {
+            let keyword = if matches!(expr, Expr::Any { .. }) {
+                "ANY"
+            } else {
+                "ALL"
+            };
+            collection(
+                path,
+                vars,
+                &list,
+                format!("cannot be quantified over with {keyword}"),
+            )?;
+            vars.push(var);
+            let result = check_types(predicate, vars, rule, schema);
+            vars.pop();
+            result?;
+        }
+        Expr::RuleRef(_) | Expr::IsNull(_) | Expr::IsNotNull(_) => {}
+    }
+    Ok(())
+}
+
+/// Register every context field path referenced by `expr`.
+fn collect_fields(expr: &Expr, registry: &mut FieldRegistry) {
+    visit_context_fields(expr, &mut |path| {
+        registry.register(path);
+    });
+}
+
+/// The context field paths referenced by `expr`, sorted and deduplicated.
+pub(crate) fn collect_field_paths(expr: &Expr) -> Vec<String> {
+    let mut paths = Vec::new();
+    visit_context_fields(expr, &mut |path| paths.push(path.to_owned()));
+    paths.sort_unstable();
+    paths.dedup();
+    paths
+}
+
+/// Call `f` with every context field path referenced by `expr`. Paths rooted
+/// at a quantifier element variable are local to that quantifier and are
+/// skipped. An indexed path contributes its root and any field-sourced keys.
+fn visit_context_fields(expr: &Expr, f: &mut impl FnMut(&str)) {
+    visit_paths(expr, &mut Vec::new(), &mut |path, vars| {
+        let path = FieldPath::parse(path).expect("paths validated by check_field_paths");
+        let keys = path.steps.iter().filter_map(|step| match step {
+            PathStep::Dynamic(key) => Some(*key),
+            PathStep::Key(_) | PathStep::Index(_) | PathStep::Wildcard => None,
+        });
+        for plain in std::iter::once(path.root).chain(keys) {
+            if !vars.contains(&root_segment(plain)) {
+                f(plain);
+            }
+        }
+    });
+}
+
+/// The first segment of a dot-separated path.
+fn root_segment(path: &str) -> &str {
+    path.split('.').next().unwrap_or(path)
+}
+
+/// Element variables bound by the quantifiers enclosing the expression being
+/// compiled, innermost last, plus the `[key]` lookups awaiting a `Lookup` node.
+///
+/// Each path rooted at a bound variable, and each indexed path, is assigned
+/// a local slot numbered after the field registry, so compiled expressions
+/// address locals and context fields through the same index space.
+struct LocalScope<'r> {
+    field_registry: &'r FieldRegistry,
+    frames: Vec<ScopeFrame>,
+    lookups: Vec<CompiledLookup>,
+    next_slot: usize,
+}
+
+struct ScopeFrame {
+    var: String,
+    bindings: Vec<(usize, Vec<String>)>,
+}
+
+impl<'r> LocalScope<'r> {
+    fn new(field_registry: &'r FieldRegistry) -> Self {
+        Self {
+            field_registry,
+            frames: Vec::new(),
+            lookups: Vec::new(),
+            next_slot: field_registry.len(),
+        }
+    }
+
+    fn alloc(&mut self) -> usize {
+        let slot = self.next_slot;
+        self.next_slot += 1;
+        slot
+    }
+
+    /// Resolve a path to its slot. An indexed path gets a local slot filled by
+    /// a pending lookup; see [`LocalScope::take_lookups`].
+    fn slot(&mut self, path: &str) -> usize {
+        let path = FieldPath::parse(path).expect("paths validated by check_field_paths");
+        if !path.is_indexed() {
+            return self.plain_slot(path.root);
+        }
+        let base = self.plain_slot(path.root);
+        let steps: Vec<CompiledStep> = path
+            .steps
+            .into_iter()
+            .map(|step| match step {
+                PathStep::Key(key) => CompiledStep::Key(key),
+                PathStep::Dynamic(key_path) => CompiledStep::Dynamic(self.plain_slot(key_path)),
+                PathStep::Index(index) => CompiledStep::Index(index),
+                PathStep::Wildcard => CompiledStep::Wildcard,
+            })
+            .collect();
+        if let Some(existing) = self
+            .lookups
+            .iter()
+            .find(|l| l.base == base && l.steps == steps)
+        {
+            return existing.slot;
+        }
+        let slot = self.alloc();
+        self.lookups.push(CompiledLookup { slot, base, steps });
+        slot
+    }
+
+    /// Resolve a dotted path to its slot: a local binding if the path is rooted
+    /// at a variable in scope, otherwise its field registry index.
+    fn plain_slot(&mut self, path: &str) -> usize {
+        let mut segments = path.split('.');
+        let root = segments.next().unwrap_or(path);
+        if let Some(index) = self.frames.iter().rposition(|f| f.var == root) {
+            let sub_path: Vec<String> = segments.map(str::to_owned).collect();
+            if let Some((slot, _)) = self.frames[index]
+                .bindings
+                .iter()
+                .find(|(_, p)| *p == sub_path)
+            {
+                return *slot;
+            }
+            let slot = self.alloc();
+            self.frames[index].bindings.push((slot, sub_path));
+            return slot;
+        }
+        self.field_registry
+            .get(path)
+            .expect("field should be registered")
+    }
+
+    fn bound(&mut self, bound: &Bound) -> CompiledBound {
+        match bound {
+            Bound::Literal(v) => CompiledBound::Literal(v.clone()),
+            Bound::Field(path) => CompiledBound::FieldIndex(self.slot(path)),
+        }
+    }
+
+    /// Take the lookups requested since the last call, for the caller to wrap
+    /// around the expression that uses them.
+    fn take_lookups(&mut self) -> Vec<CompiledLookup> {
+        std::mem::take(&mut self.lookups)
+    }
+
+    fn push(&mut self, var: &str) {
+        self.frames.push(ScopeFrame {
+            var: var.to_owned(),
+            bindings: Vec::new(),
+        });
+    }
+
+    fn pop(&mut self) -> Vec<(usize, Vec<String>)> {
+        self.frames.pop().expect("pop is paired with push").bindings
+    }
+}
+
+/// Wrap `body` in a `Lookup` node if it uses any `[key]` lookups.
+fn with_lookups(lookups: Vec<CompiledLookup>, body: CompiledExpr) -> CompiledExpr {
+    if lookups.is_empty() {
+        body
+    } else {
+        CompiledExpr::Lookup {
+            lookups,
+            body: Box::new(body),
+        }
     }
 }
 
 fn compile_expr(
     expr: &Expr,
-    field_registry: &FieldRegistry,
+    scope: &mut LocalScope<'_>,
+    rule_indices: &HashMap<String, usize>,
+) -> CompiledExpr {
+    let compiled = compile_node(expr, scope, rule_indices);
+    with_lookups(scope.take_lookups(), compiled)
+}
+
+fn compile_node(
+    expr: &Expr,
+    scope: &mut LocalScope<'_>,
     rule_indices: &HashMap<String, usize>,
 ) -> CompiledExpr {
     match expr {
         Expr::Compare { field, op, value } => CompiledExpr::Compare {
-            field_index: field_registry
-                .get(field)
-                .expect("field should be registered"),
+            field_index: scope.slot(field),
             op: *op,
             value: value.clone(),
         },
         Expr::And(a, b) => CompiledExpr::And(
-            Box::new(compile_expr(a, field_registry, rule_indices)),
-            Box::new(compile_expr(b, field_registry, rule_indices)),
+            Box::new(compile_expr(a, scope, rule_indices)),
+            Box::new(compile_expr(b, scope, rule_indices)),
         ),
         Expr::Or(a, b) => CompiledExpr::Or(
-            Box::new(compile_expr(a, field_registry, rule_indices)),
-            Box::new(compile_expr(b, field_registry, rule_indices)),
+            Box::new(compile_expr(a, scope, rule_indices)),
+            Box::new(compile_expr(b, scope, rule_indices)),
         ),
-        Expr::Not(inner) => {
-            CompiledExpr::Not(Box::new(compile_expr(inner, field_registry, rule_indices)))
-        }
+        Expr::Not(inner) => CompiledExpr::Not(Box::new(compile_expr(inner, scope, rule_indices))),
         Expr::RuleRef(name) => CompiledExpr::RuleRef(
             *rule_indices
                 .get(name)
                 .expect("rule reference should be validated"),
         ),
         Expr::In { field, members } => CompiledExpr::In {
-            field_index: field_registry
-                .get(field)
-                .expect("field should be registered"),
-            members: members
-                .iter()
-                .map(|b| compile_bound(b, field_registry))
-                .collect(),
+            field_index: scope.slot(field),
+            members: members.iter().map(|b| scope.bound(b)).collect(),
         },
         Expr::NotIn { field, members } => CompiledExpr::NotIn {
-            field_index: field_registry
-                .get(field)
-                .expect("field should be registered"),
-            members: members
-                .iter()
-                .map(|b| compile_bound(b, field_registry))
-                .collect(),
+            field_index: scope.slot(field),
+            members: members.iter().map(|b| scope.bound(b)).collect(),
         },
         Expr::Between { field, low, high } => CompiledExpr::Between {
-            field_index: field_registry
-                .get(field)
-                .expect("field should be registered"),
-            low: compile_bound(low, field_registry),
-            high: compile_bound(high, field_registry),
+            field_index: scope.slot(field),
+            low: scope.bound(low),
+            high: scope.bound(high),
         },
         Expr::Like { field, pattern } => CompiledExpr::Like {
-            field_index: field_registry
-                .get(field)
-                .expect("field should be registered"),
+            field_index: scope.slot(field),
             pattern: pattern.clone(),
         },
         Expr::NotLike { field, pattern } => CompiledExpr::NotLike {
-            field_index: field_registry
-                .get(field)
-                .expect("field should be registered"),
+            field_index: scope.slot(field),
             pattern: pattern.clone(),
         },
-        Expr::IsNull(field) => CompiledExpr::IsNull(
-            field_registry
-                .get(field)
-                .expect("field should be registered"),
-        ),
-        Expr::IsNotNull(field) => CompiledExpr::IsNotNull(
-            field_registry
-                .get(field)
-                .expect("field should be registered"),
-        ),
+        Expr::IsNull(field) => CompiledExpr::IsNull(scope.slot(field)),
+        Expr::IsNotNull(field) => CompiledExpr::IsNotNull(scope.slot(field)),
         Expr::CompareFields { left, op, right } => CompiledExpr::CompareFields {
-            left_index: field_registry
-                .get(left)
-                .expect("left field should be registered"),
+            left_index: scope.slot(left),
             op: *op,
-            right_index: field_registry
-                .get(right)
-                .expect("right field should be registered"),
+            right_index: scope.slot(right),
         },
         Expr::AtLeast { n, exprs } => CompiledExpr::AtLeast {
             n: *n,
             exprs: exprs
                 .iter()
-                .map(|e| compile_expr(e, field_registry, rule_indices))
+                .map(|e| compile_expr(e, scope, rule_indices))
                 .collect(),
         },
+        Expr::Any {
+            list,
+            var,
+            predicate,
+        } => {
+            // The list's own lookups run once, outside the per-element loop.
+            let list_index = scope.slot(list);
+            let list_lookups = scope.take_lookups();
+            scope.push(var);
+            let predicate = Box::new(compile_expr(predicate, scope, rule_indices));
+            let quantifier = CompiledExpr::Any {
+                list_index,
+                bindings: scope.pop(),
+                predicate,
+            };
+            with_lookups(list_lookups, quantifier)
+        }
+        Expr::All {
+            list,
+            var,
+            predicate,
+        } => {
+            // The list's own lookups run once, outside the per-element loop.
+            let list_index = scope.slot(list);
+            let list_lookups = scope.take_lookups();
+            scope.push(var);
+            let predicate = Box::new(compile_expr(predicate, scope, rule_indices));
+            let quantifier = CompiledExpr::All {
+                list_index,
+                bindings: scope.pop(),
+                predicate,
+            };
+            with_lookups(list_lookups, quantifier)
+        }
+        Expr::Contains { field, value } => CompiledExpr::Contains {
+            field_index: scope.slot(field),
+            value: scope.bound(value),
+        },
+        Expr::SetCompare { field, op, value } => CompiledExpr::SetCompare {
+            field_index: scope.slot(field),
+            op: *op,
+            value: scope.bound(value),
+        },
+        Expr::Len { field, op, value } => CompiledExpr::Len {
+            field_index: scope.slot(field),
+            op: *op,
+            value: scope.bound(value),
+        },
+        Expr::HasKey { field, key } => CompiledExpr::HasKey {
+            field_index: scope.slot(field),
+            key: scope.bound(key),
+        },
     }
 }
 
@@ -0,0 +1,375 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::compile::collect_rule_refs;
+use crate::types::path::quote_key;
+use crate::types::{CompiledBound, CompiledExpr, CompiledLookup, CompiledStep};
+use crate::{Bound, Expr, Rule, RuleSet, Value};
+
+/// Rebuild source rules from a compiled ruleset.
+///
+/// Field slots map back to paths through the field registry, lookup slots to
+/// the `[...]` paths that filled them, and rule references to rule names.
+/// Quantifier element variables are not kept by compilation, so each is
+/// given a fresh name that no field path starts with.
+///
+/// The compiled execution order depends on hash iteration, so rules come out
+/// dependencies first and otherwise sorted by name: the same rules always
+/// decompile to the same text.
+pub(crate) fn decompile(ruleset: &RuleSet) -> Vec<Rule> {
+    let mut names = vec![""; ruleset.rules.len()];
+    for rule in &ruleset.rules {
+        names[rule.index] = &rule.name;
+    }
+    let fields: HashMap<usize, String> = ruleset
+        .field_registry
+        .iter()
+        .map(|(path, &idx)| (idx, path.to_owned()))
+        .collect();
+    let roots: HashSet<&str> = fields
+        .values()
+        .map(|path| path.split(['.', '[']).next().unwrap_or(path))
+        .collect();
+
+    let rules = ruleset
+        .rules
+        .iter()
+        .map(|rule| {
+            let mut scope = Scope {
+                names: &names,
+                roots: &roots,
+                slots: fields.clone(),
+                vars: Vec::new(),
+            };
+            Rule {
+                name: rule.name.clone(),
+                condition: Some(scope.expr(&rule.condition)),
+                metadata: rule.metadata.clone(),
+            }
+        })
+        .collect();
+    dependency_order(rules)
+}
+
+/// Kahn's algorithm, always taking the ready rule with the smallest name.
+fn dependency_order(rules: Vec<Rule>) -> Vec<Rule> {
+    let index: HashMap<&str, usize> = rules
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| (rule.name.as_str(), i))
+        .collect();
+    let mut in_degree = vec![0; rules.len()];
+    let mut dependents = vec![Vec::new(); rules.len()];
+    for (i, rule) in rules.iter().enumerate() {
+        let mut deps: Vec<usize> = rule
+            .condition
+            .iter()
+            .flat_map(collect_rule_refs)
+            .filter_map(|name| index.get(name.as_str()).copied())
+            .collect();
+        deps.sort_unstable();
+        deps.dedup();
+        in_degree[i] = deps.len();
+        for dep in deps {
+            dependents[dep].push(i);
+        }
+    }
+
+    let mut ready: BTreeSet<(&str, usize)> = rules
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| in_degree[i] == 0)
+        .map(|(i, rule)| (rule.name.as_str(), i))
+        .collect();
+    let mut order = Vec::with_capacity(rules.len());
+    while let Some((_, i)) = ready.pop_first() {
+        order.push(i);
+        for &dependent in &dependents[i] {
+            in_degree[dependent] -= 1;
+            if in_degree[dependent] == 0 {
+                ready.insert((rules[dependent].name.as_str(), dependent));
+            }
+        }
+    }
+
+    let mut slots: Vec<Option<Rule>> = rules.into_iter().map(Some).collect();
+    order.into_iter().filter_map(|i| slots[i].take()).collect()
+}
+
+struct Scope<'a> {
+    names: &'a [&'a str],
+    /// First segments of registered paths, which element variables must avoid.
+    roots: &'a HashSet<&'a str>,
+    /// Path for every slot in scope: registry fields, lookups and bindings.
+    slots: HashMap<usize, String>,
+    /// Element variables of the enclosing quantifiers.
+    vars: Vec<String>,
+}
+
+impl Scope<'_> {
+    fn path(&self, slot: usize) -> String {
+        self.slots
+            .get(&slot)
+            .cloned()
+            .unwrap_or_else(|| format!("_slot{slot}"))
+    }
+
+    fn bound(&self, bound: &CompiledBound) -> Bound {
+        match bound {
+            CompiledBound::Literal(value) => Bound::Literal(value.clone()),
+            CompiledBound::FieldIndex(slot) => Bound::Field(self.path(*slot)),
+        }
+    }
+
+    /// `IN` members, with list constants spliced back into the list they
+    /// were written in. Membership looks inside list members, so this keeps
+    /// the meaning and reads as `IN ["a", "b"]` rather than `IN [["a", "b"]]`.
+    fn members(&self, members: &[CompiledBound]) -> Vec<Bound> {
+        let mut out = Vec::with_capacity(members.len());
+        for member in members {
+            match member {
+                CompiledBound::Literal(Value::List(items))
+                    if !items.is_empty()
+                        && !items.iter().any(|item| matches!(item, Value::List(_))) =>
+                {
+                    out.extend(items.iter().cloned().map(Bound::Literal));
+                }
+                _ => out.push(self.bound(member)),
+            }
+        }
+        out
+    }
+
+    /// `limits[user.country].daily`: the first step is always bracketed so
+    /// the path stays a lookup; later keys use `.key` where they can.
+    fn lookup(&mut self, lookup: &CompiledLookup) {
+        let mut path = self.path(lookup.base);
+        for (i, step) in lookup.steps.iter().enumerate() {
+            match step {
+                CompiledStep::Key(key)
+                    if i > 0
+                        && !key.is_empty()
+                        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+                {
+                    path.push('.');
+                    path.push_str(key);
+                }
+                CompiledStep::Key(key) => path.push_str(&quote_key(key)),
+                CompiledStep::Dynamic(slot) => {
+                    path.push('[');
+                    path.push_str(&self.path(*slot));
+                    path.push(']');
+                }
+                CompiledStep::Index(index) => path.push_str(&format!("[{index}]")),
+                CompiledStep::Wildcard => path.push_str("[*]"),
+            }
+        }
+        self.slots.insert(lookup.slot, path);
+    }
+
+    /// Bind a fresh element variable for a quantifier's predicate.
+    fn quantifier(
+        &mut self,
+        list_index: usize,
+        bindings: &[(usize, Vec<String>)],
+        predicate: &CompiledExpr,
+    ) -> (String, String, Box<Expr>) {
+        let list = self.path(list_index);
+        let var = (0..)
+            .map(|n| {
+                if n == 0 {
+                    "item".to_owned()
+                } else {
+                    format!("item{n}")
+                }
+            })
+            .find(|v| !self.roots.contains(v.as_str()) && !self.vars.contains(v))
+            .expect("an unused name exists");
+        for (slot, sub_path) in bindings {
+            let mut path = var.clone();
+            for segment in sub_path {
+                path.push('.');
+                path.push_str(segment);
+            }
+            self.slots.insert(*slot, path);
+        }
+        self.vars.push(var.clone());
+        let predicate = Box::new(self.expr(predicate));
+        self.vars.pop();
+        (list, var, predicate)
+    }
+
+    fn expr(&mut self, expr: &CompiledExpr) -> Expr {
+        match expr {
+            CompiledExpr::Compare {
+                field_index,
+                op,
+                value,
+            } => Expr::Compare {
+                field: self.path(*field_index),
+                op: *op,
+                value: value.clone(),
+            },
+            CompiledExpr::And(a, b) => Expr::And(Box::new(self.expr(a)), Box::new(self.expr(b))),
+            CompiledExpr::Or(a, b) => Expr::Or(Box::new(self.expr(a)), Box::new(self.expr(b))),
+            CompiledExpr::Not(inner) => Expr::Not(Box::new(self.expr(inner))),
+            CompiledExpr::RuleRef(idx) => Expr::RuleRef(self.names[*idx].to_owned()),
+            CompiledExpr::In {
+                field_index,
+                members,
+            } => Expr::In {
+                field: self.path(*field_index),
+                members: self.members(members),
+            },
+            CompiledExpr::NotIn {
+                field_index,
+                members,
+            } => Expr::NotIn {
+                field: self.path(*field_index),
+                members: self.members(members),
+            },
+            CompiledExpr::Between {
+                field_index,
+                low,
+                high,
+            } => Expr::Between {
+                field: self.path(*field_index),
+                low: self.bound(low),
+                high: self.bound(high),
+            },
+            CompiledExpr::Like {
+                field_index,
+                pattern,
+            } => Expr::Like {
+                field: self.path(*field_index),
+                pattern: pattern.clone(),
+            },
+            CompiledExpr::NotLike {
+                field_index,
+                pattern,
+            } => Expr::NotLike {
+                field: self.path(*field_index),
+                pattern: pattern.clone(),
+            },
+            CompiledExpr::IsNull(slot) => Expr::IsNull(self.path(*slot)),
+            CompiledExpr::IsNotNull(slot) => Expr::IsNotNull(self.path(*slot)),
+            CompiledExpr::CompareFields {
+                left_index,
+                op,
+                right_index,
+            } => Expr::CompareFields {
+                left: self.path(*left_index),
+                op: *op,
+                right: self.path(*right_index),
+            },
+            CompiledExpr::AtLeast { n, exprs } => Expr::AtLeast {
+                n: *n,
+                exprs: exprs.iter().map(|e| self.expr(e)).collect(),
+            },
+            CompiledExpr::Any {
+                list_index,
+                bindings,
+                predicate,
+            } => {
+                let (list, var, predicate) = self.quantifier(*list_index, bindings, predicate);
+                Expr::Any {
+                    list,
+                    var,
+                    predicate,
+                }
+            }
+            CompiledExpr::All {
+                list_index,
+                bindings,
+                predicate,
+            } => {
+                let (list, var, predicate) = self.quantifier(*list_index, bindings, predicate);
+                Expr::All {
+                    list,
+                    var,
+                    predicate,
+                }
+            }
+            CompiledExpr::Contains { field_index, value } => Expr::Contains {
+                field: self.path(*field_index),
+                value: self.bound(value),
+            },
+            CompiledExpr::SetCompare {
+                field_index,
+                op,
+                value,
+            } => Expr::SetCompare {
+                field: self.path(*field_index),
+                op: *op,
+                value: self.bound(value),
+            },
+            CompiledExpr::Len {
+                field_index,
+                op,
+                value,
+            } => Expr::Len {
+                field: self.path(*field_index),
+                op: *op,
+                value: self.bound(value),
+            },
+            CompiledExpr::HasKey { field_index, key } => Expr::HasKey {
+                field: self.path(*field_index),
+                key: self.bound(key),
+            },
+            CompiledExpr::Lookup { lookups, body } => {
+                for lookup in lookups {
+                    self.lookup(lookup);
+                }
+                self.expr(body)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{all, any, field, rule_ref, Expr, RuleSetBuilder};
+
+    fn round_trip(expr: Expr) -> Expr {
+        let ruleset = RuleSetBuilder::new()
+            .rule("base", |r| r.when(field("x").eq(1_i64)))
+            .rule("r", |r| r.when(expr))
+            .terminal("r", 0)
+            .compile()
+            .unwrap();
+        let rules = super::decompile(&ruleset);
+        let rule = rules.iter().find(|r| r.name == "r").unwrap();
+        rule.condition.clone().unwrap()
+    }
+
+    #[test]
+    fn plain_fields_and_rule_refs() {
+        let expr = rule_ref("base").and(field("user.age").gte(18_i64));
+        assert_eq!(round_trip(expr.clone()), expr);
+    }
+
+    #[test]
+    fn lookups_rebuild_their_paths() {
+        let expr = field("limits[user.country].daily")
+            .gt(5_i64)
+            .or(field("limits[\"U S\"]").is_null())
+            .or(field("order.items[*].sku").contains("a"));
+        assert_eq!(round_trip(expr.clone()), expr);
+    }
+
+    #[test]
+    fn quantifier_variables_avoid_field_roots() {
+        let expr = any("item.list", "x", field("x.price").gt(1_i64)).and(all(
+            "order.items",
+            "y",
+            any("y.tags", "t", field("t").eq("a")),
+        ));
+        assert_eq!(
+            round_trip(expr),
+            any("item.list", "item1", field("item1.price").gt(1_i64)).and(all(
+                "order.items",
+                "item1",
+                any("item1.tags", "item2", field("item2").eq("a"))
+            ))
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::parse::ParseError;
+use crate::parse::{ImportError, ParseError};
 use crate::CompileError;
 
 /// Unified error type covering parsing, compilation, and I/O.
@@ -13,6 +13,10 @@ pub enum OorooError {
     #[error(transparent)]
     Parse(#[from] ParseError),
 
+    /// An error resolving DSL `import` declarations.
+    #[error(transparent)]
+    Import(#[from] ImportError),
+
     /// A compilation error from ruleset validation.
     #[error(transparent)]
     Compile(#[from] CompileError),
@@ -28,4 +32,9 @@ pub enum OorooError {
     #[cfg(feature = "binary-cache")]
     #[error(transparent)]
     Deserialize(#[from] crate::serial::DeserializeError),
+
+    /// Malformed JSON passed to [`RuleSet::from_json_rules()`](crate::RuleSet::from_json_rules).
+    #[cfg(feature = "serde")]
+    #[error("invalid JSON rules: {0}")]
+    Json(#[from] serde_json::Error),
 }
@@ -1,20 +1,24 @@
+use std::borrow::Cow;
 use std::time::Instant;
 
 use crate::types::evaluation_report::EvaluationReport;
 use crate::types::value::like_match;
 use crate::types::CompareOp;
-use crate::types::{CompiledBound, CompiledExpr, CompiledRule};
+use crate::types::RuleOverride;
+use crate::types::{CompiledBound, CompiledExpr, CompiledLookup, CompiledRule, CompiledStep};
 use crate::{Terminal, Value, Verdict};
+use crate::{Truth, UnknownPolicy};
 
 /// Stack threshold: rulesets with this many rules or fewer use a stack-allocated
 /// result array instead of a heap-allocated `Vec`.
-const STACK_THRESHOLD: usize = 64;
+pub(crate) const STACK_THRESHOLD: usize = 64;
 
 pub(crate) fn evaluate(
     rules: &[CompiledRule],
     terminals: &[Terminal],
     terminal_indices: &[usize],
     field_values: &[Option<Value>],
+    overrides: &[Option<RuleOverride>],
 ) -> Option<Verdict> {
     if rules.len() <= STACK_THRESHOLD {
         let mut results = [false; STACK_THRESHOLD];
@@ -23,6 +27,7 @@ pub(crate) fn evaluate(
             terminals,
             terminal_indices,
             field_values,
+            overrides,
             &mut results,
         )
     } else {
@@ -32,6 +37,7 @@ pub(crate) fn evaluate(
             terminals,
             terminal_indices,
             field_values,
+            overrides,
             &mut results,
         )
     }
@@ -42,6 +48,7 @@ pub(crate) fn evaluate_detailed(
     terminals: &[Terminal],
     terminal_indices: &[usize],
     field_values: &[Option<Value>],
+    overrides: &[Option<RuleOverride>],
 ) -> EvaluationReport {
     let start = Instant::now();
 
@@ -57,9 +64,16 @@ pub(crate) fn evaluate_detailed(
 
     let mut evaluation_order = Vec::with_capacity(rules.len());
     let mut evaluated = Vec::new();
+    let mut overridden = Vec::new();
 
     for rule in rules {
-        results[rule.index] = eval_expr(&rule.condition, field_values, results);
+        if let Some(value) = override_of(overrides, rule.index) {
+            overridden.push((rule.name.clone(), value));
+        }
+        results[rule.index] = match forced(rule, overrides) {
+            Some(value) => value,
+            None => eval_expr(&rule.condition, field_values, &[], results),
+        };
         evaluation_order.push(rule.name.clone());
         if results[rule.index] {
             evaluated.push(rule.name.clone());
@@ -68,6 +82,9 @@ pub(crate) fn evaluate_detailed(
 
     let mut verdict = None;
     for (terminal, &idx) in terminals.iter().zip(terminal_indices) {
+        if is_disabled(overrides, idx) {
+            continue;
+        }
         if results[idx] {
             verdict = Some(Verdict::new(&terminal.rule_name, true));
             break;
@@ -75,7 +92,7 @@ pub(crate) fn evaluate_detailed(
     }
 
     let duration = start.elapsed();
-    EvaluationReport::new(verdict, evaluated, evaluation_order, duration)
+    EvaluationReport::new(verdict, evaluated, evaluation_order, overridden, duration)
 }
 
 fn evaluate_inner(
@@ -83,14 +100,21 @@ fn evaluate_inner(
     terminals: &[Terminal],
     terminal_indices: &[usize],
     field_values: &[Option<Value>],
+    overrides: &[Option<RuleOverride>],
     results: &mut [bool],
 ) -> Option<Verdict> {
     for rule in rules {
-        results[rule.index] = eval_expr(&rule.condition, field_values, results);
+        results[rule.index] = match forced(rule, overrides) {
+            Some(value) => value,
+            None => eval_expr(&rule.condition, field_values, &[], results),
+        };
     }
 
     // Terminals are pre-sorted by priority (ascending = highest priority first)
     for (terminal, &idx) in terminals.iter().zip(terminal_indices) {
+        if is_disabled(overrides, idx) {
+            continue;
+        }
         if results[idx] {
             return Some(Verdict::new(&terminal.rule_name, true));
         }
@@ -99,121 +123,469 @@ fn evaluate_inner(
     None
 }
 
+/// Evaluate with three-valued logic. A terminal whose rule is `Unknown` is
+/// skipped or, under [`UnknownPolicy::Indeterminate`], ends evaluation with an
+/// indeterminate verdict.
+pub(crate) fn evaluate_three_valued(
+    rules: &[CompiledRule],
+    terminals: &[Terminal],
+    terminal_indices: &[usize],
+    field_values: &[Option<Value>],
+    overrides: &[Option<RuleOverride>],
+) -> Option<Verdict> {
+    let mut results_buf;
+    let mut results_vec;
+    let results: &mut [Truth] = if rules.len() <= STACK_THRESHOLD {
+        results_buf = [Truth::False; STACK_THRESHOLD];
+        &mut results_buf[..]
+    } else {
+        results_vec = vec![Truth::False; rules.len()];
+        &mut results_vec[..]
+    };
+
+    for rule in rules {
+        results[rule.index] = match forced(rule, overrides) {
+            Some(value) => Truth::from(value),
+            None => eval_truth(&rule.condition, field_values, &[], results),
+        };
+    }
+
+    for (terminal, &idx) in terminals.iter().zip(terminal_indices) {
+        if is_disabled(overrides, idx) {
+            continue;
+        }
+        match results[idx] {
+            Truth::True => return Some(Verdict::new(&terminal.rule_name, true)),
+            Truth::Unknown if terminal.on_unknown == UnknownPolicy::Indeterminate => {
+                return Some(Verdict::indeterminate(&terminal.rule_name));
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[inline]
+pub(crate) fn override_of(
+    overrides: &[Option<RuleOverride>],
+    index: usize,
+) -> Option<RuleOverride> {
+    overrides.get(index).copied().flatten()
+}
+
+#[inline]
+pub(crate) fn is_disabled(overrides: &[Option<RuleOverride>], index: usize) -> bool {
+    override_of(overrides, index) == Some(RuleOverride::Disable)
+}
+
+#[inline]
+fn forced(rule: &CompiledRule, overrides: &[Option<RuleOverride>]) -> Option<bool> {
+    forced_value(rule.metadata.enabled, override_of(overrides, rule.index))
+}
+
+/// The value a rule takes without evaluating its condition, if any: forced
+/// by an override, or `false` when disabled by an override or its metadata.
+#[inline]
+pub(crate) fn forced_value(enabled: bool, value: Option<RuleOverride>) -> Option<bool> {
+    match value {
+        None => (!enabled).then_some(false),
+        Some(RuleOverride::Enable) => None,
+        Some(RuleOverride::ForceTrue) => Some(true),
+        Some(RuleOverride::ForceFalse | RuleOverride::Disable) => Some(false),
+    }
+}
+
+/// Resolve a field slot. Slots past the end of `field_values` address the
+/// element bindings of enclosing `ANY`/`ALL` quantifiers, held in `locals`.
+#[inline]
+fn slot<'a>(
+    index: usize,
+    field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
+) -> Option<&'a Value> {
+    match field_values.get(index) {
+        Some(v) => v.as_ref(),
+        None => locals.get(index - field_values.len()).copied().flatten(),
+    }
+}
+
 fn resolve_bound<'a>(
     bound: &'a CompiledBound,
     field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
 ) -> Option<&'a Value> {
     match bound {
         CompiledBound::Literal(v) => Some(v),
-        CompiledBound::FieldIndex(i) => field_values.get(*i).and_then(Option::as_ref),
+        CompiledBound::FieldIndex(i) => slot(*i, field_values, locals),
+    }
+}
+
+/// Look up a path within a quantifier element. The empty path is the element itself.
+pub(crate) fn element_path<'a, K: AsRef<str>>(element: &'a Value, path: &[K]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(element, |value, key| value.get(key.as_ref()))
+}
+
+/// A lookup step with any field-sourced key already read. Public only for
+/// generated code, through the hidden codegen runtime.
+#[derive(Debug, Clone, Copy)]
+pub enum Step<'a> {
+    /// `.key` or `["key"]`: the entry of a map.
+    Key(&'a str),
+    /// `[i]`: the element of a list; negative indices count from the end.
+    Index(i64),
+    /// `[field]`: a map entry or list element named by a field's value. A
+    /// missing value, or one that is neither a string nor an int, finds
+    /// nothing.
+    Dynamic(Option<&'a Value>),
+    /// `[*]`: every element of a list or value of a map.
+    Wildcard,
+}
+
+fn step<'a>(
+    step: &'a CompiledStep,
+    field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
+) -> Step<'a> {
+    match step {
+        CompiledStep::Key(key) => Step::Key(key),
+        CompiledStep::Index(index) => Step::Index(*index),
+        CompiledStep::Dynamic(key_slot) => Step::Dynamic(slot(*key_slot, field_values, locals)),
+        CompiledStep::Wildcard => Step::Wildcard,
+    }
+}
+
+/// Apply a lookup's steps to the value at its base slot. A lookup with a
+/// wildcard step resolves to a list of every value reached.
+fn resolve_lookup<'a>(
+    lookup: &'a CompiledLookup,
+    field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
+) -> Option<Cow<'a, Value>> {
+    let base = slot(lookup.base, field_values, locals)?;
+    apply_steps(base, &lookup.steps, |s| step(s, field_values, locals))
+}
+
+/// Apply lookup `steps` to `base`, reading each step through `step`. A
+/// lookup with a wildcard step resolves to a list of every value reached.
+pub(crate) fn apply_steps<'a, 's, S>(
+    base: &'a Value,
+    steps: &'s [S],
+    step: impl Fn(&'s S) -> Step<'s> + Copy,
+) -> Option<Cow<'a, Value>> {
+    if steps.iter().any(|s| matches!(step(s), Step::Wildcard)) {
+        let mut matches = Vec::new();
+        collect_matches(base, steps, step, &mut matches);
+        return Some(Cow::Owned(Value::List(
+            matches.into_iter().cloned().collect(),
+        )));
+    }
+    steps
+        .iter()
+        .try_fold(base, |current, s| apply_step(current, step(s)))
+        .map(Cow::Borrowed)
+}
+
+/// Apply `steps` to `current`, pushing every value reached. A wildcard fans
+/// out over list elements or map values; branches that miss contribute nothing.
+fn collect_matches<'a, 's, S>(
+    current: &'a Value,
+    steps: &'s [S],
+    step: impl Fn(&'s S) -> Step<'s> + Copy,
+    out: &mut Vec<&'a Value>,
+) {
+    let Some((first, rest)) = steps.split_first() else {
+        out.push(current);
+        return;
+    };
+    match (step(first), current) {
+        (Step::Wildcard, Value::List(items)) => {
+            for item in items {
+                collect_matches(item, rest, step, out);
+            }
+        }
+        (Step::Wildcard, Value::Map(entries)) => {
+            for value in entries.values() {
+                collect_matches(value, rest, step, out);
+            }
+        }
+        (Step::Wildcard, _) => {}
+        (next_step, _) => {
+            if let Some(next) = apply_step(current, next_step) {
+                collect_matches(next, rest, step, out);
+            }
+        }
+    }
+}
+
+/// Apply a single non-wildcard step to `current`.
+fn apply_step<'a>(current: &'a Value, step: Step<'_>) -> Option<&'a Value> {
+    match step {
+        Step::Key(key) => current.get(key),
+        Step::Index(index) => current.get_index(index),
+        Step::Dynamic(key) => match key? {
+            Value::String(key) => current.get(key),
+            Value::Int(index) => current.get_index(*index),
+            _ => None,
+        },
+        Step::Wildcard => None,
     }
 }
 
-fn eval_expr(expr: &CompiledExpr, field_values: &[Option<Value>], results: &[bool]) -> bool {
+/// Extend `locals` so that every slot in `slots` is addressable.
+fn extend_locals<'a>(
+    slots: impl Iterator<Item = usize>,
+    field_values: &[Option<Value>],
+    locals: &[Option<&'a Value>],
+) -> Vec<Option<&'a Value>> {
+    let base = field_values.len();
+    let len = slots
+        .map(|s| s - base + 1)
+        .max()
+        .unwrap_or(0)
+        .max(locals.len());
+    let mut scope = locals.to_vec();
+    scope.resize(len, None);
+    scope
+}
+
+/// Run `eval` once per element of `items`, with each element's bindings
+/// written to a local scope extending `locals`. Stops at the first element
+/// for which `eval` returns `true`, returning `true`.
+fn any_element<'a>(
+    items: &'a [Value],
+    bindings: &[(usize, Vec<String>)],
+    field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
+    mut eval: impl FnMut(&[Option<&'a Value>]) -> bool,
+) -> bool {
+    let base = field_values.len();
+    let mut scope = extend_locals(bindings.iter().map(|(s, _)| *s), field_values, locals);
+    for item in items {
+        for (s, path) in bindings {
+            scope[s - base] = element_path(item, path);
+        }
+        if eval(&scope) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Resolve every lookup of a `Lookup` node. Lookups read only plain slots, so
+/// they resolve independently before any of them is bound.
+fn resolve_lookups<'a>(
+    lookups: &'a [CompiledLookup],
+    field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
+) -> Vec<Option<Cow<'a, Value>>> {
+    lookups
+        .iter()
+        .map(|lookup| resolve_lookup(lookup, field_values, locals))
+        .collect()
+}
+
+/// Extend `locals` with the `resolved` lookup values bound to their slots.
+fn bind_lookups<'b>(
+    lookups: &[CompiledLookup],
+    resolved: &'b [Option<Cow<'_, Value>>],
+    field_values: &[Option<Value>],
+    locals: &[Option<&'b Value>],
+) -> Vec<Option<&'b Value>> {
+    let base = field_values.len();
+    let mut scope = extend_locals(lookups.iter().map(|l| l.slot), field_values, locals);
+    for (lookup, value) in lookups.iter().zip(resolved) {
+        scope[lookup.slot - base] = value.as_deref();
+    }
+    scope
+}
+
+/// Whether `value` equals a member of an `IN` list. A list-valued member
+/// contributes each of its elements; a missing member contributes nothing.
+fn is_member<'a>(
+    value: &Value,
+    members: &'a [CompiledBound],
+    field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
+) -> bool {
+    members
+        .iter()
+        .any(|m| member_matches(value, resolve_bound(m, field_values, locals)))
+}
+
+/// Whether `value` equals `member`, or an element of it if it is a list.
+pub(crate) fn member_matches(value: &Value, member: Option<&Value>) -> bool {
+    match member {
+        Some(Value::List(items)) => items
+            .iter()
+            .any(|item| value.compare(CompareOp::Eq, item) == Some(true)),
+        Some(v) => value.compare(CompareOp::Eq, v) == Some(true),
+        None => false,
+    }
+}
+
+/// `value BETWEEN low AND high`, unknown unless both ends decide it.
+pub(crate) fn between(value: &Value, low: &Value, high: &Value) -> Option<bool> {
+    match (
+        value.compare(CompareOp::Gte, low),
+        value.compare(CompareOp::Lte, high),
+    ) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+/// `value CONTAINS item`, unknown unless `value` is a list.
+pub(crate) fn list_contains(value: &Value, item: &Value) -> Option<bool> {
+    match value {
+        list @ Value::List(_) => Some(list.contains(item)),
+        _ => None,
+    }
+}
+
+/// `len(value) op n`, unknown unless `value` is a list or map.
+pub(crate) fn len_compare(value: &Value, op: CompareOp, n: &Value) -> Option<bool> {
+    let len = match value {
+        Value::List(items) => items.len(),
+        Value::Map(entries) => entries.len(),
+        _ => return None,
+    };
+    Value::Int(i64::try_from(len).ok()?).compare(op, n)
+}
+
+/// `value HAS_KEY key`, unknown unless `value` is a map and `key` a string.
+pub(crate) fn has_key(value: &Value, key: &Value) -> Option<bool> {
+    match (value, key) {
+        (Value::Map(entries), Value::String(k)) => Some(entries.contains_key(k)),
+        _ => None,
+    }
+}
+
+/// Evaluate a leaf test (anything other than a logical combinator, quantifier
+/// or rule reference). `None` means the result is unknown: a field the test
+/// needs is missing or holds a value of the wrong type. Two-valued evaluation
+/// treats that as `false`; three-valued evaluation keeps it as
+/// [`Truth::Unknown`].
+fn eval_leaf<'a>(
+    expr: &'a CompiledExpr,
+    field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
+) -> Option<bool> {
     match expr {
         CompiledExpr::Compare {
             field_index,
             op,
             value,
-        } => field_values
-            .get(*field_index)
-            .and_then(Option::as_ref)
-            .and_then(|ctx_val: &Value| ctx_val.compare(*op, value))
-            .unwrap_or(false),
-        CompiledExpr::And(a, b) => {
-            eval_expr(a, field_values, results) && eval_expr(b, field_values, results)
-        }
-        CompiledExpr::Or(a, b) => {
-            eval_expr(a, field_values, results) || eval_expr(b, field_values, results)
-        }
-        CompiledExpr::Not(inner) => !eval_expr(inner, field_values, results),
-        CompiledExpr::RuleRef(idx) => results[*idx],
+        } => slot(*field_index, field_values, locals)?.compare(*op, value),
         CompiledExpr::In {
             field_index,
             members,
-        } => field_values
-            .get(*field_index)
-            .and_then(Option::as_ref)
-            .is_some_and(|ctx_val| {
-                members
-                    .iter()
-                    .any(|m| match resolve_bound(m, field_values) {
-                        Some(Value::List(items)) => items
-                            .iter()
-                            .any(|item| ctx_val.compare(CompareOp::Eq, item) == Some(true)),
-                        Some(v) => ctx_val.compare(CompareOp::Eq, v) == Some(true),
-                        None => false,
-                    })
-            }),
+        } => {
+            let value = slot(*field_index, field_values, locals)?;
+            Some(is_member(value, members, field_values, locals))
+        }
         CompiledExpr::NotIn {
             field_index,
             members,
-        } => field_values
-            .get(*field_index)
-            .and_then(Option::as_ref)
-            .is_some_and(|ctx_val| {
-                !members
-                    .iter()
-                    .any(|m| match resolve_bound(m, field_values) {
-                        Some(Value::List(items)) => items
-                            .iter()
-                            .any(|item| ctx_val.compare(CompareOp::Eq, item) == Some(true)),
-                        Some(v) => ctx_val.compare(CompareOp::Eq, v) == Some(true),
-                        None => false,
-                    })
-            }),
+        } => {
+            let value = slot(*field_index, field_values, locals)?;
+            Some(!is_member(value, members, field_values, locals))
+        }
         CompiledExpr::Between {
             field_index,
             low,
             high,
         } => {
-            let low_val = resolve_bound(low, field_values);
-            let high_val = resolve_bound(high, field_values);
-            field_values
-                .get(*field_index)
-                .and_then(Option::as_ref)
-                .is_some_and(|ctx_val| {
-                    low_val.is_some_and(|l| ctx_val.compare(CompareOp::Gte, l) == Some(true))
-                        && high_val
-                            .is_some_and(|h| ctx_val.compare(CompareOp::Lte, h) == Some(true))
-                })
+            let value = slot(*field_index, field_values, locals)?;
+            let low = resolve_bound(low, field_values, locals)?;
+            let high = resolve_bound(high, field_values, locals)?;
+            between(value, low, high)
         }
         CompiledExpr::Like {
             field_index,
             pattern,
-        } => match field_values.get(*field_index).and_then(Option::as_ref) {
-            Some(Value::String(s)) => like_match(s, pattern),
-            _ => false,
+        } => match slot(*field_index, field_values, locals)? {
+            Value::String(s) => Some(like_match(s, pattern)),
+            _ => None,
         },
         CompiledExpr::NotLike {
             field_index,
             pattern,
-        } => match field_values.get(*field_index).and_then(Option::as_ref) {
-            Some(Value::String(s)) => !like_match(s, pattern),
-            _ => false,
+        } => match slot(*field_index, field_values, locals)? {
+            Value::String(s) => Some(!like_match(s, pattern)),
+            _ => None,
         },
         CompiledExpr::IsNull(field_index) => {
-            field_values.get(*field_index).is_none_or(Option::is_none)
+            Some(slot(*field_index, field_values, locals).is_none())
         }
         CompiledExpr::IsNotNull(field_index) => {
-            field_values.get(*field_index).is_some_and(Option::is_some)
+            Some(slot(*field_index, field_values, locals).is_some())
         }
         CompiledExpr::CompareFields {
             left_index,
             op,
             right_index,
         } => {
-            let left_val = field_values.get(*left_index).and_then(Option::as_ref);
-            let right_val = field_values.get(*right_index).and_then(Option::as_ref);
-            match (left_val, right_val) {
-                (Some(l), Some(r)) => l.compare(*op, r).unwrap_or(false),
-                _ => false,
-            }
+            let left = slot(*left_index, field_values, locals)?;
+            let right = slot(*right_index, field_values, locals)?;
+            left.compare(*op, right)
+        }
+        CompiledExpr::Contains { field_index, value } => list_contains(
+            slot(*field_index, field_values, locals)?,
+            resolve_bound(value, field_values, locals)?,
+        ),
+        CompiledExpr::SetCompare {
+            field_index,
+            op,
+            value,
+        } => slot(*field_index, field_values, locals)?
+            .set_compare(*op, resolve_bound(value, field_values, locals)?),
+        CompiledExpr::Len {
+            field_index,
+            op,
+            value,
+        } => {
+            let n = resolve_bound(value, field_values, locals)?;
+            len_compare(slot(*field_index, field_values, locals)?, *op, n)
         }
+        CompiledExpr::HasKey { field_index, key } => has_key(
+            slot(*field_index, field_values, locals)?,
+            resolve_bound(key, field_values, locals)?,
+        ),
+        CompiledExpr::And(..)
+        | CompiledExpr::Or(..)
+        | CompiledExpr::Not(_)
+        | CompiledExpr::RuleRef(_)
+        | CompiledExpr::AtLeast { .. }
+        | CompiledExpr::Any { .. }
+        | CompiledExpr::All { .. }
+        | CompiledExpr::Lookup { .. } => unreachable!("not a leaf expression"),
+    }
+}
+
+fn eval_expr<'a>(
+    expr: &'a CompiledExpr,
+    field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
+    results: &[bool],
+) -> bool {
+    match expr {
+        CompiledExpr::And(a, b) => {
+            eval_expr(a, field_values, locals, results)
+                && eval_expr(b, field_values, locals, results)
+        }
+        CompiledExpr::Or(a, b) => {
+            eval_expr(a, field_values, locals, results)
+                || eval_expr(b, field_values, locals, results)
+        }
+        CompiledExpr::Not(inner) => !eval_expr(inner, field_values, locals, results),
+        CompiledExpr::RuleRef(idx) => results[*idx],
         CompiledExpr::AtLeast { n, exprs } => {
             let mut count = 0usize;
             for e in exprs {
-                if eval_expr(e, field_values, results) {
+                if eval_expr(e, field_values, locals, results) {
                     count += 1;
                     if count >= *n {
                         return true;
@@ -222,6 +594,130 @@ fn eval_expr(expr: &CompiledExpr, field_values: &[Option<Value>], results: &[boo
             }
             count >= *n
         }
+        CompiledExpr::Any {
+            list_index,
+            bindings,
+            predicate,
+        } => match slot(*list_index, field_values, locals) {
+            Some(Value::List(items)) => {
+                any_element(items, bindings, field_values, locals, |scope| {
+                    eval_expr(predicate, field_values, scope, results)
+                })
+            }
+            _ => false,
+        },
+        CompiledExpr::All {
+            list_index,
+            bindings,
+            predicate,
+        } => match slot(*list_index, field_values, locals) {
+            Some(Value::List(items)) => {
+                !any_element(items, bindings, field_values, locals, |scope| {
+                    !eval_expr(predicate, field_values, scope, results)
+                })
+            }
+            _ => false,
+        },
+        CompiledExpr::Lookup { lookups, body } => {
+            let resolved = resolve_lookups(lookups, field_values, locals);
+            let scope = bind_lookups(lookups, &resolved, field_values, locals);
+            eval_expr(body, field_values, &scope, results)
+        }
+        leaf => eval_leaf(leaf, field_values, locals).unwrap_or(false),
+    }
+}
+
+/// Three-valued counterpart of [`eval_expr`]: leaves that cannot be decided
+/// are `Unknown`, and combinators follow Kleene logic.
+fn eval_truth<'a>(
+    expr: &'a CompiledExpr,
+    field_values: &'a [Option<Value>],
+    locals: &[Option<&'a Value>],
+    results: &[Truth],
+) -> Truth {
+    match expr {
+        CompiledExpr::And(a, b) => match eval_truth(a, field_values, locals, results) {
+            Truth::False => Truth::False,
+            left => left & eval_truth(b, field_values, locals, results),
+        },
+        CompiledExpr::Or(a, b) => match eval_truth(a, field_values, locals, results) {
+            Truth::True => Truth::True,
+            left => left | eval_truth(b, field_values, locals, results),
+        },
+        CompiledExpr::Not(inner) => !eval_truth(inner, field_values, locals, results),
+        CompiledExpr::RuleRef(idx) => results[*idx],
+        CompiledExpr::AtLeast { n, exprs } => {
+            let (mut known, mut unknown) = (0usize, 0usize);
+            for e in exprs {
+                match eval_truth(e, field_values, locals, results) {
+                    Truth::True => {
+                        known += 1;
+                        if known >= *n {
+                            return Truth::True;
+                        }
+                    }
+                    Truth::Unknown => unknown += 1,
+                    Truth::False => {}
+                }
+            }
+            if known >= *n {
+                Truth::True
+            } else if known + unknown >= *n {
+                Truth::Unknown
+            } else {
+                Truth::False
+            }
+        }
+        CompiledExpr::Any {
+            list_index,
+            bindings,
+            predicate,
+        } => match slot(*list_index, field_values, locals) {
+            Some(Value::List(items)) => {
+                let mut unknown = false;
+                let found = any_element(items, bindings, field_values, locals, |scope| {
+                    let t = eval_truth(predicate, field_values, scope, results);
+                    unknown |= t.is_unknown();
+                    t == Truth::True
+                });
+                if found {
+                    Truth::True
+                } else if unknown {
+                    Truth::Unknown
+                } else {
+                    Truth::False
+                }
+            }
+            _ => Truth::Unknown,
+        },
+        CompiledExpr::All {
+            list_index,
+            bindings,
+            predicate,
+        } => match slot(*list_index, field_values, locals) {
+            Some(Value::List(items)) => {
+                let mut unknown = false;
+                let failed = any_element(items, bindings, field_values, locals, |scope| {
+                    let t = eval_truth(predicate, field_values, scope, results);
+                    unknown |= t.is_unknown();
+                    t == Truth::False
+                });
+                if failed {
+                    Truth::False
+                } else if unknown {
+                    Truth::Unknown
+                } else {
+                    Truth::True
+                }
+            }
+            _ => Truth::Unknown,
+        },
+        CompiledExpr::Lookup { lookups, body } => {
+            let resolved = resolve_lookups(lookups, field_values, locals);
+            let scope = bind_lookups(lookups, &resolved, field_values, locals);
+            eval_truth(body, field_values, &scope, results)
+        }
+        leaf => Truth::from(eval_leaf(leaf, field_values, locals)),
     }
 }
 
@@ -0,0 +1,321 @@
+//! Rendering of the rule dependency graph as Graphviz DOT or Mermaid.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::compile::{collect_field_paths, collect_rule_refs};
+use crate::{EvaluationReport, RuleSet};
+
+/// What [`RuleSet::to_dot_with_options`] and
+/// [`RuleSet::to_mermaid_with_options`] draw besides rules and terminals.
+///
+/// # Example
+/// ```
+/// use ooroo::{Context, GraphOptions, RuleSet};
+///
+/// let ruleset = RuleSet::from_dsl(
+///     "rule adult:\n    user.age >= 18\nrule allow (priority 0):\n    adult",
+/// )
+/// .unwrap();
+/// let report = ruleset.evaluate_detailed(&Context::new().set("user.age", 30_i64));
+/// let dot = ruleset.to_dot_with_options(&GraphOptions::new().fields(true).highlight(&report));
+/// assert!(dot.contains(r#"f0 [label="user.age""#));
+/// assert!(dot.contains(r##"r0 [label="adult", style=filled, fillcolor="#c8f7c5"]"##));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GraphOptions<'r> {
+    fields: bool,
+    highlight: Option<&'r EvaluationReport>,
+}
+
+impl<'r> GraphOptions<'r> {
+    /// Options drawing only rules and terminals.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also draw the context fields each rule reads, with an edge from
+    /// every field to the rules that read it.
+    #[must_use]
+    pub fn fields(mut self, fields: bool) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Fill the rules that evaluated to `true` in `report`.
+    #[must_use]
+    pub fn highlight(mut self, report: &'r EvaluationReport) -> Self {
+        self.highlight = Some(report);
+        self
+    }
+}
+
+/// Fill colour of rules that evaluated to `true`.
+const MATCHED_FILL: &str = "#c8f7c5";
+
+/// The graph both renderers draw. Rules are numbered in dependency order
+/// (ties broken by name) and fields in sorted order, so output is stable.
+struct Graph {
+    rules: Vec<RuleNode>,
+    fields: Vec<String>,
+    /// `(dependency, dependent)` rule indices.
+    rule_edges: Vec<(usize, usize)>,
+    /// `(field, rule)` indices.
+    field_edges: Vec<(usize, usize)>,
+}
+
+struct RuleNode {
+    name: String,
+    priority: Option<u32>,
+    enabled: bool,
+    matched: bool,
+}
+
+impl RuleNode {
+    fn label(&self, line_break: &str) -> String {
+        match self.priority {
+            Some(priority) => format!("{}{line_break}priority {priority}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl Graph {
+    fn new(ruleset: &RuleSet, options: &GraphOptions<'_>) -> Self {
+        let rules = crate::decompile::decompile(ruleset);
+        let priorities: HashMap<&str, u32> = ruleset
+            .terminals
+            .iter()
+            .map(|t| (t.rule_name.as_str(), t.priority))
+            .collect();
+        let matched: HashSet<&str> = options
+            .highlight
+            .map(|report| report.evaluated().iter().map(String::as_str).collect())
+            .unwrap_or_default();
+        let index: HashMap<&str, usize> = rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| (rule.name.as_str(), i))
+            .collect();
+
+        let mut rule_edges = BTreeSet::new();
+        let mut reads = Vec::new();
+        for (i, rule) in rules.iter().enumerate() {
+            let Some(condition) = &rule.condition else {
+                continue;
+            };
+            for dep in collect_rule_refs(condition) {
+                if let Some(&d) = index.get(dep.as_str()) {
+                    rule_edges.insert((d, i));
+                }
+            }
+            if options.fields {
+                reads.extend(collect_field_paths(condition).into_iter().map(|f| (f, i)));
+            }
+        }
+        let fields: Vec<String> = reads
+            .iter()
+            .map(|(field, _)| field.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let field_index: HashMap<&str, usize> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.as_str(), i))
+            .collect();
+        let mut field_edges: Vec<(usize, usize)> = reads
+            .iter()
+            .map(|(field, rule)| (field_index[field.as_str()], *rule))
+            .collect();
+        field_edges.sort_unstable();
+
+        Self {
+            rules: rules
+                .iter()
+                .map(|rule| RuleNode {
+                    name: rule.name.clone(),
+                    priority: priorities.get(rule.name.as_str()).copied(),
+                    enabled: rule.metadata.enabled,
+                    matched: matched.contains(rule.name.as_str()),
+                })
+                .collect(),
+            fields,
+            rule_edges: rule_edges.into_iter().collect(),
+            field_edges,
+        }
+    }
+
+    fn dot(&self) -> String {
+        let mut out = String::from("digraph ruleset {\n    rankdir=LR;\n    node [shape=box];\n");
+        for (i, field) in self.fields.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    f{i} [label={}, shape=ellipse, style=dashed];",
+                dot_string(field)
+            );
+        }
+        for (i, rule) in self.rules.iter().enumerate() {
+            let mut attrs = vec![format!("label={}", dot_string(&rule.label("\n")))];
+            if rule.priority.is_some() {
+                attrs.push("peripheries=2".to_owned());
+            }
+            match (rule.matched, rule.enabled) {
+                (true, _) => {
+                    attrs.push("style=filled".to_owned());
+                    attrs.push(format!("fillcolor=\"{MATCHED_FILL}\""));
+                }
+                (false, false) => attrs.push("style=dashed".to_owned()),
+                (false, true) => {}
+            }
+            let _ = writeln!(out, "    r{i} [{}];", attrs.join(", "));
+        }
+        for (field, rule) in &self.field_edges {
+            let _ = writeln!(out, "    f{field} -> r{rule} [style=dashed];");
+        }
+        for (dep, rule) in &self.rule_edges {
+            let _ = writeln!(out, "    r{dep} -> r{rule};");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for (i, field) in self.fields.iter().enumerate() {
+            let _ = writeln!(out, "    f{i}([{}])", mermaid_string(field));
+        }
+        for (i, rule) in self.rules.iter().enumerate() {
+            let label = mermaid_string(&rule.label("<br/>"));
+            let _ = if rule.priority.is_some() {
+                writeln!(out, "    r{i}[[{label}]]")
+            } else {
+                writeln!(out, "    r{i}[{label}]")
+            };
+        }
+        for (field, rule) in &self.field_edges {
+            let _ = writeln!(out, "    f{field} -.-> r{rule}");
+        }
+        for (dep, rule) in &self.rule_edges {
+            let _ = writeln!(out, "    r{dep} --> r{rule}");
+        }
+        let fill = format!("fill:{MATCHED_FILL}");
+        self.mermaid_class(&mut out, "matched", &fill, |rule| rule.matched);
+        self.mermaid_class(&mut out, "disabled", "stroke-dasharray:4 4", |rule| {
+            !rule.matched && !rule.enabled
+        });
+        out
+    }
+
+    /// Define a Mermaid class and assign it to the rules `pick` selects.
+    fn mermaid_class(
+        &self,
+        out: &mut String,
+        name: &str,
+        style: &str,
+        pick: impl Fn(&RuleNode) -> bool,
+    ) {
+        let ids: Vec<String> = (0..self.rules.len())
+            .filter(|&i| pick(&self.rules[i]))
+            .map(|i| format!("r{i}"))
+            .collect();
+        if !ids.is_empty() {
+            let _ = writeln!(out, "    classDef {name} {style}");
+            let _ = writeln!(out, "    class {} {name}", ids.join(","));
+        }
+    }
+}
+
+/// A quoted DOT string.
+fn dot_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// A quoted Mermaid label. Mermaid has no backslash escapes; quotes are
+/// written as the `#quot;` entity.
+fn mermaid_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "#quot;"))
+}
+
+impl RuleSet {
+    /// Render the rule dependency graph in Graphviz DOT.
+    ///
+    /// Each rule is a box with an edge to every rule that references it;
+    /// terminals are drawn with a double border and their priority, and
+    /// disabled rules with a dashed border. See
+    /// [`to_dot_with_options`](Self::to_dot_with_options) to add fields and
+    /// highlight an evaluation.
+    ///
+    /// # Example
+    /// ```
+    /// use ooroo::RuleSet;
+    ///
+    /// let ruleset = RuleSet::from_dsl(
+    ///     "rule adult:\n    user.age >= 18\nrule allow (priority 0):\n    adult",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     ruleset.to_dot(),
+    ///     "digraph ruleset {\n    rankdir=LR;\n    node [shape=box];\n    \
+    ///      r0 [label=\"adult\"];\n    \
+    ///      r1 [label=\"allow\\npriority 0\", peripheries=2];\n    \
+    ///      r0 -> r1;\n}\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with_options(&GraphOptions::new())
+    }
+
+    /// Render the rule dependency graph in Graphviz DOT, with the fields
+    /// each rule reads and evaluation highlighting as set in `options`.
+    #[must_use]
+    pub fn to_dot_with_options(&self, options: &GraphOptions<'_>) -> String {
+        Graph::new(self, options).dot()
+    }
+
+    /// Render the rule dependency graph as a Mermaid flowchart, for
+    /// embedding in Markdown.
+    ///
+    /// The layout matches [`to_dot`](Self::to_dot): terminals use the
+    /// subroutine shape with their priority, and disabled rules and matched
+    /// rules get the `disabled` and `matched` classes.
+    ///
+    /// # Example
+    /// ```
+    /// use ooroo::RuleSet;
+    ///
+    /// let ruleset = RuleSet::from_dsl(
+    ///     "rule adult:\n    user.age >= 18\nrule allow (priority 0):\n    adult",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     ruleset.to_mermaid(),
+    ///     "flowchart LR\n    r0[\"adult\"]\n    r1[[\"allow<br/>priority 0\"]]\n    r0 --> r1\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        self.to_mermaid_with_options(&GraphOptions::new())
+    }
+
+    /// Render the rule dependency graph as a Mermaid flowchart, with the
+    /// fields each rule reads and evaluation highlighting as set in
+    /// `options`.
+    #[must_use]
+    pub fn to_mermaid_with_options(&self, options: &GraphOptions<'_>) -> String {
+        Graph::new(self, options).mermaid()
+    }
+}
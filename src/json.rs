@@ -0,0 +1,140 @@
+//! JSON conversions for [`Value`] and [`Context`], enabled by the `json` feature.
+
+use serde_json::Number;
+
+use crate::{Context, Value};
+
+impl Value {
+    /// Convert a JSON value.
+    ///
+    /// Objects become [`Value::Map`] and arrays [`Value::List`]. Integers that
+    /// fit in an `i64` become [`Value::Int`]; all other numbers become
+    /// [`Value::Float`].
+    ///
+    /// `null` has no `Value` counterpart: a top-level `null` returns `None`,
+    /// object members holding `null` are omitted (so `IS NULL` sees them as
+    /// absent), and `null` array elements are dropped.
+    #[must_use]
+    pub fn from_json(json: &serde_json::Value) -> Option<Value> {
+        match json {
+            serde_json::Value::Null => None,
+            serde_json::Value::Bool(b) => Some(Value::Bool(*b)),
+            serde_json::Value::Number(n) => Some(match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+            }),
+            serde_json::Value::String(s) => Some(Value::String(s.clone())),
+            serde_json::Value::Array(items) => Some(Value::List(
+                items.iter().filter_map(Value::from_json).collect(),
+            )),
+            serde_json::Value::Object(members) => Some(Value::Map(
+                members
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.clone(), Value::from_json(v)?)))
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Convert to a JSON value. Non-finite floats become `null`.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Int(i) => serde_json::Value::from(*i),
+            Value::Float(f) => {
+                Number::from_f64(*f).map_or(serde_json::Value::Null, serde_json::Value::Number)
+            }
+            Value::Bool(b) => serde_json::Value::Bool(*b),
+            Value::String(s) => serde_json::Value::String(s.clone()),
+            Value::List(items) => {
+                serde_json::Value::Array(items.iter().map(Value::to_json).collect())
+            }
+            Value::Map(entries) => serde_json::Value::Object(
+                entries
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.to_json()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Context {
+    /// Build a context from a JSON object.
+    ///
+    /// Each top-level member is stored under its key (split on `.` like
+    /// [`Context::set`]), with nested objects kept as [`Value::Map`]s. Rules
+    /// reach into them by dotted path (`user.profile.age`) or by index
+    /// (`limits[user.country]`). Members are converted with
+    /// [`Value::from_json`]. Returns `None` if `json` is not an object.
+    ///
+    /// # Example
+    /// ```
+    /// use ooroo::{Context, Value};
+    ///
+    /// let json = serde_json::json!({"user": {"age": 30, "country": "US"}});
+    /// let ctx = Context::from_json(&json).unwrap();
+    /// assert_eq!(ctx.get("user.age"), Some(&Value::Int(30)));
+    /// ```
+    #[must_use]
+    pub fn from_json(json: &serde_json::Value) -> Option<Context> {
+        let serde_json::Value::Object(members) = json else {
+            return None;
+        };
+        let mut ctx = Context::new();
+        for (key, member) in members {
+            if let Some(value) = Value::from_json(member) {
+                ctx.insert(key, value);
+            }
+        }
+        Some(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn scalars_from_json() {
+        assert_eq!(Value::from_json(&json!(42)), Some(Value::Int(42)));
+        assert_eq!(Value::from_json(&json!(2.5)), Some(Value::Float(2.5)));
+        assert_eq!(
+            Value::from_json(&json!(u64::MAX)),
+            Some(Value::Float(u64::MAX as f64))
+        );
+        assert_eq!(Value::from_json(&json!(true)), Some(Value::Bool(true)));
+        assert_eq!(Value::from_json(&json!("x")), Some(Value::from("x")));
+        assert_eq!(Value::from_json(&json!(null)), None);
+    }
+
+    #[test]
+    fn nested_from_json_drops_nulls() {
+        let value = Value::from_json(&json!({"a": [1, null, {"b": null}], "c": null})).unwrap();
+        let expected = Value::Map(BTreeMap::from([(
+            "a".to_owned(),
+            Value::List(vec![Value::Int(1), Value::Map(BTreeMap::new())]),
+        )]));
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn to_json_round_trip() {
+        let original = json!({"limits": {"US": 100, "EU": 50.5}, "tags": ["a", true]});
+        let value = Value::from_json(&original).unwrap();
+        assert_eq!(value.to_json(), original);
+        assert_eq!(Value::Float(f64::NAN).to_json(), json!(null));
+    }
+
+    #[test]
+    fn context_from_json() {
+        let ctx = Context::from_json(&json!({"user": {"age": 30}, "flag": true})).unwrap();
+        assert_eq!(ctx.get("user.age"), Some(&Value::Int(30)));
+        assert_eq!(ctx.get("flag"), Some(&Value::Bool(true)));
+        assert!(Context::from_json(&json!([1, 2])).is_none());
+    }
+}
@@ -0,0 +1,711 @@
+//! Conversion between [`Expr`] and [JsonLogic](https://jsonlogic.com),
+//! enabled by the `json` feature.
+
+use serde_json::{json, Map};
+use thiserror::Error;
+
+use crate::types::path::{is_segment, quote_key, FieldPath, PathStep};
+use crate::{Bound, CompareOp, Expr, Value};
+
+/// Errors converting between [`Expr`] and JsonLogic.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JsonLogicError {
+    /// The document uses a JsonLogic operator with no `Expr` counterpart.
+    #[error("unsupported JsonLogic operator '{0}'")]
+    UnsupportedOperator(String),
+
+    /// The expression uses a construct JsonLogic cannot express.
+    #[error("cannot express {0} in JsonLogic")]
+    Unsupported(String),
+
+    /// The document is not a well-formed condition.
+    #[error("invalid JsonLogic: {0}")]
+    Invalid(String),
+}
+
+impl Expr {
+    /// Convert a [JsonLogic](https://jsonlogic.com) rule into an expression.
+    ///
+    /// | JsonLogic                         | `Expr`                                   |
+    /// |-----------------------------------|------------------------------------------|
+    /// | `==`, `===`, `!=`, `!==`          | `Compare`, or `CompareFields` for two `var`s |
+    /// | `<`, `<=`, `>`, `>=`              | `Compare`, or `CompareFields` for two `var`s |
+    /// | `==` / `!=` against `null`        | `IsNull` / `IsNotNull`                   |
+    /// | `<=` / `<` with three operands    | `Between` / two `Compare`s               |
+    /// | `and`, `or`, `!`                  | `And`, `Or`, `Not`                       |
+    /// | `in` with an array                | `In` (members may be `var`s)             |
+    /// | `in` with a `var` list            | `Contains`                               |
+    /// | `some`, `none`                    | `Any`, `NOT Any`                         |
+    /// | `all`                             | `len(list) >= 1 AND All`                 |
+    /// | `var` on its own                  | `field == true`                          |
+    ///
+    /// JsonLogic's `all` is false for an empty list, hence the length check.
+    /// Numeric `var` segments are list positions, so `items.0.sku` becomes
+    /// `items[0].sku`. Inside `some`, `all` and `none`, `var` reads the
+    /// current element, which is bound to a variable named `item<depth>`.
+    ///
+    /// Results agree with JsonLogic whenever the fields a rule reads are
+    /// present with the types it expects; JsonLogic's type coercion makes
+    /// missing and mistyped fields behave differently.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonLogicError::UnsupportedOperator`] for operators outside
+    /// the table, including arithmetic, string operators, `var` defaults and
+    /// `in` on strings, and [`JsonLogicError::Invalid`] for malformed
+    /// documents, such as a comparison between two literals.
+    ///
+    /// # Example
+    /// ```
+    /// use ooroo::{field, Expr};
+    /// use serde_json::json;
+    ///
+    /// let logic = json!({"and": [
+    ///     {">=": [{"var": "user.age"}, 18]},
+    ///     {"in": [{"var": "user.country"}, ["US", "CA"]]}
+    /// ]});
+    /// let expr = Expr::from_json_logic(&logic).unwrap();
+    /// assert_eq!(
+    ///     expr,
+    ///     field("user.age").gte(18_i64).and(field("user.country").is_in(["US", "CA"]))
+    /// );
+    /// ```
+    pub fn from_json_logic(logic: &serde_json::Value) -> Result<Expr, JsonLogicError> {
+        Importer { scope: None }.condition(logic)
+    }
+
+    /// Convert this expression into a JsonLogic rule.
+    ///
+    /// The inverse of [`Expr::from_json_logic`]. Equality exports as the
+    /// strict `===` and `!==`, `NOT IN` as `!` applied to `in`, and `All` as
+    /// `none` of the negated predicate, since JsonLogic's `all` is false for
+    /// an empty list. An imported `all` exports as `all` again.
+    ///
+    /// `LIKE`, `AT_LEAST`, set operators, `len()`, `HAS_KEY`, rule
+    /// references, dynamic, wildcard and negative-index paths, and
+    /// quantifier predicates that read fields outside the element have no
+    /// JsonLogic equivalent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonLogicError::Unsupported`] naming the first construct
+    /// JsonLogic cannot express.
+    ///
+    /// # Example
+    /// ```
+    /// use ooroo::field;
+    /// use serde_json::json;
+    ///
+    /// let expr = field("user.age").gte(18_i64).and(!field("user.banned").eq(true));
+    /// assert_eq!(
+    ///     expr.to_json_logic().unwrap(),
+    ///     json!({"and": [
+    ///         {">=": [{"var": "user.age"}, 18]},
+    ///         {"!": [{"===": [{"var": "user.banned"}, true]}]}
+    ///     ]})
+    /// );
+    /// ```
+    pub fn to_json_logic(&self) -> Result<serde_json::Value, JsonLogicError> {
+        Exporter { scope: None }.expr(self)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Import
+// ---------------------------------------------------------------------------
+
+/// One side of a JsonLogic comparison.
+enum Operand {
+    Field(String),
+    Literal(Value),
+    Null,
+}
+
+/// Converts JsonLogic to expressions. `scope` names the variable bound to
+/// the current element inside `some`/`all`/`none`, against which `var`
+/// paths resolve.
+struct Importer<'s> {
+    scope: Option<(&'s str, usize)>,
+}
+
+impl Importer<'_> {
+    fn condition(&self, logic: &serde_json::Value) -> Result<Expr, JsonLogicError> {
+        let (op, args) = split_operation(logic)?;
+        match op {
+            "==" | "===" => self.compare(CompareOp::Eq, args),
+            "!=" | "!==" => self.compare(CompareOp::Neq, args),
+            "<" | "<=" | ">" | ">=" => self.ordering(op, args),
+            "and" | "or" => {
+                let mut exprs = args.iter().map(|arg| self.condition(arg));
+                let first = exprs.next().ok_or_else(|| {
+                    JsonLogicError::Invalid(format!("'{op}' needs at least one operand"))
+                })??;
+                exprs.try_fold(first, |acc, next| {
+                    Ok(if op == "and" {
+                        acc.and(next?)
+                    } else {
+                        acc.or(next?)
+                    })
+                })
+            }
+            "!" => Ok(!self.condition(single(op, args)?)?),
+            "in" => self.membership(args),
+            "some" | "all" | "none" => self.quantifier(op, args),
+            "var" => Ok(Expr::Compare {
+                field: self.var(logic)?,
+                op: CompareOp::Eq,
+                value: Value::Bool(true),
+            }),
+            other => Err(JsonLogicError::UnsupportedOperator(other.to_owned())),
+        }
+    }
+
+    fn compare(&self, op: CompareOp, args: &[serde_json::Value]) -> Result<Expr, JsonLogicError> {
+        let [left, right] = args else {
+            return Err(arity("comparison", 2, args.len()));
+        };
+        let null_test = |path| match op {
+            CompareOp::Eq => Ok(Expr::IsNull(path)),
+            _ => Ok(Expr::IsNotNull(path)),
+        };
+        match (self.operand(left)?, self.operand(right)?) {
+            (Operand::Field(path), Operand::Null) | (Operand::Null, Operand::Field(path)) => {
+                null_test(path)
+            }
+            (left, right) => compare_operands(op, left, right),
+        }
+    }
+
+    fn ordering(&self, op: &str, args: &[serde_json::Value]) -> Result<Expr, JsonLogicError> {
+        let op_of = |symbol| match symbol {
+            "<" => CompareOp::Lt,
+            "<=" => CompareOp::Lte,
+            ">" => CompareOp::Gt,
+            _ => CompareOp::Gte,
+        };
+        match args {
+            [left, right] => compare_operands(op_of(op), self.operand(left)?, self.operand(right)?),
+            [low, middle, high] if matches!(op, "<" | "<=") => {
+                let Operand::Field(path) = self.operand(middle)? else {
+                    return Err(JsonLogicError::Invalid(format!(
+                        "the middle operand of a three-way '{op}' must be a var"
+                    )));
+                };
+                let (low, high) = (self.operand(low)?, self.operand(high)?);
+                if op == "<=" {
+                    return Ok(Expr::Between {
+                        field: path,
+                        low: bound(low)?,
+                        high: bound(high)?,
+                    });
+                }
+                let field = || Operand::Field(path.clone());
+                Ok(
+                    compare_operands(CompareOp::Lt, low, field())?.and(compare_operands(
+                        CompareOp::Lt,
+                        field(),
+                        high,
+                    )?),
+                )
+            }
+            _ => Err(arity(op, 2, args.len())),
+        }
+    }
+
+    fn membership(&self, args: &[serde_json::Value]) -> Result<Expr, JsonLogicError> {
+        let [needle, haystack] = args else {
+            return Err(arity("in", 2, args.len()));
+        };
+        if let serde_json::Value::Array(members) = haystack {
+            let Operand::Field(field) = self.operand(needle)? else {
+                return Err(JsonLogicError::Invalid(
+                    "'in' with an array needs a var to look for".to_owned(),
+                ));
+            };
+            let members = members
+                .iter()
+                .map(|member| bound(self.operand(member)?))
+                .collect::<Result<_, _>>()?;
+            return Ok(Expr::In { field, members });
+        }
+        match (self.operand(needle)?, self.operand(haystack)?) {
+            (_, Operand::Literal(Value::String(_))) => Err(JsonLogicError::UnsupportedOperator(
+                "in (substring match)".to_owned(),
+            )),
+            (needle, Operand::Field(field)) => Ok(Expr::Contains {
+                field,
+                value: bound(needle)?,
+            }),
+            _ => Err(JsonLogicError::Invalid(
+                "'in' needs an array or a var to search".to_owned(),
+            )),
+        }
+    }
+
+    fn quantifier(&self, op: &str, args: &[serde_json::Value]) -> Result<Expr, JsonLogicError> {
+        let [list, predicate] = args else {
+            return Err(arity(op, 2, args.len()));
+        };
+        let Operand::Field(list) = self.operand(list)? else {
+            return Err(JsonLogicError::Invalid(format!(
+                "'{op}' must iterate over a var"
+            )));
+        };
+        let depth = self.scope.map_or(0, |(_, depth)| depth + 1);
+        let var = format!("item{depth}");
+        let predicate = Box::new(
+            Importer {
+                scope: Some((&var, depth)),
+            }
+            .condition(predicate)?,
+        );
+        Ok(match op {
+            "some" => Expr::Any {
+                list,
+                var,
+                predicate,
+            },
+            "none" => !Expr::Any {
+                list,
+                var,
+                predicate,
+            },
+            _ => non_empty(&list).and(Expr::All {
+                list,
+                var,
+                predicate,
+            }),
+        })
+    }
+
+    fn operand(&self, logic: &serde_json::Value) -> Result<Operand, JsonLogicError> {
+        match logic {
+            serde_json::Value::Null => Ok(Operand::Null),
+            serde_json::Value::Object(map) if map.contains_key("var") => {
+                self.var(logic).map(Operand::Field)
+            }
+            serde_json::Value::Object(map) => match map.keys().next() {
+                Some(op) if map.len() == 1 => Err(JsonLogicError::UnsupportedOperator(op.clone())),
+                _ => Err(JsonLogicError::Invalid(
+                    "operands must be literals or single-operator objects".to_owned(),
+                )),
+            },
+            literal => Value::from_json(literal)
+                .map(Operand::Literal)
+                .ok_or_else(|| JsonLogicError::Invalid("null inside a literal".to_owned())),
+        }
+    }
+
+    /// The field path read by a `{"var": ...}` object.
+    fn var(&self, logic: &serde_json::Value) -> Result<String, JsonLogicError> {
+        let (_, args) = split_operation(logic)?;
+        let path = match args {
+            [serde_json::Value::String(path)] => path.as_str(),
+            [serde_json::Value::String(_), _] => {
+                return Err(JsonLogicError::UnsupportedOperator(
+                    "var with a default".to_owned(),
+                ))
+            }
+            _ => {
+                return Err(JsonLogicError::Invalid(
+                    "var takes a path string".to_owned(),
+                ))
+            }
+        };
+        let dotted = match (self.scope, path) {
+            (Some((var, _)), "") => var.to_owned(),
+            (Some((var, _)), path) => format!("{var}.{path}"),
+            (None, "") => {
+                return Err(JsonLogicError::Invalid(
+                    "an empty var path reads the whole context".to_owned(),
+                ))
+            }
+            (None, path) => path.to_owned(),
+        };
+        Ok(import_path(&dotted))
+    }
+}
+
+/// Split a `{"op": args}` object into its operator and argument list. A
+/// single non-array argument is treated as a one-element list.
+fn split_operation(
+    logic: &serde_json::Value,
+) -> Result<(&str, &[serde_json::Value]), JsonLogicError> {
+    let serde_json::Value::Object(map) = logic else {
+        return Err(JsonLogicError::Invalid(format!(
+            "expected an operation, found {logic}"
+        )));
+    };
+    let mut entries = map.iter();
+    let (Some((op, args)), None) = (entries.next(), entries.next()) else {
+        return Err(JsonLogicError::Invalid(
+            "an operation is an object with exactly one key".to_owned(),
+        ));
+    };
+    let args = match args {
+        serde_json::Value::Array(args) => args.as_slice(),
+        single => std::slice::from_ref(single),
+    };
+    Ok((op.as_str(), args))
+}
+
+fn single<'a>(
+    op: &str,
+    args: &'a [serde_json::Value],
+) -> Result<&'a serde_json::Value, JsonLogicError> {
+    match args {
+        [arg] => Ok(arg),
+        _ => Err(arity(op, 1, args.len())),
+    }
+}
+
+fn arity(op: &str, expected: usize, actual: usize) -> JsonLogicError {
+    JsonLogicError::Invalid(format!("'{op}' takes {expected} operands, found {actual}"))
+}
+
+fn compare_operands(op: CompareOp, left: Operand, right: Operand) -> Result<Expr, JsonLogicError> {
+    match (left, right) {
+        (Operand::Field(left), Operand::Field(right)) => {
+            Ok(Expr::CompareFields { left, op, right })
+        }
+        (Operand::Field(field), Operand::Literal(value)) => Ok(Expr::Compare { field, op, value }),
+        (Operand::Literal(value), Operand::Field(field)) => Ok(Expr::Compare {
+            field,
+            op: flip(op),
+            value,
+        }),
+        (Operand::Null, _) | (_, Operand::Null) => Err(JsonLogicError::Invalid(
+            "only '==' and '!=' compare against null".to_owned(),
+        )),
+        (Operand::Literal(_), Operand::Literal(_)) => Err(JsonLogicError::Invalid(
+            "a comparison needs at least one var".to_owned(),
+        )),
+    }
+}
+
+/// The operator that holds with the operands swapped.
+fn flip(op: CompareOp) -> CompareOp {
+    match op {
+        CompareOp::Gt => CompareOp::Lt,
+        CompareOp::Gte => CompareOp::Lte,
+        CompareOp::Lt => CompareOp::Gt,
+        CompareOp::Lte => CompareOp::Gte,
+        CompareOp::Eq | CompareOp::Neq => op,
+    }
+}
+
+fn bound(operand: Operand) -> Result<Bound, JsonLogicError> {
+    match operand {
+        Operand::Field(path) => Ok(Bound::Field(path)),
+        Operand::Literal(value) => Ok(Bound::Literal(value)),
+        Operand::Null => Err(JsonLogicError::Invalid(
+            "null cannot be a range or membership bound".to_owned(),
+        )),
+    }
+}
+
+/// `len(list) >= 1`, which JsonLogic's `all` requires.
+fn non_empty(list: &str) -> Expr {
+    Expr::Len {
+        field: list.to_owned(),
+        op: CompareOp::Gte,
+        value: Bound::Literal(Value::Int(1)),
+    }
+}
+
+/// Turn a JsonLogic dotted path into a field path, with numeric segments as
+/// list indices: `items.0.sku` becomes `items[0].sku`.
+fn import_path(dotted: &str) -> String {
+    let mut segments = dotted.split('.');
+    let mut path = segments.next().unwrap_or_default().to_owned();
+    let mut indexed = false;
+    for segment in segments {
+        if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+            path.push_str(&format!("[{segment}]"));
+            indexed = true;
+        } else if indexed && !is_segment(segment) {
+            path.push_str(&quote_key(segment));
+        } else {
+            path.push('.');
+            path.push_str(segment);
+        }
+    }
+    path
+}
+
+// ---------------------------------------------------------------------------
+// Export
+// ---------------------------------------------------------------------------
+
+/// Converts expressions to JsonLogic. `scope` is the quantifier variable
+/// bound to the current element, the only root JsonLogic can read there.
+struct Exporter<'s> {
+    scope: Option<&'s str>,
+}
+
+impl Exporter<'_> {
+    fn expr(&self, expr: &Expr) -> Result<serde_json::Value, JsonLogicError> {
+        if let Some(all) = self.guarded_all(expr) {
+            return all;
+        }
+        Ok(match expr {
+            Expr::Compare { field, op, value } => {
+                if matches!(value, Value::List(_) | Value::Map(_)) {
+                    return Err(JsonLogicError::Unsupported(
+                        "comparisons against lists or maps".to_owned(),
+                    ));
+                }
+                operation(symbol(*op), vec![self.var(field)?, value.to_json()])
+            }
+            Expr::CompareFields { left, op, right } => {
+                operation(symbol(*op), vec![self.var(left)?, self.var(right)?])
+            }
+            Expr::And(..) | Expr::Or(..) => {
+                let and = matches!(expr, Expr::And(..));
+                let mut operands = Vec::new();
+                self.chain(expr, and, &mut operands)?;
+                operation(if and { "and" } else { "or" }, operands)
+            }
+            Expr::Not(inner) => operation("!", vec![self.expr(inner)?]),
+            Expr::In { field, members } => self.membership(field, members)?,
+            Expr::NotIn { field, members } => {
+                operation("!", vec![self.membership(field, members)?])
+            }
+            Expr::Between { field, low, high } => operation(
+                "<=",
+                vec![self.bound(low)?, self.var(field)?, self.bound(high)?],
+            ),
+            Expr::IsNull(field) => operation("==", vec![self.var(field)?, json!(null)]),
+            Expr::IsNotNull(field) => operation("!=", vec![self.var(field)?, json!(null)]),
+            Expr::Any {
+                list,
+                var,
+                predicate,
+            } => operation("some", self.quantified(list, var, predicate, false)?),
+            Expr::All {
+                list,
+                var,
+                predicate,
+            } => operation("none", self.quantified(list, var, predicate, true)?),
+            Expr::Contains { field, value } => {
+                operation("in", vec![self.bound(value)?, self.var(field)?])
+            }
+            Expr::Like { .. } | Expr::NotLike { .. } => return Err(unsupported("LIKE")),
+            Expr::AtLeast { .. } => return Err(unsupported("AT_LEAST")),
+            Expr::SetCompare { .. } => return Err(unsupported("set operators")),
+            Expr::Len { .. } => return Err(unsupported("len()")),
+            Expr::HasKey { .. } => return Err(unsupported("HAS_KEY")),
+            Expr::RuleRef(name) => {
+                return Err(JsonLogicError::Unsupported(format!(
+                    "the reference to rule '{name}'"
+                )))
+            }
+        })
+    }
+
+    /// Export the `len(list) >= 1 AND ALL(list ...)` pair that an imported
+    /// `all` becomes as `all` again.
+    fn guarded_all(&self, expr: &Expr) -> Option<Result<serde_json::Value, JsonLogicError>> {
+        let Expr::And(guard, all) = expr else {
+            return None;
+        };
+        let Expr::All {
+            list,
+            var,
+            predicate,
+        } = all.as_ref()
+        else {
+            return None;
+        };
+        if **guard != non_empty(list) {
+            return None;
+        }
+        Some(
+            self.quantified(list, var, predicate, false)
+                .map(|args| operation("all", args)),
+        )
+    }
+
+    /// Flatten a chain of `And` (or `Or`) nodes into one operand list.
+    fn chain(
+        &self,
+        expr: &Expr,
+        and: bool,
+        out: &mut Vec<serde_json::Value>,
+    ) -> Result<(), JsonLogicError> {
+        match expr {
+            Expr::And(left, right) | Expr::Or(left, right)
+                if matches!(expr, Expr::And(..)) == and && self.guarded_all(expr).is_none() =>
+            {
+                self.chain(left, and, out)?;
+                self.chain(right, and, out)
+            }
+            other => {
+                out.push(self.expr(other)?);
+                Ok(())
+            }
+        }
+    }
+
+    fn membership(
+        &self,
+        field: &str,
+        members: &[Bound],
+    ) -> Result<serde_json::Value, JsonLogicError> {
+        let members = members
+            .iter()
+            .map(|member| self.bound(member))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(operation(
+            "in",
+            vec![self.var(field)?, serde_json::Value::Array(members)],
+        ))
+    }
+
+    /// The `[list, predicate]` operands of `some`, `all` or `none`, with the
+    /// predicate negated for `none`.
+    fn quantified(
+        &self,
+        list: &str,
+        var: &str,
+        predicate: &Expr,
+        negate: bool,
+    ) -> Result<Vec<serde_json::Value>, JsonLogicError> {
+        let inner = Exporter { scope: Some(var) }.expr(predicate)?;
+        let predicate = if negate {
+            operation("!", vec![inner])
+        } else {
+            inner
+        };
+        Ok(vec![self.var(list)?, predicate])
+    }
+
+    fn bound(&self, bound: &Bound) -> Result<serde_json::Value, JsonLogicError> {
+        match bound {
+            Bound::Field(path) => self.var(path),
+            Bound::Literal(value @ (Value::List(_) | Value::Map(_))) => Err(
+                JsonLogicError::Unsupported(format!("the collection literal {value}")),
+            ),
+            Bound::Literal(value) => Ok(value.to_json()),
+        }
+    }
+
+    fn var(&self, path: &str) -> Result<serde_json::Value, JsonLogicError> {
+        let parsed = FieldPath::parse(path).map_err(|reason| {
+            JsonLogicError::Invalid(format!("malformed path '{path}': {reason}"))
+        })?;
+        let mut segments: Vec<String> = parsed.root.split('.').map(str::to_owned).collect();
+        for step in parsed.steps {
+            segments.push(match step {
+                PathStep::Key(key) if !key.contains('.') => key,
+                PathStep::Index(index) if index >= 0 => index.to_string(),
+                _ => {
+                    return Err(JsonLogicError::Unsupported(format!(
+                        "the path '{path}' (only keys and non-negative indices)"
+                    )))
+                }
+            });
+        }
+        if let Some(scope) = self.scope {
+            if segments[0] != scope {
+                return Err(JsonLogicError::Unsupported(format!(
+                    "reading '{path}' inside a quantifier over '{scope}'"
+                )));
+            }
+            segments.remove(0);
+        }
+        Ok(json!({"var": segments.join(".")}))
+    }
+}
+
+fn operation(op: &str, operands: Vec<serde_json::Value>) -> serde_json::Value {
+    let mut map = Map::new();
+    map.insert(op.to_owned(), serde_json::Value::Array(operands));
+    serde_json::Value::Object(map)
+}
+
+fn symbol(op: CompareOp) -> &'static str {
+    match op {
+        CompareOp::Eq => "===",
+        CompareOp::Neq => "!==",
+        CompareOp::Gt => ">",
+        CompareOp::Gte => ">=",
+        CompareOp::Lt => "<",
+        CompareOp::Lte => "<=",
+    }
+}
+
+fn unsupported(construct: &str) -> JsonLogicError {
+    JsonLogicError::Unsupported(construct.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_segments_become_indices() {
+        assert_eq!(import_path("user.age"), "user.age");
+        assert_eq!(import_path("items.0.sku"), "items[0].sku");
+        assert_eq!(
+            import_path("items.0.first name"),
+            "items[0][\"first name\"]"
+        );
+    }
+
+    #[test]
+    fn paths_export_with_dots() {
+        let exporter = Exporter { scope: None };
+        assert_eq!(
+            exporter.var("items[0].sku").unwrap(),
+            json!({"var": "items.0.sku"})
+        );
+        assert_eq!(
+            exporter.var("limits[\"US\"]").unwrap(),
+            json!({"var": "limits.US"})
+        );
+        for path in [
+            "items[-1]",
+            "items[*].sku",
+            "limits[user.country]",
+            "m[\"a.b\"]",
+        ] {
+            assert!(
+                matches!(exporter.var(path), Err(JsonLogicError::Unsupported(_))),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn scoped_paths_drop_the_variable() {
+        let exporter = Exporter {
+            scope: Some("item0"),
+        };
+        assert_eq!(exporter.var("item0").unwrap(), json!({"var": ""}));
+        assert_eq!(
+            exporter.var("item0.price").unwrap(),
+            json!({"var": "price"})
+        );
+        assert!(exporter.var("user.age").is_err());
+    }
+
+    #[test]
+    fn flipped_operators_hold_with_swapped_operands() {
+        for op in [
+            CompareOp::Eq,
+            CompareOp::Neq,
+            CompareOp::Gt,
+            CompareOp::Gte,
+            CompareOp::Lt,
+            CompareOp::Lte,
+        ] {
+            for (a, b) in [(1, 2), (2, 1), (2, 2)] {
+                assert_eq!(
+                    Value::Int(a).compare(op, &Value::Int(b)),
+                    Value::Int(b).compare(flip(op), &Value::Int(a))
+                );
+            }
+        }
+    }
+}
@@ -50,20 +50,46 @@
 //! let result = ruleset.evaluate_indexed(&ctx);
 //! ```
 
+mod codegen;
 mod compile;
+mod decompile;
 mod error;
 mod evaluate;
+mod graph;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+mod json_logic;
 pub(crate) mod parse;
 #[cfg(feature = "binary-cache")]
 pub(crate) mod serial;
+mod sql;
 mod types;
+#[cfg(feature = "binary-cache")]
+mod view;
 
+#[doc(hidden)]
+pub use codegen::runtime as __codegen;
+pub use codegen::Codegen;
+#[cfg(feature = "signing")]
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
 pub use error::OorooError;
-pub use parse::ParseError;
+pub use graph::GraphOptions;
+#[cfg(feature = "json")]
+pub use json_logic::JsonLogicError;
+pub use parse::{
+    format_dsl, is_formatted, FileLoader, ImportError, MemoryLoader, Params, ParseError,
+    SourceLoader, ToDslError,
+};
 #[cfg(feature = "binary-cache")]
-pub use serial::{DeserializeError, SerializeError};
+pub use serial::{inspect, BlobInfo, DeserializeError, SerializeError};
+pub use sql::{SqlDialect, SqlError, SqlExporter};
 pub use types::{
-    at_least, bound_field, field, rule_ref, Bound, CompareOp, CompileError, Context,
-    ContextBuilder, EvaluationReport, Expr, FieldExpr, FieldRegistry, IndexedContext, Rule,
-    RuleSet, RuleSetBuilder, Terminal, Value, Verdict,
+    all, any, at_least, bound_field, field, len, rule_ref, Bound, CompareOp, CompileError, Context,
+    ContextBuilder, EvaluationReport, Expr, FieldExpr, FieldRegistry, FieldType, IndexedContext,
+    LenExpr, OverrideError, Rule, RuleMetadata, RuleOverride, RuleOverrides, RuleSet,
+    RuleSetBuilder, RuleTest, Schema, SchemaViolation, SetOp, Terminal, TestOutcome, TestReport,
+    Truth, UnknownPolicy, ValidationReport, Value, Verdict,
 };
+#[cfg(feature = "binary-cache")]
+pub use view::RuleSetView;
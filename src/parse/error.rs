@@ -1,5 +1,7 @@
 use std::fmt;
 
+use thiserror::Error;
+
 /// Errors produced when parsing DSL input.
 #[derive(Debug)]
 pub struct ParseError {
@@ -22,6 +24,98 @@ impl fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
+/// Errors produced while resolving `import` declarations.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    /// An imported file could not be read by the source loader.
+    #[error("cannot load '{path}' imported from '{from}': {source}")]
+    Load {
+        /// The imported path.
+        path: String,
+        /// The file containing the import.
+        from: String,
+        /// The loader's error.
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// An imported file is not valid DSL syntax.
+    #[error("in '{file}': {source}")]
+    Parse {
+        /// The imported file.
+        file: String,
+        /// The parse error within it.
+        #[source]
+        source: ParseError,
+    },
+
+    /// A file imports itself, directly or through other files.
+    #[error("import cycle detected: {}", chain.join(" -> "))]
+    Cycle {
+        /// The chain of files forming the cycle, starting and ending with
+        /// the same file.
+        chain: Vec<String>,
+    },
+
+    /// A file uses the same alias for more than one import.
+    #[error("'{file}' uses the import alias '{alias}' more than once")]
+    DuplicateAlias {
+        /// The file containing the imports.
+        file: String,
+        /// The repeated alias.
+        alias: String,
+    },
+
+    /// Two files define a rule with the same fully qualified name.
+    #[error("rule '{name}' in '{file}' is already defined in '{previous}'")]
+    DuplicateRule {
+        /// The qualified rule name.
+        name: String,
+        /// The file with the second definition.
+        file: String,
+        /// The file with the first definition.
+        previous: String,
+    },
+
+    /// Two files declare the same field with different types.
+    #[error("field '{path}' is declared as {field_type} in '{file}' but as {previous_type} in '{previous}'")]
+    ConflictingField {
+        /// The field path.
+        path: String,
+        /// The file with the second declaration.
+        file: String,
+        /// The type declared there.
+        field_type: crate::FieldType,
+        /// The file with the first declaration.
+        previous: String,
+        /// The type declared there.
+        previous_type: crate::FieldType,
+    },
+
+    /// The input contains imports but was not loaded through a
+    /// [`SourceLoader`](super::SourceLoader).
+    #[error(
+        "cannot import '{path}' without a source loader; use RuleSet::from_file or RuleSet::from_loader"
+    )]
+    NoLoader {
+        /// The first imported path.
+        path: String,
+    },
+}
+
+/// Errors writing rules or expressions as DSL source.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ToDslError {
+    /// A literal has no DSL syntax: a map, or a float that is not finite.
+    #[error("cannot write {0} as a DSL literal")]
+    Value(String),
+
+    /// A rule name, field path or element variable cannot be written, even
+    /// between backticks.
+    #[error("cannot write '{0}' as a DSL name")]
+    Name(String),
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,4 +125,34 @@ mod tests {
         let err = ParseError::new("unexpected token");
         assert_eq!(err.to_string(), "parse error: unexpected token");
     }
+
+    #[test]
+    fn import_error_messages() {
+        let err = ImportError::Cycle {
+            chain: vec!["a.ooroo".into(), "b.ooroo".into(), "a.ooroo".into()],
+        };
+        assert_eq!(
+            err.to_string(),
+            "import cycle detected: a.ooroo -> b.ooroo -> a.ooroo"
+        );
+
+        let err = ImportError::DuplicateRule {
+            name: "identity.verified".into(),
+            file: "main.ooroo".into(),
+            previous: "identity.ooroo".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "rule 'identity.verified' in 'main.ooroo' is already defined in 'identity.ooroo'"
+        );
+
+        let err = ImportError::Parse {
+            file: "identity.ooroo".into(),
+            source: ParseError::new("unexpected token"),
+        };
+        assert_eq!(
+            err.to_string(),
+            "in 'identity.ooroo': parse error: unexpected token"
+        );
+    }
 }
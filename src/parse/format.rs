@@ -0,0 +1,759 @@
+use std::fmt::Write;
+
+use winnow::Parser;
+
+use crate::types::path::FieldPath;
+use crate::{Bound, Expr, Rule, RuleMetadata, RuleTest, Schema, Terminal, UnknownPolicy, Value};
+
+use super::error::{ParseError, ToDslError};
+use super::grammar::{self, Item};
+use super::template::{Expansion, Template};
+
+/// Rule bodies longer than this are split at their top-level `AND`/`OR`s.
+const MAX_WIDTH: usize = 80;
+const INDENT: &str = "    ";
+
+/// Words the grammar reads as operators or literals where a name can appear.
+/// Names spelled like one, in any case, are written between backticks.
+const KEYWORDS: &[&str] = &[
+    "AND",
+    "OR",
+    "NOT",
+    "IN",
+    "LIKE",
+    "IS",
+    "NULL",
+    "BETWEEN",
+    "CONTAINS",
+    "HAS_KEY",
+    "OVERLAPS",
+    "SUBSET_OF",
+    "SUPERSET_OF",
+    "DISJOINT_FROM",
+    "AT_LEAST",
+    "ANY",
+    "ALL",
+    "AS",
+    "LEN",
+    "TRUE",
+    "FALSE",
+];
+
+/// Binding strength of an expression, weakest first, mirroring the grammar:
+/// `OR` < `AND` < `NOT` < everything else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Prec {
+    Or,
+    And,
+    Not,
+    Atom,
+}
+
+fn prec(expr: &Expr) -> Prec {
+    match expr {
+        Expr::Or(..) => Prec::Or,
+        Expr::And(..) => Prec::And,
+        Expr::Not(_) => Prec::Not,
+        _ => Prec::Atom,
+    }
+}
+
+/// Format an expression on one line with the fewest parentheses that parse
+/// back to the same tree. `AND` and `OR` associate to the left, so only a
+/// right operand of the same operator is parenthesized.
+pub(crate) fn expr_to_dsl(expr: &Expr) -> Result<String, ToDslError> {
+    let mut out = String::new();
+    write_expr(&mut out, expr, Prec::Or)?;
+    Ok(out)
+}
+
+fn write_expr(out: &mut String, expr: &Expr, min: Prec) -> Result<(), ToDslError> {
+    if prec(expr) < min {
+        out.push('(');
+        write_expr(out, expr, Prec::Or)?;
+        out.push(')');
+        return Ok(());
+    }
+    match expr {
+        Expr::Or(a, b) => {
+            write_expr(out, a, Prec::Or)?;
+            out.push_str(" OR ");
+            write_expr(out, b, Prec::And)?;
+        }
+        Expr::And(a, b) => {
+            write_expr(out, a, Prec::And)?;
+            out.push_str(" AND ");
+            write_expr(out, b, Prec::Not)?;
+        }
+        Expr::Not(inner) => {
+            out.push_str("NOT ");
+            write_expr(out, inner, Prec::Not)?;
+        }
+        Expr::RuleRef(name) => write_name(out, name)?,
+        Expr::Compare { field, op, value } => {
+            write_path(out, field)?;
+            let _ = write!(out, " {op} ");
+            write_value(out, value)?;
+        }
+        Expr::CompareFields { left, op, right } => {
+            write_path(out, left)?;
+            let _ = write!(out, " {op} ");
+            write_path(out, right)?;
+        }
+        Expr::In { field, members } => write_membership(out, field, "IN", members)?,
+        Expr::NotIn { field, members } => write_membership(out, field, "NOT IN", members)?,
+        Expr::Between { field, low, high } => {
+            write_path(out, field)?;
+            out.push_str(" BETWEEN ");
+            write_bound(out, low)?;
+            out.push_str(", ");
+            write_bound(out, high)?;
+        }
+        Expr::Like { field, pattern } => {
+            write_path(out, field)?;
+            out.push_str(" LIKE ");
+            write_string(out, pattern);
+        }
+        Expr::NotLike { field, pattern } => {
+            write_path(out, field)?;
+            out.push_str(" NOT LIKE ");
+            write_string(out, pattern);
+        }
+        Expr::IsNull(field) => {
+            write_path(out, field)?;
+            out.push_str(" IS NULL");
+        }
+        Expr::IsNotNull(field) => {
+            write_path(out, field)?;
+            out.push_str(" IS NOT NULL");
+        }
+        Expr::AtLeast { n, exprs } => {
+            let _ = write!(out, "AT_LEAST({n}");
+            for e in exprs {
+                out.push_str(", ");
+                write_expr(out, e, Prec::Or)?;
+            }
+            out.push(')');
+        }
+        Expr::Any {
+            list,
+            var,
+            predicate,
+        } => write_quantifier(out, "ANY", list, var, predicate)?,
+        Expr::All {
+            list,
+            var,
+            predicate,
+        } => write_quantifier(out, "ALL", list, var, predicate)?,
+        Expr::Contains { field, value } => {
+            write_path(out, field)?;
+            out.push_str(" CONTAINS ");
+            write_bound(out, value)?;
+        }
+        Expr::SetCompare { field, op, value } => {
+            write_path(out, field)?;
+            let _ = write!(out, " {op} ");
+            write_bound(out, value)?;
+        }
+        Expr::Len { field, op, value } => {
+            out.push_str("len(");
+            write_path(out, field)?;
+            let _ = write!(out, ") {op} ");
+            write_bound(out, value)?;
+        }
+        Expr::HasKey { field, key } => {
+            write_path(out, field)?;
+            out.push_str(" HAS_KEY ");
+            write_bound(out, key)?;
+        }
+    }
+    Ok(())
+}
+
+/// `ANY(list AS var, predicate)`. The element variable is not a path, so it
+/// must be a plain word.
+fn write_quantifier(
+    out: &mut String,
+    keyword: &str,
+    list: &str,
+    var: &str,
+    predicate: &Expr,
+) -> Result<(), ToDslError> {
+    if var.is_empty() || !var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(ToDslError::Name(var.to_owned()));
+    }
+    let _ = write!(out, "{keyword}(");
+    write_path(out, list)?;
+    let _ = write!(out, " AS {var}, ");
+    write_expr(out, predicate, Prec::Or)?;
+    out.push(')');
+    Ok(())
+}
+
+/// Write a rule name, or the root of a field path, between backticks unless
+/// it reads back as itself.
+fn write_name(out: &mut String, name: &str) -> Result<(), ToDslError> {
+    let mut chars = name.chars();
+    let bare = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        && !KEYWORDS.iter().any(|k| name.eq_ignore_ascii_case(k));
+    if bare {
+        out.push_str(name);
+    } else if name.is_empty() || name.contains(['`', '\n']) {
+        return Err(ToDslError::Name(name.to_owned()));
+    } else {
+        let _ = write!(out, "`{name}`");
+    }
+    Ok(())
+}
+
+/// Write a field path: its root as a name, then its `[...]` lookups as
+/// they are.
+fn write_path(out: &mut String, path: &str) -> Result<(), ToDslError> {
+    let parsed = FieldPath::parse(path).map_err(|_| ToDslError::Name(path.to_owned()))?;
+    write_name(out, parsed.root)?;
+    out.push_str(&path[parsed.root.len()..]);
+    Ok(())
+}
+
+/// `x IN [a, b]`, or `x IN list` for a single list field.
+fn write_membership(
+    out: &mut String,
+    field: &str,
+    keyword: &str,
+    members: &[Bound],
+) -> Result<(), ToDslError> {
+    write_path(out, field)?;
+    let _ = write!(out, " {keyword} ");
+    if let [Bound::Field(path)] = members {
+        return write_path(out, path);
+    }
+    out.push('[');
+    for (i, member) in members.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_bound(out, member)?;
+    }
+    out.push(']');
+    Ok(())
+}
+
+fn write_bound(out: &mut String, bound: &Bound) -> Result<(), ToDslError> {
+    match bound {
+        Bound::Field(path) => write_path(out, path),
+        Bound::Literal(value) => write_value(out, value),
+    }
+}
+
+/// Write a value as a DSL literal. Floats always carry a decimal point so
+/// they read back as floats. Maps and non-finite floats have no literal
+/// syntax.
+fn write_value(out: &mut String, value: &Value) -> Result<(), ToDslError> {
+    match value {
+        Value::String(s) => write_string(out, s),
+        Value::Float(f) if f.is_finite() => {
+            let text = f.to_string();
+            out.push_str(&text);
+            if !text.contains('.') {
+                out.push_str(".0");
+            }
+        }
+        Value::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Int(_) | Value::Bool(_) => {
+            let _ = write!(out, "{value}");
+        }
+        Value::Float(_) | Value::Map(_) => return Err(ToDslError::Value(value.to_string())),
+    }
+    Ok(())
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Write a rule or template body, indented. A body that does not fit in
+/// [`MAX_WIDTH`] is split before each top-level `OR` (or `AND`), and an
+/// operand that still does not fit is split again one level deeper.
+fn write_body(
+    out: &mut String,
+    expr: &Expr,
+    min: Prec,
+    depth: usize,
+    lead: &str,
+) -> Result<(), ToDslError> {
+    let mut line = String::new();
+    write_expr(&mut line, expr, min)?;
+    let width = INDENT.len() * depth + lead.len() + line.len();
+    let chain = match expr {
+        Expr::Or(..) | Expr::And(..) if width > MAX_WIDTH && prec(expr) >= min => {
+            let mut operands = Vec::new();
+            let op = prec(expr);
+            let mut current = expr;
+            while let Expr::Or(a, b) | Expr::And(a, b) = current {
+                if prec(current) != op {
+                    break;
+                }
+                operands.push(b.as_ref());
+                current = a;
+            }
+            operands.push(current);
+            operands.reverse();
+            Some((op, operands))
+        }
+        _ => None,
+    };
+    let Some((op, operands)) = chain else {
+        let _ = writeln!(out, "{}{lead}{line}", INDENT.repeat(depth));
+        return Ok(());
+    };
+    let (keyword, right_min) = if op == Prec::Or {
+        ("OR ", Prec::And)
+    } else {
+        ("AND ", Prec::Not)
+    };
+    let inner = if lead.is_empty() { depth } else { depth + 1 };
+    write_body(out, operands[0], op, depth, lead)?;
+    for operand in &operands[1..] {
+        write_body(out, operand, right_min, inner, keyword)?;
+    }
+    Ok(())
+}
+
+fn write_annotations(out: &mut String, metadata: &RuleMetadata) {
+    let mut note = |key: &str, text: &str| {
+        let _ = write!(out, "@{key}(");
+        write_string(out, text);
+        out.push_str(")\n");
+    };
+    if let Some(description) = &metadata.description {
+        note("description", description);
+    }
+    if let Some(owner) = &metadata.owner {
+        note("owner", owner);
+    }
+    for tag in &metadata.tags {
+        note("tag", tag);
+    }
+    if let Some(link) = &metadata.link {
+        note("link", link);
+    }
+    for (key, value) in &metadata.extra {
+        note(key, value);
+    }
+    if !metadata.enabled {
+        out.push_str("@disabled\n");
+    }
+}
+
+fn write_priority(out: &mut String, priority: u32, on_unknown: UnknownPolicy) {
+    let _ = write!(out, " (priority {priority}");
+    if on_unknown == UnknownPolicy::Indeterminate {
+        out.push_str(", on_unknown indeterminate");
+    }
+    out.push(')');
+}
+
+fn write_rule(
+    out: &mut String,
+    name: &str,
+    condition: &Expr,
+    metadata: &RuleMetadata,
+    terminal: Option<&Terminal>,
+) -> Result<(), ToDslError> {
+    write_annotations(out, metadata);
+    out.push_str("rule ");
+    write_name(out, name)?;
+    if let Some(terminal) = terminal {
+        write_priority(out, terminal.priority, terminal.on_unknown);
+    }
+    out.push_str(":\n");
+    write_body(out, condition, Prec::Or, 1, "")
+}
+
+fn write_template(out: &mut String, template: &Template) -> Result<(), ToDslError> {
+    let _ = writeln!(
+        out,
+        "template {}({}):",
+        template.name,
+        template.params.join(", ")
+    );
+    write_body(out, &template.body, Prec::Or, 1, "")
+}
+
+fn write_expansion(out: &mut String, expansion: &Expansion) -> Result<(), ToDslError> {
+    write_annotations(out, &expansion.metadata);
+    let _ = write!(out, "expand {}(", expansion.template);
+    for (i, arg) in expansion.args.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_bound(out, arg)?;
+    }
+    out.push(')');
+    if let Some(name) = &expansion.name {
+        out.push_str(" as ");
+        write_name(out, name)?;
+    }
+    if let Some((priority, on_unknown)) = expansion.priority {
+        write_priority(out, priority, on_unknown);
+    }
+    out.push('\n');
+    Ok(())
+}
+
+/// Write a `test` block. The `given` values go on one line, or one per line
+/// if that line would not fit in [`MAX_WIDTH`].
+fn write_test(out: &mut String, test: &RuleTest) -> Result<(), ToDslError> {
+    out.push_str("test ");
+    write_string(out, &test.name);
+    out.push_str(" {\n");
+    if !test.given.is_empty() {
+        let bindings = test
+            .given
+            .iter()
+            .map(|(path, value)| {
+                let mut binding = String::new();
+                write_path(&mut binding, path)?;
+                binding.push_str(" = ");
+                write_value(&mut binding, value)?;
+                Ok(binding)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let line = format!("{INDENT}given {};", bindings.join(", "));
+        if line.len() <= MAX_WIDTH {
+            out.push_str(&line);
+            out.push('\n');
+        } else {
+            let _ = writeln!(out, "{INDENT}given");
+            let separator = format!(",\n{INDENT}{INDENT}");
+            let _ = writeln!(out, "{INDENT}{INDENT}{};", bindings.join(&separator));
+        }
+    }
+    let _ = write!(out, "{INDENT}expect ");
+    match &test.expect {
+        Some(terminal) => write_name(out, terminal)?,
+        None => out.push_str("none"),
+    }
+    out.push_str("\n}\n");
+    Ok(())
+}
+
+fn write_item(out: &mut String, item: &Item) -> Result<(), ToDslError> {
+    match item {
+        Item::Rule(rule, terminal) => {
+            if let Some(condition) = &rule.condition {
+                write_rule(
+                    out,
+                    &rule.name,
+                    condition,
+                    &rule.metadata,
+                    terminal.as_ref(),
+                )?;
+            }
+        }
+        Item::Field(path, field_type) => {
+            out.push_str("field ");
+            write_path(out, path)?;
+            let _ = writeln!(out, ": {field_type}");
+        }
+        Item::Const(name, value) => {
+            let _ = write!(out, "const {name} = ");
+            write_value(out, value)?;
+            out.push('\n');
+        }
+        Item::Param(name, default) => {
+            let _ = write!(out, "param {name}");
+            if let Some(value) = default {
+                out.push_str(" = ");
+                write_value(out, value)?;
+            }
+            out.push('\n');
+        }
+        Item::Import(path, alias) => {
+            out.push_str("import ");
+            write_string(out, path);
+            let _ = writeln!(out, " as {alias}");
+        }
+        Item::Template(template) => write_template(out, template)?,
+        Item::Expand(expansion, _) => write_expansion(out, expansion)?,
+        Item::Test(test) => write_test(out, test)?,
+    }
+    Ok(())
+}
+
+/// Render rules, their terminals, schema declarations and tests as DSL
+/// source: `field` lines first, then each rule as a block in the order
+/// given, then each test.
+pub(crate) fn rules_to_dsl(
+    schema: Option<&Schema>,
+    rules: &[Rule],
+    terminals: &[Terminal],
+    tests: &[RuleTest],
+) -> Result<String, ToDslError> {
+    let mut out = String::new();
+    if let Some(schema) = schema {
+        let mut fields: Vec<_> = schema.iter().collect();
+        fields.sort_by(|a, b| a.0.cmp(b.0));
+        for (path, field_type) in fields {
+            write_item(&mut out, &Item::Field(path.to_owned(), field_type))?;
+        }
+    }
+    for rule in rules {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let terminal = terminals.iter().find(|t| t.rule_name == rule.name);
+        write_item(&mut out, &Item::Rule(rule.clone(), terminal.cloned()))?;
+    }
+    for test in tests {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        write_test(&mut out, test)?;
+    }
+    Ok(out)
+}
+
+/// Rules, templates and tests are always set off by blank lines.
+fn is_block(item: &Item) -> bool {
+    match item {
+        Item::Rule(..) | Item::Template(_) | Item::Test(_) => true,
+        Item::Expand(expansion, _) => expansion.metadata != RuleMetadata::default(),
+        _ => false,
+    }
+}
+
+/// A `#` comment: its byte offset in the source and its text after the `#`,
+/// without trailing whitespace.
+struct Comment<'a> {
+    offset: usize,
+    text: &'a str,
+}
+
+/// Find every comment in `source`, skipping `#` inside string literals and
+/// backtick-quoted names.
+fn comments(source: &str) -> Vec<Comment<'_>> {
+    let mut found = Vec::new();
+    let mut chars = source.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '`' => while chars.next().is_some_and(|(_, c)| c != '`') {},
+            '#' => {
+                let end = source[i..].find('\n').map_or(source.len(), |n| i + n);
+                found.push(Comment {
+                    offset: i,
+                    text: source[i + 1..end].trim_end(),
+                });
+                while chars.next().is_some_and(|(_, c)| c != '\n') {}
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Whether `gap` (the text between two tokens) contains a blank line.
+fn has_blank_line(gap: &str) -> bool {
+    let mut lines = gap.split('\n');
+    lines.next();
+    let mut lines: Vec<&str> = lines.collect();
+    lines.pop();
+    lines.iter().any(|line| line.trim().is_empty())
+}
+
+/// What precedes an item or the end of the file.
+#[derive(Default)]
+struct Leading<'a> {
+    /// Comments, each flagged with whether a blank line came before it.
+    comments: Vec<(bool, &'a str)>,
+    /// End of the last comment on its own line above the item.
+    after_comment: Option<usize>,
+    /// Whether comments were moved up from inside the item.
+    hoisted: bool,
+}
+
+/// Format a DSL source file canonically.
+///
+/// Items keep their order. Each rule, template and test is set off by a blank
+/// line; other blank lines between items are kept, collapsed to one.
+/// Comments are kept: a comment at the end of an item's last line stays
+/// there, and a comment inside an item moves to the lines above it.
+pub(crate) fn format_source(source: &str) -> Result<String, ParseError> {
+    let items = (grammar::spanned_items, grammar::ws)
+        .map(|(items, ())| items)
+        .parse(source)
+        .map_err(|e| ParseError::new(e.to_string()))?;
+    let spans: Vec<(usize, usize)> = items
+        .iter()
+        .map(|(_, start, end)| (source.len() - start, source.len() - end))
+        .collect();
+
+    // Comments above each item, plus a final slot for the end of the file.
+    let mut leading: Vec<Leading<'_>> = (0..=items.len()).map(|_| Leading::default()).collect();
+    let mut trailing: Vec<Option<&str>> = vec![None; items.len()];
+    let mut cursor = 0;
+    let mut slot = 0;
+    for comment in comments(source) {
+        while slot < spans.len() && spans[slot].1 <= comment.offset {
+            cursor = spans[slot].1;
+            slot += 1;
+        }
+        if slot < spans.len() && spans[slot].0 <= comment.offset {
+            leading[slot].comments.push((false, comment.text));
+            leading[slot].hoisted = true;
+            continue;
+        }
+        let gap = &source[cursor..comment.offset];
+        if slot > 0 && cursor == spans[slot - 1].1 && !gap.contains('\n') {
+            trailing[slot - 1] = Some(comment.text);
+        } else {
+            leading[slot]
+                .comments
+                .push((has_blank_line(gap), comment.text));
+        }
+        cursor = comment.offset + 1 + comment.text.len();
+        if slot < spans.len() {
+            leading[slot].after_comment = Some(cursor);
+        }
+    }
+
+    let mut out = String::new();
+    let mut prev_end = 0;
+    for (i, (item, ..)) in items.iter().enumerate() {
+        let (start, end) = spans[i];
+        let lead = &leading[i];
+        let forced = i > 0 && (is_block(item) || is_block(&items[i - 1].0));
+        write_comments(&mut out, &lead.comments, i > 0, forced);
+        let gap = &source[lead.after_comment.unwrap_or(prev_end)..start];
+        let blank = has_blank_line(gap) && !lead.hoisted;
+        let separate = if lead.comments.is_empty() {
+            i > 0 && (forced || blank)
+        } else {
+            blank
+        };
+        if separate {
+            out.push('\n');
+        }
+        write_item(&mut out, item).map_err(|e| ParseError::new(e.to_string()))?;
+        if let Some(text) = trailing[i] {
+            out.pop();
+            let _ = writeln!(out, " #{text}");
+        }
+        prev_end = end;
+    }
+    write_comments(
+        &mut out,
+        &leading[items.len()].comments,
+        !items.is_empty(),
+        false,
+    );
+    Ok(out)
+}
+
+/// Write comments on their own lines, keeping blank lines between them and
+/// before the first unless it opens the file. `forced` puts a blank line
+/// before the first.
+fn write_comments(out: &mut String, comments: &[(bool, &str)], after_item: bool, forced: bool) {
+    for (i, (blank, text)) in comments.iter().enumerate() {
+        if (*blank && (after_item || i > 0)) || (i == 0 && forced) {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "#{text}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{field, rule_ref};
+
+    fn expr_to_dsl_ok(expr: &Expr) -> String {
+        expr_to_dsl(expr).unwrap()
+    }
+
+    #[test]
+    fn minimal_parentheses() {
+        let (a, b, c) = (rule_ref("a"), rule_ref("b"), rule_ref("c"));
+        assert_eq!(
+            expr_to_dsl_ok(&a.clone().and(b.clone()).and(c.clone())),
+            "a AND b AND c"
+        );
+        assert_eq!(
+            expr_to_dsl_ok(&a.clone().and(b.clone().and(c.clone()))),
+            "a AND (b AND c)"
+        );
+        assert_eq!(
+            expr_to_dsl_ok(&a.clone().and(b.clone()).or(c.clone())),
+            "a AND b OR c"
+        );
+        assert_eq!(
+            expr_to_dsl_ok(&a.clone().and(b.clone().or(c))),
+            "a AND (b OR c)"
+        );
+        assert_eq!(
+            expr_to_dsl_ok(&!(a.clone().and(b.clone()))),
+            "NOT (a AND b)"
+        );
+        assert_eq!(expr_to_dsl_ok(&!!a), "NOT NOT a");
+        assert_eq!(expr_to_dsl_ok(&!field("x").is_null()), "NOT x IS NULL");
+    }
+
+    #[test]
+    fn literals_read_back() {
+        let mut out = String::new();
+        write_value(&mut out, &Value::Float(3.0)).unwrap();
+        out.push(' ');
+        write_value(&mut out, &Value::Float(-0.5)).unwrap();
+        out.push(' ');
+        write_value(&mut out, &Value::from("a \"b\" \\ c\n")).unwrap();
+        assert_eq!(out, r#"3.0 -0.5 "a \"b\" \\ c\n""#);
+    }
+
+    #[test]
+    fn comments_skip_string_literals() {
+        let source = "rule r: # one\n    x LIKE \"#no\\\"#\" # two\n#three";
+        let found: Vec<_> = comments(source).iter().map(|c| c.text).collect();
+        assert_eq!(found, [" one", " two", "three"]);
+    }
+
+    #[test]
+    fn blank_lines_between_tokens() {
+        assert!(!has_blank_line(" # note\n"));
+        assert!(!has_blank_line("\n    "));
+        assert!(has_blank_line("\n\n"));
+        assert!(has_blank_line(" # note\n  \nrule"));
+    }
+}
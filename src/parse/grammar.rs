@@ -1,16 +1,21 @@
 use winnow::ascii::{dec_int, till_line_ending};
-use winnow::combinator::{alt, cut_err, delimited, fail, opt, preceded, repeat};
-use winnow::error::{ErrMode, ModalResult, StrContext, StrContextValue};
+use winnow::combinator::{alt, cut_err, delimited, fail, not, opt, preceded, repeat, terminated};
+use winnow::error::{ContextError, ErrMode, ModalResult, StrContext, StrContextValue};
 use winnow::prelude::*;
 use winnow::token::{any, take_while};
 
-use crate::{Bound, CompareOp, Expr, Rule, Terminal, Value};
+use crate::types::path::quote_key;
+use crate::{
+    Bound, CompareOp, Expr, FieldType, Rule, RuleMetadata, RuleTest, Schema, SetOp, Terminal,
+    UnknownPolicy, Value,
+};
 
 use super::parser::ParsedRuleSet;
+use super::template::{Expansion, Template};
 
 // -- Whitespace & comments --------------------------------------------------
 
-fn ws(input: &mut &str) -> ModalResult<()> {
+pub(super) fn ws(input: &mut &str) -> ModalResult<()> {
     let _: () = repeat(
         0..,
         alt((
@@ -24,15 +29,72 @@ fn ws(input: &mut &str) -> ModalResult<()> {
 
 // -- Identifiers ------------------------------------------------------------
 
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+/// A name: letters, digits, `_` and `.`, starting with a letter or `_`. Any
+/// other name, or one that would read as a keyword, is written between
+/// backticks: `` `my rule` ``, `` `true` ``.
 fn ident<'i>(input: &mut &'i str) -> ModalResult<&'i str> {
-    (
-        take_while(1.., |c: char| c.is_ascii_alphabetic() || c == '_'),
-        take_while(0.., |c: char| {
-            c.is_ascii_alphanumeric() || c == '_' || c == '.'
-        }),
-    )
-        .take()
-        .parse_next(input)
+    alt((
+        delimited(
+            '`',
+            take_while(1.., |c: char| c != '`' && c != '\n'),
+            cut_err('`'),
+        ),
+        (
+            take_while(1.., |c: char| c.is_ascii_alphabetic() || c == '_'),
+            take_while(0.., is_name_char),
+        )
+            .take(),
+    ))
+    .parse_next(input)
+}
+
+/// `word`, unless it only starts a longer name: `notable` is a name, not
+/// `NOT able`.
+fn keyword<'i>(word: &'static str) -> impl Parser<&'i str, &'i str, ErrMode<ContextError>> {
+    terminated(word, not(any.verify(|c: &char| is_name_char(*c))))
+}
+
+/// A field path: an identifier optionally followed by `[...]` indexes and
+/// further `.segment`s, e.g. `limits[user.country].daily`, `limits["US"]`,
+/// `order.items[0].sku` or `order.items[*].sku`.
+///
+/// The `[` must directly follow the preceding segment, which keeps
+/// `x IN [..]` and list literals unambiguous. Quoted keys are re-quoted into
+/// the canonical form understood by the compiler.
+fn field_path(input: &mut &str) -> ModalResult<String> {
+    let mut path = ident.parse_next(input)?.to_owned();
+    while opt('[').parse_next(input)?.is_some() {
+        if input.starts_with('"') {
+            let key = string_literal.parse_next(input)?;
+            path.push_str(&quote_key(&key));
+        } else if opt('*').parse_next(input)?.is_some() {
+            path.push_str("[*]");
+        } else if input.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+            let index = cut_err(dec_int::<_, i64, _>)
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "list index inside '[...]'",
+                )))
+                .parse_next(input)?;
+            path.push_str(&format!("[{index}]"));
+        } else {
+            let key = cut_err(ident)
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "quoted key, list index, '*' or field path inside '[...]'",
+                )))
+                .parse_next(input)?;
+            path.push('[');
+            path.push_str(key);
+            path.push(']');
+        }
+        cut_err(']').parse_next(input)?;
+        let tail: &str = take_while(0.., is_name_char).parse_next(input)?;
+        path.push_str(tail);
+    }
+    Ok(path)
 }
 
 /// Like `ident`, but returns a descriptive error if the identifier is
@@ -143,8 +205,8 @@ fn value(input: &mut &str) -> ModalResult<Value> {
     alt((
         value_list,
         string_literal.map(Value::String),
-        "true".value(Value::Bool(true)),
-        "false".value(Value::Bool(false)),
+        keyword("true").value(Value::Bool(true)),
+        keyword("false").value(Value::Bool(false)),
         negative_number,
         float_literal.map(Value::Float),
         dec_int::<_, i64, _>.map(Value::Int),
@@ -158,11 +220,7 @@ fn value(input: &mut &str) -> ModalResult<Value> {
 /// all scalar types have distinct lexical forms.
 fn bound(input: &mut &str) -> ModalResult<Bound> {
     ws.parse_next(input)?;
-    alt((
-        value.map(Bound::Literal),
-        ident.map(|s: &str| Bound::Field(s.to_owned())),
-    ))
-    .parse_next(input)
+    alt((value.map(Bound::Literal), field_path.map(Bound::Field))).parse_next(input)
 }
 
 // -- Comparison operators ---------------------------------------------------
@@ -180,6 +238,16 @@ fn compare_op(input: &mut &str) -> ModalResult<CompareOp> {
     .parse_next(input)
 }
 
+fn set_op(input: &mut &str) -> ModalResult<SetOp> {
+    alt((
+        alt(("OVERLAPS", "overlaps")).value(SetOp::Overlaps),
+        alt(("SUBSET_OF", "subset_of")).value(SetOp::SubsetOf),
+        alt(("SUPERSET_OF", "superset_of")).value(SetOp::SupersetOf),
+        alt(("DISJOINT_FROM", "disjoint_from")).value(SetOp::DisjointFrom),
+    ))
+    .parse_next(input)
+}
+
 // -- Expressions (precedence: OR < AND < NOT < primary) ---------------------
 
 fn at_least_expr(input: &mut &str) -> ModalResult<Expr> {
@@ -218,10 +286,142 @@ fn at_least_expr(input: &mut &str) -> ModalResult<Expr> {
     Ok(Expr::AtLeast { n, exprs })
 }
 
+/// `ANY(list AS var, predicate)` / `ALL(list AS var, predicate)`. Without
+/// `AS`, the element variable is the first segment of the first field the
+/// predicate tests: `ANY(order.items, item.price > 100)` binds `item`.
+fn quantifier_expr(input: &mut &str) -> ModalResult<Expr> {
+    let is_any = alt((
+        alt(("ANY", "any")).value(true),
+        alt(("ALL", "all")).value(false),
+    ))
+    .parse_next(input)?;
+    ws.parse_next(input)?;
+    // Only commit once we see '('; otherwise backtrack so comparison_or_rule_ref
+    // can treat "any"/"all" as a rule reference.
+    if opt('(').parse_next(input)?.is_none() {
+        return Err(ErrMode::Backtrack(winnow::error::ContextError::new()));
+    }
+    ws.parse_next(input)?;
+    let list = cut_err(field_path)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "list field path",
+        )))
+        .parse_next(input)?;
+    ws.parse_next(input)?;
+    let var = match opt(alt(("AS", "as"))).parse_next(input)? {
+        Some(_) => {
+            ws.parse_next(input)?;
+            let var = cut_err(take_while(1.., |c: char| {
+                c.is_ascii_alphanumeric() || c == '_'
+            }))
+            .context(StrContext::Expected(StrContextValue::Description(
+                "element variable name",
+            )))
+            .parse_next(input)?;
+            ws.parse_next(input)?;
+            Some(var.to_owned())
+        }
+        None => None,
+    };
+    cut_err(',')
+        .context(StrContext::Expected(StrContextValue::Description(
+            "',' after the list field or element variable",
+        )))
+        .parse_next(input)?;
+    let predicate = cut_err(expr)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "quantifier predicate",
+        )))
+        .parse_next(input)?;
+    ws.parse_next(input)?;
+    cut_err(')').parse_next(input)?;
+    let var = match var.or_else(|| first_tested_field(&predicate).map(element_root)) {
+        Some(var) => var,
+        None => {
+            return cut_err(fail)
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "a predicate testing a field, or AS and an element variable name",
+                )))
+                .parse_next(input)
+        }
+    };
+    let (list, predicate) = (list, Box::new(predicate));
+    Ok(if is_any {
+        Expr::Any {
+            list,
+            var,
+            predicate,
+        }
+    } else {
+        Expr::All {
+            list,
+            var,
+            predicate,
+        }
+    })
+}
+
+/// The field tested by the leftmost comparison in `expr`, if any.
+fn first_tested_field(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Compare { field, .. }
+        | Expr::In { field, .. }
+        | Expr::NotIn { field, .. }
+        | Expr::Between { field, .. }
+        | Expr::Like { field, .. }
+        | Expr::NotLike { field, .. }
+        | Expr::IsNull(field)
+        | Expr::IsNotNull(field)
+        | Expr::Contains { field, .. }
+        | Expr::SetCompare { field, .. }
+        | Expr::Len { field, .. }
+        | Expr::HasKey { field, .. }
+        | Expr::CompareFields { left: field, .. } => Some(field),
+        Expr::And(a, b) | Expr::Or(a, b) => first_tested_field(a).or_else(|| first_tested_field(b)),
+        Expr::Not(inner) => first_tested_field(inner),
+        Expr::AtLeast { exprs, .. } => exprs.iter().find_map(first_tested_field),
+        Expr::Any { list, .. } | Expr::All { list, .. } => Some(list),
+        Expr::RuleRef(_) => None,
+    }
+}
+
+/// The first segment of `path`, before any `.` or `[`.
+fn element_root(path: &str) -> String {
+    path.split(['.', '[']).next().unwrap_or(path).to_owned()
+}
+
+/// `len(list) <op> bound`.
+fn len_expr(input: &mut &str) -> ModalResult<Expr> {
+    alt(("LEN", "len")).parse_next(input)?;
+    ws.parse_next(input)?;
+    // Only commit once we see '('; otherwise backtrack so comparison_or_rule_ref
+    // can treat "len" as a rule reference or field path.
+    if opt('(').parse_next(input)?.is_none() {
+        return Err(ErrMode::Backtrack(winnow::error::ContextError::new()));
+    }
+    ws.parse_next(input)?;
+    let field = cut_err(field_path)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "list field path",
+        )))
+        .parse_next(input)?;
+    ws.parse_next(input)?;
+    cut_err(')').parse_next(input)?;
+    let op = cut_err(compare_op)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "comparison operator after len(...)",
+        )))
+        .parse_next(input)?;
+    let value = cut_err(bound).parse_next(input)?;
+    Ok(Expr::Len { field, op, value })
+}
+
 fn primary(input: &mut &str) -> ModalResult<Expr> {
     ws.parse_next(input)?;
     alt((
         at_least_expr,
+        quantifier_expr,
+        len_expr,
         delimited('(', expr, (ws, ')')),
         comparison_or_rule_ref,
     ))
@@ -249,8 +449,16 @@ fn bound_list(input: &mut &str) -> ModalResult<Vec<Bound>> {
     Ok(members)
 }
 
+/// The right-hand side of `IN`: a `[...]` bound list, or a single list field
+/// or constant name.
+fn in_members(input: &mut &str) -> ModalResult<Vec<Bound>> {
+    ws.parse_next(input)?;
+    alt((bound_list, field_path.map(|path| vec![Bound::Field(path)]))).parse_next(input)
+}
+
 fn comparison_or_rule_ref(input: &mut &str) -> ModalResult<Expr> {
-    let name = ident.parse_next(input)?;
+    let name = field_path.parse_next(input)?;
+    let name = name.as_str();
     let checkpoint = input.checkpoint();
     ws.parse_next(input)?;
 
@@ -270,7 +478,7 @@ fn comparison_or_rule_ref(input: &mut &str) -> ModalResult<Expr> {
     if opt(alt(("NOT", "not"))).parse_next(input)?.is_some() {
         ws.parse_next(input)?;
         if opt(alt(("IN", "in"))).parse_next(input)?.is_some() {
-            let members = cut_err(bound_list).parse_next(input)?;
+            let members = cut_err(in_members).parse_next(input)?;
             return Ok(Expr::NotIn {
                 field: name.to_owned(),
                 members,
@@ -288,7 +496,7 @@ fn comparison_or_rule_ref(input: &mut &str) -> ModalResult<Expr> {
 
     // IN
     if opt(alt(("IN", "in"))).parse_next(input)?.is_some() {
-        let members = cut_err(bound_list).parse_next(input)?;
+        let members = cut_err(in_members).parse_next(input)?;
         return Ok(Expr::In {
             field: name.to_owned(),
             members,
@@ -305,6 +513,40 @@ fn comparison_or_rule_ref(input: &mut &str) -> ModalResult<Expr> {
         });
     }
 
+    // CONTAINS
+    if opt(alt(("CONTAINS", "contains")))
+        .parse_next(input)?
+        .is_some()
+    {
+        let value = cut_err(bound).parse_next(input)?;
+        return Ok(Expr::Contains {
+            field: name.to_owned(),
+            value,
+        });
+    }
+
+    // OVERLAPS / SUBSET_OF / SUPERSET_OF / DISJOINT_FROM
+    if let Some(op) = opt(set_op).parse_next(input)? {
+        let value = cut_err(bound).parse_next(input)?;
+        return Ok(Expr::SetCompare {
+            field: name.to_owned(),
+            op,
+            value,
+        });
+    }
+
+    // HAS_KEY
+    if opt(alt(("HAS_KEY", "has_key")))
+        .parse_next(input)?
+        .is_some()
+    {
+        let key = cut_err(bound).parse_next(input)?;
+        return Ok(Expr::HasKey {
+            field: name.to_owned(),
+            key,
+        });
+    }
+
     // BETWEEN
     if opt(alt(("BETWEEN", "between")))
         .parse_next(input)?
@@ -335,11 +577,11 @@ fn comparison_or_rule_ref(input: &mut &str) -> ModalResult<Expr> {
             });
         }
         input.reset(&rhs_checkpoint);
-        if let Ok(right) = ident.parse_next(input) {
+        if let Ok(right) = field_path.parse_next(input) {
             return Ok(Expr::CompareFields {
                 left: name.to_owned(),
                 op,
-                right: right.to_owned(),
+                right,
             });
         }
         return cut_err(fail)
@@ -355,7 +597,10 @@ fn comparison_or_rule_ref(input: &mut &str) -> ModalResult<Expr> {
 
 fn unary(input: &mut &str) -> ModalResult<Expr> {
     ws.parse_next(input)?;
-    if opt(alt(("NOT", "not"))).parse_next(input)?.is_some() {
+    if opt(alt((keyword("NOT"), keyword("not"))))
+        .parse_next(input)?
+        .is_some()
+    {
         let inner = cut_err(unary).parse_next(input)?;
         Ok(Expr::Not(Box::new(inner)))
     } else {
@@ -388,17 +633,29 @@ fn expr(input: &mut &str) -> ModalResult<Expr> {
 
 // -- Rule definitions -------------------------------------------------------
 
-fn priority_annotation(input: &mut &str) -> ModalResult<u32> {
-    let n: i64 = delimited(
-        (ws, '(', ws, "priority", ws),
-        cut_err(dec_int::<_, i64, _>),
-        (ws, cut_err(')')),
-    )
-    .parse_next(input)?;
-    u32::try_from(n).map_err(|_| ErrMode::from_input(input).cut())
+/// `(priority N)` or `(priority N, on_unknown indeterminate|false)`.
+fn priority_annotation(input: &mut &str) -> ModalResult<(u32, UnknownPolicy)> {
+    (ws, '(', ws, "priority", ws).parse_next(input)?;
+    let n: i64 = cut_err(dec_int::<_, i64, _>).parse_next(input)?;
+    let priority = u32::try_from(n).map_err(|_| ErrMode::from_input(input).cut())?;
+    let on_unknown = opt(preceded(
+        (ws, ',', ws, "on_unknown", ws),
+        cut_err(alt((
+            "indeterminate".value(UnknownPolicy::Indeterminate),
+            "false".value(UnknownPolicy::AsFalse),
+        )))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "'indeterminate' or 'false'",
+        ))),
+    ))
+    .parse_next(input)?
+    .unwrap_or_default();
+    (ws, cut_err(')')).parse_next(input)?;
+    Ok((priority, on_unknown))
 }
 
 fn rule_def(input: &mut &str) -> ModalResult<(Rule, Option<Terminal>)> {
+    let metadata = annotations.parse_next(input)?;
     ws.parse_next(input)?;
     "rule".parse_next(input)?;
     ws.parse_next(input)?;
@@ -423,33 +680,420 @@ fn rule_def(input: &mut &str) -> ModalResult<(Rule, Option<Terminal>)> {
     let rule = Rule {
         name: name.to_owned(),
         condition: Some(condition),
+        metadata,
     };
 
-    let terminal = prio.map(|p| Terminal {
+    let terminal = prio.map(|(priority, on_unknown)| Terminal {
         rule_name: name.to_owned(),
-        priority: p,
+        priority,
+        on_unknown,
     });
 
     Ok((rule, terminal))
 }
 
+// -- Annotations ------------------------------------------------------------
+
+/// Zero or more annotations preceding a rule or expansion: `@key("text")`,
+/// `@enabled(true|false)` or `@disabled`. `description`, `owner`, `tag` and
+/// `link` fill the corresponding metadata fields; other keys go to `extra`.
+fn annotations(input: &mut &str) -> ModalResult<RuleMetadata> {
+    let mut metadata = RuleMetadata::default();
+    loop {
+        ws.parse_next(input)?;
+        if opt('@').parse_next(input)?.is_none() {
+            return Ok(metadata);
+        }
+        let key = cut_err(binding_name)
+            .context(StrContext::Expected(StrContextValue::Description(
+                "annotation name",
+            )))
+            .parse_next(input)?;
+        match key {
+            "disabled" => metadata.enabled = false,
+            "enabled" => {
+                metadata.enabled = cut_err(delimited(
+                    ('(', ws),
+                    alt(("true".value(true), "false".value(false))),
+                    (ws, ')'),
+                ))
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "(true) or (false)",
+                )))
+                .parse_next(input)?;
+            }
+            _ => {
+                let text = cut_err(delimited(('(', ws), string_literal, (ws, ')')))
+                    .context(StrContext::Expected(StrContextValue::Description(
+                        "quoted annotation value in parentheses",
+                    )))
+                    .parse_next(input)?;
+                match key {
+                    "description" => metadata.description = Some(text),
+                    "owner" => metadata.owner = Some(text),
+                    "link" => metadata.link = Some(text),
+                    "tag" => {
+                        if !metadata.has_tag(&text) {
+                            metadata.tags.push(text);
+                        }
+                    }
+                    _ => {
+                        metadata.extra.insert(key.to_owned(), text);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// -- Field declarations -----------------------------------------------------
+
+/// A schema declaration: `field user.age: int`.
+fn field_decl(input: &mut &str) -> ModalResult<(String, FieldType)> {
+    (ws, "field", ws).parse_next(input)?;
+    let path = cut_err(ident)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "field path",
+        )))
+        .parse_next(input)?;
+    (ws, cut_err(':'), ws).parse_next(input)?;
+    let field_type = cut_err(ident.verify_map(FieldType::from_name))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "field type (int, float, bool, string, list or map)",
+        )))
+        .parse_next(input)?;
+    Ok((path.to_owned(), field_type))
+}
+
+// -- Constants and parameters ----------------------------------------------
+
+/// A constant or parameter name: an identifier without dots.
+fn binding_name<'i>(input: &mut &'i str) -> ModalResult<&'i str> {
+    (
+        take_while(1.., |c: char| c.is_ascii_alphabetic() || c == '_'),
+        take_while(0.., |c: char| c.is_ascii_alphanumeric() || c == '_'),
+    )
+        .take()
+        .parse_next(input)
+}
+
+/// `const NAME = value`.
+fn const_decl(input: &mut &str) -> ModalResult<(String, Value)> {
+    (ws, "const", ws).parse_next(input)?;
+    let name = cut_err(binding_name)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "constant name",
+        )))
+        .parse_next(input)?;
+    (ws, cut_err('=')).parse_next(input)?;
+    let value = cut_err(value).parse_next(input)?;
+    Ok((name.to_owned(), value))
+}
+
+/// `param NAME` or `param NAME = default`.
+fn param_decl(input: &mut &str) -> ModalResult<(String, Option<Value>)> {
+    (ws, "param", ws).parse_next(input)?;
+    let name = cut_err(binding_name)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "parameter name",
+        )))
+        .parse_next(input)?;
+    let default = opt(preceded((ws, '='), cut_err(value))).parse_next(input)?;
+    Ok((name.to_owned(), default))
+}
+
+// -- Imports --------------------------------------------------------------
+
+/// `import "path" as alias`.
+fn import_decl(input: &mut &str) -> ModalResult<(String, String)> {
+    (ws, "import", ws).parse_next(input)?;
+    let path = cut_err(string_literal)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "quoted import path",
+        )))
+        .parse_next(input)?;
+    (ws, cut_err("as"), ws).parse_next(input)?;
+    let alias = cut_err(binding_name)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "import alias",
+        )))
+        .parse_next(input)?;
+    Ok((path, alias.to_owned()))
+}
+
+/// The paths of the `import` declarations in `source`, found without
+/// parsing the rest of it. String literals, backtick names and comments
+/// are skipped; an `import` that does not start a declaration is ignored.
+#[cfg(feature = "binary-cache")]
+pub(super) fn import_paths(source: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    let mut after_name = false;
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '`' => while chars.next().is_some_and(|(_, c)| c != '`') {},
+            '#' => while chars.next_if(|&(_, c)| c != '\n').is_some() {},
+            'i' if !after_name => {
+                if let Ok((path, _)) = import_decl.parse_next(&mut &source[i..]) {
+                    paths.push(path);
+                }
+            }
+            _ => {}
+        }
+        after_name = is_name_char(c);
+    }
+    paths
+}
+
+// -- Templates ------------------------------------------------------------
+
+/// `template name(param, ...): body`.
+fn template_decl(input: &mut &str) -> ModalResult<Template> {
+    (ws, "template", ws).parse_next(input)?;
+    let name = cut_err(binding_name)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "template name",
+        )))
+        .parse_next(input)?;
+    (ws, cut_err('('), ws).parse_next(input)?;
+    let mut params = Vec::new();
+    if opt(')').parse_next(input)?.is_none() {
+        loop {
+            let param = cut_err(binding_name)
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "template parameter name",
+                )))
+                .parse_next(input)?;
+            params.push(param.to_owned());
+            ws.parse_next(input)?;
+            if opt(')').parse_next(input)?.is_some() {
+                break;
+            }
+            (cut_err(','), ws).parse_next(input)?;
+        }
+    }
+    (ws, cut_err(':')).parse_next(input)?;
+    let body = cut_err(expr)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "template body",
+        )))
+        .parse_next(input)?;
+    Ok(Template {
+        name: name.to_owned(),
+        params,
+        body,
+    })
+}
+
+/// `expand name(arg, ...) [as rule_name] [(priority N)]`. Returns the
+/// expansion and the length of the input remaining at its template name,
+/// from which the caller computes the line number.
+fn expand_decl(input: &mut &str) -> ModalResult<(Expansion, usize)> {
+    let metadata = annotations.parse_next(input)?;
+    (ws, "expand", ws).parse_next(input)?;
+    let remaining = input.len();
+    let template = cut_err(binding_name)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "template name",
+        )))
+        .parse_next(input)?;
+    (ws, cut_err('('), ws).parse_next(input)?;
+    let mut args = Vec::new();
+    if opt(')').parse_next(input)?.is_none() {
+        loop {
+            args.push(cut_err(bound).parse_next(input)?);
+            ws.parse_next(input)?;
+            if opt(')').parse_next(input)?.is_some() {
+                break;
+            }
+            cut_err(',').parse_next(input)?;
+        }
+    }
+    let name = opt(preceded(
+        (ws, "as", ws),
+        cut_err(rule_name_ident).context(StrContext::Expected(StrContextValue::Description(
+            "rule name",
+        ))),
+    ))
+    .parse_next(input)?;
+    let priority = opt(priority_annotation).parse_next(input)?;
+    let expansion = Expansion {
+        template: template.to_owned(),
+        args,
+        name: name.map(str::to_owned),
+        priority,
+        metadata,
+        line: 0,
+    };
+    Ok((expansion, remaining))
+}
+
+// -- Tests -----------------------------------------------------------------
+
+/// `test "name" { given path = value, ...; expect rule }`. The `given`
+/// clause is optional, and `expect none` expects no terminal to match.
+fn test_decl(input: &mut &str) -> ModalResult<RuleTest> {
+    (ws, "test", ws).parse_next(input)?;
+    let name = cut_err(string_literal)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "quoted test name",
+        )))
+        .parse_next(input)?;
+    (ws, cut_err('{'), ws).parse_next(input)?;
+    let mut given = Vec::new();
+    if opt("given").parse_next(input)?.is_some() {
+        loop {
+            ws.parse_next(input)?;
+            let path = cut_err(ident)
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "field path",
+                )))
+                .parse_next(input)?;
+            (ws, cut_err('=')).parse_next(input)?;
+            given.push((path.to_owned(), cut_err(value).parse_next(input)?));
+            ws.parse_next(input)?;
+            if opt(';').parse_next(input)?.is_some() {
+                break;
+            }
+            cut_err(',')
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "',' or ';' after a given value",
+                )))
+                .parse_next(input)?;
+        }
+        ws.parse_next(input)?;
+    }
+    cut_err("expect")
+        .context(StrContext::Expected(StrContextValue::Description(
+            "'given' or 'expect'",
+        )))
+        .parse_next(input)?;
+    ws.parse_next(input)?;
+    let expect = cut_err(ident)
+        .context(StrContext::Expected(StrContextValue::Description(
+            "terminal rule name or 'none'",
+        )))
+        .parse_next(input)?;
+    (ws, cut_err('}')).parse_next(input)?;
+    Ok(RuleTest {
+        name,
+        given,
+        expect: (expect != "none").then(|| expect.to_owned()),
+    })
+}
+
 // -- Top-level parser -------------------------------------------------------
 
+pub(super) enum Item {
+    Rule(Rule, Option<Terminal>),
+    Field(String, FieldType),
+    Const(String, Value),
+    Param(String, Option<Value>),
+    Import(String, String),
+    Template(Template),
+    Expand(Expansion, usize),
+    Test(RuleTest),
+}
+
+fn item(input: &mut &str) -> ModalResult<Item> {
+    alt((
+        import_decl.map(|(path, alias)| Item::Import(path, alias)),
+        template_decl.map(Item::Template),
+        expand_decl.map(|(expansion, remaining)| Item::Expand(expansion, remaining)),
+        field_decl.map(|(path, ty)| Item::Field(path, ty)),
+        const_decl.map(|(name, value)| Item::Const(name, value)),
+        param_decl.map(|(name, default)| Item::Param(name, default)),
+        test_decl.map(Item::Test),
+        rule_def.map(|(rule, terminal)| Item::Rule(rule, terminal)),
+    ))
+    .parse_next(input)
+}
+
+/// Top-level items, each with the input lengths remaining at its first
+/// token and just past its last, from which the formatter recovers spans.
+/// Leading whitespace and comments are not part of an item.
+pub(super) fn spanned_items(input: &mut &str) -> ModalResult<Vec<(Item, usize, usize)>> {
+    let mut items = Vec::new();
+    loop {
+        ws.parse_next(input)?;
+        let start = input.len();
+        let checkpoint = input.checkpoint();
+        match item.parse_next(input) {
+            Ok(item) => items.push((item, start, input.len())),
+            Err(ErrMode::Backtrack(_)) => {
+                input.reset(&checkpoint);
+                return Ok(items);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// A single expression, with surrounding whitespace and comments.
+pub(super) fn expression(input: &mut &str) -> ModalResult<Expr> {
+    let parsed = expr.parse_next(input)?;
+    ws.parse_next(input)?;
+    Ok(parsed)
+}
+
 pub fn parse_ruleset(input: &mut &str) -> ModalResult<ParsedRuleSet> {
     let mut rules = Vec::new();
     let mut terminals = Vec::new();
-
-    let defs: Vec<(Rule, Option<Terminal>)> = repeat(0.., rule_def).parse_next(input)?;
-    for (rule, terminal) in defs {
-        rules.push(rule);
-        if let Some(t) = terminal {
-            terminals.push(t);
+    let mut schema: Option<Schema> = None;
+    let mut constants = Vec::new();
+    let mut params = Vec::new();
+    let mut imports = Vec::new();
+    let mut templates = Vec::new();
+    let mut expansions = Vec::new();
+    let mut tests = Vec::new();
+    let source = *input;
+
+    for (item, _, _) in spanned_items.parse_next(input)? {
+        match item {
+            Item::Rule(rule, terminal) => {
+                rules.push(rule);
+                if let Some(t) = terminal {
+                    terminals.push(t);
+                }
+            }
+            Item::Field(path, ty) => schema.get_or_insert_with(Schema::new).insert(&path, ty),
+            Item::Const(name, value) => constants.push((name, value)),
+            Item::Param(name, default) => params.push((name, default)),
+            Item::Import(path, alias) => imports.push((path, alias)),
+            Item::Template(template) => templates.push(template),
+            Item::Expand(mut expansion, remaining) => {
+                let offset = source.len() - remaining;
+                expansion.line = source[..offset].matches('\n').count() + 1;
+                expansions.push(expansion);
+            }
+            Item::Test(test) => tests.push(test),
         }
     }
 
     ws.parse_next(input)?;
 
-    Ok(ParsedRuleSet { rules, terminals })
+    Ok(ParsedRuleSet {
+        rules,
+        terminals,
+        schema,
+        constants,
+        params,
+        imports,
+        templates,
+        expansions,
+        tests,
+    })
 }
 
 #[cfg(test)]
@@ -473,6 +1117,162 @@ mod tests {
         assert_eq!(result.terminals.len(), 1);
         assert_eq!(result.terminals[0].rule_name, "allow");
         assert_eq!(result.terminals[0].priority, 10);
+        assert_eq!(result.terminals[0].on_unknown, UnknownPolicy::AsFalse);
+    }
+
+    #[test]
+    fn parse_terminal_on_unknown() {
+        let result = parse(
+            "rule deny (priority 0, on_unknown indeterminate):\n    x == 1\n\
+             rule allow (priority 1, on_unknown false):\n    y == 1",
+        )
+        .unwrap();
+        assert_eq!(result.terminals[0].on_unknown, UnknownPolicy::Indeterminate);
+        assert_eq!(result.terminals[1].on_unknown, UnknownPolicy::AsFalse);
+        assert!(parse("rule r (priority 0, on_unknown maybe):\n    x == 1").is_err());
+    }
+
+    #[test]
+    fn parse_field_declarations() {
+        let result = parse(
+            "field user.age: int\nrule a:\n    user.age > 1 AND b\n\
+             field limits : map # per-country limits\nrule b:\n    limits.US > 0",
+        )
+        .unwrap();
+        assert_eq!(result.rules.len(), 2);
+        let schema = result.schema.unwrap();
+        assert_eq!(schema.get("user.age"), Some(FieldType::Int));
+        assert_eq!(schema.get("limits"), Some(FieldType::Map));
+
+        assert!(parse("rule r:\n    x == 1").unwrap().schema.is_none());
+        assert!(parse("field x: integer\nrule r:\n    x == 1").is_err());
+        assert!(parse("field x\nrule r:\n    x == 1").is_err());
+    }
+
+    #[test]
+    fn parse_constants_and_params() {
+        let result = parse(
+            "const MIN_AGE = 18\nconst BLOCKED = [\"XX\", \"YY\"]\nparam LIMIT\n\
+             param TIER = \"gold\"\nrule r:\n    age >= MIN_AGE AND country NOT IN BLOCKED",
+        )
+        .unwrap();
+        assert_eq!(result.constants.len(), 2);
+        assert_eq!(
+            result.params,
+            [
+                ("LIMIT".to_owned(), None),
+                ("TIER".to_owned(), Some(Value::from("gold")))
+            ]
+        );
+        let Expr::And(left, right) = result.rules[0].condition.as_ref().unwrap() else {
+            panic!("expected AND");
+        };
+        assert!(matches!(
+            left.as_ref(),
+            Expr::Compare {
+                value: Value::Int(18),
+                ..
+            }
+        ));
+        assert!(matches!(
+            right.as_ref(),
+            Expr::NotIn { members, .. } if matches!(members[0], Bound::Literal(Value::List(_)))
+        ));
+
+        assert!(parse("const A = 1\nconst A = 2\nrule r:\n    x == A").is_err());
+        assert!(parse("const A = 1\nparam A\nrule r:\n    x == A").is_err());
+        assert!(parse("const A.B = 1\nrule r:\n    x == 1").is_err());
+        assert!(parse("const A = x\nrule r:\n    x == 1").is_err());
+    }
+
+    #[test]
+    fn parse_tests() {
+        let result = parse(
+            "rule deny (priority 0):\n    user.age < 18\n\
+             test \"minor is denied\" { given user.age = 15, user.tags = [\"a\"]; expect deny }\n\
+             test \"empty\" {\n    expect none # nothing set\n}",
+        )
+        .unwrap();
+        assert_eq!(
+            result.tests,
+            [
+                RuleTest {
+                    name: "minor is denied".into(),
+                    given: vec![
+                        ("user.age".into(), Value::Int(15)),
+                        ("user.tags".into(), Value::List(vec!["a".into()])),
+                    ],
+                    expect: Some("deny".into()),
+                },
+                RuleTest {
+                    name: "empty".into(),
+                    given: vec![],
+                    expect: None,
+                },
+            ]
+        );
+
+        assert!(parse("test minor { expect none }").is_err());
+        assert!(parse("test \"t\" { given x = 1 expect none }").is_err());
+        assert!(parse("test \"t\" { given x = 1; }").is_err());
+        assert!(parse("test \"t\" { given x = y; expect none }").is_err());
+        assert!(parse("test \"t\" { expect none").is_err());
+    }
+
+    #[test]
+    fn parse_imports() {
+        let result = parse(
+            "import \"common/identity.ooroo\" as identity\n\
+             rule r (priority 0):\n    identity.verified AND x == 1",
+        )
+        .unwrap();
+        assert_eq!(
+            result.imports,
+            [("common/identity.ooroo".to_owned(), "identity".to_owned())]
+        );
+        assert!(matches!(
+            result.rules[0].condition.as_ref().unwrap(),
+            Expr::And(left, _) if matches!(left.as_ref(), Expr::RuleRef(n) if n == "identity.verified")
+        ));
+
+        assert!(parse("import common as c\nrule r:\n    x == 1").is_err());
+        assert!(parse("import \"a.ooroo\" as a.b\nrule r:\n    x == 1").is_err());
+        assert!(parse("import \"a.ooroo\"\nrule r:\n    x == 1").is_err());
+    }
+
+    #[test]
+    fn parse_template_expansion() {
+        let result = parse(
+            "template gate(prod, tiers):\n    product.prod.enabled == true AND tier IN tiers\n\
+             expand gate(books, [\"gold\"]) as books_gate (priority 3)",
+        )
+        .unwrap();
+        assert_eq!(result.rules.len(), 1);
+        assert_eq!(result.rules[0].name, "books_gate");
+        assert_eq!(result.terminals[0].priority, 3);
+        let Expr::And(left, right) = result.rules[0].condition.as_ref().unwrap() else {
+            panic!("expected AND");
+        };
+        assert!(matches!(
+            left.as_ref(),
+            Expr::Compare { field, .. } if field == "product.books.enabled"
+        ));
+        assert!(matches!(
+            right.as_ref(),
+            Expr::In { members, .. } if matches!(members[0], Bound::Literal(Value::List(_)))
+        ));
+
+        assert!(parse("template t(a b):\n    a == 1").is_err());
+        assert!(parse("template t(a):\n    a == 1\nexpand t(1 2)").is_err());
+    }
+
+    #[test]
+    fn parse_in_bare_list_field() {
+        let result = parse("rule r:\n    role IN allowed_roles").unwrap();
+        assert!(matches!(
+            result.rules[0].condition.as_ref().unwrap(),
+            Expr::In { members, .. } if members == &[Bound::Field("allowed_roles".into())]
+        ));
     }
 
     #[test]
@@ -562,7 +1362,7 @@ mod tests {
     fn parse_all_value_types() {
         let cases = [
             ("42", Value::Int(42)),
-            ("3.14", Value::Float(3.14)),
+            ("3.25", Value::Float(3.25)),
             ("true", Value::Bool(true)),
             ("false", Value::Bool(false)),
             (r#""hello""#, Value::String("hello".into())),
@@ -823,7 +1623,7 @@ mod tests {
 
     #[test]
     fn parse_list_literal_mixed_types() {
-        let result = parse("rule r:\n    x == [1, \"hello\", true, 3.14]").unwrap();
+        let result = parse("rule r:\n    x == [1, \"hello\", true, 3.25]").unwrap();
         match result.rules[0].condition.as_ref().unwrap() {
             Expr::Compare { value, .. } => {
                 assert_eq!(
@@ -832,7 +1632,7 @@ mod tests {
                         Value::Int(1),
                         Value::String("hello".into()),
                         Value::Bool(true),
-                        Value::Float(3.14),
+                        Value::Float(3.25),
                     ])
                 );
             }
@@ -869,6 +1669,173 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_any_quantifier() {
+        let result = parse("rule r:\n    ANY(order.items AS item, item.price > 100)").unwrap();
+        match result.rules[0].condition.as_ref().unwrap() {
+            Expr::Any {
+                list,
+                var,
+                predicate,
+            } => {
+                assert_eq!(list, "order.items");
+                assert_eq!(var, "item");
+                assert!(matches!(
+                    predicate.as_ref(),
+                    Expr::Compare { field, .. } if field == "item.price"
+                ));
+            }
+            other => panic!("expected Any, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_all_quantifier_lowercase() {
+        let result = parse("rule r:\n    all(user.roles as role, role != \"guest\")").unwrap();
+        assert!(matches!(
+            result.rules[0].condition.as_ref().unwrap(),
+            Expr::All { var, .. } if var == "role"
+        ));
+    }
+
+    #[test]
+    fn parse_quantifier_keyword_as_rule_ref() {
+        let result = parse("rule r:\n    any AND all").unwrap();
+        assert!(matches!(
+            result.rules[0].condition.as_ref().unwrap(),
+            Expr::And(a, b)
+                if matches!(a.as_ref(), Expr::RuleRef(n) if n == "any")
+                    && matches!(b.as_ref(), Expr::RuleRef(n) if n == "all")
+        ));
+    }
+
+    #[test]
+    fn parse_contains_expression() {
+        let result = parse("rule r:\n    user.roles CONTAINS \"admin\"").unwrap();
+        match result.rules[0].condition.as_ref().unwrap() {
+            Expr::Contains { field, value } => {
+                assert_eq!(field, "user.roles");
+                assert_eq!(*value, Bound::Literal(Value::String("admin".into())));
+            }
+            other => panic!("expected Contains, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_set_operators() {
+        let result = parse(
+            "rule r:\n    a OVERLAPS b AND a subset_of [\"x\", \"y\"] AND a SUPERSET_OF b AND a DISJOINT_FROM b",
+        )
+        .unwrap();
+        let cond = result.rules[0].condition.as_ref().unwrap();
+        assert_eq!(
+            cond.to_string(),
+            "((((a OVERLAPS b) AND (a SUBSET_OF [\"x\", \"y\"])) AND (a SUPERSET_OF b)) AND (a DISJOINT_FROM b))"
+        );
+    }
+
+    #[test]
+    fn parse_len_expression() {
+        let result = parse("rule r:\n    len(user.roles) >= 2").unwrap();
+        match result.rules[0].condition.as_ref().unwrap() {
+            Expr::Len { field, op, value } => {
+                assert_eq!(field, "user.roles");
+                assert_eq!(*op, CompareOp::Gte);
+                assert_eq!(*value, Bound::Literal(Value::Int(2)));
+            }
+            other => panic!("expected Len, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_len_as_field_and_rule_ref() {
+        let result = parse("rule r:\n    len AND length > 3").unwrap();
+        assert_eq!(
+            result.rules[0].condition.as_ref().unwrap().to_string(),
+            "(len AND (length > 3))"
+        );
+    }
+
+    #[test]
+    fn parse_indexed_field_paths() {
+        let result = parse(
+            "rule r:\n    limits[user.country] >= order.total AND limits[\"a.b\"].daily == 1",
+        )
+        .unwrap();
+        assert_eq!(
+            result.rules[0].condition.as_ref().unwrap().to_string(),
+            "((limits[user.country] >= order.total) AND (limits[\"a.b\"].daily == 1))"
+        );
+    }
+
+    #[test]
+    fn parse_backtick_names_and_keyword_prefixes() {
+        let result = parse(
+            "rule `my rule`:\n    `true` == true AND `AND`[0] > 1\n\
+             rule r:\n    `my rule` AND notable AND NOT.x == falsey",
+        )
+        .unwrap();
+        assert_eq!(result.rules[0].name, "my rule");
+        assert_eq!(
+            result.rules[0].condition.as_ref().unwrap().to_string(),
+            "((true == true) AND (AND[0] > 1))"
+        );
+        assert_eq!(
+            result.rules[1].condition.as_ref().unwrap().to_string(),
+            "((my rule AND notable) AND (NOT.x == falsey))"
+        );
+    }
+
+    #[cfg(feature = "binary-cache")]
+    #[test]
+    fn import_paths_skip_strings_and_comments() {
+        let source = "import \"a.ooroo\" as a # import \"b.ooroo\" as b\n\
+                      rule r: x == \"import \\\"c.ooroo\\\" as c\" AND reimport\n\
+                      rule s: `import \"d\" as d` AND import == 1\n\
+                      import \"e/f.ooroo\" as e";
+        assert_eq!(import_paths(source), ["a.ooroo", "e/f.ooroo"]);
+    }
+
+    #[test]
+    fn parse_indexed_bound_and_in_list() {
+        // `x IN [..]` keeps working because an index must follow the name directly.
+        let result = parse("rule r:\n    x IN [a, m[k]] AND y BETWEEN 1, caps[tier]").unwrap();
+        assert_eq!(
+            result.rules[0].condition.as_ref().unwrap().to_string(),
+            "((x IN [a, m[k]]) AND (y BETWEEN 1, caps[tier]))"
+        );
+    }
+
+    #[test]
+    fn parse_list_index_and_wildcard_paths() {
+        let result = parse(
+            "rule r:\n    order.items[0].sku == \"A1\" AND wanted IN [order.items[*].tags[-1]]",
+        )
+        .unwrap();
+        assert_eq!(
+            result.rules[0].condition.as_ref().unwrap().to_string(),
+            "((order.items[0].sku == \"A1\") AND (wanted IN [order.items[*].tags[-1]]))"
+        );
+        assert!(parse("rule r:\n    items[-] == 1").is_err());
+    }
+
+    #[test]
+    fn parse_has_key() {
+        let result = parse("rule r:\n    limits HAS_KEY user.country").unwrap();
+        match result.rules[0].condition.as_ref().unwrap() {
+            Expr::HasKey { field, key } => {
+                assert_eq!(field, "limits");
+                assert_eq!(*key, Bound::Field("user.country".into()));
+            }
+            other => panic!("expected HasKey, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_unclosed_index_rejected() {
+        assert!(parse("rule r:\n    limits[user.country > 1").is_err());
+    }
+
     #[test]
     fn parse_string_with_escapes() {
         let result = parse(
@@ -0,0 +1,346 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+
+use crate::{OorooError, Schema};
+
+use super::error::ImportError;
+use super::parser::ParsedRuleSet;
+
+/// Reads the DSL sources named by `import` declarations.
+///
+/// Import paths are relative to the directory of the importing file, and
+/// are normalized before they reach the loader: an `import "../common.ooroo"`
+/// in `policies/main.ooroo` loads `common.ooroo`, and an
+/// `import "identity.ooroo"` there loads `policies/identity.ooroo`. Paths
+/// starting with `/` are used as they are. Each loader then resolves the
+/// path it receives against its own root.
+pub trait SourceLoader {
+    /// Return the source stored at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if no source exists at `path` or it cannot be read.
+    fn load(&self, path: &str) -> io::Result<String>;
+}
+
+/// Loads sources from the filesystem, relative to a root directory.
+#[derive(Debug, Clone)]
+pub struct FileLoader {
+    root: PathBuf,
+}
+
+impl FileLoader {
+    /// Create a loader that resolves paths against `root`.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl SourceLoader for FileLoader {
+    fn load(&self, path: &str) -> io::Result<String> {
+        std::fs::read_to_string(self.root.join(path))
+    }
+}
+
+/// Loads sources from an in-memory map of paths, e.g. for tests or rules
+/// stored in a database.
+///
+/// # Example
+///
+/// ```
+/// use ooroo::{Context, MemoryLoader, RuleSet};
+///
+/// let loader = MemoryLoader::new()
+///     .file("identity.ooroo", "rule verified:\n    user.verified == true")
+///     .file(
+///         "main.ooroo",
+///         "import \"identity.ooroo\" as identity\n\
+///          rule allow (priority 0):\n    identity.verified",
+///     );
+///
+/// let ruleset = RuleSet::from_loader(&loader, "main.ooroo").unwrap();
+/// let ctx = Context::new().set("user.verified", true);
+/// assert_eq!(ruleset.evaluate(&ctx).unwrap().terminal(), "allow");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLoader {
+    files: HashMap<String, String>,
+}
+
+impl MemoryLoader {
+    /// Create an empty loader.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source under `path`.
+    #[must_use]
+    pub fn file(mut self, path: &str, source: impl Into<String>) -> Self {
+        self.insert(path, source);
+        self
+    }
+
+    /// Add a source under `path` (mutable reference version).
+    pub fn insert(&mut self, path: &str, source: impl Into<String>) {
+        self.files.insert(normalize(path), source.into());
+    }
+}
+
+impl SourceLoader for MemoryLoader {
+    fn load(&self, path: &str) -> io::Result<String> {
+        self.files.get(path).cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("no source named '{path}'"))
+        })
+    }
+}
+
+/// Resolve `.` and `..` segments lexically.
+fn normalize(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if segments.last().is_some_and(|s| *s != "..") => {
+                segments.pop();
+            }
+            other => segments.push(other),
+        }
+    }
+    let joined = segments.join("/");
+    if path.starts_with('/') {
+        format!("/{joined}")
+    } else {
+        joined
+    }
+}
+
+/// The loader path of `import`, as written in `file`.
+fn resolve_import(file: &str, import: &str) -> String {
+    match file.rfind('/') {
+        Some(end) if !import.starts_with('/') => normalize(&format!("{}/{import}", &file[..end])),
+        _ => normalize(import),
+    }
+}
+
+/// Load `entry` and everything it imports, merged into one ruleset.
+///
+/// Rules of a file imported as `alias` are renamed to `alias.rule`; nested
+/// imports stack their prefixes. Errors in the entry file itself are
+/// reported as they are by [`parse`](super::parse); errors in imported
+/// files name the file they came from.
+pub(crate) fn load(loader: &dyn SourceLoader, entry: &str) -> Result<ParsedRuleSet, OorooError> {
+    let entry = normalize(entry);
+    let source = loader.load(&entry)?;
+    let parsed = super::parse(&source)?;
+    let mut state = LoadState {
+        loader,
+        stack: vec![entry.clone()],
+        merged: ParsedRuleSet {
+            rules: Vec::new(),
+            terminals: Vec::new(),
+            schema: None,
+            constants: Vec::new(),
+            params: Vec::new(),
+            imports: Vec::new(),
+            templates: Vec::new(),
+            expansions: Vec::new(),
+            tests: Vec::new(),
+        },
+        rule_files: HashMap::new(),
+        field_files: HashMap::new(),
+    };
+    state.merge_file(&entry, "", parsed)?;
+    Ok(state.merged)
+}
+
+/// The source of `entry` followed by that of every file it imports,
+/// directly or not, each once and prefixed with its loader path. Imports
+/// are found by [`import_paths`](super::grammar::import_paths), so nothing
+/// is parsed; a file without imports gives its own text.
+#[cfg(feature = "binary-cache")]
+pub(crate) fn source_closure(loader: &dyn SourceLoader, entry: &str) -> io::Result<String> {
+    let entry = normalize(entry);
+    let mut text = loader.load(&entry)?;
+    let mut pending = vec![(entry.clone(), text.clone())];
+    let mut seen = HashSet::from([entry]);
+    while let Some((file, source)) = pending.pop() {
+        for import in super::grammar::import_paths(&source) {
+            let path = resolve_import(&file, &import);
+            if seen.insert(path.clone()) {
+                let source = loader.load(&path)?;
+                text.push_str(&format!("\0{path}\0{source}"));
+                pending.push((path, source));
+            }
+        }
+    }
+    Ok(text)
+}
+
+struct LoadState<'a> {
+    loader: &'a dyn SourceLoader,
+    /// Files currently being loaded, outermost first.
+    stack: Vec<String>,
+    merged: ParsedRuleSet,
+    /// The file each qualified rule name was defined in.
+    rule_files: HashMap<String, String>,
+    /// The file each field was first declared in.
+    field_files: HashMap<String, String>,
+}
+
+impl LoadState<'_> {
+    fn merge_file(
+        &mut self,
+        file: &str,
+        prefix: &str,
+        mut parsed: ParsedRuleSet,
+    ) -> Result<(), ImportError> {
+        let mut aliases = HashSet::new();
+        for (path, alias) in std::mem::take(&mut parsed.imports) {
+            if !aliases.insert(alias.clone()) {
+                return Err(ImportError::DuplicateAlias {
+                    file: file.to_owned(),
+                    alias,
+                });
+            }
+            let path = resolve_import(file, &path);
+            if let Some(start) = self.stack.iter().position(|p| *p == path) {
+                let mut chain = self.stack[start..].to_vec();
+                chain.push(path);
+                return Err(ImportError::Cycle { chain });
+            }
+            let source = self
+                .loader
+                .load(&path)
+                .map_err(|source| ImportError::Load {
+                    path: path.clone(),
+                    from: file.to_owned(),
+                    source,
+                })?;
+            let imported = super::parse(&source).map_err(|source| ImportError::Parse {
+                file: path.clone(),
+                source,
+            })?;
+            self.stack.push(path.clone());
+            self.merge_file(&path, &format!("{prefix}{alias}."), imported)?;
+            self.stack.pop();
+        }
+
+        parsed.namespace(prefix);
+        for rule in &parsed.rules {
+            if let Some(previous) = self.rule_files.insert(rule.name.clone(), file.to_owned()) {
+                return Err(ImportError::DuplicateRule {
+                    name: rule.name.clone(),
+                    file: file.to_owned(),
+                    previous,
+                });
+            }
+        }
+        if let Some(schema) = &parsed.schema {
+            let merged = self.merged.schema.get_or_insert_with(Schema::new);
+            for (path, field_type) in schema.iter() {
+                match merged.get(path) {
+                    Some(previous_type) if previous_type != field_type => {
+                        return Err(ImportError::ConflictingField {
+                            path: path.to_owned(),
+                            file: file.to_owned(),
+                            field_type,
+                            previous: self.field_files[path].clone(),
+                            previous_type,
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        merged.insert(path, field_type);
+                        self.field_files.insert(path.to_owned(), file.to_owned());
+                    }
+                }
+            }
+        }
+        self.merged.rules.append(&mut parsed.rules);
+        self.merged.terminals.append(&mut parsed.terminals);
+        self.merged.params.append(&mut parsed.params);
+        // Tests in imported files expect that file's own verdicts, which the
+        // importing file's terminals may outrank.
+        if prefix.is_empty() {
+            self.merged.tests.append(&mut parsed.tests);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_paths() {
+        assert_eq!(normalize("a/b.ooroo"), "a/b.ooroo");
+        assert_eq!(normalize("./a//b.ooroo"), "a/b.ooroo");
+        assert_eq!(normalize("a/../b.ooroo"), "b.ooroo");
+        assert_eq!(normalize("../a/./b.ooroo"), "../a/b.ooroo");
+        assert_eq!(normalize("/rules/a.ooroo"), "/rules/a.ooroo");
+    }
+
+    #[test]
+    fn imports_resolve_against_the_importing_file() {
+        assert_eq!(resolve_import("main.ooroo", "a/b.ooroo"), "a/b.ooroo");
+        assert_eq!(resolve_import("sub/a.ooroo", "x.ooroo"), "sub/x.ooroo");
+        assert_eq!(resolve_import("sub/a.ooroo", "../x.ooroo"), "x.ooroo");
+        assert_eq!(
+            resolve_import("/r/sub/a.ooroo", "./x.ooroo"),
+            "/r/sub/x.ooroo"
+        );
+        assert_eq!(
+            resolve_import("sub/a.ooroo", "/abs/x.ooroo"),
+            "/abs/x.ooroo"
+        );
+    }
+
+    #[cfg(feature = "binary-cache")]
+    #[test]
+    fn source_closure_covers_every_imported_file() {
+        let mut loader = MemoryLoader::new()
+            .file("main.ooroo", "import \"sub/a.ooroo\" as a\nrule r: a.x")
+            .file("sub/a.ooroo", "import \"../b.ooroo\" as b\nrule x: b.y")
+            .file("b.ooroo", "rule y: v == 1");
+        let before = source_closure(&loader, "main.ooroo").unwrap();
+        assert!(before.starts_with("import \"sub/a.ooroo\" as a\nrule r: a.x\0sub/a.ooroo\0"));
+        assert!(before.ends_with("\0b.ooroo\0rule y: v == 1"));
+
+        loader.insert("b.ooroo", "rule y: v == 2");
+        assert_ne!(source_closure(&loader, "main.ooroo").unwrap(), before);
+
+        let plain = MemoryLoader::new().file("p.ooroo", "rule r: v == 1");
+        assert_eq!(source_closure(&plain, "p.ooroo").unwrap(), "rule r: v == 1");
+        assert!(source_closure(
+            &MemoryLoader::new().file("m.ooroo", "import \"x\" as x"),
+            "m.ooroo"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn nested_imports_stack_prefixes() {
+        let loader = MemoryLoader::new()
+            .file("base.ooroo", "rule adult:\n    age >= 18")
+            .file(
+                "identity.ooroo",
+                "import \"base.ooroo\" as base\nrule verified:\n    base.adult AND id == true",
+            )
+            .file(
+                "main.ooroo",
+                "import \"identity.ooroo\" as identity\nrule allow (priority 0):\n    identity.verified",
+            );
+        let merged = load(&loader, "main.ooroo").unwrap();
+        let names: Vec<&str> = merged.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["identity.base.adult", "identity.verified", "allow"]);
+        assert!(matches!(
+            merged.rules[1].condition.as_ref().unwrap(),
+            crate::Expr::And(left, _)
+                if matches!(left.as_ref(), crate::Expr::RuleRef(n) if n == "identity.base.adult")
+        ));
+    }
+}
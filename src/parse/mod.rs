@@ -1,8 +1,18 @@
 mod error;
+mod format;
 mod grammar;
+mod loader;
+mod params;
 mod parser;
+mod template;
 
-pub use error::ParseError;
+pub use error::{ImportError, ParseError, ToDslError};
+pub(crate) use format::{expr_to_dsl, rules_to_dsl};
+pub(crate) use loader::load;
+#[cfg(feature = "binary-cache")]
+pub(crate) use loader::source_closure;
+pub use loader::{FileLoader, MemoryLoader, SourceLoader};
+pub use params::Params;
 pub use parser::ParsedRuleSet;
 
 /// Parse a DSL input string into a [`ParsedRuleSet`].
@@ -12,7 +22,49 @@ pub use parser::ParsedRuleSet;
 /// Returns [`ParseError`] if the input is not valid DSL syntax.
 pub fn parse(input: &str) -> Result<ParsedRuleSet, ParseError> {
     use winnow::Parser;
-    grammar::parse_ruleset
+    let mut parsed = grammar::parse_ruleset
+        .parse(input)
+        .map_err(|e| ParseError::new(e.to_string()))?;
+    parsed.expand_templates()?;
+    parsed.resolve_constants()?;
+    Ok(parsed)
+}
+
+/// Parse a single DSL expression, such as a rule body.
+pub(crate) fn parse_expr(input: &str) -> Result<crate::Expr, ParseError> {
+    use winnow::Parser;
+    grammar::expression
         .parse(input)
         .map_err(|e| ParseError::new(e.to_string()))
 }
+
+/// Format DSL source canonically: four-space indented rule bodies, minimal
+/// parentheses, annotations one per line in a fixed order, and comments
+/// kept. Formatting is idempotent and the result parses to the same rules.
+///
+/// # Example
+///
+/// ```
+/// let source = "rule  adult (priority 1) :  (user.age >= 18)  # grown-ups\n";
+/// let formatted = ooroo::format_dsl(source).unwrap();
+/// assert_eq!(formatted, "rule adult (priority 1):\n    user.age >= 18 # grown-ups\n");
+/// assert!(ooroo::is_formatted(&formatted).unwrap());
+/// ```
+///
+/// # Errors
+///
+/// Returns [`ParseError`] if the input is not valid DSL syntax. Templates,
+/// constants and imports are formatted as written, not resolved.
+pub fn format_dsl(source: &str) -> Result<String, ParseError> {
+    format::format_source(source)
+}
+
+/// Check whether `source` is already canonically formatted, like
+/// `fmt --check`.
+///
+/// # Errors
+///
+/// Returns [`ParseError`] if the input is not valid DSL syntax.
+pub fn is_formatted(source: &str) -> Result<bool, ParseError> {
+    Ok(format::format_source(source)? == source)
+}
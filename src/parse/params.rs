@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use crate::Value;
+
+/// Values for the `param` declarations of a DSL ruleset, supplied when it is
+/// compiled with [`RuleSet::from_dsl_with_params()`](crate::RuleSet::from_dsl_with_params).
+///
+/// # Example
+///
+/// ```
+/// use ooroo::{Context, Params, RuleSet};
+///
+/// let dsl = r#"
+/// param MAX_AMOUNT = 10000
+///
+/// rule within_limit (priority 0):
+///     order.amount <= MAX_AMOUNT
+/// "#;
+///
+/// let tenant = RuleSet::from_dsl_with_params(dsl, &Params::new().set("MAX_AMOUNT", 500_i64))
+///     .unwrap();
+/// let ctx = Context::new().set("order.amount", 800_i64);
+/// assert!(tenant.evaluate(&ctx).is_none());
+/// assert!(RuleSet::from_dsl(dsl).unwrap().evaluate(&ctx).is_some());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: HashMap<String, Value>,
+}
+
+impl Params {
+    /// Create an empty set of parameter values.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a parameter value.
+    #[must_use]
+    pub fn set(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Set a parameter value (mutable reference version).
+    pub fn insert(&mut self, name: &str, value: impl Into<Value>) {
+        self.values.insert(name.to_owned(), value.into());
+    }
+
+    /// The value supplied for `name`, if any.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+}
@@ -1,8 +1,307 @@
-use crate::{Rule, Terminal};
+use std::collections::{HashMap, HashSet};
+
+use crate::{Bound, CompileError, Expr, Rule, RuleTest, Schema, Terminal, Value};
+
+use super::error::ParseError;
+use super::params::Params;
+use super::template::{self, Expansion, Template};
 
 /// The result of parsing a DSL input string.
 #[derive(Debug)]
 pub struct ParsedRuleSet {
     pub rules: Vec<Rule>,
     pub terminals: Vec<Terminal>,
+    /// Field declarations, if the input contains any.
+    pub schema: Option<Schema>,
+    /// `const` declarations, in source order. Substituted into the rules by
+    /// [`parse`](super::parse).
+    pub constants: Vec<(String, Value)>,
+    /// `param` declarations and their defaults, in source order. Substituted
+    /// into the rules by [`bind_params`](Self::bind_params).
+    pub params: Vec<(String, Option<Value>)>,
+    /// `import "path" as alias` declarations, as `(path, alias)` pairs in
+    /// source order. Resolved by [`load`](super::load).
+    pub imports: Vec<(String, String)>,
+    /// `template` definitions. Expanded by [`parse`](super::parse).
+    pub templates: Vec<Template>,
+    /// `expand` invocations, in source order.
+    pub expansions: Vec<Expansion>,
+    /// `test` blocks, in source order.
+    pub tests: Vec<RuleTest>,
+}
+
+impl ParsedRuleSet {
+    /// Turn each `expand` invocation into an ordinary rule.
+    pub(crate) fn expand_templates(&mut self) -> Result<(), ParseError> {
+        template::expand(
+            &self.templates,
+            std::mem::take(&mut self.expansions),
+            &mut self.rules,
+            &mut self.terminals,
+        )
+    }
+
+    /// Replace references to declared constants with their values.
+    pub(crate) fn resolve_constants(&mut self) -> Result<(), ParseError> {
+        let mut seen = HashSet::new();
+        let names = self
+            .constants
+            .iter()
+            .map(|(name, _)| name)
+            .chain(self.params.iter().map(|(name, _)| name));
+        for name in names {
+            if !seen.insert(name.as_str()) {
+                return Err(ParseError::new(format!(
+                    "'{name}' is declared more than once"
+                )));
+            }
+        }
+        for rule in &self.rules {
+            let Some(condition) = &rule.condition else {
+                continue;
+            };
+            let Some(name) = declared_subject(condition, &seen, &mut Vec::new()) else {
+                continue;
+            };
+            let kind = if self.constants.iter().any(|(n, _)| n == name) {
+                "constant"
+            } else {
+                "parameter"
+            };
+            return Err(ParseError::new(format!(
+                "rule '{}' uses {kind} '{name}' as a field; constants and parameters only \
+                 stand for values",
+                rule.name
+            )));
+        }
+        let values: HashMap<&str, &Value> = self
+            .constants
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+            .collect();
+        substitute(&mut self.rules, &values);
+        Ok(())
+    }
+
+    /// Replace references to declared parameters with the supplied values,
+    /// falling back to each parameter's default.
+    pub(crate) fn bind_params(&mut self, supplied: &Params) -> Result<(), CompileError> {
+        if let Some(name) = supplied
+            .names()
+            .filter(|name| !self.params.iter().any(|(declared, _)| declared == name))
+            .min()
+        {
+            return Err(CompileError::UndeclaredParam {
+                name: name.to_owned(),
+            });
+        }
+        let mut values = HashMap::new();
+        for (name, default) in &self.params {
+            let value = supplied
+                .get(name)
+                .or(default.as_ref())
+                .ok_or_else(|| CompileError::MissingParam { name: name.clone() })?;
+            values.insert(name.as_str(), value);
+        }
+        substitute(&mut self.rules, &values);
+        Ok(())
+    }
+
+    /// Move this file's rules and parameters under `prefix`, so that a file
+    /// imported as `identity` defines `identity.verified` and declares the
+    /// parameter `identity.MAX`. Rule references are prefixed as well: a
+    /// local reference names a rule of this file, and an `alias.rule`
+    /// reference names a rule that was itself imported under this prefix.
+    pub(crate) fn namespace(&mut self, prefix: &str) {
+        let renamed: HashMap<&str, String> = self
+            .params
+            .iter()
+            .map(|(name, _)| (name.as_str(), format!("{prefix}{name}")))
+            .collect();
+        let lookup = |name: &str| renamed.get(name).map(|n| Bound::Field(n.clone()));
+        for rule in &mut self.rules {
+            rule.name.insert_str(0, prefix);
+            if let Some(condition) = &mut rule.condition {
+                prefix_rule_refs(condition, prefix);
+                if !renamed.is_empty() {
+                    substitute_expr(condition, &lookup, &mut Vec::new());
+                }
+            }
+        }
+        for terminal in &mut self.terminals {
+            terminal.rule_name.insert_str(0, prefix);
+        }
+        for (name, _) in &mut self.params {
+            name.insert_str(0, prefix);
+        }
+    }
+}
+
+fn substitute(rules: &mut [Rule], values: &HashMap<&str, &Value>) {
+    if values.is_empty() {
+        return;
+    }
+    let lookup = |name: &str| values.get(name).map(|v| Bound::Literal((*v).clone()));
+    for rule in rules {
+        if let Some(condition) = &mut rule.condition {
+            substitute_expr(condition, &lookup, &mut Vec::new());
+        }
+    }
+}
+
+/// Replace named references in value positions: the right-hand side of a
+/// comparison and literal-or-field bounds. A name `lookup` maps to a literal
+/// becomes that value; one mapped to a field becomes a reference to that
+/// field. Names shadowed by a quantifier element variable are left alone.
+fn substitute_expr(
+    expr: &mut Expr,
+    lookup: &dyn Fn(&str) -> Option<Bound>,
+    vars: &mut Vec<String>,
+) {
+    let resolve = |name: &str, vars: &[String]| {
+        if vars.iter().any(|v| v == name) {
+            None
+        } else {
+            lookup(name)
+        }
+    };
+    let substitute_bound = |bound: &mut Bound, vars: &[String]| {
+        if let Bound::Field(name) = bound {
+            if let Some(replacement) = resolve(name, vars) {
+                *bound = replacement;
+            }
+        }
+    };
+    match expr {
+        Expr::CompareFields { left, op, right } => match resolve(right, vars) {
+            Some(Bound::Literal(value)) => {
+                *expr = Expr::Compare {
+                    field: std::mem::take(left),
+                    op: *op,
+                    value,
+                };
+            }
+            Some(Bound::Field(field)) => *right = field,
+            None => {}
+        },
+        Expr::In { members, .. } | Expr::NotIn { members, .. } => {
+            for member in members {
+                substitute_bound(member, vars);
+            }
+        }
+        Expr::Between { low, high, .. } => {
+            substitute_bound(low, vars);
+            substitute_bound(high, vars);
+        }
+        Expr::Contains { value, .. }
+        | Expr::SetCompare { value, .. }
+        | Expr::Len { value, .. }
+        | Expr::HasKey { key: value, .. } => substitute_bound(value, vars),
+        Expr::And(a, b) | Expr::Or(a, b) => {
+            substitute_expr(a, lookup, vars);
+            substitute_expr(b, lookup, vars);
+        }
+        Expr::Not(inner) => substitute_expr(inner, lookup, vars),
+        Expr::AtLeast { exprs, .. } => {
+            for e in exprs {
+                substitute_expr(e, lookup, vars);
+            }
+        }
+        Expr::Any { var, predicate, .. } | Expr::All { var, predicate, .. } => {
+            vars.push(var.clone());
+            substitute_expr(predicate, lookup, vars);
+            vars.pop();
+        }
+        Expr::Compare { .. }
+        | Expr::Like { .. }
+        | Expr::NotLike { .. }
+        | Expr::IsNull(_)
+        | Expr::IsNotNull(_)
+        | Expr::RuleRef(_) => {}
+    }
+}
+
+/// The first declared name in `names` that `expr` uses where a field is
+/// expected: the tested field of a comparison, or a quantifier's list. Names
+/// shadowed by a quantifier element variable are skipped.
+fn declared_subject<'e>(
+    expr: &'e Expr,
+    names: &HashSet<&str>,
+    vars: &mut Vec<&'e str>,
+) -> Option<&'e str> {
+    let check = |field: &'e str, vars: &[&str]| {
+        (names.contains(field) && !vars.contains(&field)).then_some(field)
+    };
+    match expr {
+        Expr::Compare { field, .. }
+        | Expr::In { field, .. }
+        | Expr::NotIn { field, .. }
+        | Expr::Between { field, .. }
+        | Expr::Like { field, .. }
+        | Expr::NotLike { field, .. }
+        | Expr::IsNull(field)
+        | Expr::IsNotNull(field)
+        | Expr::Contains { field, .. }
+        | Expr::SetCompare { field, .. }
+        | Expr::Len { field, .. }
+        | Expr::HasKey { field, .. }
+        | Expr::CompareFields { left: field, .. } => check(field, vars),
+        Expr::And(a, b) | Expr::Or(a, b) => {
+            declared_subject(a, names, vars).or_else(|| declared_subject(b, names, vars))
+        }
+        Expr::Not(inner) => declared_subject(inner, names, vars),
+        Expr::AtLeast { exprs, .. } => exprs.iter().find_map(|e| declared_subject(e, names, vars)),
+        Expr::Any {
+            list,
+            var,
+            predicate,
+        }
+        | Expr::All {
+            list,
+            var,
+            predicate,
+        } => check(list, vars).or_else(|| {
+            vars.push(var);
+            let found = declared_subject(predicate, names, vars);
+            vars.pop();
+            found
+        }),
+        Expr::RuleRef(_) => None,
+    }
+}
+
+/// Prefix every rule reference in `expr`.
+fn prefix_rule_refs(expr: &mut Expr, prefix: &str) {
+    match expr {
+        Expr::RuleRef(name) => name.insert_str(0, prefix),
+        Expr::And(a, b) | Expr::Or(a, b) => {
+            prefix_rule_refs(a, prefix);
+            prefix_rule_refs(b, prefix);
+        }
+        Expr::Not(inner)
+        | Expr::Any {
+            predicate: inner, ..
+        }
+        | Expr::All {
+            predicate: inner, ..
+        } => prefix_rule_refs(inner, prefix),
+        Expr::AtLeast { exprs, .. } => {
+            for e in exprs {
+                prefix_rule_refs(e, prefix);
+            }
+        }
+        Expr::Compare { .. }
+        | Expr::CompareFields { .. }
+        | Expr::In { .. }
+        | Expr::NotIn { .. }
+        | Expr::Between { .. }
+        | Expr::Like { .. }
+        | Expr::NotLike { .. }
+        | Expr::IsNull(_)
+        | Expr::IsNotNull(_)
+        | Expr::Contains { .. }
+        | Expr::SetCompare { .. }
+        | Expr::Len { .. }
+        | Expr::HasKey { .. } => {}
+    }
 }
@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::types::path::{is_segment, quote_key, FieldPath, PathStep};
+use crate::{Bound, Expr, Rule, RuleMetadata, Terminal, UnknownPolicy, Value};
+
+use super::error::ParseError;
+
+/// A `template name(params): body` definition.
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+
+/// An `expand name(args) [as rule] [(priority N)]` invocation.
+#[derive(Debug, Clone)]
+pub struct Expansion {
+    pub template: String,
+    pub args: Vec<Bound>,
+    /// Explicit rule name; generated from the template and arguments if absent.
+    pub name: Option<String>,
+    pub priority: Option<(u32, UnknownPolicy)>,
+    /// Annotations on the `expand`, given to the generated rule.
+    pub metadata: RuleMetadata,
+    /// 1-based source line of the invocation, for error messages.
+    pub line: usize,
+}
+
+impl Expansion {
+    /// `tier_gate(books, ["gold"])` becomes `tier_gate_books`: the template
+    /// name followed by every scalar argument, with characters that cannot
+    /// appear in a rule name replaced by `_`. List and map arguments are
+    /// left out.
+    fn generated_name(&self) -> String {
+        let mut name = self.template.clone();
+        for arg in &self.args {
+            let text = match arg {
+                Bound::Field(path) => path.clone(),
+                Bound::Literal(Value::String(s)) => s.clone(),
+                Bound::Literal(Value::List(_) | Value::Map(_)) => continue,
+                Bound::Literal(value) => value.to_string(),
+            };
+            name.push('_');
+            name.extend(text.chars().map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            }));
+        }
+        name
+    }
+}
+
+/// Expand every invocation into a rule (and terminal, if it has a priority),
+/// appended after the rules defined directly in the file.
+pub(crate) fn expand(
+    templates: &[Template],
+    expansions: Vec<Expansion>,
+    rules: &mut Vec<Rule>,
+    terminals: &mut Vec<Terminal>,
+) -> Result<(), ParseError> {
+    let mut by_name = HashMap::new();
+    for template in templates {
+        if by_name.insert(template.name.as_str(), template).is_some() {
+            return Err(ParseError::new(format!(
+                "template '{}' is defined more than once",
+                template.name
+            )));
+        }
+        let mut seen = HashSet::new();
+        if let Some(param) = template.params.iter().find(|p| !seen.insert(p.as_str())) {
+            return Err(ParseError::new(format!(
+                "template '{}' declares parameter '{param}' more than once",
+                template.name
+            )));
+        }
+    }
+
+    let mut names: HashSet<String> = rules.iter().map(|r| r.name.clone()).collect();
+    for expansion in expansions {
+        let site = |message: String| {
+            ParseError::new(format!(
+                "in expansion of '{}' at line {}: {message}",
+                expansion.template, expansion.line
+            ))
+        };
+        let template = by_name
+            .get(expansion.template.as_str())
+            .ok_or_else(|| site("no such template".to_owned()))?;
+        if template.params.len() != expansion.args.len() {
+            return Err(site(format!(
+                "expected {} argument(s), found {}",
+                template.params.len(),
+                expansion.args.len()
+            )));
+        }
+        let bindings: HashMap<&str, &Bound> = template
+            .params
+            .iter()
+            .map(String::as_str)
+            .zip(&expansion.args)
+            .collect();
+        let mut condition = template.body.clone();
+        instantiate(&mut condition, &bindings).map_err(site)?;
+
+        let name = expansion
+            .name
+            .clone()
+            .unwrap_or_else(|| expansion.generated_name());
+        if !names.insert(name.clone()) {
+            return Err(site(format!("rule '{name}' is already defined")));
+        }
+        if let Some((priority, on_unknown)) = expansion.priority {
+            terminals.push(Terminal {
+                rule_name: name.clone(),
+                priority,
+                on_unknown,
+            });
+        }
+        rules.push(Rule {
+            name,
+            condition: Some(condition),
+            metadata: expansion.metadata,
+        });
+    }
+    Ok(())
+}
+
+/// Substitute template arguments into `expr`.
+///
+/// A parameter standing alone in a value position (the right-hand side of a
+/// comparison, or a bound) takes the argument as is, so it may be a literal
+/// or a field. Anywhere else -- field paths, path segments, `[...]` keys and
+/// rule references -- the parameter is replaced by the argument's text,
+/// which must be a field or a string, or an integer inside `[...]`. A
+/// parameter a comparison uses as a value is not substituted into that
+/// comparison's field path, so `user.tier == tier` keeps its field.
+fn instantiate(expr: &mut Expr, bindings: &HashMap<&str, &Bound>) -> Result<(), String> {
+    match expr {
+        Expr::Compare { field, .. }
+        | Expr::Like { field, .. }
+        | Expr::NotLike { field, .. }
+        | Expr::IsNull(field)
+        | Expr::IsNotNull(field) => rewrite_field(field, bindings)?,
+        Expr::RuleRef(name) => {
+            if rewrite(name, bindings)? && !is_rule_name(name) {
+                return Err(format!("invalid rule reference '{name}'"));
+            }
+        }
+        Expr::CompareFields { left, op, right } => {
+            let value = Bound::Field(right.clone());
+            rewrite_field(left, &without_values(bindings, [&value]))?;
+            match bindings.get(right.as_str()) {
+                Some(Bound::Literal(value)) => {
+                    *expr = Expr::Compare {
+                        field: std::mem::take(left),
+                        op: *op,
+                        value: value.clone(),
+                    };
+                }
+                Some(Bound::Field(path)) => *right = path.clone(),
+                None => rewrite_field(right, bindings)?,
+            }
+        }
+        Expr::In { field, members } | Expr::NotIn { field, members } => {
+            rewrite_field(field, &without_values(bindings, members.iter()))?;
+            for member in members {
+                instantiate_bound(member, bindings)?;
+            }
+        }
+        Expr::Between { field, low, high } => {
+            rewrite_field(field, &without_values(bindings, [&*low, &*high]))?;
+            instantiate_bound(low, bindings)?;
+            instantiate_bound(high, bindings)?;
+        }
+        Expr::Contains { field, value }
+        | Expr::SetCompare { field, value, .. }
+        | Expr::Len { field, value, .. }
+        | Expr::HasKey { field, key: value } => {
+            rewrite_field(field, &without_values(bindings, [&*value]))?;
+            instantiate_bound(value, bindings)?;
+        }
+        Expr::And(a, b) | Expr::Or(a, b) => {
+            instantiate(a, bindings)?;
+            instantiate(b, bindings)?;
+        }
+        Expr::Not(inner) => instantiate(inner, bindings)?,
+        Expr::AtLeast { exprs, .. } => {
+            for e in exprs {
+                instantiate(e, bindings)?;
+            }
+        }
+        Expr::Any {
+            list,
+            var,
+            predicate,
+        }
+        | Expr::All {
+            list,
+            var,
+            predicate,
+        } => {
+            rewrite_field(list, bindings)?;
+            if bindings.contains_key(var.as_str()) {
+                let mut shadowed = bindings.clone();
+                shadowed.remove(var.as_str());
+                instantiate(predicate, &shadowed)?;
+            } else {
+                instantiate(predicate, bindings)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `bindings` without the parameters named by `values`, the value operands
+/// of a comparison.
+fn without_values<'b, 'v>(
+    bindings: &HashMap<&'b str, &'b Bound>,
+    values: impl IntoIterator<Item = &'v Bound>,
+) -> HashMap<&'b str, &'b Bound> {
+    let mut kept = bindings.clone();
+    for value in values {
+        if let Bound::Field(name) = value {
+            kept.remove(name.as_str());
+        }
+    }
+    kept
+}
+
+fn instantiate_bound(bound: &mut Bound, bindings: &HashMap<&str, &Bound>) -> Result<(), String> {
+    if let Bound::Field(path) = bound {
+        match bindings.get(path.as_str()) {
+            Some(arg) => *bound = (*arg).clone(),
+            None => rewrite_field(path, bindings)?,
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite a field path and check that the result is still a valid path.
+fn rewrite_field(path: &mut String, bindings: &HashMap<&str, &Bound>) -> Result<(), String> {
+    if rewrite(path, bindings)? {
+        check_field_path(path)
+            .map_err(|reason| format!("invalid field path '{path}': {reason}"))?;
+    }
+    Ok(())
+}
+
+/// Whether `path` reads back as the field path it was rewritten to: every
+/// dotted segment an identifier, and every `[...]` index well formed.
+fn check_field_path(path: &str) -> Result<(), String> {
+    let parsed = FieldPath::parse(path)?;
+    let dynamic = parsed.steps.iter().filter_map(|step| match step {
+        PathStep::Dynamic(key) => Some(*key),
+        PathStep::Key(_) | PathStep::Index(_) | PathStep::Wildcard => None,
+    });
+    for dotted in std::iter::once(parsed.root).chain(dynamic) {
+        if !is_rule_name(dotted) {
+            return Err(format!(
+                "'{dotted}' is not a dot-separated list of identifiers"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `name` is a dot-separated list of identifiers, none starting with
+/// a digit.
+fn is_rule_name(name: &str) -> bool {
+    name.split('.')
+        .all(|segment| is_segment(segment) && !segment.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Replace parameter names among the identifier segments of `path`, and
+/// report whether any was replaced. Quoted `["..."]` keys and numeric
+/// indexes are left alone.
+fn rewrite(path: &mut String, bindings: &HashMap<&str, &Bound>) -> Result<bool, String> {
+    let mut out = String::with_capacity(path.len());
+    let mut rest = path.as_str();
+    let mut changed = false;
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            let mut end = 1;
+            let mut escaped = false;
+            for (i, ch) in rest[1..].char_indices() {
+                end = i + 2;
+                match ch {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => break,
+                    _ => escaped = false,
+                }
+            }
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            let len = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+            let (token, tail) = rest.split_at(len);
+            rest = tail;
+            let Some(arg) = bindings.get(token).filter(|_| !c.is_ascii_digit()) else {
+                out.push_str(token);
+                continue;
+            };
+            changed = true;
+            let in_brackets = out.ends_with('[') && rest.starts_with(']');
+            match arg {
+                Bound::Field(field) => out.push_str(field),
+                Bound::Literal(Value::String(s)) if in_brackets => {
+                    out.pop();
+                    out.push_str(&quote_key(s));
+                    rest = &rest[1..];
+                }
+                Bound::Literal(Value::String(s)) => out.push_str(s),
+                Bound::Literal(Value::Int(i)) if in_brackets => out.push_str(&i.to_string()),
+                Bound::Literal(Value::List(_) | Value::Map(_)) => {
+                    return Err(format!(
+                        "parameter '{token}' is bound to a list and cannot be used in a field path or rule reference"
+                    ));
+                }
+                Bound::Literal(value) => {
+                    return Err(format!(
+                        "parameter '{token}' is bound to {value}, which cannot be used in a field path or rule reference"
+                    ));
+                }
+            }
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    *path = out;
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewritten(path: &str, param: &str, arg: Bound) -> Result<String, String> {
+        let bindings = HashMap::from([(param, &arg)]);
+        let mut path = path.to_owned();
+        rewrite(&mut path, &bindings).map(|_| path)
+    }
+
+    #[test]
+    fn rewrite_segments() {
+        let books = Bound::Literal(Value::from("books"));
+        assert_eq!(
+            rewritten("product.prod.enabled", "prod", books.clone()).unwrap(),
+            "product.books.enabled"
+        );
+        assert_eq!(
+            rewritten("product[prod].enabled", "prod", books.clone()).unwrap(),
+            "product[\"books\"].enabled"
+        );
+        assert_eq!(
+            rewritten("product[prod]", "prod", Bound::Field("user.product".into())).unwrap(),
+            "product[user.product]"
+        );
+        assert_eq!(
+            rewritten(
+                "items[prod].production",
+                "prod",
+                Bound::Literal(Value::Int(2))
+            )
+            .unwrap(),
+            "items[2].production"
+        );
+        assert_eq!(
+            rewritten("a[\"prod\"].b", "prod", books).unwrap(),
+            "a[\"prod\"].b"
+        );
+        assert!(rewritten("product.prod", "prod", Bound::Literal(Value::List(vec![]))).is_err());
+    }
+
+    #[test]
+    fn rewritten_paths_are_checked() {
+        assert!(check_field_path("product[\"a b\"].enabled").is_ok());
+        assert!(check_field_path("items[user.pick][2]").is_ok());
+        assert!(check_field_path("user.a b").is_err());
+        assert!(check_field_path("user.18").is_err());
+        assert!(check_field_path("items[user.a b]").is_err());
+        assert!(
+            rewritten("items.prod", "prod", Bound::Literal(Value::Int(2)))
+                .unwrap_err()
+                .contains("bound to 2")
+        );
+        assert!(rewritten("items[prod]", "prod", Bound::Literal(Value::Bool(true))).is_err());
+    }
+
+    #[test]
+    fn generated_names() {
+        let expansion = Expansion {
+            template: "tier_gate".into(),
+            args: vec![
+                Bound::Field("books".into()),
+                Bound::Literal(Value::List(vec![])),
+                Bound::Literal(Value::from("e-books")),
+                Bound::Literal(Value::Int(3)),
+            ],
+            name: None,
+            priority: None,
+            metadata: RuleMetadata::default(),
+            line: 1,
+        };
+        assert_eq!(expansion.generated_name(), "tier_gate_books_e_books_3");
+    }
+}
@@ -11,23 +11,68 @@
 //! 0       4     Magic bytes: b"OORO"
 //! 4       2     Format version (u16, little-endian)
 //! 6       2     Engine version (u16, little-endian)
-//! 8       4     Flags (u32, reserved)
+//! 8       4     Flags (u32, little-endian)
 //! 12      4     Payload length in bytes (u32, little-endian)
 //! 16      16    BLAKE3 hash of the payload (truncated to 16 bytes)
 //! 32..    var   Bincode-encoded payload
+//! ..      64    Ed25519 signature, if the SIGNED flag is set
+//!
+//! Flags: bit 0 SIGNED, bit 1 COMPRESSED
 //! ```
 //!
+//! ## Payload
+//!
+//! String literals, map keys and LIKE patterns are interned into a table
+//! stored once in the payload, so a string repeated across many rules
+//! costs one index per use.
+//!
+//! ## Compression
+//!
+//! With the `compression` feature, `RuleSet::to_bytes_compressed` writes
+//! the payload LZ4-compressed, with its uncompressed length prepended, and
+//! sets flag bit 1 (`COMPRESSED`). The length and checksum in the header
+//! cover the stored, compressed bytes. Decoding a compressed blob without
+//! the feature fails with [`DeserializeError::CompressionUnsupported`].
+//!
+//! ## Signatures
+//!
+//! The checksum catches corruption, not tampering. With the `signing`
+//! feature, a blob can carry an Ed25519 signature over the header and
+//! payload: flag bit 0 (`SIGNED`) is set and the 64-byte signature follows
+//! the payload. Only `RuleSet::from_bytes_verified` checks it; plain
+//! decoding ignores the signature.
+//!
 //! ## Versioning
 //!
-//! The format version in the header must match exactly. If it does not,
-//! deserialization fails immediately with [`DeserializeError::IncompatibleVersion`].
+//! Blobs are always written in the current format version. The engine also
+//! reads every format version written by the previous minor release, and
+//! upgrades it into the current types while decoding, so deploying a new
+//! engine does not invalidate existing caches; they can be rewritten at
+//! leisure. Older versions fail immediately with
+//! [`DeserializeError::IncompatibleVersion`], as do newer ones.
+//!
+//! ```text
+//! Version  Written by  Read by
+//! 4        0.3         0.3, 0.4
+//! 5        0.4         0.4
+//! ```
+//!
+//! Each readable old version has a module holding the types that changed
+//! since, and a golden blob in `tests/fixtures/blobs` that the tests decode.
 //! The engine version is informational only.
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+mod v4;
+
 use crate::types::{
-    CompareOp, CompiledBound, CompiledExpr, CompiledRule, FieldRegistry, RuleSet, Terminal, Value,
+    CompareOp, CompiledBound, CompiledExpr, CompiledLookup, CompiledRule, CompiledStep,
+    FieldRegistry, FieldType, RuleMetadata, RuleSet, Schema, SetOp, Terminal, UnknownPolicy, Value,
 };
 
 // ---------------------------------------------------------------------------
@@ -35,9 +80,17 @@ use crate::types::{
 // ---------------------------------------------------------------------------
 
 const MAGIC: &[u8; 4] = b"OORO";
-const FORMAT_VERSION: u16 = 4;
+const FORMAT_VERSION: u16 = 5;
+/// The oldest format version [`decode`] upgrades; see the module docs.
+const OLDEST_FORMAT_VERSION: u16 = 4;
 const ENGINE_VERSION: u16 = 1;
-const HEADER_SIZE: usize = 32;
+pub(crate) const HEADER_SIZE: usize = 32;
+/// Header flag: an Ed25519 signature follows the payload.
+const FLAG_SIGNED: u32 = 1;
+/// Header flag: the payload is LZ4-compressed.
+const FLAG_COMPRESSED: u32 = 2;
+#[cfg(feature = "signing")]
+const SIGNATURE_SIZE: usize = ed25519_dalek::SIGNATURE_LENGTH;
 
 // ---------------------------------------------------------------------------
 // Error types
@@ -74,6 +127,15 @@ pub enum DeserializeError {
     #[error("validation failed: {0}")]
     Validation(String),
 
+    #[error("blob is not signed")]
+    MissingSignature,
+
+    #[error("signature verification failed: not signed by a trusted key")]
+    BadSignature,
+
+    #[error("blob is compressed, but the `compression` feature is disabled")]
+    CompressionUnsupported,
+
     #[error("I/O error during deserialization: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -89,6 +151,10 @@ struct SerializedRuleSet {
     terminals: Vec<SerializedTerminal>,
     field_index: Vec<(String, usize)>,
     rule_names: Vec<(String, usize)>,
+    schema: Option<Vec<(String, SerializedFieldType)>>,
+    /// Every distinct string literal, map key and LIKE pattern, referenced
+    /// by index from values and expressions.
+    strings: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -103,6 +169,17 @@ struct RuleSetMetadata {
 struct SerializedRule {
     index: usize,
     condition: SerializedExpr,
+    metadata: SerializedRuleMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SerializedRuleMetadata {
+    description: Option<String>,
+    owner: Option<String>,
+    tags: Vec<String>,
+    link: Option<String>,
+    enabled: bool,
+    extra: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -131,11 +208,11 @@ enum SerializedExpr {
     },
     Like {
         field_slot: usize,
-        pattern: String,
+        pattern: usize,
     },
     NotLike {
         field_slot: usize,
-        pattern: String,
+        pattern: usize,
     },
     IsNull(usize),
     IsNotNull(usize),
@@ -148,6 +225,53 @@ enum SerializedExpr {
         n: usize,
         exprs: Vec<SerializedExpr>,
     },
+    Any {
+        list_slot: usize,
+        bindings: Vec<(usize, Vec<String>)>,
+        predicate: Box<SerializedExpr>,
+    },
+    All {
+        list_slot: usize,
+        bindings: Vec<(usize, Vec<String>)>,
+        predicate: Box<SerializedExpr>,
+    },
+    Contains {
+        field_slot: usize,
+        value: SerializedBound,
+    },
+    SetCompare {
+        field_slot: usize,
+        op: SerializedSetOp,
+        value: SerializedBound,
+    },
+    Len {
+        field_slot: usize,
+        op: SerializedCompareOp,
+        value: SerializedBound,
+    },
+    HasKey {
+        field_slot: usize,
+        key: SerializedBound,
+    },
+    Lookup {
+        lookups: Vec<SerializedLookup>,
+        body: Box<SerializedExpr>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SerializedLookup {
+    slot: usize,
+    base_slot: usize,
+    steps: Vec<SerializedStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum SerializedStep {
+    Key(String),
+    Dynamic(usize),
+    Index(i64),
+    Wildcard,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -155,8 +279,9 @@ enum SerializedValue {
     Int(i64),
     Float(f64),
     Bool(bool),
-    Str(String),
+    Str(usize),
     List(Vec<SerializedValue>),
+    Map(Vec<(usize, SerializedValue)>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -175,11 +300,62 @@ enum SerializedCompareOp {
     Lte,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum SerializedSetOp {
+    Overlaps,
+    SubsetOf,
+    SupersetOf,
+    DisjointFrom,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct SerializedTerminal {
     rule_index: usize,
     name: String,
     priority: u32,
+    on_unknown: SerializedUnknownPolicy,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum SerializedUnknownPolicy {
+    AsFalse,
+    Indeterminate,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum SerializedFieldType {
+    Int,
+    Float,
+    Bool,
+    String,
+    List,
+    Map,
+}
+
+// ---------------------------------------------------------------------------
+// FieldType conversion
+// ---------------------------------------------------------------------------
+
+fn serialize_field_type(ty: FieldType) -> SerializedFieldType {
+    match ty {
+        FieldType::Int => SerializedFieldType::Int,
+        FieldType::Float => SerializedFieldType::Float,
+        FieldType::Bool => SerializedFieldType::Bool,
+        FieldType::String => SerializedFieldType::String,
+        FieldType::List => SerializedFieldType::List,
+        FieldType::Map => SerializedFieldType::Map,
+    }
+}
+
+fn deserialize_field_type(ty: SerializedFieldType) -> FieldType {
+    match ty {
+        SerializedFieldType::Int => FieldType::Int,
+        SerializedFieldType::Float => FieldType::Float,
+        SerializedFieldType::Bool => FieldType::Bool,
+        SerializedFieldType::String => FieldType::String,
+        SerializedFieldType::List => FieldType::List,
+        SerializedFieldType::Map => FieldType::Map,
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -208,63 +384,161 @@ fn deserialize_op(op: SerializedCompareOp) -> CompareOp {
     }
 }
 
+// ---------------------------------------------------------------------------
+// SetOp conversion
+// ---------------------------------------------------------------------------
+
+fn serialize_set_op(op: SetOp) -> SerializedSetOp {
+    match op {
+        SetOp::Overlaps => SerializedSetOp::Overlaps,
+        SetOp::SubsetOf => SerializedSetOp::SubsetOf,
+        SetOp::SupersetOf => SerializedSetOp::SupersetOf,
+        SetOp::DisjointFrom => SerializedSetOp::DisjointFrom,
+    }
+}
+
+fn deserialize_set_op(op: SerializedSetOp) -> SetOp {
+    match op {
+        SerializedSetOp::Overlaps => SetOp::Overlaps,
+        SerializedSetOp::SubsetOf => SetOp::SubsetOf,
+        SerializedSetOp::SupersetOf => SetOp::SupersetOf,
+        SerializedSetOp::DisjointFrom => SetOp::DisjointFrom,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Value conversion
 // ---------------------------------------------------------------------------
 
-fn serialize_value(value: &Value) -> SerializedValue {
+fn serialize_value(value: &Value, strings: &mut StringTable) -> SerializedValue {
     match value {
         Value::Int(v) => SerializedValue::Int(*v),
         Value::Float(v) => SerializedValue::Float(*v),
         Value::Bool(v) => SerializedValue::Bool(*v),
-        Value::String(v) => SerializedValue::Str(v.clone()),
-        Value::List(items) => SerializedValue::List(items.iter().map(serialize_value).collect()),
+        Value::String(v) => SerializedValue::Str(strings.intern(v)),
+        Value::List(items) => SerializedValue::List(
+            items
+                .iter()
+                .map(|item| serialize_value(item, strings))
+                .collect(),
+        ),
+        Value::Map(entries) => SerializedValue::Map(
+            entries
+                .iter()
+                .map(|(k, v)| (strings.intern(k), serialize_value(v, strings)))
+                .collect(),
+        ),
     }
 }
 
-fn deserialize_value(value: SerializedValue) -> Value {
-    match value {
+fn deserialize_value(
+    value: SerializedValue,
+    strings: &[String],
+) -> Result<Value, DeserializeError> {
+    Ok(match value {
         SerializedValue::Int(v) => Value::Int(v),
         SerializedValue::Float(v) => Value::Float(v),
         SerializedValue::Bool(v) => Value::Bool(v),
-        SerializedValue::Str(v) => Value::String(v),
-        SerializedValue::List(items) => {
-            Value::List(items.into_iter().map(deserialize_value).collect())
-        }
-    }
+        SerializedValue::Str(i) => Value::String(resolve_string(strings, i)?),
+        SerializedValue::List(items) => Value::List(
+            items
+                .into_iter()
+                .map(|item| deserialize_value(item, strings))
+                .collect::<Result<_, _>>()?,
+        ),
+        SerializedValue::Map(entries) => Value::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| Ok((resolve_string(strings, k)?, deserialize_value(v, strings)?)))
+                .collect::<Result<_, DeserializeError>>()?,
+        ),
+    })
 }
 
-fn serialize_bound(bound: &CompiledBound) -> SerializedBound {
+fn serialize_bound(bound: &CompiledBound, strings: &mut StringTable) -> SerializedBound {
     match bound {
-        CompiledBound::Literal(v) => SerializedBound::Literal(serialize_value(v)),
+        CompiledBound::Literal(v) => SerializedBound::Literal(serialize_value(v, strings)),
         CompiledBound::FieldIndex(i) => SerializedBound::FieldIndex(*i),
     }
 }
 
-fn deserialize_bound(bound: SerializedBound) -> CompiledBound {
-    match bound {
-        SerializedBound::Literal(v) => CompiledBound::Literal(deserialize_value(v)),
+fn deserialize_bound(
+    bound: SerializedBound,
+    strings: &[String],
+) -> Result<CompiledBound, DeserializeError> {
+    Ok(match bound {
+        SerializedBound::Literal(v) => CompiledBound::Literal(deserialize_value(v, strings)?),
         SerializedBound::FieldIndex(i) => CompiledBound::FieldIndex(i),
+    })
+}
+
+fn serialize_bounds(bounds: &[CompiledBound], strings: &mut StringTable) -> Vec<SerializedBound> {
+    bounds
+        .iter()
+        .map(|bound| serialize_bound(bound, strings))
+        .collect()
+}
+
+fn deserialize_bounds(
+    bounds: Vec<SerializedBound>,
+    strings: &[String],
+) -> Result<Vec<CompiledBound>, DeserializeError> {
+    bounds
+        .into_iter()
+        .map(|bound| deserialize_bound(bound, strings))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// String interning
+// ---------------------------------------------------------------------------
+
+/// The strings of a payload being written. Each distinct string is stored
+/// once, however many rules use it.
+#[derive(Default)]
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl StringTable {
+    fn intern(&mut self, s: &str) -> usize {
+        if let Some(&index) = self.indices.get(s) {
+            return index;
+        }
+        let index = self.strings.len();
+        self.strings.push(s.to_owned());
+        self.indices.insert(s.to_owned(), index);
+        index
     }
 }
 
+fn resolve_string(strings: &[String], index: usize) -> Result<String, DeserializeError> {
+    strings.get(index).cloned().ok_or_else(|| {
+        DeserializeError::Validation(format!(
+            "string index {index} out of bounds (max {})",
+            strings.len()
+        ))
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Expression flattening (binary -> n-ary)
 // ---------------------------------------------------------------------------
 
-fn flatten_expr(expr: &CompiledExpr) -> SerializedExpr {
+fn flatten_expr(expr: &CompiledExpr, strings: &mut StringTable) -> SerializedExpr {
     match expr {
         CompiledExpr::And(_, _) => {
             let mut children = Vec::new();
-            collect_and_children(expr, &mut children);
+            collect_and_children(expr, &mut children, strings);
             SerializedExpr::And(children)
         }
         CompiledExpr::Or(_, _) => {
             let mut children = Vec::new();
-            collect_or_children(expr, &mut children);
+            collect_or_children(expr, &mut children, strings);
             SerializedExpr::Or(children)
         }
-        CompiledExpr::Not(inner) => SerializedExpr::Not(Box::new(flatten_expr(inner))),
+        CompiledExpr::Not(inner) => SerializedExpr::Not(Box::new(flatten_expr(inner, strings))),
         CompiledExpr::Compare {
             field_index,
             op,
@@ -272,7 +546,7 @@ fn flatten_expr(expr: &CompiledExpr) -> SerializedExpr {
         } => SerializedExpr::FieldCmp {
             field_slot: *field_index,
             op: serialize_op(*op),
-            value: serialize_value(value),
+            value: serialize_value(value, strings),
         },
         CompiledExpr::RuleRef(idx) => SerializedExpr::RuleRef(*idx),
         CompiledExpr::In {
@@ -280,14 +554,14 @@ fn flatten_expr(expr: &CompiledExpr) -> SerializedExpr {
             members,
         } => SerializedExpr::In {
             field_slot: *field_index,
-            members: members.iter().map(serialize_bound).collect(),
+            members: serialize_bounds(members, strings),
         },
         CompiledExpr::NotIn {
             field_index,
             members,
         } => SerializedExpr::NotIn {
             field_slot: *field_index,
-            members: members.iter().map(serialize_bound).collect(),
+            members: serialize_bounds(members, strings),
         },
         CompiledExpr::Between {
             field_index,
@@ -295,22 +569,22 @@ fn flatten_expr(expr: &CompiledExpr) -> SerializedExpr {
             high,
         } => SerializedExpr::Between {
             field_slot: *field_index,
-            low: serialize_bound(low),
-            high: serialize_bound(high),
+            low: serialize_bound(low, strings),
+            high: serialize_bound(high, strings),
         },
         CompiledExpr::Like {
             field_index,
             pattern,
         } => SerializedExpr::Like {
             field_slot: *field_index,
-            pattern: pattern.clone(),
+            pattern: strings.intern(pattern),
         },
         CompiledExpr::NotLike {
             field_index,
             pattern,
         } => SerializedExpr::NotLike {
             field_slot: *field_index,
-            pattern: pattern.clone(),
+            pattern: strings.intern(pattern),
         },
         CompiledExpr::IsNull(idx) => SerializedExpr::IsNull(*idx),
         CompiledExpr::IsNotNull(idx) => SerializedExpr::IsNotNull(*idx),
@@ -325,28 +599,100 @@ fn flatten_expr(expr: &CompiledExpr) -> SerializedExpr {
         },
         CompiledExpr::AtLeast { n, exprs } => SerializedExpr::AtLeast {
             n: *n,
-            exprs: exprs.iter().map(flatten_expr).collect(),
+            exprs: exprs.iter().map(|e| flatten_expr(e, strings)).collect(),
+        },
+        CompiledExpr::Any {
+            list_index,
+            bindings,
+            predicate,
+        } => SerializedExpr::Any {
+            list_slot: *list_index,
+            bindings: bindings.clone(),
+            predicate: Box::new(flatten_expr(predicate, strings)),
+        },
+        CompiledExpr::All {
+            list_index,
+            bindings,
+            predicate,
+        } => SerializedExpr::All {
+            list_slot: *list_index,
+            bindings: bindings.clone(),
+            predicate: Box::new(flatten_expr(predicate, strings)),
+        },
+        CompiledExpr::Contains { field_index, value } => SerializedExpr::Contains {
+            field_slot: *field_index,
+            value: serialize_bound(value, strings),
+        },
+        CompiledExpr::SetCompare {
+            field_index,
+            op,
+            value,
+        } => SerializedExpr::SetCompare {
+            field_slot: *field_index,
+            op: serialize_set_op(*op),
+            value: serialize_bound(value, strings),
+        },
+        CompiledExpr::Len {
+            field_index,
+            op,
+            value,
+        } => SerializedExpr::Len {
+            field_slot: *field_index,
+            op: serialize_op(*op),
+            value: serialize_bound(value, strings),
+        },
+        CompiledExpr::HasKey { field_index, key } => SerializedExpr::HasKey {
+            field_slot: *field_index,
+            key: serialize_bound(key, strings),
+        },
+        CompiledExpr::Lookup { lookups, body } => SerializedExpr::Lookup {
+            lookups: lookups
+                .iter()
+                .map(|l| SerializedLookup {
+                    slot: l.slot,
+                    base_slot: l.base,
+                    steps: l
+                        .steps
+                        .iter()
+                        .map(|step| match step {
+                            CompiledStep::Key(k) => SerializedStep::Key(k.clone()),
+                            CompiledStep::Dynamic(i) => SerializedStep::Dynamic(*i),
+                            CompiledStep::Index(i) => SerializedStep::Index(*i),
+                            CompiledStep::Wildcard => SerializedStep::Wildcard,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            body: Box::new(flatten_expr(body, strings)),
         },
     }
 }
 
-fn collect_and_children(expr: &CompiledExpr, out: &mut Vec<SerializedExpr>) {
+fn collect_and_children(
+    expr: &CompiledExpr,
+    out: &mut Vec<SerializedExpr>,
+    strings: &mut StringTable,
+) {
     match expr {
         CompiledExpr::And(left, right) => {
-            collect_and_children(left, out);
-            collect_and_children(right, out);
+            collect_and_children(left, out, strings);
+            collect_and_children(right, out, strings);
         }
-        other => out.push(flatten_expr(other)),
+        other => out.push(flatten_expr(other, strings)),
     }
 }
 
-fn collect_or_children(expr: &CompiledExpr, out: &mut Vec<SerializedExpr>) {
+fn collect_or_children(
+    expr: &CompiledExpr,
+    out: &mut Vec<SerializedExpr>,
+    strings: &mut StringTable,
+) {
     match expr {
         CompiledExpr::Or(left, right) => {
-            collect_or_children(left, out);
-            collect_or_children(right, out);
+            collect_or_children(left, out, strings);
+            collect_or_children(right, out, strings);
         }
-        other => out.push(flatten_expr(other)),
+        other => out.push(flatten_expr(other, strings)),
     }
 }
 
@@ -354,35 +700,46 @@ fn collect_or_children(expr: &CompiledExpr, out: &mut Vec<SerializedExpr>) {
 // Expression unflattening (n-ary -> binary)
 // ---------------------------------------------------------------------------
 
-fn unflatten_expr(expr: SerializedExpr) -> Result<CompiledExpr, DeserializeError> {
+fn unflatten_expr(
+    expr: SerializedExpr,
+    strings: &[String],
+) -> Result<CompiledExpr, DeserializeError> {
     match expr {
         SerializedExpr::And(children) => {
             if children.len() == 1 {
-                return unflatten_expr(children.into_iter().next().expect("length checked above"));
+                return unflatten_expr(
+                    children.into_iter().next().expect("length checked above"),
+                    strings,
+                );
             }
             let mut iter = children.into_iter();
-            let first = unflatten_expr(iter.next().expect("validated non-empty"))?;
+            let first = unflatten_expr(iter.next().expect("validated non-empty"), strings)?;
             iter.try_fold(first, |acc, child| {
                 Ok(CompiledExpr::And(
                     Box::new(acc),
-                    Box::new(unflatten_expr(child)?),
+                    Box::new(unflatten_expr(child, strings)?),
                 ))
             })
         }
         SerializedExpr::Or(children) => {
             if children.len() == 1 {
-                return unflatten_expr(children.into_iter().next().expect("length checked above"));
+                return unflatten_expr(
+                    children.into_iter().next().expect("length checked above"),
+                    strings,
+                );
             }
             let mut iter = children.into_iter();
-            let first = unflatten_expr(iter.next().expect("validated non-empty"))?;
+            let first = unflatten_expr(iter.next().expect("validated non-empty"), strings)?;
             iter.try_fold(first, |acc, child| {
                 Ok(CompiledExpr::Or(
                     Box::new(acc),
-                    Box::new(unflatten_expr(child)?),
+                    Box::new(unflatten_expr(child, strings)?),
                 ))
             })
         }
-        SerializedExpr::Not(inner) => Ok(CompiledExpr::Not(Box::new(unflatten_expr(*inner)?))),
+        SerializedExpr::Not(inner) => Ok(CompiledExpr::Not(Box::new(unflatten_expr(
+            *inner, strings,
+        )?))),
         SerializedExpr::FieldCmp {
             field_slot,
             op,
@@ -390,7 +747,7 @@ fn unflatten_expr(expr: SerializedExpr) -> Result<CompiledExpr, DeserializeError
         } => Ok(CompiledExpr::Compare {
             field_index: field_slot,
             op: deserialize_op(op),
-            value: deserialize_value(value),
+            value: deserialize_value(value, strings)?,
         }),
         SerializedExpr::RuleRef(idx) => Ok(CompiledExpr::RuleRef(idx)),
         SerializedExpr::In {
@@ -398,14 +755,14 @@ fn unflatten_expr(expr: SerializedExpr) -> Result<CompiledExpr, DeserializeError
             members,
         } => Ok(CompiledExpr::In {
             field_index: field_slot,
-            members: members.into_iter().map(deserialize_bound).collect(),
+            members: deserialize_bounds(members, strings)?,
         }),
         SerializedExpr::NotIn {
             field_slot,
             members,
         } => Ok(CompiledExpr::NotIn {
             field_index: field_slot,
-            members: members.into_iter().map(deserialize_bound).collect(),
+            members: deserialize_bounds(members, strings)?,
         }),
         SerializedExpr::Between {
             field_slot,
@@ -413,22 +770,22 @@ fn unflatten_expr(expr: SerializedExpr) -> Result<CompiledExpr, DeserializeError
             high,
         } => Ok(CompiledExpr::Between {
             field_index: field_slot,
-            low: deserialize_bound(low),
-            high: deserialize_bound(high),
+            low: deserialize_bound(low, strings)?,
+            high: deserialize_bound(high, strings)?,
         }),
         SerializedExpr::Like {
             field_slot,
             pattern,
         } => Ok(CompiledExpr::Like {
             field_index: field_slot,
-            pattern,
+            pattern: resolve_string(strings, pattern)?,
         }),
         SerializedExpr::NotLike {
             field_slot,
             pattern,
         } => Ok(CompiledExpr::NotLike {
             field_index: field_slot,
-            pattern,
+            pattern: resolve_string(strings, pattern)?,
         }),
         SerializedExpr::IsNull(idx) => Ok(CompiledExpr::IsNull(idx)),
         SerializedExpr::IsNotNull(idx) => Ok(CompiledExpr::IsNotNull(idx)),
@@ -445,9 +802,73 @@ fn unflatten_expr(expr: SerializedExpr) -> Result<CompiledExpr, DeserializeError
             n,
             exprs: exprs
                 .into_iter()
-                .map(unflatten_expr)
+                .map(|e| unflatten_expr(e, strings))
                 .collect::<Result<Vec<_>, _>>()?,
         }),
+        SerializedExpr::Any {
+            list_slot,
+            bindings,
+            predicate,
+        } => Ok(CompiledExpr::Any {
+            list_index: list_slot,
+            bindings,
+            predicate: Box::new(unflatten_expr(*predicate, strings)?),
+        }),
+        SerializedExpr::All {
+            list_slot,
+            bindings,
+            predicate,
+        } => Ok(CompiledExpr::All {
+            list_index: list_slot,
+            bindings,
+            predicate: Box::new(unflatten_expr(*predicate, strings)?),
+        }),
+        SerializedExpr::Contains { field_slot, value } => Ok(CompiledExpr::Contains {
+            field_index: field_slot,
+            value: deserialize_bound(value, strings)?,
+        }),
+        SerializedExpr::SetCompare {
+            field_slot,
+            op,
+            value,
+        } => Ok(CompiledExpr::SetCompare {
+            field_index: field_slot,
+            op: deserialize_set_op(op),
+            value: deserialize_bound(value, strings)?,
+        }),
+        SerializedExpr::Len {
+            field_slot,
+            op,
+            value,
+        } => Ok(CompiledExpr::Len {
+            field_index: field_slot,
+            op: deserialize_op(op),
+            value: deserialize_bound(value, strings)?,
+        }),
+        SerializedExpr::HasKey { field_slot, key } => Ok(CompiledExpr::HasKey {
+            field_index: field_slot,
+            key: deserialize_bound(key, strings)?,
+        }),
+        SerializedExpr::Lookup { lookups, body } => Ok(CompiledExpr::Lookup {
+            lookups: lookups
+                .into_iter()
+                .map(|l| CompiledLookup {
+                    slot: l.slot,
+                    base: l.base_slot,
+                    steps: l
+                        .steps
+                        .into_iter()
+                        .map(|step| match step {
+                            SerializedStep::Key(k) => CompiledStep::Key(k),
+                            SerializedStep::Dynamic(i) => CompiledStep::Dynamic(i),
+                            SerializedStep::Index(i) => CompiledStep::Index(i),
+                            SerializedStep::Wildcard => CompiledStep::Wildcard,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            body: Box::new(unflatten_expr(*body, strings)?),
+        }),
     }
 }
 
@@ -458,12 +879,21 @@ fn unflatten_expr(expr: SerializedExpr) -> Result<CompiledExpr, DeserializeError
 fn ruleset_to_serialized(ruleset: &RuleSet, source_text: Option<&str>) -> SerializedRuleSet {
     let source_digest = source_text.map(|s| *blake3::hash(s.as_bytes()).as_bytes());
 
+    let mut strings = StringTable::default();
     let rules: Vec<SerializedRule> = ruleset
         .rules
         .iter()
         .map(|r| SerializedRule {
             index: r.index,
-            condition: flatten_expr(&r.condition),
+            condition: flatten_expr(&r.condition, &mut strings),
+            metadata: SerializedRuleMetadata {
+                description: r.metadata.description.clone(),
+                owner: r.metadata.owner.clone(),
+                tags: r.metadata.tags.clone(),
+                link: r.metadata.link.clone(),
+                enabled: r.metadata.enabled,
+                extra: r.metadata.extra.clone().into_iter().collect(),
+            },
         })
         .collect();
 
@@ -475,6 +905,10 @@ fn ruleset_to_serialized(ruleset: &RuleSet, source_text: Option<&str>) -> Serial
             rule_index: idx,
             name: t.rule_name.clone(),
             priority: t.priority,
+            on_unknown: match t.on_unknown {
+                UnknownPolicy::AsFalse => SerializedUnknownPolicy::AsFalse,
+                UnknownPolicy::Indeterminate => SerializedUnknownPolicy::Indeterminate,
+            },
         })
         .collect();
 
@@ -492,6 +926,13 @@ fn ruleset_to_serialized(ruleset: &RuleSet, source_text: Option<&str>) -> Serial
         .map(|r| (r.name.clone(), r.index))
         .collect();
 
+    let schema = ruleset.schema.as_ref().map(|schema| {
+        schema
+            .iter()
+            .map(|(path, ty)| (path.to_owned(), serialize_field_type(ty)))
+            .collect()
+    });
+
     SerializedRuleSet {
         metadata: RuleSetMetadata {
             rule_count: ruleset.rules.len(),
@@ -503,6 +944,8 @@ fn ruleset_to_serialized(ruleset: &RuleSet, source_text: Option<&str>) -> Serial
         terminals,
         field_index,
         rule_names,
+        schema,
+        strings: strings.strings,
     }
 }
 
@@ -520,11 +963,20 @@ fn serialized_to_ruleset(ser: SerializedRuleSet) -> Result<RuleSet, DeserializeE
         .into_iter()
         .zip(ser.rule_names)
         .map(|(sr, (name, _))| {
-            let condition = unflatten_expr(sr.condition)?;
+            let condition = unflatten_expr(sr.condition, &ser.strings)?;
+            let metadata = RuleMetadata {
+                description: sr.metadata.description,
+                owner: sr.metadata.owner,
+                tags: sr.metadata.tags,
+                link: sr.metadata.link,
+                enabled: sr.metadata.enabled,
+                extra: sr.metadata.extra.into_iter().collect(),
+            };
             Ok(CompiledRule {
                 name,
                 condition,
                 index: sr.index,
+                metadata,
             })
         })
         .collect::<Result<Vec<_>, DeserializeError>>()?;
@@ -535,15 +987,29 @@ fn serialized_to_ruleset(ser: SerializedRuleSet) -> Result<RuleSet, DeserializeE
         terminals.push(Terminal {
             rule_name: st.name,
             priority: st.priority,
+            on_unknown: match st.on_unknown {
+                SerializedUnknownPolicy::AsFalse => UnknownPolicy::AsFalse,
+                SerializedUnknownPolicy::Indeterminate => UnknownPolicy::Indeterminate,
+            },
         });
         terminal_indices.push(st.rule_index);
     }
 
+    let schema = ser.schema.map(|fields| {
+        fields
+            .into_iter()
+            .fold(Schema::new(), |schema, (path, ty)| {
+                schema.field(&path, deserialize_field_type(ty))
+            })
+    });
+
     Ok(RuleSet {
         rules,
         terminals,
         field_registry,
         terminal_indices,
+        schema,
+        tests: Vec::new(),
     })
 }
 
@@ -585,9 +1051,17 @@ fn validate(ser: &SerializedRuleSet) -> Result<(), DeserializeError> {
         )));
     }
 
-    // Field slot bounds and rule ref bounds in all expressions
+    // Field slot bounds and rule ref bounds in all expressions. Slots past
+    // the field registry are quantifier locals.
     for rule in &ser.rules {
-        validate_expr(&rule.condition, field_count, rule_count, rule.index)?;
+        let slot_count = field_count.max(local_slot_end(&rule.condition));
+        validate_expr(
+            &rule.condition,
+            field_count,
+            slot_count,
+            rule_count,
+            rule.index,
+        )?;
     }
 
     // Terminal rule refs valid
@@ -615,6 +1089,7 @@ fn validate(ser: &SerializedRuleSet) -> Result<(), DeserializeError> {
 fn validate_expr(
     expr: &SerializedExpr,
     field_count: usize,
+    slot_count: usize,
     rule_count: usize,
     current_rule_index: usize,
 ) -> Result<(), DeserializeError> {
@@ -622,9 +1097,9 @@ fn validate_expr(
         SerializedExpr::FieldCmp { field_slot, .. }
         | SerializedExpr::Like { field_slot, .. }
         | SerializedExpr::NotLike { field_slot, .. } => {
-            if *field_slot >= field_count {
+            if *field_slot >= slot_count {
                 return Err(DeserializeError::Validation(format!(
-                    "field slot {field_slot} out of bounds (max {field_count})"
+                    "field slot {field_slot} out of bounds (max {slot_count})"
                 )));
             }
             Ok(())
@@ -637,16 +1112,16 @@ fn validate_expr(
             field_slot,
             members,
         } => {
-            if *field_slot >= field_count {
+            if *field_slot >= slot_count {
                 return Err(DeserializeError::Validation(format!(
-                    "field slot {field_slot} out of bounds (max {field_count})"
+                    "field slot {field_slot} out of bounds (max {slot_count})"
                 )));
             }
             for m in members {
                 if let SerializedBound::FieldIndex(idx) = m {
-                    if *idx >= field_count {
+                    if *idx >= slot_count {
                         return Err(DeserializeError::Validation(format!(
-                            "bound field slot {idx} out of bounds (max {field_count})"
+                            "bound field slot {idx} out of bounds (max {slot_count})"
                         )));
                     }
                 }
@@ -658,16 +1133,16 @@ fn validate_expr(
             low,
             high,
         } => {
-            if *field_slot >= field_count {
+            if *field_slot >= slot_count {
                 return Err(DeserializeError::Validation(format!(
-                    "field slot {field_slot} out of bounds (max {field_count})"
+                    "field slot {field_slot} out of bounds (max {slot_count})"
                 )));
             }
             for bound in [low, high] {
                 if let SerializedBound::FieldIndex(idx) = bound {
-                    if *idx >= field_count {
+                    if *idx >= slot_count {
                         return Err(DeserializeError::Validation(format!(
-                            "bound field slot {idx} out of bounds (max {field_count})"
+                            "bound field slot {idx} out of bounds (max {slot_count})"
                         )));
                     }
                 }
@@ -675,9 +1150,9 @@ fn validate_expr(
             Ok(())
         }
         SerializedExpr::IsNull(idx) | SerializedExpr::IsNotNull(idx) => {
-            if *idx >= field_count {
+            if *idx >= slot_count {
                 return Err(DeserializeError::Validation(format!(
-                    "field slot {idx} out of bounds (max {field_count})"
+                    "field slot {idx} out of bounds (max {slot_count})"
                 )));
             }
             Ok(())
@@ -702,36 +1177,184 @@ fn validate_expr(
                 ));
             }
             for child in children {
-                validate_expr(child, field_count, rule_count, current_rule_index)?;
+                validate_expr(
+                    child,
+                    field_count,
+                    slot_count,
+                    rule_count,
+                    current_rule_index,
+                )?;
             }
             Ok(())
         }
-        SerializedExpr::Not(inner) => {
-            validate_expr(inner, field_count, rule_count, current_rule_index)
-        }
+        SerializedExpr::Not(inner) => validate_expr(
+            inner,
+            field_count,
+            slot_count,
+            rule_count,
+            current_rule_index,
+        ),
         SerializedExpr::CompareFields {
             left_slot,
             right_slot,
             ..
         } => {
-            if *left_slot >= field_count {
+            if *left_slot >= slot_count {
                 return Err(DeserializeError::Validation(format!(
-                    "left field slot {left_slot} out of bounds (max {field_count})"
+                    "left field slot {left_slot} out of bounds (max {slot_count})"
                 )));
             }
-            if *right_slot >= field_count {
+            if *right_slot >= slot_count {
                 return Err(DeserializeError::Validation(format!(
-                    "right field slot {right_slot} out of bounds (max {field_count})"
+                    "right field slot {right_slot} out of bounds (max {slot_count})"
                 )));
             }
             Ok(())
         }
         SerializedExpr::AtLeast { exprs, .. } => {
             for child in exprs {
-                validate_expr(child, field_count, rule_count, current_rule_index)?;
+                validate_expr(
+                    child,
+                    field_count,
+                    slot_count,
+                    rule_count,
+                    current_rule_index,
+                )?;
+            }
+            Ok(())
+        }
+        SerializedExpr::Contains { field_slot, value }
+        | SerializedExpr::SetCompare {
+            field_slot, value, ..
+        }
+        | SerializedExpr::Len {
+            field_slot, value, ..
+        }
+        | SerializedExpr::HasKey {
+            field_slot,
+            key: value,
+        } => {
+            if *field_slot >= slot_count {
+                return Err(DeserializeError::Validation(format!(
+                    "field slot {field_slot} out of bounds (max {slot_count})"
+                )));
+            }
+            if let SerializedBound::FieldIndex(idx) = value {
+                if *idx >= slot_count {
+                    return Err(DeserializeError::Validation(format!(
+                        "bound field slot {idx} out of bounds (max {slot_count})"
+                    )));
+                }
             }
             Ok(())
         }
+        SerializedExpr::Lookup { lookups, body } => {
+            for lookup in lookups {
+                if !(field_count..slot_count).contains(&lookup.slot) {
+                    return Err(DeserializeError::Validation(format!(
+                        "lookup slot {} outside local range {field_count}..{slot_count}",
+                        lookup.slot
+                    )));
+                }
+                let dynamic = lookup.steps.iter().filter_map(|step| match step {
+                    SerializedStep::Dynamic(i) => Some(*i),
+                    SerializedStep::Key(_)
+                    | SerializedStep::Index(_)
+                    | SerializedStep::Wildcard => None,
+                });
+                for slot in std::iter::once(lookup.base_slot).chain(dynamic) {
+                    if slot >= slot_count {
+                        return Err(DeserializeError::Validation(format!(
+                            "lookup source slot {slot} out of bounds (max {slot_count})"
+                        )));
+                    }
+                }
+            }
+            validate_expr(
+                body,
+                field_count,
+                slot_count,
+                rule_count,
+                current_rule_index,
+            )
+        }
+        SerializedExpr::Any {
+            list_slot,
+            bindings,
+            predicate,
+        }
+        | SerializedExpr::All {
+            list_slot,
+            bindings,
+            predicate,
+        } => {
+            if *list_slot >= slot_count {
+                return Err(DeserializeError::Validation(format!(
+                    "list field slot {list_slot} out of bounds (max {slot_count})"
+                )));
+            }
+            for (slot, _) in bindings {
+                if !(field_count..slot_count).contains(slot) {
+                    return Err(DeserializeError::Validation(format!(
+                        "local slot {slot} outside local range {field_count}..{slot_count}"
+                    )));
+                }
+            }
+            validate_expr(
+                predicate,
+                field_count,
+                slot_count,
+                rule_count,
+                current_rule_index,
+            )
+        }
+    }
+}
+
+/// One past the highest local slot bound by any quantifier or lookup in `expr`, or 0.
+fn local_slot_end(expr: &SerializedExpr) -> usize {
+    match expr {
+        SerializedExpr::Any {
+            bindings,
+            predicate,
+            ..
+        }
+        | SerializedExpr::All {
+            bindings,
+            predicate,
+            ..
+        } => bindings
+            .iter()
+            .map(|(slot, _)| slot + 1)
+            .chain([local_slot_end(predicate)])
+            .max()
+            .unwrap_or(0),
+        SerializedExpr::And(children)
+        | SerializedExpr::Or(children)
+        | SerializedExpr::AtLeast {
+            exprs: children, ..
+        } => children.iter().map(local_slot_end).max().unwrap_or(0),
+        SerializedExpr::Not(inner) => local_slot_end(inner),
+        SerializedExpr::Lookup { lookups, body } => lookups
+            .iter()
+            .map(|l| l.slot + 1)
+            .chain([local_slot_end(body)])
+            .max()
+            .unwrap_or(0),
+        SerializedExpr::FieldCmp { .. }
+        | SerializedExpr::RuleRef(_)
+        | SerializedExpr::In { .. }
+        | SerializedExpr::NotIn { .. }
+        | SerializedExpr::Between { .. }
+        | SerializedExpr::Like { .. }
+        | SerializedExpr::NotLike { .. }
+        | SerializedExpr::IsNull(_)
+        | SerializedExpr::IsNotNull(_)
+        | SerializedExpr::CompareFields { .. }
+        | SerializedExpr::Contains { .. }
+        | SerializedExpr::SetCompare { .. }
+        | SerializedExpr::Len { .. }
+        | SerializedExpr::HasKey { .. } => 0,
     }
 }
 
@@ -739,14 +1362,16 @@ fn validate_expr(
 // Header I/O
 // ---------------------------------------------------------------------------
 
-fn write_header(buf: &mut Vec<u8>, payload: &[u8]) {
+/// Write the 32-byte header for `payload`. The view layout shares it under
+/// its own magic and version.
+pub(crate) fn write_header(buf: &mut Vec<u8>, magic: &[u8; 4], version: u16, payload: &[u8]) {
     let hash = blake3::hash(payload);
     let hash_bytes = hash.as_bytes();
 
-    buf.extend_from_slice(MAGIC);
-    buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    buf.extend_from_slice(magic);
+    buf.extend_from_slice(&version.to_le_bytes());
     buf.extend_from_slice(&ENGINE_VERSION.to_le_bytes());
-    buf.extend_from_slice(&0u32.to_le_bytes()); // flags (reserved)
+    buf.extend_from_slice(&0u32.to_le_bytes()); // flags
     #[allow(clippy::cast_possible_truncation)] // payload will never exceed 4 GiB
     let payload_len = payload.len() as u32;
     buf.extend_from_slice(&payload_len.to_le_bytes());
@@ -754,7 +1379,7 @@ fn write_header(buf: &mut Vec<u8>, payload: &[u8]) {
 }
 
 #[allow(clippy::cast_possible_truncation)] // HEADER_SIZE is 32, always fits in u32
-fn read_header(bytes: &[u8]) -> Result<(u16, u32, [u8; 16]), DeserializeError> {
+fn read_header(bytes: &[u8], magic: &[u8; 4]) -> Result<(u16, u32, [u8; 16]), DeserializeError> {
     if bytes.len() < HEADER_SIZE {
         return Err(DeserializeError::LengthMismatch {
             expected: HEADER_SIZE as u32,
@@ -762,13 +1387,13 @@ fn read_header(bytes: &[u8]) -> Result<(u16, u32, [u8; 16]), DeserializeError> {
         });
     }
 
-    if &bytes[0..4] != MAGIC {
+    if &bytes[0..4] != magic {
         return Err(DeserializeError::BadMagic);
     }
 
     let format_version = u16::from_le_bytes([bytes[4], bytes[5]]);
     // bytes[6..8] is engine_version (informational, not used for checks)
-    // bytes[8..12] is flags (reserved)
+    // bytes[8..12] is flags, read by `read_flags` once the header is checked
     let payload_len = u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
 
     let mut hash = [0u8; 16];
@@ -777,30 +1402,279 @@ fn read_header(bytes: &[u8]) -> Result<(u16, u32, [u8; 16]), DeserializeError> {
     Ok((format_version, payload_len, hash))
 }
 
+/// The flags of a blob whose header [`read_header`] accepted.
+fn read_flags(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]])
+}
+
 // ---------------------------------------------------------------------------
 // Public encode/decode
 // ---------------------------------------------------------------------------
 
+/// What a binary-cache blob's header and metadata say about it, read by
+/// [`inspect()`] without decoding the rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobInfo {
+    /// Format version of the payload.
+    pub format_version: u16,
+    /// Version of the engine that wrote the blob. Informational only.
+    pub engine_version: u16,
+    /// Header flags. Bit 0 is set on signed blobs, bit 1 on compressed ones.
+    pub flags: u32,
+    /// Payload length in bytes, as recorded in the header. For compressed
+    /// blobs this is the compressed length.
+    pub payload_len: u32,
+    /// Whether the payload matches the header's BLAKE3 checksum. When
+    /// `false`, the counts and digest below come from a damaged payload.
+    pub checksum_valid: bool,
+    /// Number of compiled rules.
+    pub rule_count: usize,
+    /// Number of terminals.
+    pub terminal_count: usize,
+    /// Number of distinct field paths the rules read.
+    pub field_count: usize,
+    /// BLAKE3 digest of the source text passed to
+    /// [`RuleSet::to_bytes()`](crate::RuleSet::to_bytes), if any.
+    pub source_digest: Option<[u8; 32]>,
+}
+
+impl BlobInfo {
+    /// Returns `true` if the blob carries a signature.
+    #[must_use]
+    pub fn is_signed(&self) -> bool {
+        self.flags & FLAG_SIGNED != 0
+    }
+
+    /// Returns `true` if the payload is compressed.
+    #[must_use]
+    pub fn is_compressed(&self) -> bool {
+        self.flags & FLAG_COMPRESSED != 0
+    }
+
+    /// Returns `true` if the blob was written from exactly `source`, so a
+    /// cache built from it is still fresh.
+    #[must_use]
+    pub fn matches_source(&self, source: &str) -> bool {
+        self.source_digest == Some(*blake3::hash(source.as_bytes()).as_bytes())
+    }
+}
+
+/// Read the header and ruleset metadata of a binary-cache blob.
+///
+/// Only the metadata at the start of the payload is decoded, so this is
+/// much cheaper than [`RuleSet::from_bytes()`](crate::RuleSet::from_bytes)
+/// for deciding whether a cache is stale. Compressed payloads are
+/// decompressed first. The checksum is computed and reported in
+/// [`BlobInfo::checksum_valid`] rather than failing.
+///
+/// # Errors
+///
+/// Returns [`DeserializeError`] if the magic bytes are wrong, the format
+/// version cannot be read, the payload is truncated or cannot be
+/// decompressed, or its metadata does not decode.
+///
+/// # Example
+///
+/// ```
+/// use ooroo::{inspect, RuleSet};
+///
+/// let source = "rule adult (priority 0): user.age >= 18";
+/// let blob = RuleSet::from_dsl(source)
+///     .unwrap()
+///     .to_bytes(Some(source))
+///     .unwrap();
+///
+/// let info = inspect(&blob).unwrap();
+/// assert_eq!(info.rule_count, 1);
+/// assert!(info.checksum_valid);
+/// assert!(info.matches_source(source));
+/// ```
+pub fn inspect(bytes: &[u8]) -> Result<BlobInfo, DeserializeError> {
+    let (format_version, payload_len, stored_hash) = read_header(bytes, MAGIC)?;
+    if !(OLDEST_FORMAT_VERSION..=FORMAT_VERSION).contains(&format_version) {
+        return Err(DeserializeError::IncompatibleVersion {
+            blob: format_version,
+            supported: FORMAT_VERSION,
+        });
+    }
+    let payload = bytes
+        .get(HEADER_SIZE..HEADER_SIZE + payload_len as usize)
+        .ok_or(DeserializeError::LengthMismatch {
+            expected: payload_len,
+            actual: bytes.len() - HEADER_SIZE,
+        })?;
+
+    let flags = read_flags(bytes);
+
+    // Every readable version starts its payload with the same metadata.
+    let (metadata, _): (RuleSetMetadata, usize) =
+        bincode::serde::decode_from_slice(&unpack(flags, payload)?, bincode::config::standard())?;
+
+    Ok(BlobInfo {
+        format_version,
+        engine_version: u16::from_le_bytes([bytes[6], bytes[7]]),
+        flags,
+        payload_len,
+        checksum_valid: blake3::hash(payload).as_bytes()[..16] == stored_hash,
+        rule_count: metadata.rule_count,
+        terminal_count: metadata.terminal_count,
+        field_count: metadata.field_count,
+        source_digest: metadata.source_digest,
+    })
+}
+
 pub(crate) fn encode(
     ruleset: &RuleSet,
     source_text: Option<&str>,
 ) -> Result<Vec<u8>, SerializeError> {
+    let payload = encode_payload(ruleset, source_text)?;
+    Ok(frame(&payload, 0))
+}
+
+/// Encode `ruleset` with an LZ4-compressed payload and the `COMPRESSED`
+/// flag set.
+#[cfg(feature = "compression")]
+pub(crate) fn encode_compressed(
+    ruleset: &RuleSet,
+    source_text: Option<&str>,
+) -> Result<Vec<u8>, SerializeError> {
+    let payload = encode_payload(ruleset, source_text)?;
+    let compressed = lz4_flex::block::compress_prepend_size(&payload);
+    Ok(frame(&compressed, FLAG_COMPRESSED))
+}
+
+fn encode_payload(ruleset: &RuleSet, source_text: Option<&str>) -> Result<Vec<u8>, SerializeError> {
     let serialized = ruleset_to_serialized(ruleset, source_text);
-    let payload = bincode::serde::encode_to_vec(&serialized, bincode::config::standard())?;
+    Ok(bincode::serde::encode_to_vec(
+        &serialized,
+        bincode::config::standard(),
+    )?)
+}
 
+/// Prefix `payload` with a header carrying `flags`.
+fn frame(payload: &[u8], flags: u32) -> Vec<u8> {
     let mut buf = Vec::with_capacity(HEADER_SIZE + payload.len());
-    write_header(&mut buf, &payload);
-    buf.extend_from_slice(&payload);
-    Ok(buf)
+    write_header(&mut buf, MAGIC, FORMAT_VERSION, payload);
+    buf[8..12].copy_from_slice(&flags.to_le_bytes());
+    buf.extend_from_slice(payload);
+    buf
 }
 
 pub(crate) fn decode(bytes: &[u8]) -> Result<RuleSet, DeserializeError> {
-    let (format_version, payload_len, stored_hash) = read_header(bytes)?;
+    let (version, payload) = checked_payload(bytes, MAGIC, OLDEST_FORMAT_VERSION..=FORMAT_VERSION)?;
+    decode_payload(version, &unpack(read_flags(bytes), payload)?)
+}
+
+/// Add the `SIGNED` flag to an encoded blob, keeping its other flags, and
+/// append `key`'s signature over the header and payload.
+#[cfg(feature = "signing")]
+pub(crate) fn sign(mut blob: Vec<u8>, key: &ed25519_dalek::SigningKey) -> Vec<u8> {
+    use ed25519_dalek::Signer;
+
+    let flags = read_flags(&blob) | FLAG_SIGNED;
+    blob[8..12].copy_from_slice(&flags.to_le_bytes());
+    let signature = key.sign(&blob);
+    blob.extend_from_slice(&signature.to_bytes());
+    blob
+}
+
+/// Decode a signed blob, checking its signature against `keys` before the
+/// payload is decoded.
+#[cfg(feature = "signing")]
+pub(crate) fn decode_verified(
+    bytes: &[u8],
+    keys: &[ed25519_dalek::VerifyingKey],
+) -> Result<RuleSet, DeserializeError> {
+    let (version, payload) = checked_payload(bytes, MAGIC, OLDEST_FORMAT_VERSION..=FORMAT_VERSION)?;
+    let flags = read_flags(bytes);
+    if flags & FLAG_SIGNED == 0 {
+        return Err(DeserializeError::MissingSignature);
+    }
+
+    let (signed, signature) = bytes.split_at(HEADER_SIZE + payload.len());
+    let signature = <[u8; SIGNATURE_SIZE]>::try_from(signature)
+        .map(|bytes| ed25519_dalek::Signature::from_bytes(&bytes))
+        .map_err(|_| DeserializeError::BadSignature)?;
+    if !keys
+        .iter()
+        .any(|key| key.verify_strict(signed, &signature).is_ok())
+    {
+        return Err(DeserializeError::BadSignature);
+    }
+    decode_payload(version, &unpack(flags, payload)?)
+}
+
+/// Undo the compression recorded in `flags`, if any.
+fn unpack(flags: u32, payload: &[u8]) -> Result<Cow<'_, [u8]>, DeserializeError> {
+    if flags & FLAG_COMPRESSED == 0 {
+        return Ok(Cow::Borrowed(payload));
+    }
+    #[cfg(feature = "compression")]
+    {
+        decompress(payload).map(Cow::Owned)
+    }
+    #[cfg(not(feature = "compression"))]
+    {
+        Err(DeserializeError::CompressionUnsupported)
+    }
+}
+
+#[cfg(feature = "compression")]
+fn decompress(payload: &[u8]) -> Result<Vec<u8>, DeserializeError> {
+    /// LZ4 cannot expand data more than 255-fold, so a larger prepended
+    /// length is corrupt and must not be allocated.
+    const MAX_RATIO: usize = 255;
+
+    let invalid = |reason: String| {
+        DeserializeError::Validation(format!("invalid compressed payload: {reason}"))
+    };
+    let (size, block) =
+        lz4_flex::block::uncompressed_size(payload).map_err(|e| invalid(e.to_string()))?;
+    if size > block.len().saturating_mul(MAX_RATIO) {
+        return Err(invalid(format!(
+            "{size} bytes cannot come from a {}-byte block",
+            block.len()
+        )));
+    }
+    let decompressed =
+        lz4_flex::block::decompress(block, size).map_err(|e| invalid(e.to_string()))?;
+    if decompressed.len() != size {
+        return Err(invalid(format!(
+            "expected {size} bytes, got {}",
+            decompressed.len()
+        )));
+    }
+    Ok(decompressed)
+}
+
+/// Decode a payload written in format `version`, upgrading older versions.
+fn decode_payload(version: u16, payload: &[u8]) -> Result<RuleSet, DeserializeError> {
+    let config = bincode::config::standard();
+    let serialized = match version {
+        4 => {
+            let (v4, _): (v4::SerializedRuleSetV4, usize) =
+                bincode::serde::decode_from_slice(payload, config)?;
+            v4.into()
+        }
+        _ => bincode::serde::decode_from_slice::<SerializedRuleSet, _>(payload, config)?.0,
+    };
+
+    serialized_to_ruleset(serialized)
+}
+
+/// Check the header of `bytes` against `magic` and `versions`, then the
+/// payload's length and checksum, and return the version and payload.
+pub(crate) fn checked_payload<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+    versions: RangeInclusive<u16>,
+) -> Result<(u16, &'a [u8]), DeserializeError> {
+    let (format_version, payload_len, stored_hash) = read_header(bytes, magic)?;
 
-    if format_version != FORMAT_VERSION {
+    if !versions.contains(&format_version) {
         return Err(DeserializeError::IncompatibleVersion {
             blob: format_version,
-            supported: FORMAT_VERSION,
+            supported: *versions.end(),
         });
     }
 
@@ -819,11 +1693,7 @@ pub(crate) fn decode(bytes: &[u8]) -> Result<RuleSet, DeserializeError> {
     if computed_hash.as_bytes()[..16] != stored_hash {
         return Err(DeserializeError::ChecksumMismatch);
     }
-
-    let (serialized, _): (SerializedRuleSet, usize) =
-        bincode::serde::decode_from_slice(payload, bincode::config::standard())?;
-
-    serialized_to_ruleset(serialized)
+    Ok((format_version, payload))
 }
 
 // ---------------------------------------------------------------------------
@@ -842,6 +1712,12 @@ mod tests {
         }
     }
 
+    fn round_trip_value(value: &Value) -> Value {
+        let mut strings = StringTable::default();
+        let serialized = serialize_value(value, &mut strings);
+        deserialize_value(serialized, &strings.strings).unwrap()
+    }
+
     // -- CompareOp round-trip --
 
     #[test]
@@ -859,30 +1735,121 @@ mod tests {
         }
     }
 
+    #[test]
+    fn set_op_round_trip() {
+        let ops = [
+            SetOp::Overlaps,
+            SetOp::SubsetOf,
+            SetOp::SupersetOf,
+            SetOp::DisjointFrom,
+        ];
+        for op in ops {
+            assert_eq!(deserialize_set_op(serialize_set_op(op)), op);
+        }
+    }
+
     // -- Value round-trip --
 
     #[test]
     fn value_round_trip_int() {
         let v = Value::Int(42);
-        assert_eq!(deserialize_value(serialize_value(&v)), v);
+        assert_eq!(round_trip_value(&v), v);
     }
 
     #[test]
     fn value_round_trip_float() {
-        let v = Value::Float(3.14);
-        assert_eq!(deserialize_value(serialize_value(&v)), v);
+        let v = Value::Float(3.25);
+        assert_eq!(round_trip_value(&v), v);
     }
 
     #[test]
     fn value_round_trip_bool() {
         let v = Value::Bool(true);
-        assert_eq!(deserialize_value(serialize_value(&v)), v);
+        assert_eq!(round_trip_value(&v), v);
     }
 
     #[test]
     fn value_round_trip_string() {
         let v = Value::String("hello".to_owned());
-        assert_eq!(deserialize_value(serialize_value(&v)), v);
+        assert_eq!(round_trip_value(&v), v);
+    }
+
+    #[test]
+    fn value_round_trip_map() {
+        let v = Value::Map(
+            [
+                ("US".to_owned(), Value::from("US")),
+                ("k".to_owned(), 1.into()),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(round_trip_value(&v), v);
+    }
+
+    // -- String interning --
+
+    #[test]
+    fn repeated_strings_are_interned_once() {
+        let mut strings = StringTable::default();
+        let v = Value::List(vec!["a".into(), "b".into(), "a".into()]);
+        let serialized = serialize_value(&v, &mut strings);
+        assert_eq!(strings.strings, ["a", "b"]);
+        assert!(matches!(
+            serialized,
+            SerializedValue::List(items)
+                if matches!(items[..], [SerializedValue::Str(0), SerializedValue::Str(1), SerializedValue::Str(0)])
+        ));
+    }
+
+    #[test]
+    fn string_index_oob_rejected() {
+        let strings = ["a".to_owned()];
+        assert!(matches!(
+            deserialize_value(SerializedValue::Str(1), &strings),
+            Err(DeserializeError::Validation(_))
+        ));
+        let like = SerializedExpr::Like {
+            field_slot: 0,
+            pattern: 3,
+        };
+        assert!(matches!(
+            unflatten_expr(like, &strings),
+            Err(DeserializeError::Validation(_))
+        ));
+    }
+
+    // -- Compression --
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn decompress_round_trip() {
+        let payload = b"abcabcabcabcabcabcabcabc".repeat(10);
+        let compressed = lz4_flex::block::compress_prepend_size(&payload);
+        assert!(compressed.len() < payload.len());
+        assert_eq!(decompress(&compressed).unwrap(), payload);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn decompress_rejects_impossible_sizes() {
+        // A 4 GiB length prefix in front of a two-byte block must fail
+        // before anything is allocated.
+        let bomb = [0xFF, 0xFF, 0xFF, 0xFF, 0x10, 0x61];
+        assert!(matches!(
+            decompress(&bomb),
+            Err(DeserializeError::Validation(_))
+        ));
+        assert!(decompress(&[1, 0]).is_err());
+    }
+
+    #[cfg(not(feature = "compression"))]
+    #[test]
+    fn compressed_payloads_need_the_feature() {
+        assert!(matches!(
+            unpack(FLAG_COMPRESSED, b"payload"),
+            Err(DeserializeError::CompressionUnsupported)
+        ));
     }
 
     // -- Expression flatten/unflatten --
@@ -893,12 +1860,12 @@ mod tests {
             Box::new(make_compare(0, CompareOp::Eq, Value::Int(1))),
             Box::new(make_compare(1, CompareOp::Gt, Value::Int(2))),
         );
-        let flat = flatten_expr(&expr);
+        let flat = flatten_expr(&expr, &mut StringTable::default());
         match &flat {
             SerializedExpr::And(children) => assert_eq!(children.len(), 2),
             other => panic!("expected And, got {other:?}"),
         }
-        let restored = unflatten_expr(flat).unwrap();
+        let restored = unflatten_expr(flat, &[]).unwrap();
         assert_eq!(restored, expr);
     }
 
@@ -912,7 +1879,7 @@ mod tests {
             Box::new(CompiledExpr::And(Box::new(a.clone()), Box::new(b.clone()))),
             Box::new(c.clone()),
         );
-        let flat = flatten_expr(&expr);
+        let flat = flatten_expr(&expr, &mut StringTable::default());
         match &flat {
             SerializedExpr::And(children) => assert_eq!(children.len(), 3),
             other => panic!("expected And with 3 children, got {other:?}"),
@@ -929,7 +1896,7 @@ mod tests {
             Box::new(CompiledExpr::Or(Box::new(a), Box::new(b))),
             Box::new(c),
         );
-        let flat = flatten_expr(&expr);
+        let flat = flatten_expr(&expr, &mut StringTable::default());
         match &flat {
             SerializedExpr::And(children) => {
                 assert_eq!(children.len(), 2);
@@ -943,25 +1910,25 @@ mod tests {
     fn unflatten_single_child_unwraps() {
         let inner = SerializedExpr::RuleRef(0);
         let wrapped = SerializedExpr::And(vec![inner]);
-        let result = unflatten_expr(wrapped).unwrap();
+        let result = unflatten_expr(wrapped, &[]).unwrap();
         assert_eq!(result, CompiledExpr::RuleRef(0));
     }
 
     #[test]
     fn flatten_not() {
         let expr = CompiledExpr::Not(Box::new(make_compare(0, CompareOp::Eq, Value::Bool(true))));
-        let flat = flatten_expr(&expr);
+        let flat = flatten_expr(&expr, &mut StringTable::default());
         assert!(matches!(flat, SerializedExpr::Not(_)));
-        let restored = unflatten_expr(flat).unwrap();
+        let restored = unflatten_expr(flat, &[]).unwrap();
         assert_eq!(restored, expr);
     }
 
     #[test]
     fn flatten_rule_ref() {
         let expr = CompiledExpr::RuleRef(3);
-        let flat = flatten_expr(&expr);
+        let flat = flatten_expr(&expr, &mut StringTable::default());
         assert!(matches!(flat, SerializedExpr::RuleRef(3)));
-        let restored = unflatten_expr(flat).unwrap();
+        let restored = unflatten_expr(flat, &[]).unwrap();
         assert_eq!(restored, expr);
     }
 
@@ -971,10 +1938,10 @@ mod tests {
     fn header_round_trip() {
         let payload = b"test payload data";
         let mut buf = Vec::new();
-        write_header(&mut buf, payload);
+        write_header(&mut buf, MAGIC, FORMAT_VERSION, payload);
         assert_eq!(buf.len(), HEADER_SIZE);
 
-        let (format_version, payload_len, hash) = read_header(&buf).unwrap();
+        let (format_version, payload_len, hash) = read_header(&buf, MAGIC).unwrap();
         assert_eq!(format_version, FORMAT_VERSION);
         assert_eq!(payload_len as usize, payload.len());
 
@@ -986,14 +1953,17 @@ mod tests {
     fn header_bad_magic() {
         let mut buf = vec![0u8; HEADER_SIZE];
         buf[0..4].copy_from_slice(b"BAAD");
-        assert!(matches!(read_header(&buf), Err(DeserializeError::BadMagic)));
+        assert!(matches!(
+            read_header(&buf, MAGIC),
+            Err(DeserializeError::BadMagic)
+        ));
     }
 
     #[test]
     fn header_too_short() {
         let buf = vec![0u8; 10];
         assert!(matches!(
-            read_header(&buf),
+            read_header(&buf, MAGIC),
             Err(DeserializeError::LengthMismatch { .. })
         ));
     }
@@ -1003,14 +1973,14 @@ mod tests {
     #[test]
     fn validate_empty_and_rejected() {
         let expr = SerializedExpr::And(vec![]);
-        let result = validate_expr(&expr, 1, 1, 0);
+        let result = validate_expr(&expr, 1, 1, 1, 0);
         assert!(matches!(result, Err(DeserializeError::Validation(_))));
     }
 
     #[test]
     fn validate_empty_or_rejected() {
         let expr = SerializedExpr::Or(vec![]);
-        let result = validate_expr(&expr, 1, 1, 0);
+        let result = validate_expr(&expr, 1, 1, 1, 0);
         assert!(matches!(result, Err(DeserializeError::Validation(_))));
     }
 
@@ -1021,14 +1991,54 @@ mod tests {
             op: SerializedCompareOp::Eq,
             value: SerializedValue::Int(1),
         };
-        let result = validate_expr(&expr, 3, 1, 0);
+        let result = validate_expr(&expr, 3, 3, 1, 0);
         assert!(matches!(result, Err(DeserializeError::Validation(_))));
     }
 
     #[test]
     fn validate_rule_ref_oob() {
         let expr = SerializedExpr::RuleRef(10);
-        let result = validate_expr(&expr, 1, 5, 3);
+        let result = validate_expr(&expr, 1, 1, 5, 3);
+        assert!(matches!(result, Err(DeserializeError::Validation(_))));
+    }
+
+    #[test]
+    fn validate_quantifier_binding_inside_field_range_rejected() {
+        // A binding must address a local slot, never a context field slot
+        let expr = SerializedExpr::Any {
+            list_slot: 0,
+            bindings: vec![(1, vec![])],
+            predicate: Box::new(SerializedExpr::IsNotNull(1)),
+        };
+        let result = validate_expr(&expr, 2, 2, 1, 0);
+        assert!(matches!(result, Err(DeserializeError::Validation(_))));
+    }
+
+    #[test]
+    fn validate_lookup_slot_inside_field_range_rejected() {
+        let expr = SerializedExpr::Lookup {
+            lookups: vec![SerializedLookup {
+                slot: 0,
+                base_slot: 1,
+                steps: vec![SerializedStep::Key("k".into())],
+            }],
+            body: Box::new(SerializedExpr::IsNotNull(0)),
+        };
+        let result = validate_expr(&expr, 2, 2, 1, 0);
+        assert!(matches!(result, Err(DeserializeError::Validation(_))));
+    }
+
+    #[test]
+    fn validate_lookup_dynamic_key_oob() {
+        let expr = SerializedExpr::Lookup {
+            lookups: vec![SerializedLookup {
+                slot: 1,
+                base_slot: 0,
+                steps: vec![SerializedStep::Dynamic(7)],
+            }],
+            body: Box::new(SerializedExpr::IsNotNull(1)),
+        };
+        let result = validate_expr(&expr, 1, 2, 1, 0);
         assert!(matches!(result, Err(DeserializeError::Validation(_))));
     }
 
@@ -1036,7 +2046,7 @@ mod tests {
     fn validate_rule_ref_topological_violation() {
         // Rule at index 1 references rule at index 2 (forward reference)
         let expr = SerializedExpr::RuleRef(2);
-        let result = validate_expr(&expr, 1, 5, 1);
+        let result = validate_expr(&expr, 1, 1, 5, 1);
         assert!(matches!(result, Err(DeserializeError::Validation(_))));
     }
 }
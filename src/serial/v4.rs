@@ -0,0 +1,244 @@
+//! Format version 4, written by ooroo 0.3.
+//!
+//! Version 5 added rule metadata, terminal `on_unknown` policies, the
+//! schema and new expression kinds, and moved string literals and LIKE
+//! patterns into a shared string table. The v4 types are redeclared here
+//! and upgraded with the defaults a v4 ruleset had, interning their
+//! strings on the way.
+
+use serde::Deserialize;
+
+use super::{
+    RuleSetMetadata, SerializedBound, SerializedCompareOp, SerializedExpr, SerializedRule,
+    SerializedRuleMetadata, SerializedRuleSet, SerializedTerminal, SerializedUnknownPolicy,
+    SerializedValue, StringTable,
+};
+
+#[derive(Debug, Deserialize)]
+pub(super) struct SerializedRuleSetV4 {
+    metadata: RuleSetMetadata,
+    rules: Vec<SerializedRuleV4>,
+    terminals: Vec<SerializedTerminalV4>,
+    field_index: Vec<(String, usize)>,
+    rule_names: Vec<(String, usize)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerializedRuleV4 {
+    index: usize,
+    condition: SerializedExprV4,
+}
+
+#[derive(Debug, Deserialize)]
+enum SerializedExprV4 {
+    FieldCmp {
+        field_slot: usize,
+        op: SerializedCompareOp,
+        value: SerializedValueV4,
+    },
+    RuleRef(usize),
+    And(Vec<SerializedExprV4>),
+    Or(Vec<SerializedExprV4>),
+    Not(Box<SerializedExprV4>),
+    In {
+        field_slot: usize,
+        members: Vec<SerializedBoundV4>,
+    },
+    NotIn {
+        field_slot: usize,
+        members: Vec<SerializedBoundV4>,
+    },
+    Between {
+        field_slot: usize,
+        low: SerializedBoundV4,
+        high: SerializedBoundV4,
+    },
+    Like {
+        field_slot: usize,
+        pattern: String,
+    },
+    NotLike {
+        field_slot: usize,
+        pattern: String,
+    },
+    IsNull(usize),
+    IsNotNull(usize),
+    CompareFields {
+        left_slot: usize,
+        op: SerializedCompareOp,
+        right_slot: usize,
+    },
+    AtLeast {
+        n: usize,
+        exprs: Vec<SerializedExprV4>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+enum SerializedValueV4 {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    List(Vec<SerializedValueV4>),
+}
+
+#[derive(Debug, Deserialize)]
+enum SerializedBoundV4 {
+    Literal(SerializedValueV4),
+    FieldIndex(usize),
+}
+
+#[derive(Debug, Deserialize)]
+struct SerializedTerminalV4 {
+    rule_index: usize,
+    name: String,
+    priority: u32,
+}
+
+impl From<SerializedRuleSetV4> for SerializedRuleSet {
+    fn from(v4: SerializedRuleSetV4) -> Self {
+        let mut strings = StringTable::default();
+        Self {
+            metadata: v4.metadata,
+            rules: v4
+                .rules
+                .into_iter()
+                .map(|rule| SerializedRule {
+                    index: rule.index,
+                    condition: upgrade_expr(rule.condition, &mut strings),
+                    metadata: SerializedRuleMetadata {
+                        description: None,
+                        owner: None,
+                        tags: Vec::new(),
+                        link: None,
+                        enabled: true,
+                        extra: Vec::new(),
+                    },
+                })
+                .collect(),
+            terminals: v4
+                .terminals
+                .into_iter()
+                .map(|terminal| SerializedTerminal {
+                    rule_index: terminal.rule_index,
+                    name: terminal.name,
+                    priority: terminal.priority,
+                    on_unknown: SerializedUnknownPolicy::AsFalse,
+                })
+                .collect(),
+            field_index: v4.field_index,
+            rule_names: v4.rule_names,
+            schema: None,
+            strings: strings.strings,
+        }
+    }
+}
+
+fn upgrade_expr(expr: SerializedExprV4, strings: &mut StringTable) -> SerializedExpr {
+    let upgrade_all = |exprs: Vec<SerializedExprV4>, strings: &mut StringTable| {
+        exprs
+            .into_iter()
+            .map(|e| upgrade_expr(e, strings))
+            .collect()
+    };
+    let upgrade_bounds = |bounds: Vec<SerializedBoundV4>, strings: &mut StringTable| {
+        bounds
+            .into_iter()
+            .map(|b| upgrade_bound(b, strings))
+            .collect()
+    };
+    match expr {
+        SerializedExprV4::FieldCmp {
+            field_slot,
+            op,
+            value,
+        } => SerializedExpr::FieldCmp {
+            field_slot,
+            op,
+            value: upgrade_value(value, strings),
+        },
+        SerializedExprV4::RuleRef(idx) => SerializedExpr::RuleRef(idx),
+        SerializedExprV4::And(children) => SerializedExpr::And(upgrade_all(children, strings)),
+        SerializedExprV4::Or(children) => SerializedExpr::Or(upgrade_all(children, strings)),
+        SerializedExprV4::Not(inner) => {
+            SerializedExpr::Not(Box::new(upgrade_expr(*inner, strings)))
+        }
+        SerializedExprV4::In {
+            field_slot,
+            members,
+        } => SerializedExpr::In {
+            field_slot,
+            members: upgrade_bounds(members, strings),
+        },
+        SerializedExprV4::NotIn {
+            field_slot,
+            members,
+        } => SerializedExpr::NotIn {
+            field_slot,
+            members: upgrade_bounds(members, strings),
+        },
+        SerializedExprV4::Between {
+            field_slot,
+            low,
+            high,
+        } => SerializedExpr::Between {
+            field_slot,
+            low: upgrade_bound(low, strings),
+            high: upgrade_bound(high, strings),
+        },
+        SerializedExprV4::Like {
+            field_slot,
+            pattern,
+        } => SerializedExpr::Like {
+            field_slot,
+            pattern: strings.intern(&pattern),
+        },
+        SerializedExprV4::NotLike {
+            field_slot,
+            pattern,
+        } => SerializedExpr::NotLike {
+            field_slot,
+            pattern: strings.intern(&pattern),
+        },
+        SerializedExprV4::IsNull(idx) => SerializedExpr::IsNull(idx),
+        SerializedExprV4::IsNotNull(idx) => SerializedExpr::IsNotNull(idx),
+        SerializedExprV4::CompareFields {
+            left_slot,
+            op,
+            right_slot,
+        } => SerializedExpr::CompareFields {
+            left_slot,
+            op,
+            right_slot,
+        },
+        SerializedExprV4::AtLeast { n, exprs } => SerializedExpr::AtLeast {
+            n,
+            exprs: upgrade_all(exprs, strings),
+        },
+    }
+}
+
+fn upgrade_bound(bound: SerializedBoundV4, strings: &mut StringTable) -> SerializedBound {
+    match bound {
+        SerializedBoundV4::Literal(value) => {
+            SerializedBound::Literal(upgrade_value(value, strings))
+        }
+        SerializedBoundV4::FieldIndex(idx) => SerializedBound::FieldIndex(idx),
+    }
+}
+
+fn upgrade_value(value: SerializedValueV4, strings: &mut StringTable) -> SerializedValue {
+    match value {
+        SerializedValueV4::Int(v) => SerializedValue::Int(v),
+        SerializedValueV4::Float(v) => SerializedValue::Float(v),
+        SerializedValueV4::Bool(v) => SerializedValue::Bool(v),
+        SerializedValueV4::Str(v) => SerializedValue::Str(strings.intern(&v)),
+        SerializedValueV4::List(items) => SerializedValue::List(
+            items
+                .into_iter()
+                .map(|item| upgrade_value(item, strings))
+                .collect(),
+        ),
+    }
+}
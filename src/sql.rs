@@ -0,0 +1,492 @@
+//! Export of rule conditions as SQL `WHERE` clauses.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{Bound, CompareOp, Expr, Rule, RuleSet, Value};
+
+/// The SQL dialect a [`SqlExporter`] writes.
+///
+/// Dialects differ in how identifiers are quoted, whether booleans are
+/// literals, how backslashes behave in strings, and which characters are
+/// special in `LIKE` patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SqlDialect {
+    /// Standard SQL: `"identifiers"`, `TRUE`/`FALSE`.
+    #[default]
+    Ansi,
+    /// PostgreSQL: as [`Ansi`](SqlDialect::Ansi), with backslashes escaped
+    /// in `LIKE` patterns.
+    Postgres,
+    /// MySQL and MariaDB: `` `identifiers` ``, with backslashes escaped in
+    /// strings and `LIKE` patterns.
+    MySql,
+    /// SQLite: as [`Ansi`](SqlDialect::Ansi).
+    Sqlite,
+    /// SQL Server: `[identifiers]`, `1`/`0` for booleans, and `[` escaped in
+    /// `LIKE` patterns.
+    SqlServer,
+}
+
+/// Errors exporting a rule as SQL.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SqlError {
+    /// The ruleset has no rule with this name.
+    #[error("unknown rule '{0}'")]
+    UnknownRule(String),
+
+    /// A field the condition reads has no column mapping.
+    #[error("no column mapped for field '{0}'")]
+    UnmappedField(String),
+
+    /// The condition uses a construct with no portable SQL equivalent.
+    #[error("cannot express {0} in SQL")]
+    Unsupported(String),
+}
+
+/// Writes rule conditions as SQL `WHERE` clauses for a dialect, with field
+/// paths mapped to columns.
+///
+/// Every field a condition reads must be mapped with [`column`](Self::column).
+/// Column names are quoted for the dialect; a `.` separates a table or schema
+/// qualifier, so `orders.total` becomes `"orders"."total"`.
+///
+/// # Example
+/// ```
+/// use ooroo::{RuleSet, SqlDialect, SqlExporter};
+///
+/// let ruleset = RuleSet::from_dsl(
+///     "rule adult:\n    user.age >= 18\n\
+///      rule allow (priority 0):\n    adult AND user.country IN [\"US\", \"CA\"]",
+/// )
+/// .unwrap();
+/// let exporter = SqlExporter::new(SqlDialect::Postgres)
+///     .column("user.age", "u.age")
+///     .column("user.country", "u.country");
+/// assert_eq!(
+///     ruleset.to_sql_where("allow", &exporter).unwrap(),
+///     r#""u"."age" >= 18 AND "u"."country" IN ('US', 'CA')"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SqlExporter {
+    dialect: SqlDialect,
+    columns: HashMap<String, String>,
+}
+
+impl SqlExporter {
+    /// Create an exporter for `dialect` with no columns mapped.
+    #[must_use]
+    pub fn new(dialect: SqlDialect) -> Self {
+        Self {
+            dialect,
+            columns: HashMap::new(),
+        }
+    }
+
+    /// Map a field path, as written in the rules, to a column name.
+    #[must_use]
+    pub fn column(mut self, field: &str, column: &str) -> Self {
+        self.columns.insert(field.to_owned(), column.to_owned());
+        self
+    }
+
+    /// Map several field paths at once. See [`column`](Self::column).
+    #[must_use]
+    pub fn columns<'a>(mut self, mapping: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        for (field, column) in mapping {
+            self.columns.insert(field.to_owned(), column.to_owned());
+        }
+        self
+    }
+
+    /// The dialect this exporter writes.
+    #[must_use]
+    pub fn dialect(&self) -> SqlDialect {
+        self.dialect
+    }
+
+    fn write(&self, expr: &Expr, rules: Option<&HashMap<&str, &Rule>>) -> Result<String, SqlError> {
+        Writer {
+            exporter: self,
+            rules,
+            inlining: Vec::new(),
+        }
+        .expr(expr)
+    }
+}
+
+impl RuleSet {
+    /// Write a rule's condition as an SQL `WHERE` clause (without the
+    /// `WHERE` keyword), with the rules it references inlined.
+    ///
+    /// A reference to a disabled rule becomes `FALSE`, as it evaluates; the
+    /// exported rule's own `enabled` flag is ignored. Rows whose columns are
+    /// `NULL` follow SQL's three-valued logic: a row matches when
+    /// [`evaluate_three_valued`](Self::evaluate_three_valued) would find the
+    /// condition definitely true. `LIKE` is case-sensitive in ooroo but not
+    /// under the default collations of MySQL, SQLite and SQL Server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SqlError::UnknownRule`] if there is no rule `rule_name`,
+    /// [`SqlError::UnmappedField`] for the first field without a column, and
+    /// [`SqlError::Unsupported`] for the first construct SQL cannot express
+    /// portably: `ANY`, `ALL`, `CONTAINS`, set operators, `len()`,
+    /// `HAS_KEY`, list or map literals outside `IN`, and non-finite floats.
+    pub fn to_sql_where(
+        &self,
+        rule_name: &str,
+        exporter: &SqlExporter,
+    ) -> Result<String, SqlError> {
+        let rules = crate::decompile::decompile(self);
+        let by_name: HashMap<&str, &Rule> = rules.iter().map(|r| (r.name.as_str(), r)).collect();
+        let condition = by_name
+            .get(rule_name)
+            .and_then(|rule| rule.condition.as_ref())
+            .ok_or_else(|| SqlError::UnknownRule(rule_name.to_owned()))?;
+        exporter.write(condition, Some(&by_name))
+    }
+}
+
+impl Expr {
+    /// Write this expression as an SQL `WHERE` clause (without the `WHERE`
+    /// keyword). See [`RuleSet::to_sql_where`] for the semantics.
+    ///
+    /// # Errors
+    ///
+    /// As [`RuleSet::to_sql_where`], and [`SqlError::Unsupported`] for rule
+    /// references, which only a ruleset can inline.
+    ///
+    /// # Example
+    /// ```
+    /// use ooroo::{field, SqlDialect, SqlExporter};
+    ///
+    /// let exporter = SqlExporter::new(SqlDialect::MySql).column("sku", "sku");
+    /// assert_eq!(
+    ///     field("sku").like("A\\_%").to_sql_where(&exporter).unwrap(),
+    ///     r"`sku` LIKE 'A\\\\_%'"
+    /// );
+    /// ```
+    pub fn to_sql_where(&self, exporter: &SqlExporter) -> Result<String, SqlError> {
+        exporter.write(self, None)
+    }
+}
+
+/// Binding strength of an operator, so operands are parenthesized only
+/// where SQL needs it.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Precedence {
+    Or,
+    And,
+    Atom,
+}
+
+struct Writer<'a> {
+    exporter: &'a SqlExporter,
+    rules: Option<&'a HashMap<&'a str, &'a Rule>>,
+    /// Rules being inlined, outermost first.
+    inlining: Vec<&'a str>,
+}
+
+impl<'a> Writer<'a> {
+    fn expr(&mut self, expr: &'a Expr) -> Result<String, SqlError> {
+        Ok(self.term(expr)?.0)
+    }
+
+    /// The SQL for `expr` and how tightly it binds.
+    fn term(&mut self, expr: &'a Expr) -> Result<(String, Precedence), SqlError> {
+        let atom = |sql| Ok((sql, Precedence::Atom));
+        match expr {
+            Expr::Compare { field, op, value } => atom(format!(
+                "{} {} {}",
+                self.column(field)?,
+                symbol(*op),
+                self.literal(value)?
+            )),
+            Expr::CompareFields { left, op, right } => atom(format!(
+                "{} {} {}",
+                self.column(left)?,
+                symbol(*op),
+                self.column(right)?
+            )),
+            Expr::And(..) => self.chain(expr, Precedence::And),
+            Expr::Or(..) => self.chain(expr, Precedence::Or),
+            Expr::Not(inner) => atom(format!("NOT ({})", self.expr(inner)?)),
+            Expr::In { field, members } => self.membership(field, members, "IN"),
+            Expr::NotIn { field, members } => self.membership(field, members, "NOT IN"),
+            Expr::Between { field, low, high } => atom(format!(
+                "{} BETWEEN {} AND {}",
+                self.column(field)?,
+                self.bound(low)?,
+                self.bound(high)?
+            )),
+            Expr::Like { field, pattern } => atom(self.like(field, pattern, "LIKE")?),
+            Expr::NotLike { field, pattern } => atom(self.like(field, pattern, "NOT LIKE")?),
+            Expr::IsNull(field) => atom(format!("{} IS NULL", self.column(field)?)),
+            Expr::IsNotNull(field) => atom(format!("{} IS NOT NULL", self.column(field)?)),
+            Expr::AtLeast { n, exprs } => self.at_least(*n, exprs),
+            Expr::RuleRef(name) => self.rule_ref(name),
+            Expr::Any { .. } => Err(unsupported("ANY")),
+            Expr::All { .. } => Err(unsupported("ALL")),
+            Expr::Contains { .. } => Err(unsupported("CONTAINS")),
+            Expr::SetCompare { .. } => Err(unsupported("set operators")),
+            Expr::Len { .. } => Err(unsupported("len()")),
+            Expr::HasKey { .. } => Err(unsupported("HAS_KEY")),
+        }
+    }
+
+    /// A run of `AND` (or `OR`) nodes joined flat, with operands that bind
+    /// more loosely parenthesized.
+    fn chain(
+        &mut self,
+        expr: &'a Expr,
+        level: Precedence,
+    ) -> Result<(String, Precedence), SqlError> {
+        let mut operands = Vec::new();
+        self.collect(expr, level, &mut operands)?;
+        let keyword = if level == Precedence::And {
+            " AND "
+        } else {
+            " OR "
+        };
+        Ok((operands.join(keyword), level))
+    }
+
+    fn collect(
+        &mut self,
+        expr: &'a Expr,
+        level: Precedence,
+        out: &mut Vec<String>,
+    ) -> Result<(), SqlError> {
+        match (expr, level) {
+            (Expr::And(left, right), Precedence::And) | (Expr::Or(left, right), Precedence::Or) => {
+                self.collect(left, level, out)?;
+                self.collect(right, level, out)
+            }
+            _ => {
+                let (sql, precedence) = self.term(expr)?;
+                out.push(if precedence < level {
+                    format!("({sql})")
+                } else {
+                    sql
+                });
+                Ok(())
+            }
+        }
+    }
+
+    fn membership(
+        &mut self,
+        field: &str,
+        members: &[Bound],
+        keyword: &str,
+    ) -> Result<(String, Precedence), SqlError> {
+        let column = self.column(field)?;
+        let mut items = Vec::with_capacity(members.len());
+        for member in members {
+            match member {
+                // Membership looks inside list members, so a list constant
+                // contributes its elements.
+                Bound::Literal(Value::List(values)) => {
+                    for value in values {
+                        items.push(self.literal(value)?);
+                    }
+                }
+                member => items.push(self.bound(member)?),
+            }
+        }
+        if items.is_empty() {
+            return Ok((self.boolean(keyword == "NOT IN"), Precedence::Atom));
+        }
+        Ok((
+            format!("{column} {keyword} ({})", items.join(", ")),
+            Precedence::Atom,
+        ))
+    }
+
+    fn like(&self, field: &str, pattern: &str, keyword: &str) -> Result<String, SqlError> {
+        let pattern = match self.exporter.dialect {
+            SqlDialect::Postgres | SqlDialect::MySql => pattern.replace('\\', "\\\\"),
+            SqlDialect::SqlServer => pattern.replace('[', "[[]"),
+            SqlDialect::Ansi | SqlDialect::Sqlite => pattern.to_owned(),
+        };
+        Ok(format!(
+            "{} {keyword} {}",
+            self.column(field)?,
+            self.string(&pattern)
+        ))
+    }
+
+    /// `AT_LEAST(n, ...)` as a comparison that is `NULL` when it is
+    /// unknown: true once `n` operands are true, false once fewer than `n`
+    /// can still be, so that `NOT` of it matches only what three-valued
+    /// evaluation finds definitely true.
+    fn at_least(&mut self, n: usize, exprs: &'a [Expr]) -> Result<(String, Precedence), SqlError> {
+        if n == 0 || n > exprs.len() {
+            return Ok((self.boolean(n == 0), Precedence::Atom));
+        }
+        let operands = exprs
+            .iter()
+            .map(|expr| self.expr(expr))
+            .collect::<Result<Vec<_>, SqlError>>()?;
+        let count = |case: &dyn Fn(&str) -> String| {
+            operands
+                .iter()
+                .map(|sql| case(sql))
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+        let definitely = count(&|sql| format!("CASE WHEN {sql} THEN 1 ELSE 0 END"));
+        let possibly = count(&|sql| format!("CASE WHEN NOT ({sql}) THEN 0 ELSE 1 END"));
+        Ok((
+            format!(
+                "CASE WHEN ({definitely}) >= {n} THEN 1 WHEN ({possibly}) >= {n} THEN NULL \
+                 ELSE 0 END = 1"
+            ),
+            Precedence::Atom,
+        ))
+    }
+
+    fn rule_ref(&mut self, name: &'a str) -> Result<(String, Precedence), SqlError> {
+        let Some(rules) = self.rules else {
+            return Err(SqlError::Unsupported(format!(
+                "the reference to rule '{name}' outside a ruleset"
+            )));
+        };
+        let rule = rules
+            .get(name)
+            .ok_or_else(|| SqlError::UnknownRule(name.to_owned()))?;
+        let condition = match &rule.condition {
+            Some(condition) if rule.metadata.enabled => condition,
+            _ => return Ok((self.boolean(false), Precedence::Atom)),
+        };
+        // Compiled rulesets are acyclic; this only guards the recursion.
+        if self.inlining.contains(&name) {
+            return Err(SqlError::Unsupported(format!(
+                "the cyclic reference to rule '{name}'"
+            )));
+        }
+        self.inlining.push(name);
+        let inlined = self.term(condition);
+        self.inlining.pop();
+        inlined
+    }
+
+    fn column(&self, field: &str) -> Result<String, SqlError> {
+        let column = self
+            .exporter
+            .columns
+            .get(field)
+            .ok_or_else(|| SqlError::UnmappedField(field.to_owned()))?;
+        Ok(column
+            .split('.')
+            .map(|part| self.identifier(part))
+            .collect::<Vec<_>>()
+            .join("."))
+    }
+
+    fn identifier(&self, name: &str) -> String {
+        match self.exporter.dialect {
+            SqlDialect::MySql => format!("`{}`", name.replace('`', "``")),
+            SqlDialect::SqlServer => format!("[{}]", name.replace(']', "]]")),
+            SqlDialect::Ansi | SqlDialect::Postgres | SqlDialect::Sqlite => {
+                format!("\"{}\"", name.replace('"', "\"\""))
+            }
+        }
+    }
+
+    fn bound(&self, bound: &Bound) -> Result<String, SqlError> {
+        match bound {
+            Bound::Literal(value) => self.literal(value),
+            Bound::Field(path) => self.column(path),
+        }
+    }
+
+    fn literal(&self, value: &Value) -> Result<String, SqlError> {
+        match value {
+            Value::Int(i) => Ok(i.to_string()),
+            Value::Float(f) if f.is_finite() => Ok(format!("{f:?}")),
+            Value::Float(f) => Err(SqlError::Unsupported(format!("the float {f}"))),
+            Value::Bool(b) => Ok(match self.exporter.dialect {
+                SqlDialect::SqlServer => u8::from(*b).to_string(),
+                _ => b.to_string().to_uppercase(),
+            }),
+            Value::String(s) => Ok(self.string(s)),
+            Value::List(_) | Value::Map(_) => Err(SqlError::Unsupported(format!(
+                "the collection literal {value}"
+            ))),
+        }
+    }
+
+    fn string(&self, s: &str) -> String {
+        let escaped = s.replace('\'', "''");
+        match self.exporter.dialect {
+            SqlDialect::MySql => format!("'{}'", escaped.replace('\\', "\\\\")),
+            _ => format!("'{escaped}'"),
+        }
+    }
+
+    /// A condition that is always true or always false.
+    fn boolean(&self, value: bool) -> String {
+        match (self.exporter.dialect, value) {
+            (SqlDialect::SqlServer, true) => "1 = 1".to_owned(),
+            (SqlDialect::SqlServer, false) => "1 = 0".to_owned(),
+            (_, true) => "TRUE".to_owned(),
+            (_, false) => "FALSE".to_owned(),
+        }
+    }
+}
+
+fn symbol(op: CompareOp) -> &'static str {
+    match op {
+        CompareOp::Eq => "=",
+        CompareOp::Neq => "<>",
+        CompareOp::Gt => ">",
+        CompareOp::Gte => ">=",
+        CompareOp::Lt => "<",
+        CompareOp::Lte => "<=",
+    }
+}
+
+fn unsupported(construct: &str) -> SqlError {
+    SqlError::Unsupported(construct.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn writer(exporter: &SqlExporter) -> Writer<'_> {
+        Writer {
+            exporter,
+            rules: None,
+            inlining: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn identifiers_are_quoted_per_dialect() {
+        let quoted = |dialect| writer(&SqlExporter::new(dialect)).identifier("a\"b`c]d");
+        assert_eq!(quoted(SqlDialect::Ansi), "\"a\"\"b`c]d\"");
+        assert_eq!(quoted(SqlDialect::MySql), "`a\"b``c]d`");
+        assert_eq!(quoted(SqlDialect::SqlServer), "[a\"b`c]]d]");
+    }
+
+    #[test]
+    fn strings_are_escaped_per_dialect() {
+        let quoted = |dialect| writer(&SqlExporter::new(dialect)).string(r"it's a\b");
+        assert_eq!(quoted(SqlDialect::Postgres), r"'it''s a\b'");
+        assert_eq!(quoted(SqlDialect::MySql), r"'it''s a\\b'");
+    }
+
+    #[test]
+    fn floats_keep_a_decimal_point() {
+        let exporter = SqlExporter::default();
+        let writer = writer(&exporter);
+        assert_eq!(writer.literal(&Value::Float(2.0)).unwrap(), "2.0");
+        assert_eq!(writer.literal(&Value::Float(-0.5)).unwrap(), "-0.5");
+        assert!(writer.literal(&Value::Float(f64::NAN)).is_err());
+    }
+}
@@ -1,6 +1,9 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
+use super::path::{FieldPath, PathStep};
 use super::Value;
+use crate::evaluate::{self, Step};
 
 /// Evaluation context mapping dot-separated field paths to [`Value`]s.
 ///
@@ -36,14 +39,70 @@ impl Context {
         Self::insert_recursive(&mut self.data, &segments, value);
     }
 
-    /// Look up a value by dot-separated path.
-    /// Returns `None` if the path does not exist or points to a nested map.
+    /// Look up a value by field path, as a rule would read it.
+    ///
+    /// A path that continues past a stored [`Value::Map`] resolves to the entry
+    /// within it, so `ctx.set("limits", map).get("limits.US")` finds the `US` key.
+    /// Indexed paths such as `order.items[0].sku`, `limits["US"]` or
+    /// `limits[user.country]` resolve as in rules. A wildcard path like
+    /// `order.items[*].sku` collects a new list, which only
+    /// [`lookup`](Self::lookup) can return; `get` returns `None` for it.
+    /// Returns `None` if the path does not exist or points to an intermediate
+    /// path segment created by [`set`](Self::set).
     #[must_use]
     pub fn get(&self, path: &str) -> Option<&Value> {
+        match self.lookup(path)? {
+            Cow::Borrowed(value) => Some(value),
+            Cow::Owned(_) => None,
+        }
+    }
+
+    /// Look up a value by field path, including wildcard paths, which
+    /// resolve to a list of every value reached.
+    #[must_use]
+    pub fn lookup(&self, path: &str) -> Option<Cow<'_, Value>> {
+        let Ok(parsed) = FieldPath::parse(path) else {
+            return self.get_plain(path).map(Cow::Borrowed);
+        };
+        let base = self.get_plain(parsed.root)?;
+        if !parsed.is_indexed() {
+            return Some(Cow::Borrowed(base));
+        }
+        evaluate::apply_steps(base, &parsed.steps, |step| match step {
+            PathStep::Key(key) => Step::Key(key),
+            PathStep::Dynamic(key_path) => Step::Dynamic(self.get_plain(key_path)),
+            PathStep::Index(index) => Step::Index(*index),
+            PathStep::Wildcard => Step::Wildcard,
+        })
+    }
+
+    /// Look up a value by dot-separated path, without `[...]` steps.
+    fn get_plain(&self, path: &str) -> Option<&Value> {
         let segments: Vec<&str> = path.split('.').collect();
         Self::get_recursive(&self.data, &segments)
     }
 
+    /// Every path holding a value, in no particular order.
+    pub(crate) fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        Self::collect_paths(&self.data, "", &mut paths);
+        paths
+    }
+
+    fn collect_paths(map: &HashMap<String, ContextValue>, prefix: &str, out: &mut Vec<String>) {
+        for (key, entry) in map {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            match entry {
+                ContextValue::Leaf(_) => out.push(path),
+                ContextValue::Nested(nested) => Self::collect_paths(nested, &path, out),
+            }
+        }
+    }
+
     fn insert_recursive(map: &mut HashMap<String, ContextValue>, segments: &[&str], value: Value) {
         match segments {
             [] => {}
@@ -80,7 +139,7 @@ impl Context {
             },
             [first, rest @ ..] => match map.get(*first)? {
                 ContextValue::Nested(nested) => Self::get_recursive(nested, rest),
-                ContextValue::Leaf(_) => None,
+                ContextValue::Leaf(value) => rest.iter().try_fold(value, |v, key| v.get(key)),
             },
         }
     }
@@ -162,6 +221,33 @@ mod tests {
         assert_eq!(ctx.get("anything"), None);
     }
 
+    #[test]
+    fn get_resolves_into_stored_map() {
+        let daily = Value::from(HashMap::from([("daily".to_owned(), Value::Int(50))]));
+        let limits = Value::from(HashMap::from([
+            ("US".to_owned(), Value::Int(100)),
+            ("EU".to_owned(), daily),
+        ]));
+        let ctx = Context::new().set("account.limits", limits.clone());
+
+        assert_eq!(ctx.get("account.limits"), Some(&limits));
+        assert_eq!(ctx.get("account.limits.US"), Some(&Value::Int(100)));
+        assert_eq!(ctx.get("account.limits.EU.daily"), Some(&Value::Int(50)));
+        assert_eq!(ctx.get("account.limits.JP"), None);
+        assert_eq!(ctx.get("account.limits.US.daily"), None);
+    }
+
+    #[test]
+    fn paths_lists_leaves() {
+        let ctx = Context::new()
+            .set("user.age", 25_i64)
+            .set("user.profile.name", "alice")
+            .set("flag", true);
+        let mut paths = ctx.paths();
+        paths.sort();
+        assert_eq!(paths, ["flag", "user.age", "user.profile.name"]);
+    }
+
     #[test]
     fn deeply_nested_path() {
         let ctx = Context::new().set("a.b.c.d.e", 42_i64);
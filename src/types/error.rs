@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use super::schema::FieldType;
+
 /// Errors produced during ruleset compilation.
 #[derive(Debug, Error)]
 pub enum CompileError {
@@ -52,6 +54,69 @@ pub enum CompileError {
         /// The duplicated terminal name.
         terminal: String,
     },
+
+    /// A field path has malformed `[key]` index syntax.
+    #[error("invalid field path '{path}' in rule '{rule}': {reason}")]
+    InvalidFieldPath {
+        /// The rule containing the bad path.
+        rule: String,
+        /// The path as written.
+        path: String,
+        /// What is wrong with the path.
+        reason: String,
+    },
+
+    /// A rule references a field that the schema does not declare.
+    #[error("rule '{rule}' references field '{path}', which is not declared in the schema")]
+    UnknownField {
+        /// The rule containing the reference.
+        rule: String,
+        /// The undeclared path.
+        path: String,
+    },
+
+    /// A field is used in a way its declared schema type does not support.
+    #[error("type mismatch in rule '{rule}': field '{path}' is {field_type} and {reason}")]
+    TypeMismatch {
+        /// The rule containing the bad comparison.
+        rule: String,
+        /// The declared field path.
+        path: String,
+        /// The type declared for the field.
+        field_type: FieldType,
+        /// What the field is used with.
+        reason: String,
+    },
+
+    /// A DSL `param` has neither a supplied value nor a default.
+    #[error("parameter '{name}' has no value and no default")]
+    MissingParam {
+        /// The parameter name.
+        name: String,
+    },
+
+    /// A value was supplied for a parameter the DSL does not declare.
+    #[error("parameter '{name}' is not declared")]
+    UndeclaredParam {
+        /// The supplied parameter name.
+        name: String,
+    },
+
+    /// A DSL `test` expects a verdict from a rule that is not a terminal.
+    #[error("test '{test}' expects '{terminal}', which is not a terminal rule")]
+    UndefinedTestTerminal {
+        /// The test's name.
+        test: String,
+        /// The expected name with no matching terminal.
+        terminal: String,
+    },
+
+    /// Two DSL `test` blocks share the same name.
+    #[error("duplicate test name '{name}'")]
+    DuplicateTest {
+        /// The duplicated test name.
+        name: String,
+    },
 }
 
 #[cfg(test)]
@@ -127,4 +192,62 @@ mod tests {
             "duplicate terminal 'can_proceed'; each rule may only be registered as a terminal once"
         );
     }
+
+    #[test]
+    fn invalid_field_path_message() {
+        let err = CompileError::InvalidFieldPath {
+            rule: "r".into(),
+            path: "limits[".into(),
+            reason: "missing ']' after index".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "invalid field path 'limits[' in rule 'r': missing ']' after index"
+        );
+    }
+
+    #[test]
+    fn unknown_field_message() {
+        let err = CompileError::UnknownField {
+            rule: "r".into(),
+            path: "user.agee".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "rule 'r' references field 'user.agee', which is not declared in the schema"
+        );
+    }
+
+    #[test]
+    fn type_mismatch_message() {
+        let err = CompileError::TypeMismatch {
+            rule: "r".into(),
+            path: "user.age".into(),
+            field_type: FieldType::Int,
+            reason: "cannot be compared with string".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "type mismatch in rule 'r': field 'user.age' is int and cannot be compared with string"
+        );
+    }
+
+    #[test]
+    fn missing_param_message() {
+        let err = CompileError::MissingParam {
+            name: "MAX_AMOUNT".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "parameter 'MAX_AMOUNT' has no value and no default"
+        );
+    }
+
+    #[test]
+    fn undeclared_param_message() {
+        let err = CompileError::UndeclaredParam {
+            name: "MAX_AMOUT".into(),
+        };
+        assert_eq!(err.to_string(), "parameter 'MAX_AMOUT' is not declared");
+    }
 }
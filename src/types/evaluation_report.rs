@@ -1,19 +1,22 @@
 use std::fmt;
 use std::time::Duration;
 
+use super::overrides::RuleOverride;
 use super::verdict::Verdict;
 
 /// Detailed evaluation report returned by
 /// [`RuleSet::evaluate_detailed()`](super::ruleset::RuleSet::evaluate_detailed).
 ///
 /// Contains the verdict, which rules evaluated to `true`, the
-/// evaluation order, and the wall-clock duration of the evaluation.
+/// evaluation order, any runtime overrides that applied, and the wall-clock
+/// duration of the evaluation.
 #[derive(Debug, Clone)]
 #[must_use]
 pub struct EvaluationReport {
     verdict: Option<Verdict>,
     evaluated: Vec<String>,
     evaluation_order: Vec<String>,
+    overridden: Vec<(String, RuleOverride)>,
     duration: Duration,
 }
 
@@ -22,12 +25,14 @@ impl EvaluationReport {
         verdict: Option<Verdict>,
         evaluated: Vec<String>,
         evaluation_order: Vec<String>,
+        overridden: Vec<(String, RuleOverride)>,
         duration: Duration,
     ) -> Self {
         Self {
             verdict,
             evaluated,
             evaluation_order,
+            overridden,
             duration,
         }
     }
@@ -50,6 +55,13 @@ impl EvaluationReport {
         &self.evaluation_order
     }
 
+    /// Rules whose result came from a [`RuleOverride`] rather than their
+    /// condition, in evaluation order.
+    #[must_use]
+    pub fn overridden(&self) -> &[(String, RuleOverride)] {
+        &self.overridden
+    }
+
     /// Wall-clock duration of the evaluation.
     #[must_use]
     pub fn duration(&self) -> Duration {
@@ -64,6 +76,14 @@ impl fmt::Display for EvaluationReport {
             None => write!(f, "verdict: none")?,
         }
         write!(f, ", evaluated: [{}]", self.evaluated.join(", "))?;
+        if !self.overridden.is_empty() {
+            let overridden: Vec<_> = self
+                .overridden
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect();
+            write!(f, ", overridden: [{}]", overridden.join(", "))?;
+        }
         write!(f, ", duration: {:?}", self.duration)?;
         Ok(())
     }
@@ -79,12 +99,17 @@ mod tests {
             Some(Verdict::new("allow", true)),
             vec!["r1".into(), "r2".into()],
             vec!["r1".into(), "r2".into(), "r3".into()],
+            vec![("r3".into(), RuleOverride::ForceFalse)],
             Duration::from_nanos(500),
         );
 
         assert_eq!(report.verdict(), Some(&Verdict::new("allow", true)));
         assert_eq!(report.evaluated(), &["r1", "r2"]);
         assert_eq!(report.evaluation_order(), &["r1", "r2", "r3"]);
+        assert_eq!(
+            report.overridden(),
+            &[("r3".to_owned(), RuleOverride::ForceFalse)]
+        );
         assert_eq!(report.duration(), Duration::from_nanos(500));
     }
 
@@ -94,18 +119,26 @@ mod tests {
             Some(Verdict::new("allow", true)),
             vec!["r1".into(), "r2".into()],
             vec!["r1".into(), "r2".into()],
+            vec![("r2".into(), RuleOverride::ForceTrue)],
             Duration::from_nanos(500),
         );
         let s = report.to_string();
         assert!(s.contains("verdict: allow = true"));
         assert!(s.contains("evaluated: [r1, r2]"));
+        assert!(s.contains("overridden: [r2=force_true]"));
     }
 
     #[test]
     fn report_display_no_verdict() {
-        let report =
-            EvaluationReport::new(None, vec![], vec!["r1".into()], Duration::from_nanos(100));
+        let report = EvaluationReport::new(
+            None,
+            vec![],
+            vec!["r1".into()],
+            vec![],
+            Duration::from_nanos(100),
+        );
         let s = report.to_string();
         assert!(s.contains("verdict: none"));
+        assert!(!s.contains("overridden"));
     }
 }
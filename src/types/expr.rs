@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fmt;
 use std::ops::Not;
 
@@ -8,6 +9,11 @@ use super::Value;
 /// Each bound is independently either a literal scalar value or a reference to
 /// a context field whose value is resolved at evaluation time.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Bound {
     /// A static scalar value.
     Literal(Value),
@@ -60,6 +66,12 @@ impl From<String> for Bound {
     }
 }
 
+impl From<Vec<Value>> for Bound {
+    fn from(v: Vec<Value>) -> Self {
+        Bound::Literal(Value::List(v))
+    }
+}
+
 /// Create a [`Bound`] that references a context field by path.
 ///
 /// Use this alongside literal values when constructing range or membership
@@ -84,8 +96,33 @@ pub(crate) enum CompiledBound {
     FieldIndex(usize),
 }
 
+/// A compiled `[...]` lookup: the value reached by applying `steps` to the
+/// value at slot `base` is written to the local `slot`. A lookup containing a
+/// [`CompiledStep::Wildcard`] writes a list of every value reached.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CompiledLookup {
+    pub(crate) slot: usize,
+    pub(crate) base: usize,
+    pub(crate) steps: Vec<CompiledStep>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CompiledStep {
+    Key(String),
+    /// Map key (string) or list position (integer) read from the value at the
+    /// given slot.
+    Dynamic(usize),
+    Index(i64),
+    Wildcard,
+}
+
 /// Comparison operators supported in rule expressions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum CompareOp {
     /// Equal (`==`).
     Eq,
@@ -101,9 +138,35 @@ pub enum CompareOp {
     Lte,
 }
 
+/// Set relations between two list values, used by [`Expr::SetCompare`].
+///
+/// Elements are matched with the same equality semantics as [`Value::contains`],
+/// so duplicates are ignored and `1` matches `1.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum SetOp {
+    /// At least one element is shared (`OVERLAPS`).
+    Overlaps,
+    /// Every element of the left list is in the right list (`SUBSET_OF`).
+    SubsetOf,
+    /// Every element of the right list is in the left list (`SUPERSET_OF`).
+    SupersetOf,
+    /// No element is shared (`DISJOINT_FROM`).
+    DisjointFrom,
+}
+
 /// User-facing expression AST. Field paths and rule names are strings.
 /// Transformed into a compiled representation during compilation.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Expr {
     /// A field comparison (e.g., `user.age >= 18`).
     Compare {
@@ -184,11 +247,76 @@ pub enum Expr {
         /// The set of expressions to evaluate.
         exprs: Vec<Expr>,
     },
+    /// True when the list field holds at least one element satisfying `predicate`.
+    ///
+    /// Inside `predicate`, the path `var` refers to the current element and
+    /// `var.sub.path` to a path within it. All other paths resolve against
+    /// the context as usual. A missing or non-list field evaluates to `false`.
+    Any {
+        /// Dot-separated path of the list-valued field.
+        list: String,
+        /// Name the current element is bound to within `predicate`.
+        var: String,
+        /// Condition tested against each element.
+        predicate: Box<Expr>,
+    },
+    /// True when every element of the list field satisfies `predicate`.
+    ///
+    /// Element binding works as for [`Expr::Any`]. An empty list is vacuously
+    /// `true`; a missing or non-list field evaluates to `false`.
+    All {
+        /// Dot-separated path of the list-valued field.
+        list: String,
+        /// Name the current element is bound to within `predicate`.
+        var: String,
+        /// Condition tested against each element.
+        predicate: Box<Expr>,
+    },
+    /// True when the list field contains the given value (`roles CONTAINS "admin"`).
+    /// Elements are matched with the same equality semantics as [`Value::contains`].
+    Contains {
+        /// Dot-separated path of the list-valued field.
+        field: String,
+        /// The value to look for, either a literal or a context field reference.
+        value: Bound,
+    },
+    /// Set relation between two lists (e.g., `user.perms SUPERSET_OF resource.required`).
+    /// False unless both sides resolve to lists.
+    SetCompare {
+        /// Dot-separated path of the left-hand list field.
+        field: String,
+        /// The set relation.
+        op: SetOp,
+        /// The right-hand list, either a literal or a context field reference.
+        value: Bound,
+    },
+    /// Compares the number of elements in a list or map field (e.g., `len(user.roles) >= 2`).
+    /// False when the field is missing or neither a list nor a map.
+    Len {
+        /// Dot-separated path of the list-valued field.
+        field: String,
+        /// The comparison operator.
+        op: CompareOp,
+        /// The count to compare against, either a literal or a context field reference.
+        value: Bound,
+    },
+    /// True when the map field has an entry for the given key (`limits HAS_KEY user.country`).
+    /// False when the field is not a map or the key does not resolve to a string.
+    HasKey {
+        /// Dot-separated path of the map-valued field.
+        field: String,
+        /// The key, either a string literal or a context field reference.
+        key: Bound,
+    },
 }
 
 /// Compiled expression with all string lookups resolved to integer indices.
 /// Field paths are resolved via the [`FieldRegistry`](super::FieldRegistry) and rule
 /// references are resolved to their topological sort index.
+///
+/// Field indices at or beyond the registry's length are local slots holding
+/// the element bindings of an enclosing `Any`/`All` quantifier or the result
+/// of an enclosing `Lookup`.
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum CompiledExpr {
     Compare {
@@ -232,6 +360,57 @@ pub(crate) enum CompiledExpr {
         n: usize,
         exprs: Vec<CompiledExpr>,
     },
+    /// Quantifier over a list. Each binding is a `(slot, path)` pair: before
+    /// `predicate` is evaluated for an element, the value at `path` within the
+    /// element is written to the local `slot`.
+    Any {
+        list_index: usize,
+        bindings: Vec<(usize, Vec<String>)>,
+        predicate: Box<CompiledExpr>,
+    },
+    All {
+        list_index: usize,
+        bindings: Vec<(usize, Vec<String>)>,
+        predicate: Box<CompiledExpr>,
+    },
+    Contains {
+        field_index: usize,
+        value: CompiledBound,
+    },
+    SetCompare {
+        field_index: usize,
+        op: SetOp,
+        value: CompiledBound,
+    },
+    Len {
+        field_index: usize,
+        op: CompareOp,
+        value: CompiledBound,
+    },
+    HasKey {
+        field_index: usize,
+        key: CompiledBound,
+    },
+    /// Resolves the `[key]` lookups used by `body` into local slots, then
+    /// evaluates `body`.
+    Lookup {
+        lookups: Vec<CompiledLookup>,
+        body: Box<CompiledExpr>,
+    },
+}
+
+impl CompareOp {
+    /// Whether the operator holds for operands ordered as `ord`.
+    pub(crate) fn holds(self, ord: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ord == Ordering::Equal,
+            CompareOp::Neq => ord != Ordering::Equal,
+            CompareOp::Gt => ord == Ordering::Greater,
+            CompareOp::Gte => ord != Ordering::Less,
+            CompareOp::Lt => ord == Ordering::Less,
+            CompareOp::Lte => ord != Ordering::Greater,
+        }
+    }
 }
 
 impl fmt::Display for CompareOp {
@@ -247,6 +426,17 @@ impl fmt::Display for CompareOp {
     }
 }
 
+impl fmt::Display for SetOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetOp::Overlaps => write!(f, "OVERLAPS"),
+            SetOp::SubsetOf => write!(f, "SUBSET_OF"),
+            SetOp::SupersetOf => write!(f, "SUPERSET_OF"),
+            SetOp::DisjointFrom => write!(f, "DISJOINT_FROM"),
+        }
+    }
+}
+
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -275,6 +465,20 @@ impl fmt::Display for Expr {
                 let parts: Vec<String> = exprs.iter().map(ToString::to_string).collect();
                 write!(f, "AT_LEAST({n}, {})", parts.join(", "))
             }
+            Expr::Any {
+                list,
+                var,
+                predicate,
+            } => write!(f, "ANY({list} AS {var}, {predicate})"),
+            Expr::All {
+                list,
+                var,
+                predicate,
+            } => write!(f, "ALL({list} AS {var}, {predicate})"),
+            Expr::Contains { field, value } => write!(f, "({field} CONTAINS {value})"),
+            Expr::SetCompare { field, op, value } => write!(f, "({field} {op} {value})"),
+            Expr::Len { field, op, value } => write!(f, "(len({field}) {op} {value})"),
+            Expr::HasKey { field, key } => write!(f, "({field} HAS_KEY {key})"),
         }
     }
 }
@@ -291,6 +495,42 @@ impl Expr {
     pub fn or(self, other: Expr) -> Expr {
         Expr::Or(Box::new(self), Box::new(other))
     }
+
+    /// Format as DSL with the fewest parentheses needed to parse back to the
+    /// same expression. Unlike `Display`, which parenthesizes every
+    /// operation, the result can be pasted into a rule body. Names that are
+    /// not plain identifiers, or are spelled like a keyword, are written
+    /// between backticks.
+    ///
+    /// ```
+    /// use ooroo::{field, rule_ref, Expr};
+    ///
+    /// let expr = rule_ref("a").or(field("x").gt(1_i64).and(!rule_ref("my rule")));
+    /// let dsl = expr.to_dsl().unwrap();
+    /// assert_eq!(dsl, "a OR x > 1 AND NOT `my rule`");
+    /// assert_eq!(Expr::from_dsl(&dsl).unwrap(), expr);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ToDslError`](crate::ToDslError) if the expression holds a
+    /// value with no DSL literal, such as a map or a NaN, or a name that
+    /// cannot be quoted.
+    pub fn to_dsl(&self) -> Result<String, crate::ToDslError> {
+        crate::parse::expr_to_dsl(self)
+    }
+
+    /// Parse a single DSL expression, such as a rule body. Names that could
+    /// be rule references or constants are read as rule references, as in a
+    /// rule body before constants are substituted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError`](crate::ParseError) if the input is not a valid
+    /// expression.
+    pub fn from_dsl(input: &str) -> Result<Expr, crate::ParseError> {
+        crate::parse::parse_expr(input)
+    }
 }
 
 impl Not for Expr {
@@ -460,6 +700,105 @@ impl FieldExpr {
         Expr::IsNotNull(self.path)
     }
 
+    /// Build a `CONTAINS` test: true when this list-valued field contains `value`.
+    ///
+    /// `value` accepts any literal (via `Into<Value>`) or a field reference
+    /// created with [`bound_field`].
+    #[must_use]
+    pub fn contains(self, value: impl Into<Bound>) -> Expr {
+        Expr::Contains {
+            field: self.path,
+            value: value.into(),
+        }
+    }
+
+    /// Build an `OVERLAPS` test: true when this list field shares at least one
+    /// element with `other`.
+    ///
+    /// `other` accepts a list literal (`Vec<Value>`) or a field reference
+    /// created with [`bound_field`].
+    #[must_use]
+    pub fn overlaps(self, other: impl Into<Bound>) -> Expr {
+        self.set_compare(SetOp::Overlaps, other.into())
+    }
+
+    /// Build a `SUBSET_OF` test: true when every element of this list field
+    /// is in `other`.
+    #[must_use]
+    pub fn subset_of(self, other: impl Into<Bound>) -> Expr {
+        self.set_compare(SetOp::SubsetOf, other.into())
+    }
+
+    /// Build a `SUPERSET_OF` test: true when every element of `other` is in
+    /// this list field.
+    #[must_use]
+    pub fn superset_of(self, other: impl Into<Bound>) -> Expr {
+        self.set_compare(SetOp::SupersetOf, other.into())
+    }
+
+    /// Build a `DISJOINT_FROM` test: true when this list field shares no
+    /// element with `other`.
+    #[must_use]
+    pub fn disjoint_from(self, other: impl Into<Bound>) -> Expr {
+        self.set_compare(SetOp::DisjointFrom, other.into())
+    }
+
+    fn set_compare(self, op: SetOp, value: Bound) -> Expr {
+        Expr::SetCompare {
+            field: self.path,
+            op,
+            value,
+        }
+    }
+
+    /// Build a `HAS_KEY` test: true when this map-valued field has an entry for `key`.
+    ///
+    /// `key` accepts a string literal or a field reference created with
+    /// [`bound_field`].
+    #[must_use]
+    pub fn has_key(self, key: impl Into<Bound>) -> Expr {
+        Expr::HasKey {
+            field: self.path,
+            key: key.into(),
+        }
+    }
+
+    /// Index into this map-valued field with a literal key (`limits["US"]`).
+    ///
+    /// The key is quoted, so it may contain dots or other punctuation.
+    #[must_use]
+    pub fn key(mut self, key: &str) -> FieldExpr {
+        self.path.push_str(&super::path::quote_key(key));
+        self
+    }
+
+    /// Index into this map-valued field with a key read from another field
+    /// at evaluation time (`limits[user.country]`).
+    #[must_use]
+    pub fn key_field(mut self, key_path: &str) -> FieldExpr {
+        self.path.push('[');
+        self.path.push_str(key_path);
+        self.path.push(']');
+        self
+    }
+
+    /// Index into this list-valued field by position (`items[0]`). Negative
+    /// positions count from the end, so `-1` is the last element.
+    #[must_use]
+    pub fn index(mut self, index: i64) -> FieldExpr {
+        self.path.push_str(&format!("[{index}]"));
+        self
+    }
+
+    /// Fan out over every element of this list-valued field, or every value of
+    /// a map (`items[*]`). Later steps apply to each element, and the path
+    /// resolves to a `Value::List` of the results.
+    #[must_use]
+    pub fn wildcard(mut self) -> FieldExpr {
+        self.path.push_str("[*]");
+        self
+    }
+
     /// Build a field-to-field equality comparison (`left == right`).
     #[must_use]
     pub fn eq_field(self, right: &str) -> Expr {
@@ -522,6 +861,15 @@ impl FieldExpr {
 }
 
 /// Create a [`FieldExpr`] for building field comparison expressions.
+///
+/// Besides dotted paths, `path` may index into map and list values with
+/// `[...]`: `limits["US"]` uses a literal key, `limits[user.country]` reads the
+/// key (or list position) from another field at evaluation time, `items[0]`
+/// picks a list element (`items[-1]` the last), and `items[*]` fans out over
+/// every element. Dotted segments may follow an index
+/// (`order.items[*].sku`). A path containing `[*]` resolves to a
+/// `Value::List` of every value it reaches, so it works with `IN`,
+/// quantifiers and set operators.
 #[must_use]
 pub fn field(path: &str) -> FieldExpr {
     FieldExpr {
@@ -529,6 +877,84 @@ pub fn field(path: &str) -> FieldExpr {
     }
 }
 
+/// Intermediate builder for list length comparisons.
+/// Created by [`len()`]; requires a comparison method to produce a valid [`Expr`].
+///
+/// Each method accepts an integer literal or a field reference created with
+/// [`bound_field`].
+#[derive(Debug, Clone)]
+pub struct LenExpr {
+    path: String,
+}
+
+impl LenExpr {
+    /// Build a length equality comparison (`len(list) == n`).
+    #[must_use]
+    pub fn eq(self, n: impl Into<Bound>) -> Expr {
+        self.compare(CompareOp::Eq, n.into())
+    }
+
+    /// Build a length not-equal comparison (`len(list) != n`).
+    #[must_use]
+    pub fn neq(self, n: impl Into<Bound>) -> Expr {
+        self.compare(CompareOp::Neq, n.into())
+    }
+
+    /// Build a length greater-than comparison (`len(list) > n`).
+    #[must_use]
+    pub fn gt(self, n: impl Into<Bound>) -> Expr {
+        self.compare(CompareOp::Gt, n.into())
+    }
+
+    /// Build a length greater-than-or-equal comparison (`len(list) >= n`).
+    #[must_use]
+    pub fn gte(self, n: impl Into<Bound>) -> Expr {
+        self.compare(CompareOp::Gte, n.into())
+    }
+
+    /// Build a length less-than comparison (`len(list) < n`).
+    #[must_use]
+    pub fn lt(self, n: impl Into<Bound>) -> Expr {
+        self.compare(CompareOp::Lt, n.into())
+    }
+
+    /// Build a length less-than-or-equal comparison (`len(list) <= n`).
+    #[must_use]
+    pub fn lte(self, n: impl Into<Bound>) -> Expr {
+        self.compare(CompareOp::Lte, n.into())
+    }
+
+    fn compare(self, op: CompareOp, value: Bound) -> Expr {
+        Expr::Len {
+            field: self.path,
+            op,
+            value,
+        }
+    }
+}
+
+/// Create a [`LenExpr`] for comparing the number of elements in a list field.
+///
+/// # Example
+/// ```
+/// use ooroo::{len, Context, RuleSetBuilder, Value};
+///
+/// let ruleset = RuleSetBuilder::new()
+///     .rule("multi_role", |r| r.when(len("user.roles").gte(2_i64)))
+///     .terminal("multi_role", 0)
+///     .compile()
+///     .unwrap();
+///
+/// let ctx = Context::new().set("user.roles", vec![Value::from("admin"), Value::from("editor")]);
+/// assert!(ruleset.evaluate(&ctx).is_some());
+/// ```
+#[must_use]
+pub fn len(path: &str) -> LenExpr {
+    LenExpr {
+        path: path.to_owned(),
+    }
+}
+
 /// Create an [`Expr`] that references another rule by name.
 #[must_use]
 pub fn rule_ref(name: &str) -> Expr {
@@ -564,6 +990,48 @@ pub fn at_least(n: usize, exprs: impl IntoIterator<Item = Expr>) -> Expr {
     }
 }
 
+/// Create an [`Expr::Any`] that is true when at least one element of the list
+/// field `list` satisfies `predicate`.
+///
+/// Within `predicate`, `var` names the current element; `var.x` addresses a
+/// path inside it.
+///
+/// # Example
+/// ```
+/// use ooroo::{any, field, Context, RuleSetBuilder, Value};
+///
+/// let ruleset = RuleSetBuilder::new()
+///     .rule("big_order", |r| r.when(any("order.amounts", "amount", field("amount").gt(100_i64))))
+///     .terminal("big_order", 0)
+///     .compile()
+///     .unwrap();
+///
+/// let ctx = Context::new().set("order.amounts", vec![Value::Int(20), Value::Int(150)]);
+/// assert!(ruleset.evaluate(&ctx).is_some());
+/// ```
+#[must_use]
+pub fn any(list: &str, var: &str, predicate: Expr) -> Expr {
+    Expr::Any {
+        list: list.to_owned(),
+        var: var.to_owned(),
+        predicate: Box::new(predicate),
+    }
+}
+
+/// Create an [`Expr::All`] that is true when every element of the list field
+/// `list` satisfies `predicate`.
+///
+/// Element binding works as for [`any`]. An empty list satisfies `all`
+/// vacuously; a missing or non-list field does not.
+#[must_use]
+pub fn all(list: &str, var: &str, predicate: Expr) -> Expr {
+    Expr::All {
+        list: list.to_owned(),
+        var: var.to_owned(),
+        predicate: Box::new(predicate),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -787,6 +1255,73 @@ mod tests {
         assert_eq!(expr, Expr::IsNotNull("middle_name".to_owned()));
     }
 
+    #[test]
+    fn any_binds_element_var() {
+        let expr = any("order.items", "item", field("item.price").gt(100_i64));
+        assert_eq!(
+            expr,
+            Expr::Any {
+                list: "order.items".to_owned(),
+                var: "item".to_owned(),
+                predicate: Box::new(field("item.price").gt(100_i64)),
+            }
+        );
+        assert_eq!(
+            expr.to_string(),
+            "ANY(order.items AS item, (item.price > 100))"
+        );
+    }
+
+    #[test]
+    fn field_contains() {
+        let expr = field("user.roles").contains("admin");
+        assert_eq!(
+            expr,
+            Expr::Contains {
+                field: "user.roles".to_owned(),
+                value: Bound::Literal(Value::String("admin".to_owned())),
+            }
+        );
+    }
+
+    #[test]
+    fn field_set_ops() {
+        let ops = vec![
+            (field("f").overlaps(bound_field("g")), SetOp::Overlaps),
+            (field("f").subset_of(bound_field("g")), SetOp::SubsetOf),
+            (field("f").superset_of(bound_field("g")), SetOp::SupersetOf),
+            (
+                field("f").disjoint_from(bound_field("g")),
+                SetOp::DisjointFrom,
+            ),
+        ];
+        for (expr, expected_op) in ops {
+            match expr {
+                Expr::SetCompare { op, value, .. } => {
+                    assert_eq!(op, expected_op);
+                    assert_eq!(value, Bound::Field("g".to_owned()));
+                }
+                _ => panic!("expected SetCompare"),
+            }
+        }
+        let expr = field("perms").superset_of(vec![Value::from("read")]);
+        assert_eq!(expr.to_string(), "(perms SUPERSET_OF [\"read\"])");
+    }
+
+    #[test]
+    fn len_compare() {
+        let expr = len("user.roles").gte(2_i64);
+        assert_eq!(
+            expr,
+            Expr::Len {
+                field: "user.roles".to_owned(),
+                op: CompareOp::Gte,
+                value: Bound::Literal(Value::Int(2)),
+            }
+        );
+        assert_eq!(expr.to_string(), "(len(user.roles) >= 2)");
+    }
+
     #[test]
     fn all_compare_ops() {
         let ops = vec![
@@ -1,4 +1,6 @@
 use super::field_registry::FieldRegistry;
+use super::ruleset::RuleSet;
+use super::validation::{self, ValidationReport};
 use super::value::Value;
 
 /// A pre-indexed context for fast evaluation. Values are stored in a flat `Vec`
@@ -30,36 +32,116 @@ impl IndexedContext {
 /// [`RuleSet::context_builder()`](super::ruleset::RuleSet::context_builder).
 ///
 /// Field paths are resolved to integer indices using the compiled ruleset's
-/// field registry. Unknown fields (not referenced by any rule) are silently ignored.
+/// field registry. Unknown fields (not referenced by any rule) are silently
+/// ignored, unless the builder is [`strict`](Self::strict).
 #[derive(Debug)]
 pub struct ContextBuilder<'a> {
-    registry: &'a FieldRegistry,
+    ruleset: &'a RuleSet,
     values: Vec<Option<Value>>,
+    /// Unknown paths that were set, recorded only in strict mode.
+    unknown: Option<Vec<String>>,
 }
 
 impl<'a> ContextBuilder<'a> {
-    pub(crate) fn new(registry: &'a FieldRegistry) -> Self {
+    pub(crate) fn new(ruleset: &'a RuleSet) -> Self {
         Self {
-            registry,
-            values: vec![None; registry.len()],
+            ruleset,
+            values: vec![None; ruleset.field_registry.len()],
+            unknown: None,
         }
     }
 
+    /// Record paths that no rule references instead of silently ignoring
+    /// them, so they show up in [`report()`](Self::report) and make
+    /// [`try_build()`](Self::try_build) fail.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ooroo::{field, RuleSetBuilder};
+    ///
+    /// let ruleset = RuleSetBuilder::new()
+    ///     .rule("adult", |r| r.when(field("user.age").gte(18_i64)))
+    ///     .terminal("adult", 0)
+    ///     .compile()
+    ///     .unwrap();
+    ///
+    /// let report = ruleset
+    ///     .context_builder()
+    ///     .strict()
+    ///     .set("user.agee", 30_i64)
+    ///     .try_build()
+    ///     .unwrap_err();
+    /// assert_eq!(report.unknown(), ["user.agee"]);
+    /// assert_eq!(report.missing(), ["user.age"]);
+    /// ```
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.unknown.get_or_insert_with(Vec::new);
+        self
+    }
+
     /// Set a field value by path. If the path is not referenced by any rule
-    /// in the compiled ruleset, the value is silently ignored.
+    /// in the compiled ruleset, the value is ignored (and, in strict mode, recorded
+    /// as unknown).
+    ///
+    /// Setting a [`Value::Map`] also fills any referenced paths that continue
+    /// into it, matching how [`Context::get`](super::Context::get) resolves them.
     #[must_use]
     pub fn set(mut self, path: &str, value: impl Into<Value>) -> Self {
-        if let Some(idx) = self.registry.get(path) {
-            self.values[idx] = Some(value.into());
-        }
+        self.insert(path, value);
         self
     }
 
     /// Set a field value by path (mutable reference version).
     pub fn insert(&mut self, path: &str, value: impl Into<Value>) {
-        if let Some(idx) = self.registry.get(path) {
-            self.values[idx] = Some(value.into());
+        let value = value.into();
+        if let Value::Map(_) = value {
+            let prefix = format!("{path}.");
+            for (registered, &idx) in self.registry().iter() {
+                if let Some(inner) = registered.strip_prefix(&prefix) {
+                    self.values[idx] = value.get_path(inner).cloned();
+                }
+            }
+        }
+        if let Some(unknown) = &mut self.unknown {
+            if !validation::is_known_path(self.ruleset, path) {
+                unknown.push(path.to_owned());
+            }
         }
+        if let Some(idx) = self.registry().get(path) {
+            self.values[idx] = Some(value);
+        }
+    }
+
+    fn registry(&self) -> &'a FieldRegistry {
+        &self.ruleset.field_registry
+    }
+
+    /// Check the values set so far: unknown paths (in strict mode), fields
+    /// the rules reference that have not been set, and values of the wrong
+    /// type.
+    pub fn report(&self) -> ValidationReport {
+        validation::report(
+            self.ruleset,
+            &self.values,
+            self.unknown.clone().unwrap_or_default(),
+        )
+    }
+
+    /// Build the indexed context, failing if [`report()`](Self::report) has
+    /// errors: an unknown path was set in strict mode, or a value has the
+    /// wrong type. Missing fields alone do not fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`ValidationReport`] if it has errors.
+    pub fn try_build(self) -> Result<IndexedContext, ValidationReport> {
+        let report = self.report();
+        if report.has_errors() {
+            return Err(report);
+        }
+        Ok(self.build())
     }
 
     /// Build the indexed context.
@@ -73,7 +155,9 @@ impl<'a> ContextBuilder<'a> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{field, RuleSetBuilder};
+    use std::collections::HashMap;
+
+    use crate::{field, RuleSetBuilder, Value};
 
     #[test]
     fn context_builder_sets_known_fields() {
@@ -105,6 +189,20 @@ mod tests {
         assert_eq!(ctx.values().len(), 1);
     }
 
+    #[test]
+    fn context_builder_fills_paths_inside_map() {
+        let ruleset = RuleSetBuilder::new()
+            .rule("r", |r| r.when(field("limits.US").gte(100_i64)))
+            .terminal("r", 0)
+            .compile()
+            .unwrap();
+
+        let limits = Value::from(HashMap::from([("US".to_owned(), Value::Int(100))]));
+        let ctx = ruleset.context_builder().set("limits", limits).build();
+        assert_eq!(ctx.get(0), Some(&Value::Int(100)));
+        assert!(ruleset.evaluate_indexed(&ctx).is_some());
+    }
+
     #[test]
     fn context_builder_insert_mutable() {
         let ruleset = RuleSetBuilder::new()
@@ -4,8 +4,14 @@ pub(crate) mod evaluation_report;
 mod expr;
 mod field_registry;
 mod indexed_context;
+mod overrides;
+pub(crate) mod path;
 mod rule;
+mod rule_test;
 mod ruleset;
+pub(crate) mod schema;
+mod truth;
+pub(crate) mod validation;
 pub(crate) mod value;
 mod verdict;
 
@@ -14,11 +20,20 @@ pub use error::CompileError;
 pub use evaluation_report::EvaluationReport;
 pub(crate) use expr::CompiledBound;
 pub(crate) use expr::CompiledExpr;
-pub use expr::{at_least, bound_field, field, rule_ref, Bound, CompareOp, Expr, FieldExpr};
+pub use expr::{
+    all, any, at_least, bound_field, field, len, rule_ref, Bound, CompareOp, Expr, FieldExpr,
+    LenExpr, SetOp,
+};
+pub(crate) use expr::{CompiledLookup, CompiledStep};
 pub use field_registry::FieldRegistry;
 pub use indexed_context::{ContextBuilder, IndexedContext};
+pub use overrides::{OverrideError, RuleOverride, RuleOverrides};
 pub(crate) use rule::CompiledRule;
-pub use rule::{Rule, Terminal};
+pub use rule::{Rule, RuleMetadata, Terminal, UnknownPolicy};
+pub use rule_test::{RuleTest, TestOutcome, TestReport};
 pub use ruleset::{RuleSet, RuleSetBuilder};
+pub use schema::{FieldType, Schema, SchemaViolation};
+pub use truth::Truth;
+pub use validation::ValidationReport;
 pub use value::Value;
 pub use verdict::Verdict;
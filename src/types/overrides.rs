@@ -0,0 +1,240 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use thiserror::Error;
+
+use super::ruleset::RuleSet;
+
+/// A runtime override for one rule, applied by
+/// [`RuleSet::evaluate_with_overrides()`](super::ruleset::RuleSet::evaluate_with_overrides)
+/// and its siblings without recompiling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuleOverride {
+    /// The rule is `true` without evaluating its condition.
+    ForceTrue,
+    /// The rule is `false` without evaluating its condition.
+    ForceFalse,
+    /// The rule is switched off: it is `false`, and as a terminal it is
+    /// skipped.
+    Disable,
+    /// The rule is evaluated even if its metadata marks it disabled.
+    Enable,
+}
+
+impl fmt::Display for RuleOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ForceTrue => "force_true",
+            Self::ForceFalse => "force_false",
+            Self::Disable => "disable",
+            Self::Enable => "enable",
+        })
+    }
+}
+
+/// Errors produced when editing [`RuleOverrides`].
+#[derive(Debug, Error)]
+pub enum OverrideError {
+    /// The named rule does not exist in the ruleset.
+    #[error("cannot override unknown rule '{name}'")]
+    UnknownRule {
+        /// The rule name that was not found.
+        name: String,
+    },
+}
+
+/// A set of [`RuleOverride`]s layered over a compiled [`RuleSet`] at
+/// evaluation time.
+///
+/// Created empty by [`RuleSet::overrides()`](super::ruleset::RuleSet::overrides)
+/// and keyed by the ruleset's rule indices, so applying it costs one slice
+/// lookup per rule. The overrides remember each rule's name: applied to a
+/// different ruleset, such as one recompiled with its rules reordered, they
+/// are matched to its rules by name, and overrides for rules it lacks are
+/// ignored. The ruleset itself stays immutable; to flip a rule in a running
+/// service, share the overrides behind a lock and swap in a new set.
+///
+/// # Example
+///
+/// ```
+/// use ooroo::{field, Context, RuleOverride, RuleSetBuilder, Verdict};
+///
+/// let ruleset = RuleSetBuilder::new()
+///     .rule("deny", |r| r.when(field("user.banned").eq(true)))
+///     .rule("allow", |r| r.when(field("user.age").gte(18_i64)))
+///     .terminal("deny", 0)
+///     .terminal("allow", 1)
+///     .compile()
+///     .unwrap();
+///
+/// let mut overrides = ruleset.overrides();
+/// overrides.set("deny", RuleOverride::Disable).unwrap();
+///
+/// let ctx = Context::new().set("user.banned", true).set("user.age", 30_i64);
+/// assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("deny", true)));
+/// assert_eq!(
+///     ruleset.evaluate_with_overrides(&ctx, &overrides),
+///     Some(Verdict::new("allow", true))
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RuleOverrides {
+    /// Rule names, indexed by compiled rule index.
+    names: Vec<String>,
+    states: Vec<Option<RuleOverride>>,
+}
+
+impl RuleOverrides {
+    pub(crate) fn new(ruleset: &RuleSet) -> Self {
+        let mut names = vec![String::new(); ruleset.rules.len()];
+        for rule in &ruleset.rules {
+            names[rule.index].clone_from(&rule.name);
+        }
+        Self::from_names(names)
+    }
+
+    /// Overrides for rules named by compiled rule index.
+    pub(crate) fn from_names(names: Vec<String>) -> Self {
+        Self {
+            states: vec![None; names.len()],
+            names,
+        }
+    }
+
+    fn index_of(&self, rule: &str) -> Result<usize, OverrideError> {
+        self.names
+            .iter()
+            .position(|n| n == rule)
+            .ok_or_else(|| OverrideError::UnknownRule {
+                name: rule.to_owned(),
+            })
+    }
+
+    /// Override `rule`, replacing any previous override for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OverrideError::UnknownRule`] if the ruleset has no such rule.
+    pub fn set(&mut self, rule: &str, value: RuleOverride) -> Result<(), OverrideError> {
+        let idx = self.index_of(rule)?;
+        self.states[idx] = Some(value);
+        Ok(())
+    }
+
+    /// Remove the override for `rule`, returning it if there was one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OverrideError::UnknownRule`] if the ruleset has no such rule.
+    pub fn clear(&mut self, rule: &str) -> Result<Option<RuleOverride>, OverrideError> {
+        let idx = self.index_of(rule)?;
+        Ok(self.states[idx].take())
+    }
+
+    /// Remove every override.
+    pub fn clear_all(&mut self) {
+        self.states.fill(None);
+    }
+
+    /// The override for `rule`, if any.
+    #[must_use]
+    pub fn get(&self, rule: &str) -> Option<RuleOverride> {
+        self.index_of(rule).ok().and_then(|idx| self.states[idx])
+    }
+
+    /// Returns `true` if no rule is overridden.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.states.iter().all(Option::is_none)
+    }
+
+    /// The overridden rules and their overrides, in rule index order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, RuleOverride)> {
+        self.names
+            .iter()
+            .zip(&self.states)
+            .filter_map(|(name, state)| state.map(|s| (name.as_str(), s)))
+    }
+
+    /// The overrides indexed by the compiled rule indices of the ruleset
+    /// whose `(index, name)` pairs are `rules`, for the evaluator. Borrowed
+    /// when that ruleset is the one these overrides were created for,
+    /// otherwise remapped by rule name.
+    pub(crate) fn states_for<'n, I>(&self, rules: I) -> Cow<'_, [Option<RuleOverride>]>
+    where
+        I: ExactSizeIterator<Item = (usize, &'n str)> + Clone,
+    {
+        if rules.len() == self.names.len()
+            && rules.clone().all(|(index, name)| self.names[index] == name)
+        {
+            return Cow::Borrowed(&self.states);
+        }
+        let mut states = vec![None; rules.len()];
+        for (index, name) in rules {
+            states[index] = self.get(name);
+        }
+        Cow::Owned(states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{field, RuleSetBuilder};
+
+    fn ruleset() -> RuleSet {
+        RuleSetBuilder::new()
+            .rule("a", |r| r.when(field("x").eq(1_i64)))
+            .rule("b", |r| r.when(field("y").eq(1_i64)))
+            .terminal("a", 0)
+            .terminal("b", 1)
+            .compile()
+            .unwrap()
+    }
+
+    #[test]
+    fn set_get_and_clear() {
+        let ruleset = ruleset();
+        let mut overrides = ruleset.overrides();
+        assert!(overrides.is_empty());
+
+        overrides.set("a", RuleOverride::ForceTrue).unwrap();
+        overrides.set("a", RuleOverride::Disable).unwrap();
+        assert_eq!(overrides.get("a"), Some(RuleOverride::Disable));
+        assert_eq!(overrides.get("b"), None);
+        assert_eq!(
+            overrides.iter().collect::<Vec<_>>(),
+            [("a", RuleOverride::Disable)]
+        );
+
+        assert_eq!(overrides.clear("a").unwrap(), Some(RuleOverride::Disable));
+        assert!(overrides.is_empty());
+
+        overrides.set("b", RuleOverride::ForceFalse).unwrap();
+        overrides.clear_all();
+        assert!(overrides.is_empty());
+    }
+
+    #[test]
+    fn unknown_rule_is_an_error() {
+        let mut overrides = ruleset().overrides();
+        let err = overrides.set("c", RuleOverride::Enable).unwrap_err();
+        assert_eq!(err.to_string(), "cannot override unknown rule 'c'");
+        assert!(overrides.clear("c").is_err());
+        assert_eq!(overrides.get("c"), None);
+    }
+
+    #[test]
+    fn states_are_remapped_by_name() {
+        let ruleset = ruleset();
+        let mut overrides = ruleset.overrides();
+        overrides.set("b", RuleOverride::ForceTrue).unwrap();
+
+        let same = overrides.states_for(ruleset.rule_indices());
+        assert!(matches!(same, Cow::Borrowed(_)));
+
+        let reordered = overrides.states_for([(0, "b"), (1, "c"), (2, "a")].into_iter());
+        assert!(matches!(reordered, Cow::Owned(_)));
+        assert_eq!(*reordered, [Some(RuleOverride::ForceTrue), None, None]);
+    }
+}
@@ -0,0 +1,205 @@
+/// A field path split into a plain dotted root and the lookup steps that
+/// follow its first `[...]` index.
+///
+/// `limits[user.country].daily` parses to root `limits` with steps
+/// `[Dynamic("user.country"), Key("daily")]`, and `order.items[*].sku` to root
+/// `order.items` with steps `[Wildcard, Key("sku")]`. Paths without brackets
+/// have no steps and resolve exactly as before.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FieldPath<'p> {
+    pub(crate) root: &'p str,
+    pub(crate) steps: Vec<PathStep<'p>>,
+}
+
+/// One lookup step applied to the value found at a [`FieldPath`] root.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PathStep<'p> {
+    /// A literal map key, from `["key"]` or a `.key` segment after an index.
+    Key(String),
+    /// A map key (string) or list position (integer) read from another field
+    /// at evaluation time, from `[other.path]`.
+    Dynamic(&'p str),
+    /// A list position, from `[2]`. Negative positions count from the end.
+    Index(i64),
+    /// Every element of a list or every value of a map, from `[*]`.
+    Wildcard,
+}
+
+impl<'p> FieldPath<'p> {
+    /// Parse a path, returning a description of the problem if it is malformed.
+    pub(crate) fn parse(path: &'p str) -> Result<Self, String> {
+        let Some(open) = path.find('[') else {
+            return Ok(Self {
+                root: path,
+                steps: Vec::new(),
+            });
+        };
+        let root = &path[..open];
+        if root.is_empty() {
+            return Err("path must start with a field name before '['".to_owned());
+        }
+        let mut steps = Vec::new();
+        let mut rest = &path[open..];
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('[') {
+                let (step, remaining) = parse_index(after)?;
+                steps.push(step);
+                rest = remaining;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                let segment = &after[..end];
+                if !is_segment(segment) {
+                    return Err(format!("invalid path segment '{segment}'"));
+                }
+                steps.push(PathStep::Key(segment.to_owned()));
+                rest = &after[end..];
+            } else {
+                return Err(format!("unexpected '{rest}' after ']'"));
+            }
+        }
+        Ok(Self { root, steps })
+    }
+
+    /// Whether the path contains any `[...]` lookups.
+    pub(crate) fn is_indexed(&self) -> bool {
+        !self.steps.is_empty()
+    }
+}
+
+/// Parse the contents of one `[...]` index (the opening bracket already
+/// consumed), returning the step and the input after the closing bracket.
+fn parse_index(input: &str) -> Result<(PathStep<'_>, &str), String> {
+    if let Some(quoted) = input.strip_prefix('"') {
+        let mut key = String::new();
+        let mut chars = quoted.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, escaped @ ('"' | '\\'))) => key.push(escaped),
+                    _ => return Err("invalid escape in quoted key".to_owned()),
+                },
+                '"' => {
+                    let after = &quoted[i + 1..];
+                    let rest = after
+                        .strip_prefix(']')
+                        .ok_or_else(|| "expected ']' after quoted key".to_owned())?;
+                    return Ok((PathStep::Key(key), rest));
+                }
+                _ => key.push(c),
+            }
+        }
+        return Err("unterminated quoted key".to_owned());
+    }
+    let close = input
+        .find(']')
+        .ok_or_else(|| "missing ']' after index".to_owned())?;
+    let inner = &input[..close];
+    let rest = &input[close + 1..];
+    if inner == "*" {
+        return Ok((PathStep::Wildcard, rest));
+    }
+    if inner.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+        let index = inner
+            .parse()
+            .map_err(|_| format!("invalid list index '{inner}'"))?;
+        return Ok((PathStep::Index(index), rest));
+    }
+    if inner.is_empty() || !inner.split('.').all(is_segment) {
+        return Err(format!(
+            "index '{inner}' must be a quoted key, an integer, '*' or a field path"
+        ));
+    }
+    Ok((PathStep::Dynamic(inner), rest))
+}
+
+pub(crate) fn is_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Quote `key` for use inside a `[...]` index.
+pub(crate) fn quote_key(key: &str) -> String {
+    let escaped = key.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[\"{escaped}\"]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_path_has_no_steps() {
+        let path = FieldPath::parse("user.profile.age").unwrap();
+        assert_eq!(path.root, "user.profile.age");
+        assert!(!path.is_indexed());
+    }
+
+    #[test]
+    fn dynamic_and_literal_keys() {
+        let path = FieldPath::parse("limits[user.country].daily[\"a.b\"]").unwrap();
+        assert_eq!(path.root, "limits");
+        assert_eq!(
+            path.steps,
+            vec![
+                PathStep::Dynamic("user.country"),
+                PathStep::Key("daily".to_owned()),
+                PathStep::Key("a.b".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn list_indices_and_wildcards() {
+        let path = FieldPath::parse("order.items[0].tags[-1]").unwrap();
+        assert_eq!(path.root, "order.items");
+        assert_eq!(
+            path.steps,
+            vec![
+                PathStep::Index(0),
+                PathStep::Key("tags".to_owned()),
+                PathStep::Index(-1),
+            ]
+        );
+
+        let path = FieldPath::parse("orders[*].items[*].sku").unwrap();
+        assert_eq!(
+            path.steps,
+            vec![
+                PathStep::Wildcard,
+                PathStep::Key("items".to_owned()),
+                PathStep::Wildcard,
+                PathStep::Key("sku".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_key_escapes_round_trip() {
+        let key = "say \"hi\" \\ ]";
+        let path = format!("m{}", quote_key(key));
+        let parsed = FieldPath::parse(&path).unwrap();
+        assert_eq!(parsed.steps, vec![PathStep::Key(key.to_owned())]);
+    }
+
+    #[test]
+    fn malformed_paths_rejected() {
+        for bad in [
+            "[x]",
+            "m[",
+            "m[]",
+            "m[\"x\"",
+            "m[\"x\"]y",
+            "m[a b]",
+            "m[x].",
+            "m[x]..y",
+            "m[1x]",
+            "m[-]",
+            "m[**]",
+            "m[99999999999999999999]",
+        ] {
+            assert!(FieldPath::parse(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+}
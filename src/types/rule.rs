@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use super::expr::{CompiledExpr, Expr};
 
 /// A named rule with an optional boolean condition expression.
@@ -6,11 +8,68 @@ use super::expr::{CompiledExpr, Expr};
 /// string with [`RuleSet::from_dsl()`](super::RuleSet::from_dsl). The condition
 /// is `None` until set via the builder's `.when()` method.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rule {
     /// The rule's unique name.
     pub name: String,
     /// The boolean condition expression, or `None` if not yet set.
     pub condition: Option<Expr>,
+    /// Descriptive metadata and the `enabled` flag.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub metadata: RuleMetadata,
+}
+
+/// Descriptive metadata attached to a rule.
+///
+/// Metadata is carried through compilation and the binary cache and can be
+/// queried on a [`RuleSet`](super::RuleSet). Only `enabled` affects
+/// evaluation: a disabled rule always evaluates to `false`, so terminals
+/// and rules that reference it behave as if its condition never matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RuleMetadata {
+    /// Free-form description of what the rule checks.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub description: Option<String>,
+    /// The team or person responsible for the rule.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub owner: Option<String>,
+    /// Tags for grouping rules, in the order they were added.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub tags: Vec<String>,
+    /// A link to a ticket or design document.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub link: Option<String>,
+    /// Whether the rule is evaluated. Defaults to `true`.
+    pub enabled: bool,
+    /// Any other key-value annotations.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "BTreeMap::is_empty"))]
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Default for RuleMetadata {
+    fn default() -> Self {
+        Self {
+            description: None,
+            owner: None,
+            tags: Vec::new(),
+            link: None,
+            enabled: true,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+impl RuleMetadata {
+    /// Returns `true` if the rule carries `tag`.
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
 }
 
 /// A rule whose field paths and rule references have been resolved to integer
@@ -23,6 +82,7 @@ pub(crate) struct CompiledRule {
     pub(crate) name: String,
     pub(crate) condition: CompiledExpr,
     pub(crate) index: usize,
+    pub(crate) metadata: RuleMetadata,
 }
 
 /// Marks a rule as a terminal output of evaluation, with a priority that
@@ -32,9 +92,33 @@ pub(crate) struct CompiledRule {
 /// patterns (e.g., a `banned` terminal at priority 0 is checked before an
 /// `allowed` terminal at priority 10).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Terminal {
     /// The name of the rule this terminal references.
     pub rule_name: String,
     /// Priority for evaluation ordering; lower values are checked first.
     pub priority: u32,
+    /// How an `Unknown` result is handled under three-valued evaluation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub on_unknown: UnknownPolicy,
+}
+
+/// How a terminal whose rule evaluates to [`Truth::Unknown`](super::Truth::Unknown)
+/// is handled by [`RuleSet::evaluate_three_valued()`](super::RuleSet::evaluate_three_valued).
+///
+/// Ordinary two-valued evaluation never produces `Unknown`, so the policy
+/// has no effect there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum UnknownPolicy {
+    /// Treat `Unknown` as `false` and move on to the next terminal.
+    #[default]
+    AsFalse,
+    /// Stop and return an indeterminate [`Verdict`](super::Verdict) for this
+    /// terminal, so that a lower-priority terminal cannot match in its place.
+    Indeterminate,
 }
@@ -0,0 +1,221 @@
+use std::fmt;
+
+use super::context::Context;
+use super::evaluation_report::EvaluationReport;
+use super::value::Value;
+
+/// A test case declared inline in the DSL:
+///
+/// ```text
+/// test "minor is denied" {
+///     given user.age = 15, user.status = "active";
+///     expect deny
+/// }
+/// ```
+///
+/// Run with [`RuleSet::run_tests()`](super::RuleSet::run_tests).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleTest {
+    /// The quoted name after `test`.
+    pub name: String,
+    /// The `given` field values, in source order.
+    pub given: Vec<(String, Value)>,
+    /// The terminal the ruleset should decide on, or `None` for
+    /// `expect none`.
+    pub expect: Option<String>,
+}
+
+impl RuleTest {
+    /// The context built from the `given` values.
+    #[must_use]
+    pub fn context(&self) -> Context {
+        let mut ctx = Context::new();
+        for (path, value) in &self.given {
+            ctx.insert(path, value.clone());
+        }
+        ctx
+    }
+}
+
+/// The result of running one [`RuleTest`].
+#[derive(Debug, Clone)]
+pub struct TestOutcome {
+    name: String,
+    expected: Option<String>,
+    actual: Option<String>,
+    report: Option<EvaluationReport>,
+}
+
+impl TestOutcome {
+    pub(crate) fn new(test: &RuleTest, report: EvaluationReport) -> Self {
+        let actual = report.verdict().map(|v| v.terminal().to_owned());
+        let passed = actual == test.expect;
+        Self {
+            name: test.name.clone(),
+            expected: test.expect.clone(),
+            actual,
+            report: (!passed).then_some(report),
+        }
+    }
+
+    /// The test's name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The terminal the test expected, or `None` for `expect none`.
+    #[must_use]
+    pub fn expected(&self) -> Option<&str> {
+        self.expected.as_deref()
+    }
+
+    /// The terminal the ruleset decided on, or `None` if no terminal matched.
+    #[must_use]
+    pub fn actual(&self) -> Option<&str> {
+        self.actual.as_deref()
+    }
+
+    /// Whether the verdict was the expected one.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.report.is_none()
+    }
+
+    /// The detailed evaluation of a failed test; `None` if it passed.
+    #[must_use]
+    pub fn report(&self) -> Option<&EvaluationReport> {
+        self.report.as_ref()
+    }
+}
+
+impl fmt::Display for TestOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.report {
+            None => write!(f, "test \"{}\" ... ok", self.name),
+            Some(report) => write!(
+                f,
+                "test \"{}\" ... FAILED: expected {}, got {} ({report})",
+                self.name,
+                self.expected().unwrap_or("none"),
+                self.actual().unwrap_or("none"),
+            ),
+        }
+    }
+}
+
+/// The results of [`RuleSet::run_tests()`](super::RuleSet::run_tests), in
+/// declaration order.
+///
+/// `Display` prints one line per test and a summary, like `cargo test`.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct TestReport {
+    outcomes: Vec<TestOutcome>,
+}
+
+impl TestReport {
+    pub(crate) fn new(outcomes: Vec<TestOutcome>) -> Self {
+        Self { outcomes }
+    }
+
+    /// Every test's outcome, in declaration order.
+    #[must_use]
+    pub fn outcomes(&self) -> &[TestOutcome] {
+        &self.outcomes
+    }
+
+    /// The tests that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &TestOutcome> {
+        self.outcomes.iter().filter(|o| !o.passed())
+    }
+
+    /// Whether every test passed. `true` if there are no tests.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.outcomes.iter().all(TestOutcome::passed)
+    }
+
+    /// Panic with the report if any test failed, for use as the body of a
+    /// `#[test]` function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any test failed.
+    pub fn assert_passed(&self) {
+        assert!(self.passed(), "DSL tests failed:\n{self}");
+    }
+}
+
+impl fmt::Display for TestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for outcome in &self.outcomes {
+            writeln!(f, "{outcome}")?;
+        }
+        let failed = self.failures().count();
+        write!(
+            f,
+            "{} passed; {failed} failed",
+            self.outcomes.len() - failed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::Verdict;
+
+    fn test_case(expect: Option<&str>) -> RuleTest {
+        RuleTest {
+            name: "minor".into(),
+            given: vec![("user.age".into(), Value::Int(15))],
+            expect: expect.map(str::to_owned),
+        }
+    }
+
+    fn report(verdict: Option<&str>) -> EvaluationReport {
+        EvaluationReport::new(
+            verdict.map(|t| Verdict::new(t, true)),
+            vec![],
+            vec![],
+            vec![],
+            Duration::ZERO,
+        )
+    }
+
+    #[test]
+    fn context_from_given() {
+        let ctx = test_case(None).context();
+        assert_eq!(ctx.get("user.age"), Some(&Value::Int(15)));
+    }
+
+    #[test]
+    fn outcome_keeps_report_only_on_failure() {
+        let pass = TestOutcome::new(&test_case(Some("deny")), report(Some("deny")));
+        assert!(pass.passed());
+        assert!(pass.report().is_none());
+        assert_eq!(pass.to_string(), "test \"minor\" ... ok");
+
+        let fail = TestOutcome::new(&test_case(None), report(Some("deny")));
+        assert!(!fail.passed());
+        assert_eq!(fail.actual(), Some("deny"));
+        assert!(fail
+            .to_string()
+            .starts_with("test \"minor\" ... FAILED: expected none, got deny (verdict: deny"));
+    }
+
+    #[test]
+    fn report_summary() {
+        let results = TestReport::new(vec![
+            TestOutcome::new(&test_case(Some("deny")), report(Some("deny"))),
+            TestOutcome::new(&test_case(Some("deny")), report(None)),
+        ]);
+        assert!(!results.passed());
+        assert_eq!(results.failures().count(), 1);
+        assert!(results.to_string().ends_with("1 passed; 1 failed"));
+        TestReport::new(vec![]).assert_passed();
+    }
+}
@@ -6,7 +6,11 @@ use super::evaluation_report::EvaluationReport;
 use super::expr::{CompiledExpr, Expr};
 use super::field_registry::FieldRegistry;
 use super::indexed_context::{ContextBuilder, IndexedContext};
-use super::rule::{CompiledRule, Rule, Terminal};
+use super::overrides::RuleOverrides;
+use super::rule::{CompiledRule, Rule, RuleMetadata, Terminal, UnknownPolicy};
+use super::rule_test::{RuleTest, TestOutcome, TestReport};
+use super::schema::{Schema, SchemaViolation};
+use super::validation::{self, ValidationReport};
 use super::value::Value;
 use super::verdict::Verdict;
 
@@ -32,12 +36,14 @@ use super::verdict::Verdict;
 pub struct RuleSetBuilder {
     rules: Vec<Rule>,
     terminals: Vec<Terminal>,
+    schema: Option<Schema>,
 }
 
 /// Intermediate builder passed to the rule definition closure.
 #[derive(Debug)]
 pub struct RuleBuilder {
     condition: Option<Expr>,
+    metadata: RuleMetadata,
 }
 
 impl RuleSetBuilder {
@@ -53,10 +59,14 @@ impl RuleSetBuilder {
     /// [`CompileError::MissingCondition`].
     #[must_use]
     pub fn rule(mut self, name: &str, f: impl FnOnce(RuleBuilder) -> RuleBuilder) -> Self {
-        let builder = f(RuleBuilder { condition: None });
+        let builder = f(RuleBuilder {
+            condition: None,
+            metadata: RuleMetadata::default(),
+        });
         self.rules.push(Rule {
             name: name.to_owned(),
             condition: builder.condition,
+            metadata: builder.metadata,
         });
         self
     }
@@ -64,21 +74,42 @@ impl RuleSetBuilder {
     /// Register a rule as a terminal with the given priority.
     /// Lower priority numbers are evaluated first.
     #[must_use]
-    pub fn terminal(mut self, rule_name: &str, priority: u32) -> Self {
+    pub fn terminal(self, rule_name: &str, priority: u32) -> Self {
+        self.terminal_on_unknown(rule_name, priority, UnknownPolicy::AsFalse)
+    }
+
+    /// Register a rule as a terminal with the given priority and a policy for
+    /// `Unknown` results under [`RuleSet::evaluate_three_valued()`].
+    #[must_use]
+    pub fn terminal_on_unknown(
+        mut self,
+        rule_name: &str,
+        priority: u32,
+        on_unknown: UnknownPolicy,
+    ) -> Self {
         self.terminals.push(Terminal {
             rule_name: rule_name.to_owned(),
             priority,
+            on_unknown,
         });
         self
     }
 
+    /// Attach a [`Schema`]. Compilation then rejects references to undeclared
+    /// fields and comparisons against values of the wrong type.
+    #[must_use]
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
     /// Compile the rules into an immutable `RuleSet`.
     ///
     /// # Errors
     ///
     /// Returns [`CompileError`] if validation fails.
     pub fn compile(self) -> Result<RuleSet, CompileError> {
-        crate::compile::compile(&self.rules, self.terminals)
+        crate::compile::compile(&self.rules, self.terminals, self.schema)
     }
 }
 
@@ -89,6 +120,50 @@ impl RuleBuilder {
         self.condition = Some(condition);
         self
     }
+
+    /// Describe what the rule checks.
+    #[must_use]
+    pub fn description(mut self, description: &str) -> Self {
+        self.metadata.description = Some(description.to_owned());
+        self
+    }
+
+    /// Set the team or person responsible for the rule.
+    #[must_use]
+    pub fn owner(mut self, owner: &str) -> Self {
+        self.metadata.owner = Some(owner.to_owned());
+        self
+    }
+
+    /// Add a tag. May be called more than once.
+    #[must_use]
+    pub fn tag(mut self, tag: &str) -> Self {
+        if !self.metadata.has_tag(tag) {
+            self.metadata.tags.push(tag.to_owned());
+        }
+        self
+    }
+
+    /// Link the rule to a ticket or design document.
+    #[must_use]
+    pub fn link(mut self, link: &str) -> Self {
+        self.metadata.link = Some(link.to_owned());
+        self
+    }
+
+    /// Enable or disable the rule. A disabled rule always evaluates to `false`.
+    #[must_use]
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.metadata.enabled = enabled;
+        self
+    }
+
+    /// Attach any other key-value annotation.
+    #[must_use]
+    pub fn meta(mut self, key: &str, value: &str) -> Self {
+        self.metadata.extra.insert(key.to_owned(), value.to_owned());
+        self
+    }
 }
 
 /// A compiled, immutable ruleset. Thread-safe and designed to live behind `Arc`.
@@ -99,6 +174,9 @@ pub struct RuleSet {
     pub(crate) field_registry: FieldRegistry,
     /// Pre-resolved indices into `rules` for each terminal, in priority order.
     pub(crate) terminal_indices: Vec<usize>,
+    pub(crate) schema: Option<Schema>,
+    /// `test` blocks from the DSL source, in declaration order.
+    pub(crate) tests: Vec<RuleTest>,
 }
 
 impl RuleSet {
@@ -114,6 +192,7 @@ impl RuleSet {
             &self.terminals,
             &self.terminal_indices,
             &field_values,
+            &[],
         )
     }
 
@@ -121,7 +200,7 @@ impl RuleSet {
     /// to map field paths to pre-resolved indices for fast evaluation.
     #[must_use]
     pub fn context_builder(&self) -> ContextBuilder<'_> {
-        ContextBuilder::new(&self.field_registry)
+        ContextBuilder::new(self)
     }
 
     /// Evaluate this ruleset against a pre-indexed context.
@@ -135,6 +214,61 @@ impl RuleSet {
             &self.terminals,
             &self.terminal_indices,
             ctx.values(),
+            &[],
+        )
+    }
+
+    /// Evaluate this ruleset with three-valued (Kleene) logic.
+    ///
+    /// Unlike [`evaluate()`](Self::evaluate), a test on a missing or
+    /// type-mismatched field is [`Truth::Unknown`](crate::Truth::Unknown)
+    /// rather than `false`, and `Unknown` propagates through `AND`, `OR`,
+    /// `NOT`, `AT_LEAST` and quantifiers the way SQL `NULL` does. So
+    /// `NOT (user.age < 18)` does not pass for a user with no age.
+    ///
+    /// Terminals are checked in priority order. The first `true` terminal
+    /// wins; an `Unknown` terminal is skipped, or returns
+    /// [`Verdict::indeterminate`] if it was registered with
+    /// [`UnknownPolicy::Indeterminate`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ooroo::{field, Context, RuleSetBuilder, UnknownPolicy};
+    ///
+    /// let ruleset = RuleSetBuilder::new()
+    ///     .rule("adult", |r| r.when(!field("user.age").lt(18_i64)))
+    ///     .terminal_on_unknown("adult", 0, UnknownPolicy::Indeterminate)
+    ///     .compile()
+    ///     .unwrap();
+    ///
+    /// let ctx = Context::new();
+    /// assert!(ruleset.evaluate(&ctx).is_some());
+    /// assert!(ruleset.evaluate_three_valued(&ctx).unwrap().is_indeterminate());
+    /// ```
+    #[must_use]
+    pub fn evaluate_three_valued(&self, ctx: &Context) -> Option<Verdict> {
+        let field_values = self.flatten_context(ctx);
+        crate::evaluate::evaluate_three_valued(
+            &self.rules,
+            &self.terminals,
+            &self.terminal_indices,
+            &field_values,
+            &[],
+        )
+    }
+
+    /// Evaluate with three-valued logic against a pre-indexed context.
+    ///
+    /// See [`evaluate_three_valued()`](Self::evaluate_three_valued).
+    #[must_use]
+    pub fn evaluate_three_valued_indexed(&self, ctx: &IndexedContext) -> Option<Verdict> {
+        crate::evaluate::evaluate_three_valued(
+            &self.rules,
+            &self.terminals,
+            &self.terminal_indices,
+            ctx.values(),
+            &[],
         )
     }
 
@@ -149,6 +283,7 @@ impl RuleSet {
             &self.terminals,
             &self.terminal_indices,
             &field_values,
+            &[],
         )
     }
 
@@ -159,30 +294,325 @@ impl RuleSet {
             &self.terminals,
             &self.terminal_indices,
             ctx.values(),
+            &[],
+        )
+    }
+
+    /// Create an empty set of runtime overrides for this ruleset.
+    ///
+    /// See [`RuleOverrides`] and
+    /// [`evaluate_with_overrides()`](Self::evaluate_with_overrides).
+    #[must_use]
+    pub fn overrides(&self) -> RuleOverrides {
+        RuleOverrides::new(self)
+    }
+
+    /// Evaluate with runtime overrides layered over the compiled rules.
+    ///
+    /// A forced rule takes its forced value without evaluating its
+    /// condition, and rules that depend on it see that value. A disabled
+    /// rule is `false` and, if it is a terminal, is skipped. `Enable`
+    /// evaluates a rule that its metadata disables.
+    #[must_use]
+    pub fn evaluate_with_overrides(
+        &self,
+        ctx: &Context,
+        overrides: &RuleOverrides,
+    ) -> Option<Verdict> {
+        let field_values = self.flatten_context(ctx);
+        crate::evaluate::evaluate(
+            &self.rules,
+            &self.terminals,
+            &self.terminal_indices,
+            &field_values,
+            &overrides.states_for(self.rule_indices()),
+        )
+    }
+
+    /// Evaluate a pre-indexed context with runtime overrides.
+    ///
+    /// See [`evaluate_with_overrides()`](Self::evaluate_with_overrides).
+    #[must_use]
+    pub fn evaluate_indexed_with_overrides(
+        &self,
+        ctx: &IndexedContext,
+        overrides: &RuleOverrides,
+    ) -> Option<Verdict> {
+        crate::evaluate::evaluate(
+            &self.rules,
+            &self.terminals,
+            &self.terminal_indices,
+            ctx.values(),
+            &overrides.states_for(self.rule_indices()),
+        )
+    }
+
+    /// Evaluate with three-valued logic and runtime overrides. Forced rules
+    /// are `True` or `False`, never `Unknown`.
+    #[must_use]
+    pub fn evaluate_three_valued_with_overrides(
+        &self,
+        ctx: &Context,
+        overrides: &RuleOverrides,
+    ) -> Option<Verdict> {
+        let field_values = self.flatten_context(ctx);
+        crate::evaluate::evaluate_three_valued(
+            &self.rules,
+            &self.terminals,
+            &self.terminal_indices,
+            &field_values,
+            &overrides.states_for(self.rule_indices()),
+        )
+    }
+
+    /// Evaluate a pre-indexed context with three-valued logic and runtime
+    /// overrides.
+    #[must_use]
+    pub fn evaluate_three_valued_indexed_with_overrides(
+        &self,
+        ctx: &IndexedContext,
+        overrides: &RuleOverrides,
+    ) -> Option<Verdict> {
+        crate::evaluate::evaluate_three_valued(
+            &self.rules,
+            &self.terminals,
+            &self.terminal_indices,
+            ctx.values(),
+            &overrides.states_for(self.rule_indices()),
+        )
+    }
+
+    /// Evaluate with detailed diagnostics and runtime overrides. The report
+    /// lists the overrides that applied in
+    /// [`overridden()`](EvaluationReport::overridden).
+    pub fn evaluate_detailed_with_overrides(
+        &self,
+        ctx: &Context,
+        overrides: &RuleOverrides,
+    ) -> EvaluationReport {
+        let field_values = self.flatten_context(ctx);
+        crate::evaluate::evaluate_detailed(
+            &self.rules,
+            &self.terminals,
+            &self.terminal_indices,
+            &field_values,
+            &overrides.states_for(self.rule_indices()),
+        )
+    }
+
+    /// Evaluate a pre-indexed context with detailed diagnostics and runtime
+    /// overrides.
+    pub fn evaluate_detailed_indexed_with_overrides(
+        &self,
+        ctx: &IndexedContext,
+        overrides: &RuleOverrides,
+    ) -> EvaluationReport {
+        crate::evaluate::evaluate_detailed(
+            &self.rules,
+            &self.terminals,
+            &self.terminal_indices,
+            ctx.values(),
+            &overrides.states_for(self.rule_indices()),
+        )
+    }
+
+    /// Each rule's compiled index and name, for matching overrides.
+    pub(crate) fn rule_indices(&self) -> impl ExactSizeIterator<Item = (usize, &str)> + Clone {
+        self.rules.iter().map(|r| (r.index, r.name.as_str()))
+    }
+
+    /// Run the DSL's `test` blocks: evaluate each one's `given` context and
+    /// compare the verdict's terminal with its `expect`. Failed tests carry
+    /// the [`EvaluationReport`] of their evaluation.
+    ///
+    /// # Example
+    ///
+    /// A `#[test]` that checks a rules file in CI:
+    ///
+    /// ```
+    /// use ooroo::RuleSet;
+    ///
+    /// let ruleset = RuleSet::from_dsl(r#"
+    /// rule deny (priority 0):
+    ///     user.age < 18
+    ///
+    /// test "minor is denied" {
+    ///     given user.age = 15;
+    ///     expect deny
+    /// }
+    ///
+    /// test "adult is not" {
+    ///     given user.age = 30;
+    ///     expect none
+    /// }
+    /// "#).unwrap();
+    /// ruleset.run_tests().assert_passed();
+    /// ```
+    pub fn run_tests(&self) -> TestReport {
+        TestReport::new(
+            self.tests
+                .iter()
+                .map(|test| TestOutcome::new(test, self.evaluate_detailed(&test.context())))
+                .collect(),
         )
     }
 
     /// Parse a DSL string and compile into a `RuleSet`.
     ///
     /// This is a convenience method that parses and compiles in one step.
+    /// Inputs with `import` declarations must be loaded with
+    /// [`from_file()`](Self::from_file) or [`from_loader()`](Self::from_loader).
     ///
     /// # Errors
     ///
     /// Returns [`OorooError`](crate::OorooError) on parse or compile failure.
     pub fn from_dsl(input: &str) -> Result<Self, crate::OorooError> {
+        Self::from_dsl_with_params(input, &crate::Params::new())
+    }
+
+    /// Parse a DSL string and compile it, substituting `params` for the
+    /// input's `param` declarations.
+    ///
+    /// Each declared parameter takes its supplied value, or its default
+    /// (`param NAME = default`) if none is supplied. Parameters are
+    /// referenced like constants, wherever a literal value may appear.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OorooError`](crate::OorooError) on parse or compile failure,
+    /// including [`CompileError::MissingParam`] for a parameter with no value
+    /// and [`CompileError::UndeclaredParam`] for a supplied name the input
+    /// does not declare.
+    pub fn from_dsl_with_params(
+        input: &str,
+        params: &crate::Params,
+    ) -> Result<Self, crate::OorooError> {
         let parsed = crate::parse::parse(input)?;
-        let ruleset = crate::compile::compile(&parsed.rules, parsed.terminals)?;
-        Ok(ruleset)
+        if let Some((path, _)) = parsed.imports.first() {
+            return Err(crate::ImportError::NoLoader { path: path.clone() }.into());
+        }
+        Self::from_parsed(parsed, params)
+    }
+
+    /// Compile rules and terminals stored as JSON, for example by a
+    /// rule-authoring UI.
+    ///
+    /// The document is an object with a `rules` array of serialized
+    /// [`Rule`]s and a `terminals` array of serialized [`Terminal`]s:
+    ///
+    /// ```json
+    /// {
+    ///   "rules": [
+    ///     {"name": "adult", "condition": {"compare": {"field": "user.age", "op": "gte", "value": 18}}},
+    ///     {"name": "allow", "condition": {"and": [{"rule_ref": "adult"}, {"not": {"is_null": "user.id"}}]},
+    ///      "metadata": {"owner": "risk", "tags": ["kyc"]}}
+    ///   ],
+    ///   "terminals": [{"rule_name": "allow", "priority": 0, "on_unknown": "indeterminate"}]
+    /// }
+    /// ```
+    ///
+    /// Each [`Expr`] is an object with a single snake_case key naming the
+    /// variant: struct variants hold an object of their fields, `and`/`or`
+    /// a two-element array, and `not`, `rule_ref`, `is_null` and
+    /// `is_not_null` their one operand. [`Value`]s are plain JSON scalars,
+    /// arrays and objects; a [`Bound`](crate::Bound) is `{"literal": value}`
+    /// or `{"field": path}`. Operators are snake_case names (`"gte"`,
+    /// `"subset_of"`). `metadata` and `on_unknown` may be omitted. Writing
+    /// `Vec<Rule>` and `Vec<Terminal>` with serde produces this shape.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OorooError::Json`](crate::OorooError::Json) if `json` does
+    /// not match this shape, and [`OorooError::Compile`](crate::OorooError::Compile)
+    /// if the rules do not compile.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ooroo::{Context, RuleSet};
+    ///
+    /// let ruleset = RuleSet::from_json_rules(r#"{
+    ///     "rules": [{"name": "adult", "condition": {"compare": {"field": "age", "op": "gte", "value": 18}}}],
+    ///     "terminals": [{"rule_name": "adult", "priority": 0}]
+    /// }"#).unwrap();
+    /// assert!(ruleset.evaluate(&Context::new().set("age", 30_i64)).is_some());
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn from_json_rules(json: &str) -> Result<Self, crate::OorooError> {
+        /// The document read by `from_json_rules`.
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct JsonRules {
+            rules: Vec<Rule>,
+            #[serde(default)]
+            terminals: Vec<Terminal>,
+        }
+
+        let doc: JsonRules = serde_json::from_str(json)?;
+        Ok(crate::compile::compile(&doc.rules, doc.terminals, None)?)
     }
 
     /// Read a DSL file and compile into a `RuleSet`.
     ///
+    /// Imports are resolved by a [`FileLoader`](crate::FileLoader) rooted at
+    /// the file's directory.
+    ///
     /// # Errors
     ///
-    /// Returns [`OorooError`](crate::OorooError) on I/O, parse, or compile failure.
+    /// Returns [`OorooError`](crate::OorooError) on I/O, parse, import, or
+    /// compile failure.
     pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, crate::OorooError> {
-        let input = std::fs::read_to_string(path)?;
-        Self::from_dsl(&input)
+        let path = path.as_ref();
+        let root = path.parent().unwrap_or_else(|| std::path::Path::new(""));
+        let entry = path.file_name().unwrap_or_default().to_string_lossy();
+        Self::from_loader(&crate::FileLoader::new(root), &entry)
+    }
+
+    /// Load the DSL source `entry` from `loader`, resolve its imports through
+    /// the same loader, and compile the result.
+    ///
+    /// A file imported with `import "common/identity.ooroo" as identity`
+    /// contributes its rules as `identity.<rule>`, and its parameters as
+    /// `identity.<NAME>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OorooError`](crate::OorooError) on I/O, parse, import, or
+    /// compile failure. Problems in imported files are reported as
+    /// [`ImportError`](crate::ImportError)s naming the file.
+    pub fn from_loader(
+        loader: &dyn crate::SourceLoader,
+        entry: &str,
+    ) -> Result<Self, crate::OorooError> {
+        Self::from_loader_with_params(loader, entry, &crate::Params::new())
+    }
+
+    /// Like [`from_loader()`](Self::from_loader), substituting `params` for
+    /// `param` declarations as [`from_dsl_with_params()`](Self::from_dsl_with_params)
+    /// does.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OorooError`](crate::OorooError) on I/O, parse, import, or
+    /// compile failure.
+    pub fn from_loader_with_params(
+        loader: &dyn crate::SourceLoader,
+        entry: &str,
+        params: &crate::Params,
+    ) -> Result<Self, crate::OorooError> {
+        let parsed = crate::parse::load(loader, entry)?;
+        Self::from_parsed(parsed, params)
+    }
+
+    fn from_parsed(
+        mut parsed: crate::parse::ParsedRuleSet,
+        params: &crate::Params,
+    ) -> Result<Self, crate::OorooError> {
+        parsed.bind_params(params)?;
+        let mut ruleset = crate::compile::compile(&parsed.rules, parsed.terminals, parsed.schema)?;
+        crate::compile::check_tests(&parsed.tests, &ruleset.terminals)?;
+        ruleset.tests = parsed.tests;
+        Ok(ruleset)
     }
 
     /// Returns the compiled rule names in execution (topological) order.
@@ -220,6 +650,151 @@ impl RuleSet {
         })
     }
 
+    /// Returns the metadata of a rule.
+    ///
+    /// Returns `None` if the rule name is not found.
+    #[must_use]
+    pub fn metadata(&self, rule_name: &str) -> Option<&RuleMetadata> {
+        self.rules
+            .iter()
+            .find(|r| r.name == rule_name)
+            .map(|r| &r.metadata)
+    }
+
+    /// Returns the names of rules carrying `tag`, in execution order.
+    #[must_use]
+    pub fn rules_with_tag(&self, tag: &str) -> Vec<&str> {
+        self.rules_where(|m| m.has_tag(tag))
+    }
+
+    /// Returns the names of rules owned by `owner`, in execution order.
+    #[must_use]
+    pub fn rules_owned_by(&self, owner: &str) -> Vec<&str> {
+        self.rules_where(|m| m.owner.as_deref() == Some(owner))
+    }
+
+    /// Returns the names of disabled rules, in execution order.
+    #[must_use]
+    pub fn disabled_rules(&self) -> Vec<&str> {
+        self.rules_where(|m| !m.enabled)
+    }
+
+    /// Returns the names of rules whose metadata satisfies `predicate`, in
+    /// execution order.
+    pub fn rules_where(&self, predicate: impl Fn(&RuleMetadata) -> bool) -> Vec<&str> {
+        self.rules
+            .iter()
+            .filter(|r| predicate(&r.metadata))
+            .map(|r| r.name.as_str())
+            .collect()
+    }
+
+    /// Returns the schema the ruleset was compiled against, if any.
+    #[must_use]
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+
+    /// The `test` blocks declared in the DSL source, in declaration order.
+    ///
+    /// Only the entry file's tests are kept: tests in imported files are
+    /// written against that file's own terminals. Rulesets built any other
+    /// way, or read from the binary cache, have none.
+    #[must_use]
+    pub fn tests(&self) -> &[RuleTest] {
+        &self.tests
+    }
+
+    /// Reconstruct DSL source from the compiled rules: schema `field`
+    /// declarations, then every rule with its annotations and, for
+    /// terminals, its priority, then any `test` blocks. Rules are written
+    /// dependencies first and otherwise by name, so equal rulesets give
+    /// identical text.
+    ///
+    /// Paths come from the field registry, so a ruleset loaded from a
+    /// binary blob can be audited or diffed without its source. Constants,
+    /// templates and imports were resolved at compile time and appear
+    /// inlined; comments and quantifier variable names are not kept. Rule
+    /// names and field paths that are not plain identifiers, or are spelled
+    /// like a keyword, are written between backticks. The output parses
+    /// back to an equivalent ruleset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ooroo::RuleSet;
+    ///
+    /// let ruleset = RuleSet::from_dsl(
+    ///     "const MIN = 18\nrule adult: user.age >= MIN\nrule allow (priority 1): adult",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     ruleset.to_dsl().unwrap(),
+    ///     "rule adult:\n    user.age >= 18\n\nrule allow (priority 1):\n    adult\n"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ToDslError`](crate::ToDslError) if a rule compares against
+    /// a value with no DSL literal, such as a map or a NaN, or uses a name
+    /// that cannot be quoted. Such rulesets can only be built with
+    /// [`RuleSetBuilder`].
+    pub fn to_dsl(&self) -> Result<String, crate::ToDslError> {
+        crate::parse::rules_to_dsl(
+            self.schema.as_ref(),
+            &crate::decompile::decompile(self),
+            &self.terminals,
+            &self.tests,
+        )
+    }
+
+    /// Check `ctx` against the ruleset's schema. Always succeeds when the
+    /// ruleset was compiled without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns every declared field whose value has the wrong type. See
+    /// [`Schema::validate`].
+    pub fn validate_context(&self, ctx: &Context) -> Result<(), Vec<SchemaViolation>> {
+        self.schema
+            .as_ref()
+            .map_or(Ok(()), |schema| schema.validate(ctx))
+    }
+
+    /// Check `ctx` against what the rules expect: paths no rule references
+    /// (likely typos), referenced fields that are missing, and values whose
+    /// type does not match the schema or, for undeclared fields, cannot be
+    /// compared with the literals the rules test them against.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ooroo::{field, Context, RuleSetBuilder};
+    ///
+    /// let ruleset = RuleSetBuilder::new()
+    ///     .rule("adult", |r| r.when(field("user.age").gte(18_i64)))
+    ///     .rule("named", |r| r.when(field("user.name").like("a%")))
+    ///     .terminal("adult", 0)
+    ///     .terminal("named", 1)
+    ///     .compile()
+    ///     .unwrap();
+    ///
+    /// let ctx = Context::new().set("user.age", "30").set("user.nmae", "ann");
+    /// let report = ruleset.check_context(&ctx);
+    /// assert_eq!(report.unknown(), ["user.nmae"]);
+    /// assert_eq!(report.missing(), ["user.name"]);
+    /// assert_eq!(report.mistyped()[0].path, "user.age");
+    /// ```
+    pub fn check_context(&self, ctx: &Context) -> ValidationReport {
+        let unknown = ctx
+            .paths()
+            .into_iter()
+            .filter(|path| !validation::is_known_path(self, path))
+            .collect();
+        validation::report(self, &self.flatten_context(ctx), unknown)
+    }
+
     /// Flatten a `Context` into a `Vec<Option<Value>>` using the field registry.
     fn flatten_context(&self, ctx: &Context) -> Vec<Option<Value>> {
         let mut values = vec![None; self.field_registry.len()];
@@ -275,6 +850,111 @@ impl RuleSet {
         Ok(())
     }
 
+    /// Like [`to_bytes`](Self::to_bytes), but with the payload
+    /// LZ4-compressed.
+    ///
+    /// [`from_bytes`](Self::from_bytes) detects compressed blobs from their
+    /// header and decompresses them, so callers load both kinds the same
+    /// way. Compression pays off for large rulesets stored or shipped over
+    /// the network; decoding takes slightly longer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SerializeError`](crate::serial::SerializeError) if encoding fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ooroo::RuleSet;
+    ///
+    /// let ruleset = RuleSet::from_dsl("rule adult (priority 0): user.age >= 18").unwrap();
+    /// let blob = ruleset.to_bytes_compressed(None).unwrap();
+    /// assert!(ooroo::inspect(&blob).unwrap().is_compressed());
+    /// assert!(RuleSet::from_bytes(&blob).is_ok());
+    /// ```
+    #[cfg(feature = "compression")]
+    pub fn to_bytes_compressed(
+        &self,
+        source_text: Option<&str>,
+    ) -> Result<Vec<u8>, crate::serial::SerializeError> {
+        crate::serial::encode_compressed(self, source_text)
+    }
+
+    /// Like [`to_bytes`](Self::to_bytes), but signed with `key`.
+    ///
+    /// The Ed25519 signature covers the header and payload, so
+    /// [`from_bytes_verified`](Self::from_bytes_verified) rejects a blob
+    /// whose rules were changed by anyone without the key. Signed blobs
+    /// still load with [`from_bytes`](Self::from_bytes), which ignores the
+    /// signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SerializeError`](crate::serial::SerializeError) if encoding fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ooroo::{field, RuleSet, RuleSetBuilder, SigningKey};
+    ///
+    /// let ruleset = RuleSetBuilder::new()
+    ///     .rule("adult", |r| r.when(field("user.age").gte(18_i64)))
+    ///     .terminal("adult", 0)
+    ///     .compile()
+    ///     .unwrap();
+    /// let key = SigningKey::from_bytes(&[7; 32]);
+    ///
+    /// let blob = ruleset.to_bytes_signed(None, &key).unwrap();
+    /// assert!(RuleSet::from_bytes_verified(&blob, &[key.verifying_key()]).is_ok());
+    /// ```
+    #[cfg(feature = "signing")]
+    pub fn to_bytes_signed(
+        &self,
+        source_text: Option<&str>,
+        key: &ed25519_dalek::SigningKey,
+    ) -> Result<Vec<u8>, crate::serial::SerializeError> {
+        Ok(crate::serial::sign(self.to_bytes(source_text)?, key))
+    }
+
+    /// Like [`to_bytes_compressed`](Self::to_bytes_compressed), but signed
+    /// with `key` as by [`to_bytes_signed`](Self::to_bytes_signed). The
+    /// signature covers the compressed bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SerializeError`](crate::serial::SerializeError) if encoding fails.
+    #[cfg(all(feature = "signing", feature = "compression"))]
+    pub fn to_bytes_compressed_signed(
+        &self,
+        source_text: Option<&str>,
+        key: &ed25519_dalek::SigningKey,
+    ) -> Result<Vec<u8>, crate::serial::SerializeError> {
+        Ok(crate::serial::sign(
+            self.to_bytes_compressed(source_text)?,
+            key,
+        ))
+    }
+
+    /// Deserialize a blob produced by [`to_bytes_signed`](Self::to_bytes_signed),
+    /// accepting it only if it was signed by one of `keys`.
+    ///
+    /// The signature is checked before the payload is decoded. Passing
+    /// several keys allows rotating the signing key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeserializeError::MissingSignature`](crate::serial::DeserializeError::MissingSignature)
+    /// for an unsigned blob, [`DeserializeError::BadSignature`](crate::serial::DeserializeError::BadSignature)
+    /// if no key in `keys` verifies the signature, and the errors of
+    /// [`from_bytes`](Self::from_bytes) otherwise.
+    #[cfg(feature = "signing")]
+    pub fn from_bytes_verified(
+        bytes: &[u8],
+        keys: &[ed25519_dalek::VerifyingKey],
+    ) -> Result<Self, crate::serial::DeserializeError> {
+        crate::serial::decode_verified(bytes, keys)
+    }
+
     /// Read a file and deserialize the compiled ruleset it contains.
     ///
     /// # Errors
@@ -287,6 +967,83 @@ impl RuleSet {
         let bytes = std::fs::read(path)?;
         Self::from_bytes(&bytes)
     }
+
+    /// Load the ruleset compiled from the DSL file at `dsl_path`, using the
+    /// binary cache at `cache_path` while it is fresh.
+    ///
+    /// The cache is fresh if its [`source_digest`](crate::BlobInfo::source_digest)
+    /// matches the current text of `dsl_path` and of every file it imports,
+    /// directly or not, and it decodes. Imports are found by scanning for
+    /// `import` declarations, without parsing the rules. Otherwise the file
+    /// is recompiled with [`from_file()`](Self::from_file) and the cache
+    /// rewritten, so a missing, stale or damaged cache is rebuilt on the
+    /// next call. For a file without imports the digest is that of its text.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OorooError`](crate::OorooError) if the DSL file or a file
+    /// it imports cannot be read or compiled, or the cache cannot be
+    /// written.
+    pub fn load_cached(
+        dsl_path: impl AsRef<std::path::Path>,
+        cache_path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, crate::OorooError> {
+        let (dsl_path, cache_path) = (dsl_path.as_ref(), cache_path.as_ref());
+        let root = dsl_path
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new(""));
+        let entry = dsl_path.file_name().unwrap_or_default().to_string_lossy();
+        let loader = crate::FileLoader::new(root);
+        let sources = crate::parse::source_closure(&loader, &entry);
+
+        if let (Ok(sources), Ok(bytes)) = (&sources, std::fs::read(cache_path)) {
+            let fresh = crate::serial::inspect(&bytes)
+                .is_ok_and(|info| info.checksum_valid && info.matches_source(sources));
+            if fresh {
+                if let Ok(ruleset) = Self::from_bytes(&bytes) {
+                    return Ok(ruleset);
+                }
+            }
+        }
+
+        let ruleset = Self::from_loader(&loader, &entry)?;
+        ruleset.to_binary_file(cache_path, Some(&sources?))?;
+        Ok(ruleset)
+    }
+
+    /// Write this ruleset in the layout read by
+    /// [`RuleSetView`](crate::RuleSetView), which evaluates it in place.
+    ///
+    /// The bytes carry rule names, conditions, the `enabled` flag,
+    /// terminals and field paths; other metadata and the schema are only
+    /// kept by [`to_bytes`](Self::to_bytes).
+    #[must_use]
+    pub fn to_view_bytes(&self) -> Vec<u8> {
+        let payload = crate::view::layout::encode(self);
+        let mut buf = Vec::with_capacity(crate::serial::HEADER_SIZE + payload.len());
+        crate::serial::write_header(
+            &mut buf,
+            crate::view::layout::MAGIC,
+            crate::view::layout::VERSION,
+            &payload,
+        );
+        buf.extend_from_slice(&payload);
+        buf
+    }
+
+    /// Write [`to_view_bytes`](Self::to_view_bytes) to a file, ready to be
+    /// memory-mapped and passed to [`RuleSetView::from_bytes`](crate::RuleSetView::from_bytes).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SerializeError`](crate::serial::SerializeError) on I/O failure.
+    pub fn to_view_file(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), crate::serial::SerializeError> {
+        std::fs::write(path, self.to_view_bytes())?;
+        Ok(())
+    }
 }
 
 fn collect_rule_ref_indices(expr: &CompiledExpr, out: &mut Vec<usize>) {
@@ -296,7 +1053,14 @@ fn collect_rule_ref_indices(expr: &CompiledExpr, out: &mut Vec<usize>) {
             collect_rule_ref_indices(a, out);
             collect_rule_ref_indices(b, out);
         }
-        CompiledExpr::Not(inner) => collect_rule_ref_indices(inner, out),
+        CompiledExpr::Not(inner)
+        | CompiledExpr::Any {
+            predicate: inner, ..
+        }
+        | CompiledExpr::All {
+            predicate: inner, ..
+        }
+        | CompiledExpr::Lookup { body: inner, .. } => collect_rule_ref_indices(inner, out),
         CompiledExpr::AtLeast { exprs, .. } => {
             for e in exprs {
                 collect_rule_ref_indices(e, out);
@@ -310,7 +1074,11 @@ fn collect_rule_ref_indices(expr: &CompiledExpr, out: &mut Vec<usize>) {
         | CompiledExpr::NotLike { .. }
         | CompiledExpr::IsNull(_)
         | CompiledExpr::IsNotNull(_)
-        | CompiledExpr::CompareFields { .. } => {}
+        | CompiledExpr::CompareFields { .. }
+        | CompiledExpr::Contains { .. }
+        | CompiledExpr::SetCompare { .. }
+        | CompiledExpr::Len { .. }
+        | CompiledExpr::HasKey { .. } => {}
     }
 }
 
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use thiserror::Error;
+
+use super::context::Context;
+use super::expr::CompareOp;
+use super::value::Value;
+
+/// The declared type of a schema field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Int,
+    Float,
+    Bool,
+    String,
+    List,
+    Map,
+}
+
+impl FieldType {
+    /// The type of a value.
+    #[must_use]
+    pub fn of(value: &Value) -> FieldType {
+        match value {
+            Value::Int(_) => FieldType::Int,
+            Value::Float(_) => FieldType::Float,
+            Value::Bool(_) => FieldType::Bool,
+            Value::String(_) => FieldType::String,
+            Value::List(_) => FieldType::List,
+            Value::Map(_) => FieldType::Map,
+        }
+    }
+
+    /// Whether a field declared with this type may hold `value`. A `float`
+    /// field also accepts integers.
+    #[must_use]
+    pub fn accepts(self, value: &Value) -> bool {
+        let found = FieldType::of(value);
+        found == self || (self == FieldType::Float && found == FieldType::Int)
+    }
+
+    /// Whether values of the two types can be compared with `op`, following
+    /// [`Value::compare`]: numbers compare across `int` and `float`, bools,
+    /// lists and maps only support `==` and `!=`.
+    pub(crate) fn comparable(self, other: FieldType, op: CompareOp) -> bool {
+        let equality = matches!(op, CompareOp::Eq | CompareOp::Neq);
+        match (self, other) {
+            (FieldType::Int | FieldType::Float, FieldType::Int | FieldType::Float) => true,
+            (FieldType::String, FieldType::String) => true,
+            (FieldType::Bool, FieldType::Bool)
+            | (FieldType::List, FieldType::List)
+            | (FieldType::Map, FieldType::Map) => equality,
+            _ => false,
+        }
+    }
+
+    /// Parse a type name as written in the DSL (`int`, `float`, ...).
+    pub(crate) fn from_name(name: &str) -> Option<FieldType> {
+        Some(match name {
+            "int" => FieldType::Int,
+            "float" => FieldType::Float,
+            "bool" => FieldType::Bool,
+            "string" => FieldType::String,
+            "list" => FieldType::List,
+            "map" => FieldType::Map,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Int => write!(f, "int"),
+            FieldType::Float => write!(f, "float"),
+            FieldType::Bool => write!(f, "bool"),
+            FieldType::String => write!(f, "string"),
+            FieldType::List => write!(f, "list"),
+            FieldType::Map => write!(f, "map"),
+        }
+    }
+}
+
+/// Declared types for the context fields a ruleset may reference.
+///
+/// When a schema is attached to a [`RuleSetBuilder`](super::RuleSetBuilder)
+/// (or declared in the DSL with `field user.age: int`), compilation rejects
+/// rules that reference undeclared fields or compare a field against a value
+/// of an incompatible type. Paths below a field declared as `map` are not
+/// checked, since the schema does not describe the map's entries.
+///
+/// # Example
+///
+/// ```
+/// use ooroo::{field, CompileError, FieldType, RuleSetBuilder, Schema};
+///
+/// let schema = Schema::new().field("user.age", FieldType::Int);
+///
+/// let err = RuleSetBuilder::new()
+///     .schema(schema)
+///     .rule("adult", |r| r.when(field("user.age").gte("eighteen")))
+///     .terminal("adult", 0)
+///     .compile()
+///     .unwrap_err();
+/// assert!(matches!(err, CompileError::TypeMismatch { .. }));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    fields: BTreeMap<String, FieldType>,
+}
+
+impl Schema {
+    /// Create an empty schema.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a field. Declaring the same path again replaces its type.
+    #[must_use]
+    pub fn field(mut self, path: &str, field_type: FieldType) -> Self {
+        self.insert(path, field_type);
+        self
+    }
+
+    /// Declare a field (mutable reference version).
+    pub fn insert(&mut self, path: &str, field_type: FieldType) {
+        self.fields.insert(path.to_owned(), field_type);
+    }
+
+    /// The declared type of `path`, if it is declared.
+    #[must_use]
+    pub fn get(&self, path: &str) -> Option<FieldType> {
+        self.fields.get(path).copied()
+    }
+
+    /// Iterate over the declared fields in path order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, FieldType)> {
+        self.fields.iter().map(|(path, ty)| (path.as_str(), *ty))
+    }
+
+    /// Number of declared fields.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Whether no fields are declared.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Resolve a dotted path for type checking: `Some(Some(ty))` for a
+    /// declared field, `Some(None)` for a path inside a declared `map`, and
+    /// `None` for an undeclared path.
+    pub(crate) fn resolve(&self, path: &str) -> Option<Option<FieldType>> {
+        if let Some(ty) = self.get(path) {
+            return Some(Some(ty));
+        }
+        path.match_indices('.')
+            .any(|(i, _)| self.get(&path[..i]) == Some(FieldType::Map))
+            .then_some(None)
+    }
+
+    /// Check the values in `ctx` against the declared types.
+    ///
+    /// Missing fields are not violations; use `IS NOT NULL` rules to require
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// Returns every declared field whose value has the wrong type.
+    pub fn validate(&self, ctx: &Context) -> Result<(), Vec<SchemaViolation>> {
+        let violations: Vec<SchemaViolation> = self
+            .iter()
+            .filter_map(|(path, expected)| {
+                let value = ctx.get(path)?;
+                (!expected.accepts(value)).then(|| SchemaViolation {
+                    path: path.to_owned(),
+                    expected,
+                    found: FieldType::of(value),
+                })
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// A context value whose type does not match its [`Schema`] declaration.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("field '{path}' should be {expected} but is {found}")]
+pub struct SchemaViolation {
+    /// The declared field path.
+    pub path: String,
+    /// The declared type.
+    pub expected: FieldType,
+    /// The type of the value in the context.
+    pub found: FieldType,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn float_accepts_int_but_not_the_reverse() {
+        assert!(FieldType::Float.accepts(&Value::Int(1)));
+        assert!(!FieldType::Int.accepts(&Value::Float(1.0)));
+        assert!(!FieldType::String.accepts(&Value::Int(1)));
+    }
+
+    #[test]
+    fn comparability_follows_value_compare() {
+        assert!(FieldType::Int.comparable(FieldType::Float, CompareOp::Lt));
+        assert!(FieldType::Bool.comparable(FieldType::Bool, CompareOp::Eq));
+        assert!(!FieldType::Bool.comparable(FieldType::Bool, CompareOp::Gt));
+        assert!(!FieldType::Int.comparable(FieldType::String, CompareOp::Eq));
+    }
+
+    #[test]
+    fn resolve_declared_and_map_paths() {
+        let schema = Schema::new()
+            .field("user.age", FieldType::Int)
+            .field("limits", FieldType::Map);
+        assert_eq!(schema.resolve("user.age"), Some(Some(FieldType::Int)));
+        assert_eq!(schema.resolve("limits.US.daily"), Some(None));
+        assert_eq!(schema.resolve("user"), None);
+        assert_eq!(schema.resolve("user.age.x"), None);
+    }
+
+    #[test]
+    fn validate_reports_mistyped_fields() {
+        let schema = Schema::new()
+            .field("user.age", FieldType::Int)
+            .field("user.name", FieldType::String)
+            .field("score", FieldType::Float);
+        let ctx = Context::new().set("user.age", "old").set("score", 3_i64);
+        assert_eq!(
+            schema.validate(&ctx),
+            Err(vec![SchemaViolation {
+                path: "user.age".into(),
+                expected: FieldType::Int,
+                found: FieldType::String,
+            }])
+        );
+        assert_eq!(
+            schema.validate(&ctx).unwrap_err()[0].to_string(),
+            "field 'user.age' should be int but is string"
+        );
+        assert!(schema.validate(&Context::new()).is_ok());
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::ops::{BitAnd, BitOr, Not};
+
+/// A truth value in three-valued (Kleene) logic.
+///
+/// Produced by [`RuleSet::evaluate_three_valued()`](super::RuleSet::evaluate_three_valued),
+/// where a comparison against a missing or type-mismatched field is
+/// `Unknown` rather than `False`. `Unknown` propagates the way SQL `NULL`
+/// does: `False AND Unknown` is `False`, `True OR Unknown` is `True`, and
+/// `NOT Unknown` is `Unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Truth {
+    /// Definitely true.
+    True,
+    /// Definitely false.
+    False,
+    /// Not decidable from the context: a field was missing or had the
+    /// wrong type.
+    Unknown,
+}
+
+impl Truth {
+    /// Whether this is `Truth::True`.
+    #[must_use]
+    pub fn is_true(self) -> bool {
+        self == Truth::True
+    }
+
+    /// Whether this is `Truth::Unknown`.
+    #[must_use]
+    pub fn is_unknown(self) -> bool {
+        self == Truth::Unknown
+    }
+}
+
+impl From<bool> for Truth {
+    fn from(b: bool) -> Self {
+        if b {
+            Truth::True
+        } else {
+            Truth::False
+        }
+    }
+}
+
+/// `None` is `Unknown`.
+impl From<Option<bool>> for Truth {
+    fn from(b: Option<bool>) -> Self {
+        b.map_or(Truth::Unknown, Truth::from)
+    }
+}
+
+impl Not for Truth {
+    type Output = Truth;
+
+    fn not(self) -> Truth {
+        match self {
+            Truth::True => Truth::False,
+            Truth::False => Truth::True,
+            Truth::Unknown => Truth::Unknown,
+        }
+    }
+}
+
+impl BitAnd for Truth {
+    type Output = Truth;
+
+    fn bitand(self, rhs: Truth) -> Truth {
+        match (self, rhs) {
+            (Truth::False, _) | (_, Truth::False) => Truth::False,
+            (Truth::True, Truth::True) => Truth::True,
+            _ => Truth::Unknown,
+        }
+    }
+}
+
+impl BitOr for Truth {
+    type Output = Truth;
+
+    fn bitor(self, rhs: Truth) -> Truth {
+        match (self, rhs) {
+            (Truth::True, _) | (_, Truth::True) => Truth::True,
+            (Truth::False, Truth::False) => Truth::False,
+            _ => Truth::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for Truth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Truth::True => write!(f, "true"),
+            Truth::False => write!(f, "false"),
+            Truth::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Truth::{False, True, Unknown};
+    use super::*;
+
+    #[test]
+    fn kleene_and_or_not() {
+        assert_eq!(True & Unknown, Unknown);
+        assert_eq!(False & Unknown, False);
+        assert_eq!(Unknown & Unknown, Unknown);
+        assert_eq!(True | Unknown, True);
+        assert_eq!(False | Unknown, Unknown);
+        assert_eq!(!Unknown, Unknown);
+        assert_eq!(!True, False);
+    }
+
+    #[test]
+    fn from_option_and_display() {
+        assert_eq!(Truth::from(Some(true)), True);
+        assert_eq!(Truth::from(None), Unknown);
+        assert_eq!(Unknown.to_string(), "unknown");
+    }
+}
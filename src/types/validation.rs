@@ -0,0 +1,274 @@
+use std::fmt;
+
+use super::expr::{CompareOp, CompiledBound, CompiledExpr};
+use super::ruleset::RuleSet;
+use super::schema::{FieldType, SchemaViolation};
+use super::value::Value;
+
+/// Problems found when checking a context against a compiled ruleset.
+///
+/// Returned by [`RuleSet::check_context()`](super::RuleSet::check_context)
+/// and by strict [`ContextBuilder`](super::ContextBuilder)s. Paths in each
+/// list are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[must_use]
+pub struct ValidationReport {
+    unknown: Vec<String>,
+    missing: Vec<String>,
+    mistyped: Vec<SchemaViolation>,
+}
+
+impl ValidationReport {
+    pub(crate) fn new(
+        mut unknown: Vec<String>,
+        mut missing: Vec<String>,
+        mut mistyped: Vec<SchemaViolation>,
+    ) -> Self {
+        unknown.sort();
+        unknown.dedup();
+        missing.sort();
+        mistyped.sort_by(|a, b| a.path.cmp(&b.path));
+        Self {
+            unknown,
+            missing,
+            mistyped,
+        }
+    }
+
+    /// Paths that were set but that no rule references (and no schema
+    /// declares). These are usually typos.
+    #[must_use]
+    pub fn unknown(&self) -> &[String] {
+        &self.unknown
+    }
+
+    /// Fields referenced by rules that have no value.
+    #[must_use]
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// Values whose type does not match the field's schema declaration or,
+    /// for undeclared fields, cannot be compared with the literals the rules
+    /// test it against.
+    #[must_use]
+    pub fn mistyped(&self) -> &[SchemaViolation] {
+        &self.mistyped
+    }
+
+    /// Whether there are unknown or mistyped fields. Missing fields alone
+    /// are not errors, since rules may test for them with `IS NULL`.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        !self.unknown.is_empty() || !self.mistyped.is_empty()
+    }
+
+    /// Whether the report lists no problems at all.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.has_errors() && self.missing.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "context is valid");
+        }
+        let problems: Vec<String> = self
+            .unknown
+            .iter()
+            .map(|path| format!("unknown field '{path}'"))
+            .chain(
+                self.missing
+                    .iter()
+                    .map(|path| format!("missing field '{path}'")),
+            )
+            .chain(self.mistyped.iter().map(ToString::to_string))
+            .collect();
+        write!(f, "{}", problems.join("; "))
+    }
+}
+
+/// The type a ruleset expects for one of its context fields.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Expected {
+    /// Declared in the schema: values must have exactly this type (a `float`
+    /// field also takes integers).
+    Declared(FieldType),
+    /// Inferred from how the rules use the field: values must be comparable
+    /// with this type.
+    Inferred(FieldType),
+}
+
+impl Expected {
+    /// A violation if `value` does not meet the expectation.
+    pub(crate) fn check(self, path: &str, value: &Value) -> Option<SchemaViolation> {
+        let (expected, ok) = match self {
+            Expected::Declared(ty) => (ty, ty.accepts(value)),
+            Expected::Inferred(ty) => (ty, ty.comparable(FieldType::of(value), CompareOp::Eq)),
+        };
+        (!ok).then(|| SchemaViolation {
+            path: path.to_owned(),
+            expected,
+            found: FieldType::of(value),
+        })
+    }
+}
+
+/// The expected type of each registered context field, indexed by field
+/// registry slot. Schema declarations take precedence; otherwise the first
+/// use in execution order that implies a type wins.
+pub(crate) fn expected_types(ruleset: &RuleSet) -> Vec<Option<Expected>> {
+    let mut types: Vec<Option<Expected>> = vec![None; ruleset.field_registry.len()];
+    if let Some(schema) = &ruleset.schema {
+        for (path, &idx) in ruleset.field_registry.iter() {
+            types[idx] = schema.resolve(path).flatten().map(Expected::Declared);
+        }
+    }
+    for rule in &ruleset.rules {
+        infer_types(&rule.condition, &mut types);
+    }
+    types
+}
+
+fn infer_types(expr: &CompiledExpr, types: &mut [Option<Expected>]) {
+    // Slots past the registry are quantifier and lookup locals.
+    let mut expect = |slot: usize, ty: FieldType| {
+        if let Some(entry @ None) = types.get_mut(slot) {
+            *entry = Some(Expected::Inferred(ty));
+        }
+    };
+    let literal_type = |bound: &CompiledBound| match bound {
+        CompiledBound::Literal(Value::List(items)) => items.first().map(FieldType::of),
+        CompiledBound::Literal(value) => Some(FieldType::of(value)),
+        CompiledBound::FieldIndex(_) => None,
+    };
+    match expr {
+        CompiledExpr::Compare {
+            field_index, value, ..
+        } => expect(*field_index, FieldType::of(value)),
+        CompiledExpr::Between {
+            field_index,
+            low,
+            high,
+        } => {
+            if let Some(ty) = literal_type(low).or_else(|| literal_type(high)) {
+                expect(*field_index, ty);
+            }
+        }
+        CompiledExpr::In {
+            field_index,
+            members,
+        }
+        | CompiledExpr::NotIn {
+            field_index,
+            members,
+        } => {
+            if let Some(ty) = members.iter().find_map(literal_type) {
+                expect(*field_index, ty);
+            }
+        }
+        CompiledExpr::Like { field_index, .. } | CompiledExpr::NotLike { field_index, .. } => {
+            expect(*field_index, FieldType::String);
+        }
+        CompiledExpr::Contains { field_index, .. }
+        | CompiledExpr::SetCompare { field_index, .. } => expect(*field_index, FieldType::List),
+        CompiledExpr::HasKey { field_index, .. } => expect(*field_index, FieldType::Map),
+        CompiledExpr::Any {
+            list_index,
+            predicate,
+            ..
+        }
+        | CompiledExpr::All {
+            list_index,
+            predicate,
+            ..
+        } => {
+            expect(*list_index, FieldType::List);
+            infer_types(predicate, types);
+        }
+        CompiledExpr::And(a, b) | CompiledExpr::Or(a, b) => {
+            infer_types(a, types);
+            infer_types(b, types);
+        }
+        CompiledExpr::Not(inner) | CompiledExpr::Lookup { body: inner, .. } => {
+            infer_types(inner, types);
+        }
+        CompiledExpr::AtLeast { exprs, .. } => {
+            for e in exprs {
+                infer_types(e, types);
+            }
+        }
+        CompiledExpr::RuleRef(_)
+        | CompiledExpr::IsNull(_)
+        | CompiledExpr::IsNotNull(_)
+        | CompiledExpr::CompareFields { .. }
+        | CompiledExpr::Len { .. } => {}
+    }
+}
+
+/// Whether setting `path` can affect evaluation: a rule references it or a
+/// path inside it, or the schema declares it.
+pub(crate) fn is_known_path(ruleset: &RuleSet, path: &str) -> bool {
+    ruleset.field_registry.get(path).is_some()
+        || ruleset.field_registry.iter().any(|(registered, _)| {
+            registered
+                .strip_prefix(path)
+                .is_some_and(|rest| rest.starts_with('.'))
+        })
+        || ruleset
+            .schema
+            .as_ref()
+            .is_some_and(|schema| schema.resolve(path).is_some())
+}
+
+/// Build a report from the flattened field values of a context.
+pub(crate) fn report(
+    ruleset: &RuleSet,
+    values: &[Option<Value>],
+    unknown: Vec<String>,
+) -> ValidationReport {
+    let expected = expected_types(ruleset);
+    let mut missing = Vec::new();
+    let mut mistyped = Vec::new();
+    for (path, &idx) in ruleset.field_registry.iter() {
+        match &values[idx] {
+            None => missing.push(path.to_owned()),
+            Some(value) => mistyped.extend(expected[idx].and_then(|e| e.check(path, value))),
+        }
+    }
+    ValidationReport::new(unknown, missing, mistyped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_lists_every_problem() {
+        let report = ValidationReport::new(
+            vec!["user.agee".into()],
+            vec!["user.age".into()],
+            vec![SchemaViolation {
+                path: "score".into(),
+                expected: FieldType::Int,
+                found: FieldType::String,
+            }],
+        );
+        assert!(report.has_errors());
+        assert_eq!(
+            report.to_string(),
+            "unknown field 'user.agee'; missing field 'user.age'; \
+             field 'score' should be int but is string"
+        );
+        assert_eq!(ValidationReport::default().to_string(), "context is valid");
+    }
+
+    #[test]
+    fn missing_fields_alone_are_not_errors() {
+        let report = ValidationReport::new(vec![], vec!["x".into()], vec![]);
+        assert!(!report.has_errors());
+        assert!(!report.is_empty());
+    }
+}
@@ -1,10 +1,19 @@
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 
-use super::expr::CompareOp;
+use super::expr::{CompareOp, SetOp};
 
 /// Supported value types for rule evaluation.
+///
+/// With the `serde` feature, values serialize as plain JSON: integers,
+/// floats, booleans, strings, arrays and objects.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(untagged)
+)]
 pub enum Value {
     /// A 64-bit signed integer.
     Int(i64),
@@ -16,30 +25,30 @@ pub enum Value {
     String(String),
     /// An ordered list of values (heterogeneous elements allowed).
     List(Vec<Value>),
+    /// A string-keyed map of values. Paths and `[key]` lookups descend into it.
+    Map(BTreeMap<String, Value>),
 }
 
 impl Value {
     /// Compare this value to another using the given operator.
     /// Returns `None` for incompatible types or unsupported operations (e.g. Gt on bools).
-    /// Lists only support `Eq` and `Neq`; ordering comparisons return `None`.
+    /// Lists and maps only support `Eq` and `Neq`; ordering comparisons return `None`.
     #[must_use]
     pub fn compare(&self, op: CompareOp, other: &Value) -> Option<bool> {
-        if let (Value::List(a), Value::List(b)) = (self, other) {
+        let equal = match (self, other) {
+            (Value::List(a), Value::List(b)) => Some(a == b),
+            (Value::Map(a), Value::Map(b)) => Some(a == b),
+            _ => None,
+        };
+        if let Some(equal) = equal {
             return match op {
-                CompareOp::Eq => Some(a == b),
-                CompareOp::Neq => Some(a != b),
+                CompareOp::Eq => Some(equal),
+                CompareOp::Neq => Some(!equal),
                 _ => None,
             };
         }
         let ord = self.partial_cmp_value(other)?;
-        Some(match op {
-            CompareOp::Eq => ord == Ordering::Equal,
-            CompareOp::Neq => ord != Ordering::Equal,
-            CompareOp::Gt => ord == Ordering::Greater,
-            CompareOp::Gte => ord != Ordering::Less,
-            CompareOp::Lt => ord == Ordering::Less,
-            CompareOp::Lte => ord != Ordering::Greater,
-        })
+        Some(op.holds(ord))
     }
 
     /// Returns `true` if `self` is a `Value::List` that contains `item`.
@@ -54,6 +63,57 @@ impl Value {
         }
     }
 
+    /// Returns the entry for `key` if `self` is a `Value::Map`.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries.get(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the element at `index` if `self` is a `Value::List`. Negative
+    /// indices count back from the end, so `-1` is the last element.
+    #[must_use]
+    pub fn get_index(&self, index: i64) -> Option<&Value> {
+        let Value::List(items) = self else {
+            return None;
+        };
+        let position = if index < 0 {
+            items
+                .len()
+                .checked_sub(usize::try_from(index.unsigned_abs()).ok()?)?
+        } else {
+            usize::try_from(index).ok()?
+        };
+        items.get(position)
+    }
+
+    /// Descend through nested maps along a dot-separated path.
+    /// The empty path returns `self`.
+    pub(crate) fn get_path(&self, path: &str) -> Option<&Value> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        path.split('.').try_fold(self, |value, key| value.get(key))
+    }
+
+    /// Apply a set relation between two lists, treating `self` as the left operand.
+    /// Membership uses the same equality semantics as [`Value::contains`].
+    /// Returns `None` unless both values are lists.
+    #[must_use]
+    pub fn set_compare(&self, op: SetOp, other: &Value) -> Option<bool> {
+        let (Value::List(left), Value::List(_)) = (self, other) else {
+            return None;
+        };
+        Some(match op {
+            SetOp::Overlaps => left.iter().any(|v| other.contains(v)),
+            SetOp::SubsetOf => left.iter().all(|v| other.contains(v)),
+            SetOp::SupersetOf => return other.set_compare(SetOp::SubsetOf, self),
+            SetOp::DisjointFrom => !left.iter().any(|v| other.contains(v)),
+        })
+    }
+
     #[allow(clippy::cast_precision_loss)]
     fn partial_cmp_value(&self, other: &Value) -> Option<Ordering> {
         match (self, other) {
@@ -159,6 +219,18 @@ impl From<Vec<Value>> for Value {
     }
 }
 
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(v: BTreeMap<String, Value>) -> Self {
+        Value::Map(v)
+    }
+}
+
+impl From<HashMap<String, Value>> for Value {
+    fn from(v: HashMap<String, Value>) -> Self {
+        Value::Map(v.into_iter().collect())
+    }
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -176,6 +248,16 @@ impl fmt::Display for Value {
                 }
                 write!(f, "]")
             }
+            Value::Map(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, item)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "\"{key}\": {item}")?;
+                }
+                write!(f, "}}")
+            }
         }
     }
 }
@@ -191,7 +273,7 @@ mod tests {
 
     #[test]
     fn from_f64() {
-        assert_eq!(Value::from(3.14_f64), Value::Float(3.14));
+        assert_eq!(Value::from(3.25_f64), Value::Float(3.25));
     }
 
     #[test]
@@ -215,7 +297,7 @@ mod tests {
     #[test]
     fn display() {
         assert_eq!(Value::Int(42).to_string(), "42");
-        assert_eq!(Value::Float(3.14).to_string(), "3.14");
+        assert_eq!(Value::Float(3.25).to_string(), "3.25");
         assert_eq!(Value::Bool(true).to_string(), "true");
         assert_eq!(Value::String("hello".into()).to_string(), "\"hello\"");
     }
@@ -409,6 +491,94 @@ mod tests {
         assert!(list.contains(&Value::Int(10)));
     }
 
+    #[test]
+    fn set_compare_relations() {
+        let ab = Value::List(vec![Value::from("a"), Value::from("b")]);
+        let abc = Value::List(vec![Value::from("a"), Value::from("b"), Value::from("c")]);
+        let xy = Value::List(vec![Value::from("x"), Value::from("y")]);
+
+        assert_eq!(ab.set_compare(SetOp::SubsetOf, &abc), Some(true));
+        assert_eq!(abc.set_compare(SetOp::SubsetOf, &ab), Some(false));
+        assert_eq!(abc.set_compare(SetOp::SupersetOf, &ab), Some(true));
+        assert_eq!(ab.set_compare(SetOp::Overlaps, &abc), Some(true));
+        assert_eq!(ab.set_compare(SetOp::Overlaps, &xy), Some(false));
+        assert_eq!(ab.set_compare(SetOp::DisjointFrom, &xy), Some(true));
+        assert_eq!(ab.set_compare(SetOp::DisjointFrom, &abc), Some(false));
+    }
+
+    #[test]
+    fn set_compare_empty_and_cross_type() {
+        let empty = Value::List(vec![]);
+        let ints = Value::List(vec![Value::Int(1), Value::Int(1)]);
+        let floats = Value::List(vec![Value::Float(1.0)]);
+
+        assert_eq!(empty.set_compare(SetOp::SubsetOf, &ints), Some(true));
+        assert_eq!(empty.set_compare(SetOp::Overlaps, &ints), Some(false));
+        assert_eq!(empty.set_compare(SetOp::DisjointFrom, &empty), Some(true));
+        // Duplicates are ignored and Int/Float compare equal, as for `contains`.
+        assert_eq!(ints.set_compare(SetOp::SubsetOf, &floats), Some(true));
+        assert_eq!(floats.set_compare(SetOp::SupersetOf, &ints), Some(true));
+    }
+
+    #[test]
+    fn set_compare_non_list_returns_none() {
+        let list = Value::List(vec![Value::Int(1)]);
+        assert_eq!(list.set_compare(SetOp::Overlaps, &Value::Int(1)), None);
+        assert_eq!(Value::Int(1).set_compare(SetOp::SubsetOf, &list), None);
+    }
+
+    fn sample_map() -> Value {
+        let mut limits = BTreeMap::new();
+        limits.insert("US".to_owned(), Value::Int(100));
+        limits.insert(
+            "EU".to_owned(),
+            Value::Map(BTreeMap::from([("daily".to_owned(), Value::Int(50))])),
+        );
+        Value::Map(limits)
+    }
+
+    #[test]
+    fn map_get_and_get_path() {
+        let map = sample_map();
+        assert_eq!(map.get("US"), Some(&Value::Int(100)));
+        assert_eq!(map.get("JP"), None);
+        assert_eq!(map.get_path("EU.daily"), Some(&Value::Int(50)));
+        assert_eq!(map.get_path("US.daily"), None);
+        assert_eq!(map.get_path(""), Some(&map));
+        assert_eq!(Value::Int(1).get("US"), None);
+    }
+
+    #[test]
+    fn list_get_index() {
+        let list = Value::List(vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+        assert_eq!(list.get_index(0), Some(&Value::Int(1)));
+        assert_eq!(list.get_index(-1), Some(&Value::Int(3)));
+        assert_eq!(list.get_index(-3), Some(&Value::Int(1)));
+        assert_eq!(list.get_index(3), None);
+        assert_eq!(list.get_index(-4), None);
+        assert_eq!(list.get_index(i64::MIN), None);
+        assert_eq!(Value::Int(1).get_index(0), None);
+    }
+
+    #[test]
+    fn map_compare_eq_neq_only() {
+        let a = sample_map();
+        let b = sample_map();
+        assert_eq!(a.compare(CompareOp::Eq, &b), Some(true));
+        assert_eq!(a.compare(CompareOp::Neq, &b), Some(false));
+        assert_eq!(a.compare(CompareOp::Gt, &b), None);
+        assert_eq!(a.compare(CompareOp::Eq, &Value::List(vec![])), None);
+    }
+
+    #[test]
+    fn map_display_and_from_hashmap() {
+        let map = Value::from(HashMap::from([
+            ("b".to_owned(), Value::Int(2)),
+            ("a".to_owned(), Value::from("x")),
+        ]));
+        assert_eq!(map.to_string(), "{\"a\": \"x\", \"b\": 2}");
+    }
+
     #[test]
     fn compare_type_mismatch_returns_none() {
         let i = Value::Int(1);
@@ -1,18 +1,22 @@
 use std::fmt;
 
+use super::truth::Truth;
+
 /// The result of evaluating a [`RuleSet`](super::RuleSet) against a context.
 ///
 /// Contains the name of the matched terminal and whether it evaluated to `true`.
+/// Under three-valued evaluation a verdict may instead be indeterminate; see
+/// [`UnknownPolicy::Indeterminate`](super::UnknownPolicy::Indeterminate).
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[must_use]
 pub struct Verdict {
     terminal: String,
-    result: bool,
+    truth: Truth,
 }
 
 impl fmt::Display for Verdict {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} = {}", self.terminal, self.result)
+        write!(f, "{} = {}", self.terminal, self.truth)
     }
 }
 
@@ -21,7 +25,16 @@ impl Verdict {
     pub fn new(terminal: impl Into<String>, result: bool) -> Self {
         Self {
             terminal: terminal.into(),
-            result,
+            truth: Truth::from(result),
+        }
+    }
+
+    /// Create an indeterminate verdict: the terminal's rule evaluated to
+    /// [`Truth::Unknown`] and its policy is to surface that.
+    pub fn indeterminate(terminal: impl Into<String>) -> Self {
+        Self {
+            terminal: terminal.into(),
+            truth: Truth::Unknown,
         }
     }
 
@@ -31,10 +44,24 @@ impl Verdict {
         &self.terminal
     }
 
-    /// Whether the terminal's rule evaluated to `true`.
+    /// Whether the terminal's rule evaluated to `true`. `false` for an
+    /// indeterminate verdict.
     #[must_use]
     pub fn result(&self) -> bool {
-        self.result
+        self.truth.is_true()
+    }
+
+    /// The terminal's truth value: `Unknown` for an indeterminate verdict.
+    #[must_use]
+    pub fn truth(&self) -> Truth {
+        self.truth
+    }
+
+    /// Whether the terminal's rule could not be decided because of missing or
+    /// mismatched fields.
+    #[must_use]
+    pub fn is_indeterminate(&self) -> bool {
+        self.truth.is_unknown()
     }
 }
 
@@ -49,6 +76,16 @@ mod tests {
         assert!(v.result());
     }
 
+    #[test]
+    fn indeterminate_verdict() {
+        let v = Verdict::indeterminate("deny");
+        assert!(v.is_indeterminate());
+        assert!(!v.result());
+        assert_eq!(v.truth(), Truth::Unknown);
+        assert_eq!(v.to_string(), "deny = unknown");
+        assert_ne!(v, Verdict::new("deny", false));
+    }
+
     #[test]
     fn verdict_equality() {
         let v1 = Verdict::new("deny", false);
@@ -0,0 +1,745 @@
+//! Evaluation straight from a view's records. This mirrors
+//! `crate::evaluate`, with literals read in place as [`Lit`]s instead of
+//! owned [`Value`]s.
+
+use std::borrow::Cow;
+use std::time::Instant;
+
+use super::layout::{decode_compare_op, decode_set_op, step, tag, value_tag, Layout, FIELD_BOUND};
+use crate::evaluate::{forced_value, is_disabled, override_of, STACK_THRESHOLD};
+use crate::types::evaluation_report::EvaluationReport;
+use crate::types::value::like_match;
+use crate::types::{CompareOp, RuleOverride, SetOp};
+use crate::{Truth, UnknownPolicy, Value, Verdict};
+
+// ---------------------------------------------------------------------------
+// Literals
+// ---------------------------------------------------------------------------
+
+/// A literal value read in place from the value records.
+#[derive(Clone, Copy)]
+enum Lit<'e> {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(&'e str),
+    /// Pool start and length of the item value indices.
+    List(usize, usize),
+    /// Pool start and length of the `(key offset, key length, value)` entries.
+    Map(usize, usize),
+}
+
+/// A bound: a value from the context, or a literal.
+#[derive(Clone, Copy)]
+enum Operand<'e> {
+    Field(&'e Value),
+    Lit(Lit<'e>),
+}
+
+impl<'e> Layout<'e> {
+    fn lit(&self, value: u32) -> Lit<'e> {
+        let (tag, payload) = self.value(value);
+        #[allow(clippy::cast_possible_truncation)] // split in halves
+        let (low, high) = (payload as u32, (payload >> 32) as u32);
+        match tag {
+            value_tag::INT => Lit::Int(i64::from_le_bytes(payload.to_le_bytes())),
+            value_tag::FLOAT => Lit::Float(f64::from_bits(payload)),
+            value_tag::BOOL => Lit::Bool(payload != 0),
+            value_tag::STR => Lit::Str(self.string(low, high)),
+            value_tag::LIST => Lit::List(low as usize, high as usize),
+            _ => Lit::Map(low as usize, high as usize),
+        }
+    }
+
+    fn items(&self, start: usize, len: usize) -> impl Iterator<Item = Lit<'e>> + '_ {
+        (start..start + len).map(move |i| self.lit(self.pool(i)))
+    }
+
+    fn entries(&self, start: usize, len: usize) -> impl Iterator<Item = (&'e str, Lit<'e>)> + '_ {
+        (0..len).map(move |i| {
+            let at = start + i * 3;
+            (
+                self.string(self.pool(at), self.pool(at + 1)),
+                self.lit(self.pool(at + 2)),
+            )
+        })
+    }
+
+    /// Whether `value` equals `lit` exactly, as `Value`'s `PartialEq` does.
+    fn same(&self, value: &Value, lit: Lit<'e>) -> bool {
+        match (value, lit) {
+            (Value::Int(a), Lit::Int(b)) => *a == b,
+            (Value::Float(a), Lit::Float(b)) => *a == b,
+            (Value::Bool(a), Lit::Bool(b)) => *a == b,
+            (Value::String(a), Lit::Str(b)) => a == b,
+            (Value::List(a), Lit::List(start, len)) => {
+                a.len() == len
+                    && a.iter()
+                        .zip(self.items(start, len))
+                        .all(|(a, b)| self.same(a, b))
+            }
+            (Value::Map(a), Lit::Map(start, len)) => {
+                a.len() == len
+                    && a.iter()
+                        .zip(self.entries(start, len))
+                        .all(|((ka, va), (kb, vb))| ka == kb && self.same(va, vb))
+            }
+            _ => false,
+        }
+    }
+
+    /// [`Value::compare`] against a literal.
+    fn compare_lit(&self, value: &Value, op: CompareOp, lit: Lit<'e>) -> Option<bool> {
+        let equal = match (value, lit) {
+            (Value::List(_), Lit::List(..)) | (Value::Map(_), Lit::Map(..)) => {
+                self.same(value, lit)
+            }
+            (_, Lit::Int(n)) => return value.compare(op, &Value::Int(n)),
+            (_, Lit::Float(n)) => return value.compare(op, &Value::Float(n)),
+            (_, Lit::Bool(b)) => return value.compare(op, &Value::Bool(b)),
+            (Value::String(a), Lit::Str(b)) => return Some(op.holds(a.as_str().cmp(b))),
+            _ => return None,
+        };
+        match op {
+            CompareOp::Eq => Some(equal),
+            CompareOp::Neq => Some(!equal),
+            _ => None,
+        }
+    }
+
+    fn compare(&self, value: &Value, op: CompareOp, operand: Operand<'e>) -> Option<bool> {
+        match operand {
+            Operand::Field(other) => value.compare(op, other),
+            Operand::Lit(lit) => self.compare_lit(value, op, lit),
+        }
+    }
+
+    fn lit_contains(&self, start: usize, len: usize, value: &Value) -> bool {
+        self.items(start, len)
+            .any(|item| self.compare_lit(value, CompareOp::Eq, item) == Some(true))
+    }
+
+    /// Whether `value` equals a member of an `IN` list. A list-valued member
+    /// contributes each of its elements; a missing member contributes nothing.
+    fn is_member(
+        &self,
+        value: &Value,
+        start: usize,
+        len: usize,
+        field_values: &[Option<&'e Value>],
+        locals: &[Option<&'e Value>],
+    ) -> bool {
+        (start..start + len).any(|i| match self.bound(self.pool(i), field_values, locals) {
+            Some(Operand::Field(Value::List(items))) => items
+                .iter()
+                .any(|item| value.compare(CompareOp::Eq, item) == Some(true)),
+            Some(Operand::Lit(Lit::List(start, len))) => self.lit_contains(start, len, value),
+            Some(member) => self.compare(value, CompareOp::Eq, member) == Some(true),
+            None => false,
+        })
+    }
+
+    /// [`Value::set_compare`] with the right operand possibly a literal.
+    fn set_compare(&self, left: &Value, op: SetOp, right: Operand<'e>) -> Option<bool> {
+        let (start, len) = match right {
+            Operand::Field(right) => return left.set_compare(op, right),
+            Operand::Lit(Lit::List(start, len)) => (start, len),
+            Operand::Lit(_) => return None,
+        };
+        let Value::List(items) = left else {
+            return None;
+        };
+        let in_right = |v: &Value| self.lit_contains(start, len, v);
+        Some(match op {
+            SetOp::Overlaps => items.iter().any(in_right),
+            SetOp::SubsetOf => items.iter().all(in_right),
+            SetOp::SupersetOf => self.items(start, len).all(|r| {
+                items
+                    .iter()
+                    .any(|v| self.compare_lit(v, CompareOp::Eq, r) == Some(true))
+            }),
+            SetOp::DisjointFrom => !items.iter().any(in_right),
+        })
+    }
+
+    fn len_compare(&self, len: usize, op: CompareOp, n: Operand<'e>) -> Option<bool> {
+        self.compare(&Value::Int(i64::try_from(len).ok()?), op, n)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Slots, lookups and quantifiers
+// ---------------------------------------------------------------------------
+
+/// Resolve a field slot. Slots past the end of `field_values` address the
+/// element bindings of enclosing quantifiers and lookups, held in `locals`.
+#[inline]
+fn slot<'e>(
+    index: u32,
+    field_values: &[Option<&'e Value>],
+    locals: &[Option<&'e Value>],
+) -> Option<&'e Value> {
+    let index = index as usize;
+    match field_values.get(index) {
+        Some(v) => *v,
+        None => locals.get(index - field_values.len()).copied().flatten(),
+    }
+}
+
+/// A lookup step read from a lookups block.
+enum Step<'e> {
+    Key(&'e str),
+    Dynamic(u32),
+    Index(i64),
+    Wildcard,
+}
+
+/// One lookup of a lookups block: its local slot, base slot and steps.
+struct Lookup<'e> {
+    slot: u32,
+    base: u32,
+    steps: Vec<Step<'e>>,
+}
+
+/// Extend `locals` so that every slot in `slots` is addressable.
+fn extend_locals<'e>(
+    slots: impl Iterator<Item = u32>,
+    field_values: &[Option<&Value>],
+    locals: &[Option<&'e Value>],
+) -> Vec<Option<&'e Value>> {
+    let base = field_values.len();
+    let len = slots
+        .map(|s| s as usize - base + 1)
+        .max()
+        .unwrap_or(0)
+        .max(locals.len());
+    let mut scope = locals.to_vec();
+    scope.resize(len, None);
+    scope
+}
+
+/// Apply `steps` to `current`, pushing every value reached. A wildcard fans
+/// out over list elements or map values; branches that miss contribute nothing.
+fn collect_matches<'e>(
+    current: &'e Value,
+    steps: &[Step<'_>],
+    field_values: &[Option<&'e Value>],
+    locals: &[Option<&'e Value>],
+    out: &mut Vec<&'e Value>,
+) {
+    let Some((first, rest)) = steps.split_first() else {
+        out.push(current);
+        return;
+    };
+    match (first, current) {
+        (Step::Wildcard, Value::List(items)) => {
+            for item in items {
+                collect_matches(item, rest, field_values, locals, out);
+            }
+        }
+        (Step::Wildcard, Value::Map(entries)) => {
+            for value in entries.values() {
+                collect_matches(value, rest, field_values, locals, out);
+            }
+        }
+        (Step::Wildcard, _) => {}
+        _ => {
+            if let Some(next) = apply_step(current, first, field_values, locals) {
+                collect_matches(next, rest, field_values, locals, out);
+            }
+        }
+    }
+}
+
+/// Apply a single non-wildcard step to `current`.
+fn apply_step<'e>(
+    current: &'e Value,
+    step: &Step<'_>,
+    field_values: &[Option<&'e Value>],
+    locals: &[Option<&'e Value>],
+) -> Option<&'e Value> {
+    match step {
+        Step::Key(key) => current.get(key),
+        Step::Index(index) => current.get_index(*index),
+        Step::Dynamic(key_slot) => match slot(*key_slot, field_values, locals)? {
+            Value::String(key) => current.get(key),
+            Value::Int(index) => current.get_index(*index),
+            _ => None,
+        },
+        Step::Wildcard => None,
+    }
+}
+
+/// Apply a lookup's steps to the value at its base slot. A lookup with a
+/// wildcard step resolves to a list of every value reached.
+fn resolve_lookup<'e>(
+    lookup: &Lookup<'_>,
+    field_values: &[Option<&'e Value>],
+    locals: &[Option<&'e Value>],
+) -> Option<Cow<'e, Value>> {
+    let base = slot(lookup.base, field_values, locals)?;
+    if lookup.steps.iter().any(|s| matches!(s, Step::Wildcard)) {
+        let mut matches = Vec::new();
+        collect_matches(base, &lookup.steps, field_values, locals, &mut matches);
+        return Some(Cow::Owned(Value::List(
+            matches.into_iter().cloned().collect(),
+        )));
+    }
+    lookup
+        .steps
+        .iter()
+        .try_fold(base, |current, step| {
+            apply_step(current, step, field_values, locals)
+        })
+        .map(Cow::Borrowed)
+}
+
+/// Extend `locals` with the `resolved` lookup values bound to their slots.
+fn bind_lookups<'b>(
+    lookups: &[Lookup<'_>],
+    resolved: &'b [Option<Cow<'_, Value>>],
+    field_values: &[Option<&Value>],
+    locals: &[Option<&'b Value>],
+) -> Vec<Option<&'b Value>> {
+    let base = field_values.len();
+    let mut scope = extend_locals(lookups.iter().map(|l| l.slot), field_values, locals);
+    for (lookup, value) in lookups.iter().zip(resolved) {
+        scope[lookup.slot as usize - base] = value.as_deref();
+    }
+    scope
+}
+
+impl<'e> Layout<'e> {
+    fn bound(
+        &self,
+        bound: u32,
+        field_values: &[Option<&'e Value>],
+        locals: &[Option<&'e Value>],
+    ) -> Option<Operand<'e>> {
+        if bound & FIELD_BOUND == 0 {
+            Some(Operand::Lit(self.lit(bound)))
+        } else {
+            slot(bound & !FIELD_BOUND, field_values, locals).map(Operand::Field)
+        }
+    }
+
+    fn lookups(&self, start: u32) -> Vec<Lookup<'e>> {
+        let mut at = start as usize;
+        let count = self.pool(at);
+        at += 1;
+        (0..count)
+            .map(|_| {
+                let (slot, base, len) = (self.pool(at), self.pool(at + 1), self.pool(at + 2));
+                at += 3;
+                let steps = (0..len)
+                    .map(|_| {
+                        let (kind, x, y) = (self.pool(at), self.pool(at + 1), self.pool(at + 2));
+                        at += 3;
+                        match kind {
+                            step::KEY => Step::Key(self.string(x, y)),
+                            step::DYNAMIC => Step::Dynamic(x),
+                            step::INDEX => {
+                                let bits = u64::from(x) | u64::from(y) << 32;
+                                Step::Index(i64::from_le_bytes(bits.to_le_bytes()))
+                            }
+                            _ => Step::Wildcard,
+                        }
+                    })
+                    .collect();
+                Lookup { slot, base, steps }
+            })
+            .collect()
+    }
+
+    /// Resolve every lookup of a `LOOKUP` node and evaluate its body with
+    /// them bound.
+    fn with_lookups<T>(
+        &self,
+        start: u32,
+        field_values: &[Option<&'e Value>],
+        locals: &[Option<&'e Value>],
+        eval: impl FnOnce(&[Option<&Value>]) -> T,
+    ) -> T {
+        let lookups = self.lookups(start);
+        let resolved: Vec<_> = lookups
+            .iter()
+            .map(|lookup| resolve_lookup(lookup, field_values, locals))
+            .collect();
+        let scope = bind_lookups(&lookups, &resolved, field_values, locals);
+        eval(&scope)
+    }
+
+    /// Run `eval` once per element of `items`, with each element's bindings
+    /// written to a local scope extending `locals`. Stops at the first element
+    /// for which `eval` returns `true`, returning `true`.
+    fn any_element(
+        &self,
+        items: &'e [Value],
+        bindings: u32,
+        field_values: &[Option<&'e Value>],
+        locals: &[Option<&'e Value>],
+        mut eval: impl FnMut(&[Option<&'e Value>]) -> bool,
+    ) -> bool {
+        let mut at = bindings as usize;
+        let count = self.pool(at);
+        at += 1;
+        let bindings: Vec<(u32, Vec<&str>)> = (0..count)
+            .map(|_| {
+                let (slot, len) = (self.pool(at), self.pool(at + 1));
+                at += 2;
+                let path = (0..len)
+                    .map(|_| {
+                        let segment = self.string(self.pool(at), self.pool(at + 1));
+                        at += 2;
+                        segment
+                    })
+                    .collect();
+                (slot, path)
+            })
+            .collect();
+
+        let base = field_values.len();
+        let mut scope = extend_locals(bindings.iter().map(|(s, _)| *s), field_values, locals);
+        for item in items {
+            for (s, path) in &bindings {
+                scope[*s as usize - base] = path.iter().try_fold(item, |value, key| value.get(key));
+            }
+            if eval(&scope) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Expressions
+// ---------------------------------------------------------------------------
+
+impl<'e> Layout<'e> {
+    /// Evaluate a leaf test. `None` means the result is unknown.
+    fn eval_leaf(
+        &self,
+        node: u32,
+        field_values: &[Option<&'e Value>],
+        locals: &[Option<&'e Value>],
+    ) -> Option<bool> {
+        let n = self.node(node);
+        match n.tag {
+            tag::COMPARE => {
+                let value = slot(n.a, field_values, locals)?;
+                self.compare_lit(value, decode_compare_op(n.op), self.lit(n.b))
+            }
+            tag::IN => {
+                let value = slot(n.a, field_values, locals)?;
+                Some(self.is_member(value, n.b as usize, n.c as usize, field_values, locals))
+            }
+            tag::NOT_IN => {
+                let value = slot(n.a, field_values, locals)?;
+                Some(!self.is_member(value, n.b as usize, n.c as usize, field_values, locals))
+            }
+            tag::BETWEEN => {
+                let value = slot(n.a, field_values, locals)?;
+                let low = self.bound(n.b, field_values, locals)?;
+                let high = self.bound(n.c, field_values, locals)?;
+                match (
+                    self.compare(value, CompareOp::Gte, low),
+                    self.compare(value, CompareOp::Lte, high),
+                ) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                }
+            }
+            tag::LIKE | tag::NOT_LIKE => match slot(n.a, field_values, locals)? {
+                Value::String(s) => {
+                    Some(like_match(s, self.string(n.b, n.c)) == (n.tag == tag::LIKE))
+                }
+                _ => None,
+            },
+            tag::IS_NULL => Some(slot(n.a, field_values, locals).is_none()),
+            tag::IS_NOT_NULL => Some(slot(n.a, field_values, locals).is_some()),
+            tag::COMPARE_FIELDS => {
+                let left = slot(n.a, field_values, locals)?;
+                let right = slot(n.b, field_values, locals)?;
+                left.compare(decode_compare_op(n.op), right)
+            }
+            tag::CONTAINS => match slot(n.a, field_values, locals)? {
+                Value::List(items) => {
+                    let item = self.bound(n.b, field_values, locals)?;
+                    Some(
+                        items
+                            .iter()
+                            .any(|v| self.compare(v, CompareOp::Eq, item) == Some(true)),
+                    )
+                }
+                _ => None,
+            },
+            tag::SET_COMPARE => {
+                let left = slot(n.a, field_values, locals)?;
+                let right = self.bound(n.b, field_values, locals)?;
+                self.set_compare(left, decode_set_op(n.op), right)
+            }
+            tag::LEN => {
+                let bound = self.bound(n.b, field_values, locals)?;
+                match slot(n.a, field_values, locals)? {
+                    Value::List(items) => {
+                        self.len_compare(items.len(), decode_compare_op(n.op), bound)
+                    }
+                    Value::Map(entries) => {
+                        self.len_compare(entries.len(), decode_compare_op(n.op), bound)
+                    }
+                    _ => None,
+                }
+            }
+            tag::HAS_KEY => match (
+                slot(n.a, field_values, locals)?,
+                self.bound(n.b, field_values, locals)?,
+            ) {
+                (Value::Map(entries), Operand::Field(Value::String(k))) => {
+                    Some(entries.contains_key(k))
+                }
+                (Value::Map(entries), Operand::Lit(Lit::Str(k))) => Some(entries.contains_key(k)),
+                _ => None,
+            },
+            _ => unreachable!("not a leaf node"),
+        }
+    }
+
+    fn eval_expr(
+        &self,
+        node: u32,
+        field_values: &[Option<&'e Value>],
+        locals: &[Option<&'e Value>],
+        results: &[bool],
+    ) -> bool {
+        let n = self.node(node);
+        let children = || (n.b as usize..(n.b + n.c) as usize).map(|i| self.pool(i));
+        match n.tag {
+            tag::AND => children().all(|c| self.eval_expr(c, field_values, locals, results)),
+            tag::OR => children().any(|c| self.eval_expr(c, field_values, locals, results)),
+            tag::NOT => !self.eval_expr(n.a, field_values, locals, results),
+            tag::RULE_REF => results[n.a as usize],
+            tag::AT_LEAST => {
+                let mut count = 0u32;
+                for c in children() {
+                    if self.eval_expr(c, field_values, locals, results) {
+                        count += 1;
+                        if count >= n.a {
+                            return true;
+                        }
+                    }
+                }
+                count >= n.a
+            }
+            tag::ANY => match slot(n.a, field_values, locals) {
+                Some(Value::List(items)) => {
+                    self.any_element(items, n.c, field_values, locals, |scope| {
+                        self.eval_expr(n.b, field_values, scope, results)
+                    })
+                }
+                _ => false,
+            },
+            tag::ALL => match slot(n.a, field_values, locals) {
+                Some(Value::List(items)) => {
+                    !self.any_element(items, n.c, field_values, locals, |scope| {
+                        !self.eval_expr(n.b, field_values, scope, results)
+                    })
+                }
+                _ => false,
+            },
+            tag::LOOKUP => self.with_lookups(n.b, field_values, locals, |scope| {
+                self.eval_expr(n.a, field_values, scope, results)
+            }),
+            _ => self.eval_leaf(node, field_values, locals).unwrap_or(false),
+        }
+    }
+
+    /// Three-valued counterpart of [`Layout::eval_expr`].
+    fn eval_truth(
+        &self,
+        node: u32,
+        field_values: &[Option<&'e Value>],
+        locals: &[Option<&'e Value>],
+        results: &[Truth],
+    ) -> Truth {
+        let n = self.node(node);
+        let children = || (n.b as usize..(n.b + n.c) as usize).map(|i| self.pool(i));
+        match n.tag {
+            tag::AND => {
+                let mut acc = Truth::True;
+                for c in children() {
+                    acc = acc & self.eval_truth(c, field_values, locals, results);
+                    if acc == Truth::False {
+                        break;
+                    }
+                }
+                acc
+            }
+            tag::OR => {
+                let mut acc = Truth::False;
+                for c in children() {
+                    acc = acc | self.eval_truth(c, field_values, locals, results);
+                    if acc == Truth::True {
+                        break;
+                    }
+                }
+                acc
+            }
+            tag::NOT => !self.eval_truth(n.a, field_values, locals, results),
+            tag::RULE_REF => results[n.a as usize],
+            tag::AT_LEAST => {
+                let (mut known, mut unknown) = (0u32, 0u32);
+                for c in children() {
+                    match self.eval_truth(c, field_values, locals, results) {
+                        Truth::True => {
+                            known += 1;
+                            if known >= n.a {
+                                return Truth::True;
+                            }
+                        }
+                        Truth::Unknown => unknown += 1,
+                        Truth::False => {}
+                    }
+                }
+                if known >= n.a {
+                    Truth::True
+                } else if known + unknown >= n.a {
+                    Truth::Unknown
+                } else {
+                    Truth::False
+                }
+            }
+            tag::ANY | tag::ALL => match slot(n.a, field_values, locals) {
+                Some(Value::List(items)) => {
+                    // ANY looks for a true element, ALL for a false one.
+                    let target = if n.tag == tag::ANY {
+                        Truth::True
+                    } else {
+                        Truth::False
+                    };
+                    let mut unknown = false;
+                    let found = self.any_element(items, n.c, field_values, locals, |scope| {
+                        let t = self.eval_truth(n.b, field_values, scope, results);
+                        unknown |= t.is_unknown();
+                        t == target
+                    });
+                    if found {
+                        target
+                    } else if unknown {
+                        Truth::Unknown
+                    } else {
+                        !target
+                    }
+                }
+                _ => Truth::Unknown,
+            },
+            tag::LOOKUP => self.with_lookups(n.b, field_values, locals, |scope| {
+                self.eval_truth(n.a, field_values, scope, results)
+            }),
+            _ => Truth::from(self.eval_leaf(node, field_values, locals)),
+        }
+    }
+
+    #[inline]
+    fn forced(&self, rule: usize, overrides: &[Option<RuleOverride>]) -> Option<bool> {
+        forced_value(self.rule_enabled(rule), override_of(overrides, rule))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rulesets
+// ---------------------------------------------------------------------------
+
+fn with_results<T: Copy, R>(len: usize, init: T, f: impl FnOnce(&mut [T]) -> R) -> R {
+    if len <= STACK_THRESHOLD {
+        let mut results = [init; STACK_THRESHOLD];
+        f(&mut results)
+    } else {
+        f(&mut vec![init; len])
+    }
+}
+
+pub(super) fn evaluate(
+    layout: &Layout<'_>,
+    field_values: &[Option<&Value>],
+    overrides: &[Option<RuleOverride>],
+) -> Option<Verdict> {
+    with_results(layout.rule_count(), false, |results| {
+        for rule in 0..layout.rule_count() {
+            results[rule] = match layout.forced(rule, overrides) {
+                Some(value) => value,
+                None => layout.eval_expr(layout.rule_root(rule), field_values, &[], results),
+            };
+        }
+        (0..layout.terminal_count())
+            .map(|t| layout.terminal(t).0)
+            .find(|&rule| !is_disabled(overrides, rule) && results[rule])
+            .map(|rule| Verdict::new(layout.rule_name(rule), true))
+    })
+}
+
+pub(super) fn evaluate_three_valued(
+    layout: &Layout<'_>,
+    field_values: &[Option<&Value>],
+    overrides: &[Option<RuleOverride>],
+) -> Option<Verdict> {
+    with_results(layout.rule_count(), Truth::False, |results| {
+        for rule in 0..layout.rule_count() {
+            results[rule] = match layout.forced(rule, overrides) {
+                Some(value) => Truth::from(value),
+                None => layout.eval_truth(layout.rule_root(rule), field_values, &[], results),
+            };
+        }
+        for t in 0..layout.terminal_count() {
+            let (rule, _, on_unknown) = layout.terminal(t);
+            if is_disabled(overrides, rule) {
+                continue;
+            }
+            match results[rule] {
+                Truth::True => return Some(Verdict::new(layout.rule_name(rule), true)),
+                Truth::Unknown if on_unknown == UnknownPolicy::Indeterminate => {
+                    return Some(Verdict::indeterminate(layout.rule_name(rule)));
+                }
+                _ => {}
+            }
+        }
+        None
+    })
+}
+
+pub(super) fn evaluate_detailed(
+    layout: &Layout<'_>,
+    field_values: &[Option<&Value>],
+    overrides: &[Option<RuleOverride>],
+) -> EvaluationReport {
+    let start = Instant::now();
+    let rule_count = layout.rule_count();
+    let mut evaluation_order = Vec::with_capacity(rule_count);
+    let mut evaluated = Vec::new();
+    let mut overridden = Vec::new();
+
+    let verdict = with_results(rule_count, false, |results| {
+        for rule in 0..rule_count {
+            let name = layout.rule_name(rule);
+            if let Some(value) = override_of(overrides, rule) {
+                overridden.push((name.to_owned(), value));
+            }
+            results[rule] = match layout.forced(rule, overrides) {
+                Some(value) => value,
+                None => layout.eval_expr(layout.rule_root(rule), field_values, &[], results),
+            };
+            evaluation_order.push(name.to_owned());
+            if results[rule] {
+                evaluated.push(name.to_owned());
+            }
+        }
+        (0..layout.terminal_count())
+            .map(|t| layout.terminal(t).0)
+            .find(|&rule| !is_disabled(overrides, rule) && results[rule])
+            .map(|rule| Verdict::new(layout.rule_name(rule), true))
+    });
+
+    let duration = start.elapsed();
+    EvaluationReport::new(verdict, evaluated, evaluation_order, overridden, duration)
+}
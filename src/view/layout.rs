@@ -0,0 +1,1062 @@
+//! The view layout: fixed-size little-endian records that can be read in
+//! place, so a blob is evaluated without decoding it into owned structures.
+//!
+//! ## Payload
+//!
+//! The payload follows the same 32-byte header as the bincode format, with
+//! magic `b"OORV"`. It starts with eight `u32` counts, then one section per
+//! count, back to back:
+//!
+//! ```text
+//! Section    Record size  Contents
+//! counts     32           rules, terminals, fields, nodes, values, pool
+//!                         words, string bytes, reserved (0)
+//! rules      20           name (offset, length), first node, root node, flags
+//! terminals  12           rule index, priority, on_unknown
+//! fields     8            path (offset, length), by field slot
+//! nodes      16           tag | op << 8, then three operands a, b, c
+//! values     16           tag, reserved, 8-byte payload
+//! pool       4            u32 words for variable-length operands
+//! strings    1            UTF-8 text referenced by (offset, length) pairs
+//! ```
+//!
+//! Each rule owns the contiguous run of nodes ending at its root, and every
+//! node refers only to earlier nodes, so validation is one forward pass and
+//! evaluation cannot loop. Values likewise refer only to earlier values.
+//!
+//! A bound is one pool word: a field slot with [`FIELD_BOUND`] set, or a
+//! value index. The node operands are:
+//!
+//! ```text
+//! Tag                          a            b               c
+//! AND, OR                      -            pool start      child count
+//! NOT                          child        -               -
+//! RULE_REF                     rule index   -               -
+//! COMPARE                      slot         value           -
+//! IN, NOT_IN                   slot         pool start      bound count
+//! BETWEEN                      slot         low bound       high bound
+//! LIKE, NOT_LIKE               slot         pattern offset  pattern length
+//! IS_NULL, IS_NOT_NULL         slot         -               -
+//! COMPARE_FIELDS               left slot    right slot      -
+//! AT_LEAST                     n            pool start      child count
+//! ANY, ALL                     list slot    predicate       bindings start
+//! CONTAINS, SET_COMPARE, LEN,  slot         bound           -
+//! HAS_KEY
+//! LOOKUP                       body         lookups start   -
+//! ```
+//!
+//! A bindings block is a count, then per binding its local slot, a segment
+//! count and a string reference per segment. A lookups block is a count,
+//! then per lookup its local slot, base slot, a step count and three words
+//! per step: kind, then a string reference for `KEY`, a slot for
+//! `DYNAMIC`, or the low and high halves of the index for `INDEX`.
+
+use std::collections::HashMap;
+
+use crate::serial::DeserializeError;
+use crate::types::{
+    CompareOp, CompiledBound, CompiledExpr, CompiledRule, CompiledStep, RuleSet, SetOp,
+    UnknownPolicy, Value,
+};
+
+pub(crate) const MAGIC: &[u8; 4] = b"OORV";
+pub(crate) const VERSION: u16 = 1;
+
+const COUNTS_SIZE: usize = 32;
+const RULE_SIZE: usize = 20;
+const TERMINAL_SIZE: usize = 12;
+const FIELD_SIZE: usize = 8;
+const NODE_SIZE: usize = 16;
+const VALUE_SIZE: usize = 16;
+
+/// Set on a bound word that holds a field slot rather than a value index.
+pub(super) const FIELD_BOUND: u32 = 1 << 31;
+
+/// Node tags.
+pub(super) mod tag {
+    pub(crate) const AND: u8 = 0;
+    pub(crate) const OR: u8 = 1;
+    pub(crate) const NOT: u8 = 2;
+    pub(crate) const RULE_REF: u8 = 3;
+    pub(crate) const COMPARE: u8 = 4;
+    pub(crate) const IN: u8 = 5;
+    pub(crate) const NOT_IN: u8 = 6;
+    pub(crate) const BETWEEN: u8 = 7;
+    pub(crate) const LIKE: u8 = 8;
+    pub(crate) const NOT_LIKE: u8 = 9;
+    pub(crate) const IS_NULL: u8 = 10;
+    pub(crate) const IS_NOT_NULL: u8 = 11;
+    pub(crate) const COMPARE_FIELDS: u8 = 12;
+    pub(crate) const AT_LEAST: u8 = 13;
+    pub(crate) const ANY: u8 = 14;
+    pub(crate) const ALL: u8 = 15;
+    pub(crate) const CONTAINS: u8 = 16;
+    pub(crate) const SET_COMPARE: u8 = 17;
+    pub(crate) const LEN: u8 = 18;
+    pub(crate) const HAS_KEY: u8 = 19;
+    pub(crate) const LOOKUP: u8 = 20;
+}
+
+/// Value tags.
+pub(super) mod value_tag {
+    pub(crate) const INT: u32 = 0;
+    pub(crate) const FLOAT: u32 = 1;
+    pub(crate) const BOOL: u32 = 2;
+    pub(crate) const STR: u32 = 3;
+    pub(crate) const LIST: u32 = 4;
+    pub(crate) const MAP: u32 = 5;
+}
+
+/// Lookup step kinds.
+pub(super) mod step {
+    pub(crate) const KEY: u32 = 0;
+    pub(crate) const DYNAMIC: u32 = 1;
+    pub(crate) const INDEX: u32 = 2;
+    pub(crate) const WILDCARD: u32 = 3;
+}
+
+/// Rule flag: the rule's metadata marks it enabled.
+const ENABLED: u32 = 1;
+
+#[inline]
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().expect("four bytes"))
+}
+
+#[inline]
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().expect("eight bytes"))
+}
+
+// ---------------------------------------------------------------------------
+// Encoding
+// ---------------------------------------------------------------------------
+
+#[allow(clippy::cast_possible_truncation)] // blobs never exceed 4 GiB
+fn word(n: usize) -> u32 {
+    n as u32
+}
+
+#[derive(Default)]
+struct Encoder {
+    nodes: Vec<[u32; 4]>,
+    values: Vec<(u32, u64)>,
+    pool: Vec<u32>,
+    strings: String,
+    interned: HashMap<String, (u32, u32)>,
+}
+
+impl Encoder {
+    fn string(&mut self, s: &str) -> (u32, u32) {
+        if let Some(&r) = self.interned.get(s) {
+            return r;
+        }
+        let r = (word(self.strings.len()), word(s.len()));
+        self.strings.push_str(s);
+        self.interned.insert(s.to_owned(), r);
+        r
+    }
+
+    fn value(&mut self, value: &Value) -> u32 {
+        let record = match value {
+            Value::Int(v) => (value_tag::INT, u64::from_le_bytes(v.to_le_bytes())),
+            Value::Float(v) => (value_tag::FLOAT, v.to_bits()),
+            Value::Bool(v) => (value_tag::BOOL, u64::from(*v)),
+            Value::String(s) => {
+                let (offset, len) = self.string(s);
+                (value_tag::STR, u64::from(offset) | u64::from(len) << 32)
+            }
+            Value::List(items) => {
+                let items: Vec<u32> = items.iter().map(|item| self.value(item)).collect();
+                let start = word(self.pool.len());
+                self.pool.extend(&items);
+                (
+                    value_tag::LIST,
+                    u64::from(start) | u64::from(word(items.len())) << 32,
+                )
+            }
+            Value::Map(entries) => {
+                let entries: Vec<(u32, u32, u32)> = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let (offset, len) = self.string(key);
+                        (offset, len, self.value(value))
+                    })
+                    .collect();
+                let start = word(self.pool.len());
+                for (offset, len, value) in &entries {
+                    self.pool.extend([offset, len, value]);
+                }
+                (
+                    value_tag::MAP,
+                    u64::from(start) | u64::from(word(entries.len())) << 32,
+                )
+            }
+        };
+        self.values.push(record);
+        word(self.values.len() - 1)
+    }
+
+    fn bound(&mut self, bound: &CompiledBound) -> u32 {
+        match bound {
+            CompiledBound::Literal(value) => self.value(value),
+            CompiledBound::FieldIndex(slot) => FIELD_BOUND | word(*slot),
+        }
+    }
+
+    fn node(&mut self, tag: u8, op: u8, operands: [u32; 3]) -> u32 {
+        let [a, b, c] = operands;
+        self.nodes
+            .push([u32::from(tag) | u32::from(op) << 8, a, b, c]);
+        word(self.nodes.len() - 1)
+    }
+
+    /// Append `words` to the pool, returning where they start.
+    fn block(&mut self, words: &[u32]) -> u32 {
+        let start = word(self.pool.len());
+        self.pool.extend(words);
+        start
+    }
+
+    /// Encode `expr`'s children before `expr` itself, so every node refers
+    /// only to earlier nodes. `AND` and `OR` chains become one n-ary node.
+    fn expr(&mut self, expr: &CompiledExpr) -> u32 {
+        match expr {
+            CompiledExpr::And(..) | CompiledExpr::Or(..) => {
+                let is_and = matches!(expr, CompiledExpr::And(..));
+                let mut operands = Vec::new();
+                chain(expr, is_and, &mut operands);
+                let children: Vec<u32> = operands.into_iter().map(|e| self.expr(e)).collect();
+                let start = self.block(&children);
+                let tag = if is_and { tag::AND } else { tag::OR };
+                self.node(tag, 0, [0, start, word(children.len())])
+            }
+            CompiledExpr::Not(inner) => {
+                let inner = self.expr(inner);
+                self.node(tag::NOT, 0, [inner, 0, 0])
+            }
+            CompiledExpr::RuleRef(idx) => self.node(tag::RULE_REF, 0, [word(*idx), 0, 0]),
+            CompiledExpr::Compare {
+                field_index,
+                op,
+                value,
+            } => {
+                let value = self.value(value);
+                self.node(
+                    tag::COMPARE,
+                    compare_op(*op),
+                    [word(*field_index), value, 0],
+                )
+            }
+            CompiledExpr::In {
+                field_index,
+                members,
+            }
+            | CompiledExpr::NotIn {
+                field_index,
+                members,
+            } => {
+                let bounds: Vec<u32> = members.iter().map(|m| self.bound(m)).collect();
+                let start = self.block(&bounds);
+                let tag = if matches!(expr, CompiledExpr::In { .. }) {
+                    tag::IN
+                } else {
+                    tag::NOT_IN
+                };
+                self.node(tag, 0, [word(*field_index), start, word(bounds.len())])
+            }
+            CompiledExpr::Between {
+                field_index,
+                low,
+                high,
+            } => {
+                let low = self.bound(low);
+                let high = self.bound(high);
+                self.node(tag::BETWEEN, 0, [word(*field_index), low, high])
+            }
+            CompiledExpr::Like {
+                field_index,
+                pattern,
+            }
+            | CompiledExpr::NotLike {
+                field_index,
+                pattern,
+            } => {
+                let (offset, len) = self.string(pattern);
+                let tag = if matches!(expr, CompiledExpr::Like { .. }) {
+                    tag::LIKE
+                } else {
+                    tag::NOT_LIKE
+                };
+                self.node(tag, 0, [word(*field_index), offset, len])
+            }
+            CompiledExpr::IsNull(slot) => self.node(tag::IS_NULL, 0, [word(*slot), 0, 0]),
+            CompiledExpr::IsNotNull(slot) => self.node(tag::IS_NOT_NULL, 0, [word(*slot), 0, 0]),
+            CompiledExpr::CompareFields {
+                left_index,
+                op,
+                right_index,
+            } => self.node(
+                tag::COMPARE_FIELDS,
+                compare_op(*op),
+                [word(*left_index), word(*right_index), 0],
+            ),
+            CompiledExpr::AtLeast { n, exprs } => {
+                let children: Vec<u32> = exprs.iter().map(|e| self.expr(e)).collect();
+                let start = self.block(&children);
+                self.node(tag::AT_LEAST, 0, [word(*n), start, word(children.len())])
+            }
+            CompiledExpr::Any {
+                list_index,
+                bindings,
+                predicate,
+            }
+            | CompiledExpr::All {
+                list_index,
+                bindings,
+                predicate,
+            } => {
+                let predicate = self.expr(predicate);
+                let mut words = vec![word(bindings.len())];
+                for (slot, path) in bindings {
+                    words.extend([word(*slot), word(path.len())]);
+                    for segment in path {
+                        let (offset, len) = self.string(segment);
+                        words.extend([offset, len]);
+                    }
+                }
+                let start = self.block(&words);
+                let tag = if matches!(expr, CompiledExpr::Any { .. }) {
+                    tag::ANY
+                } else {
+                    tag::ALL
+                };
+                self.node(tag, 0, [word(*list_index), predicate, start])
+            }
+            CompiledExpr::Contains { field_index, value } => {
+                let value = self.bound(value);
+                self.node(tag::CONTAINS, 0, [word(*field_index), value, 0])
+            }
+            CompiledExpr::SetCompare {
+                field_index,
+                op,
+                value,
+            } => {
+                let value = self.bound(value);
+                self.node(
+                    tag::SET_COMPARE,
+                    set_op(*op),
+                    [word(*field_index), value, 0],
+                )
+            }
+            CompiledExpr::Len {
+                field_index,
+                op,
+                value,
+            } => {
+                let value = self.bound(value);
+                self.node(tag::LEN, compare_op(*op), [word(*field_index), value, 0])
+            }
+            CompiledExpr::HasKey { field_index, key } => {
+                let key = self.bound(key);
+                self.node(tag::HAS_KEY, 0, [word(*field_index), key, 0])
+            }
+            CompiledExpr::Lookup { lookups, body } => {
+                let body = self.expr(body);
+                let mut words = vec![word(lookups.len())];
+                for lookup in lookups {
+                    words.extend([
+                        word(lookup.slot),
+                        word(lookup.base),
+                        word(lookup.steps.len()),
+                    ]);
+                    for s in &lookup.steps {
+                        match s {
+                            CompiledStep::Key(key) => {
+                                let (offset, len) = self.string(key);
+                                words.extend([step::KEY, offset, len]);
+                            }
+                            CompiledStep::Dynamic(slot) => {
+                                words.extend([step::DYNAMIC, word(*slot), 0]);
+                            }
+                            CompiledStep::Index(index) => {
+                                let bits = u64::from_le_bytes(index.to_le_bytes());
+                                #[allow(clippy::cast_possible_truncation)] // split in halves
+                                words.extend([step::INDEX, bits as u32, (bits >> 32) as u32]);
+                            }
+                            CompiledStep::Wildcard => words.extend([step::WILDCARD, 0, 0]),
+                        }
+                    }
+                }
+                let start = self.block(&words);
+                self.node(tag::LOOKUP, 0, [body, start, 0])
+            }
+        }
+    }
+}
+
+/// The operands of a chain of `AND` (or `OR`) nodes, left to right.
+fn chain<'e>(expr: &'e CompiledExpr, is_and: bool, out: &mut Vec<&'e CompiledExpr>) {
+    match (expr, is_and) {
+        (CompiledExpr::And(a, b), true) | (CompiledExpr::Or(a, b), false) => {
+            chain(a, is_and, out);
+            chain(b, is_and, out);
+        }
+        _ => out.push(expr),
+    }
+}
+
+fn compare_op(op: CompareOp) -> u8 {
+    match op {
+        CompareOp::Eq => 0,
+        CompareOp::Neq => 1,
+        CompareOp::Gt => 2,
+        CompareOp::Gte => 3,
+        CompareOp::Lt => 4,
+        CompareOp::Lte => 5,
+    }
+}
+
+pub(super) fn decode_compare_op(op: u8) -> CompareOp {
+    match op {
+        0 => CompareOp::Eq,
+        1 => CompareOp::Neq,
+        2 => CompareOp::Gt,
+        3 => CompareOp::Gte,
+        4 => CompareOp::Lt,
+        _ => CompareOp::Lte,
+    }
+}
+
+fn set_op(op: SetOp) -> u8 {
+    match op {
+        SetOp::Overlaps => 0,
+        SetOp::SubsetOf => 1,
+        SetOp::SupersetOf => 2,
+        SetOp::DisjointFrom => 3,
+    }
+}
+
+pub(super) fn decode_set_op(op: u8) -> SetOp {
+    match op {
+        0 => SetOp::Overlaps,
+        1 => SetOp::SubsetOf,
+        2 => SetOp::SupersetOf,
+        _ => SetOp::DisjointFrom,
+    }
+}
+
+/// Encode `ruleset` as a view payload, without the header.
+pub(crate) fn encode(ruleset: &RuleSet) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+
+    let mut rules: Vec<&CompiledRule> = ruleset.rules.iter().collect();
+    rules.sort_by_key(|rule| rule.index);
+    let mut rule_records = Vec::with_capacity(rules.len());
+    for rule in rules {
+        let first = word(encoder.nodes.len());
+        let root = encoder.expr(&rule.condition);
+        let (offset, len) = encoder.string(&rule.name);
+        let flags = if rule.metadata.enabled { ENABLED } else { 0 };
+        rule_records.push([offset, len, first, root, flags]);
+    }
+
+    let mut paths = vec![""; ruleset.field_registry.len()];
+    for (path, &idx) in ruleset.field_registry.iter() {
+        paths[idx] = path;
+    }
+    let fields: Vec<(u32, u32)> = paths.iter().map(|path| encoder.string(path)).collect();
+
+    let terminals =
+        ruleset
+            .terminals
+            .iter()
+            .zip(&ruleset.terminal_indices)
+            .map(|(terminal, &idx)| {
+                let on_unknown = match terminal.on_unknown {
+                    UnknownPolicy::AsFalse => 0,
+                    UnknownPolicy::Indeterminate => 1,
+                };
+                [word(idx), terminal.priority, on_unknown]
+            });
+
+    let counts = [
+        rule_records.len(),
+        ruleset.terminals.len(),
+        fields.len(),
+        encoder.nodes.len(),
+        encoder.values.len(),
+        encoder.pool.len(),
+        encoder.strings.len(),
+        0,
+    ];
+    let mut out = Vec::new();
+    let mut put = |w: u32| out.extend_from_slice(&w.to_le_bytes());
+    for count in counts {
+        put(word(count));
+    }
+    for record in rule_records {
+        record.into_iter().for_each(&mut put);
+    }
+    for record in terminals {
+        record.into_iter().for_each(&mut put);
+    }
+    for (offset, len) in fields {
+        put(offset);
+        put(len);
+    }
+    for record in &encoder.nodes {
+        record.iter().copied().for_each(&mut put);
+    }
+    for &(tag, payload) in &encoder.values {
+        put(tag);
+        put(0);
+        #[allow(clippy::cast_possible_truncation)] // split in halves
+        {
+            put(payload as u32);
+            put((payload >> 32) as u32);
+        }
+    }
+    for &w in &encoder.pool {
+        put(w);
+    }
+    out.extend_from_slice(encoder.strings.as_bytes());
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Reading
+// ---------------------------------------------------------------------------
+
+/// A node record.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Node {
+    pub(super) tag: u8,
+    pub(super) op: u8,
+    pub(super) a: u32,
+    pub(super) b: u32,
+    pub(super) c: u32,
+}
+
+/// The sections of a validated view payload. Every accessor trusts the
+/// checks made by [`Layout::parse`].
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Layout<'a> {
+    rules: &'a [u8],
+    terminals: &'a [u8],
+    fields: &'a [u8],
+    nodes: &'a [u8],
+    values: &'a [u8],
+    pool: &'a [u8],
+    strings: &'a str,
+}
+
+impl<'a> Layout<'a> {
+    pub(super) fn rule_count(&self) -> usize {
+        self.rules.len() / RULE_SIZE
+    }
+
+    pub(super) fn rule_name(&self, rule: usize) -> &'a str {
+        let at = rule * RULE_SIZE;
+        self.string(read_u32(self.rules, at), read_u32(self.rules, at + 4))
+    }
+
+    pub(super) fn rule_root(&self, rule: usize) -> u32 {
+        read_u32(self.rules, rule * RULE_SIZE + 12)
+    }
+
+    pub(super) fn rule_enabled(&self, rule: usize) -> bool {
+        read_u32(self.rules, rule * RULE_SIZE + 16) & ENABLED != 0
+    }
+
+    pub(super) fn terminal_count(&self) -> usize {
+        self.terminals.len() / TERMINAL_SIZE
+    }
+
+    /// The rule index, priority and unknown policy of a terminal.
+    pub(super) fn terminal(&self, terminal: usize) -> (usize, u32, UnknownPolicy) {
+        let at = terminal * TERMINAL_SIZE;
+        let on_unknown = if read_u32(self.terminals, at + 8) == 0 {
+            UnknownPolicy::AsFalse
+        } else {
+            UnknownPolicy::Indeterminate
+        };
+        (
+            read_u32(self.terminals, at) as usize,
+            read_u32(self.terminals, at + 4),
+            on_unknown,
+        )
+    }
+
+    pub(super) fn field_count(&self) -> usize {
+        self.fields.len() / FIELD_SIZE
+    }
+
+    pub(super) fn field(&self, slot: usize) -> &'a str {
+        let at = slot * FIELD_SIZE;
+        self.string(read_u32(self.fields, at), read_u32(self.fields, at + 4))
+    }
+
+    fn node_count(&self) -> usize {
+        self.nodes.len() / NODE_SIZE
+    }
+
+    #[inline]
+    pub(super) fn node(&self, node: u32) -> Node {
+        let at = node as usize * NODE_SIZE;
+        let head = read_u32(self.nodes, at);
+        #[allow(clippy::cast_possible_truncation)] // tag and op are single bytes
+        Node {
+            tag: head as u8,
+            op: (head >> 8) as u8,
+            a: read_u32(self.nodes, at + 4),
+            b: read_u32(self.nodes, at + 8),
+            c: read_u32(self.nodes, at + 12),
+        }
+    }
+
+    fn value_count(&self) -> usize {
+        self.values.len() / VALUE_SIZE
+    }
+
+    /// The tag and payload of a value record.
+    #[inline]
+    pub(super) fn value(&self, value: u32) -> (u32, u64) {
+        let at = value as usize * VALUE_SIZE;
+        (read_u32(self.values, at), read_u64(self.values, at + 8))
+    }
+
+    fn pool_len(&self) -> usize {
+        self.pool.len() / 4
+    }
+
+    #[inline]
+    pub(super) fn pool(&self, index: usize) -> u32 {
+        read_u32(self.pool, index * 4)
+    }
+
+    #[inline]
+    pub(super) fn string(&self, offset: u32, len: u32) -> &'a str {
+        &self.strings[offset as usize..offset as usize + len as usize]
+    }
+
+    /// Split a view payload into its sections and check every reference in
+    /// it, so that evaluation can index without further checks.
+    pub(super) fn parse(payload: &'a [u8]) -> Result<Self, DeserializeError> {
+        if payload.len() < COUNTS_SIZE {
+            return Err(invalid(format!(
+                "payload is {} bytes, shorter than its {COUNTS_SIZE}-byte counts",
+                payload.len()
+            )));
+        }
+        let count = |i: usize| read_u32(payload, i * 4) as usize;
+        let sizes = [
+            count(0).checked_mul(RULE_SIZE),
+            count(1).checked_mul(TERMINAL_SIZE),
+            count(2).checked_mul(FIELD_SIZE),
+            count(3).checked_mul(NODE_SIZE),
+            count(4).checked_mul(VALUE_SIZE),
+            count(5).checked_mul(4),
+            Some(count(6)),
+        ];
+        let mut sections = [&payload[..0]; 7];
+        let mut at = COUNTS_SIZE;
+        for (section, size) in sections.iter_mut().zip(sizes) {
+            let end = size
+                .and_then(|size| at.checked_add(size))
+                .filter(|&end| end <= payload.len())
+                .ok_or_else(|| invalid("section extends past the payload".to_owned()))?;
+            *section = &payload[at..end];
+            at = end;
+        }
+        if at != payload.len() {
+            return Err(invalid(format!(
+                "{} trailing bytes after the string table",
+                payload.len() - at
+            )));
+        }
+        let [rules, terminals, fields, nodes, values, pool, strings] = sections;
+        let strings = std::str::from_utf8(strings)
+            .map_err(|_| invalid("string table is not valid UTF-8".to_owned()))?;
+
+        let layout = Layout {
+            rules,
+            terminals,
+            fields,
+            nodes,
+            values,
+            pool,
+            strings,
+        };
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    fn validate(&self) -> Result<(), DeserializeError> {
+        for slot in 0..self.field_count() {
+            let at = slot * FIELD_SIZE;
+            self.check_string(read_u32(self.fields, at), read_u32(self.fields, at + 4))?;
+        }
+        for value in 0..self.value_count() {
+            self.check_value(value)?;
+        }
+
+        let mut next_node = 0;
+        for rule in 0..self.rule_count() {
+            let at = rule * RULE_SIZE;
+            self.check_string(read_u32(self.rules, at), read_u32(self.rules, at + 4))?;
+            let first = read_u32(self.rules, at + 8) as usize;
+            let root = read_u32(self.rules, at + 12) as usize;
+            if first != next_node || root < first || root >= self.node_count() {
+                return Err(invalid(format!(
+                    "rule {rule} owns nodes {first}..={root}, expected a run from {next_node}"
+                )));
+            }
+            if read_u32(self.rules, at + 16) & !ENABLED != 0 {
+                return Err(invalid(format!("rule {rule} has unknown flags")));
+            }
+            for node in first..=root {
+                self.check_node(node, first, rule)?;
+            }
+            next_node = root + 1;
+        }
+        if next_node != self.node_count() {
+            return Err(invalid(format!("nodes {next_node}.. belong to no rule")));
+        }
+
+        let mut last_priority = 0;
+        for terminal in 0..self.terminal_count() {
+            let at = terminal * TERMINAL_SIZE;
+            let rule = read_u32(self.terminals, at) as usize;
+            let priority = read_u32(self.terminals, at + 4);
+            if rule >= self.rule_count() {
+                return Err(invalid(format!(
+                    "terminal {terminal} references rule index {rule} but only {} rules exist",
+                    self.rule_count()
+                )));
+            }
+            if read_u32(self.terminals, at + 8) > 1 {
+                return Err(invalid(format!(
+                    "terminal {terminal} has an unknown on_unknown policy"
+                )));
+            }
+            if priority < last_priority {
+                return Err(invalid(
+                    "terminals not sorted by ascending priority".to_owned(),
+                ));
+            }
+            last_priority = priority;
+        }
+        Ok(())
+    }
+
+    fn check_string(&self, offset: u32, len: u32) -> Result<(), DeserializeError> {
+        let (start, end) = (offset as usize, offset as usize + len as usize);
+        if end > self.strings.len()
+            || !self.strings.is_char_boundary(start)
+            || !self.strings.is_char_boundary(end)
+        {
+            return Err(invalid(format!(
+                "string {start}..{end} is outside the string table or splits a character"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that `len` words from `start` lie inside the pool.
+    fn check_block(&self, start: usize, len: usize) -> Result<(), DeserializeError> {
+        match start.checked_add(len) {
+            Some(end) if end <= self.pool_len() => Ok(()),
+            _ => Err(invalid(format!(
+                "pool block {start}+{len} extends past the pool"
+            ))),
+        }
+    }
+
+    fn check_value(&self, value: usize) -> Result<(), DeserializeError> {
+        let (tag, payload) = self.value(word(value));
+        #[allow(clippy::cast_possible_truncation)] // split in halves
+        let (low, high) = (payload as u32, (payload >> 32) as u32);
+        match tag {
+            value_tag::INT | value_tag::FLOAT => Ok(()),
+            value_tag::BOOL if payload <= 1 => Ok(()),
+            value_tag::STR => self.check_string(low, high),
+            value_tag::LIST => {
+                self.check_block(low as usize, high as usize)?;
+                for i in 0..high as usize {
+                    let item = self.pool(low as usize + i) as usize;
+                    if item >= value {
+                        return Err(invalid(format!(
+                            "value {value} contains value {item}, which is not an earlier value"
+                        )));
+                    }
+                }
+                Ok(())
+            }
+            value_tag::MAP => {
+                self.check_block(low as usize, (high as usize).saturating_mul(3))?;
+                let mut previous: Option<&str> = None;
+                for i in 0..high as usize {
+                    let at = low as usize + i * 3;
+                    self.check_string(self.pool(at), self.pool(at + 1))?;
+                    let key = self.string(self.pool(at), self.pool(at + 1));
+                    if previous.is_some_and(|p| p >= key) {
+                        return Err(invalid(format!("map value {value} has keys out of order")));
+                    }
+                    previous = Some(key);
+                    let item = self.pool(at + 2) as usize;
+                    if item >= value {
+                        return Err(invalid(format!(
+                            "value {value} contains value {item}, which is not an earlier value"
+                        )));
+                    }
+                }
+                Ok(())
+            }
+            _ => Err(invalid(format!("value {value} has unknown tag {tag}"))),
+        }
+    }
+
+    /// Local slots number past the field slots, one per binding or lookup,
+    /// so the pool size bounds them and the scopes evaluation allocates.
+    fn check_local(&self, slot: u32) -> Result<(), DeserializeError> {
+        let (start, end) = (self.field_count(), self.field_count() + self.pool_len());
+        if !(start..end).contains(&(slot as usize)) {
+            return Err(invalid(format!(
+                "local slot {slot} outside local range {start}..{end}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_value_index(&self, value: u32) -> Result<(), DeserializeError> {
+        if value as usize >= self.value_count() {
+            return Err(invalid(format!(
+                "value {value} out of bounds (max {})",
+                self.value_count()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Field slots need no check: a slot nothing fills reads as missing.
+    fn check_bound(&self, bound: u32) -> Result<(), DeserializeError> {
+        if bound & FIELD_BOUND == 0 {
+            self.check_value_index(bound)?;
+        }
+        Ok(())
+    }
+
+    fn check_child(&self, child: u32, first: usize, node: usize) -> Result<(), DeserializeError> {
+        if !(first..node).contains(&(child as usize)) {
+            return Err(invalid(format!(
+                "node {node} refers to node {child} outside its rule's earlier nodes"
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_node(&self, node: usize, first: usize, rule: usize) -> Result<(), DeserializeError> {
+        let Node { tag, op, a, b, c } = self.node(word(node));
+        let bad_op = match tag {
+            tag::COMPARE | tag::COMPARE_FIELDS | tag::LEN => op > 5,
+            tag::SET_COMPARE => op > 3,
+            _ => op != 0,
+        };
+        if bad_op {
+            return Err(invalid(format!("node {node} has unknown operator {op}")));
+        }
+        match tag {
+            tag::AND | tag::OR | tag::AT_LEAST => {
+                if c == 0 && tag != tag::AT_LEAST {
+                    return Err(invalid("empty And/Or expression".to_owned()));
+                }
+                self.check_block(b as usize, c as usize)?;
+                for i in 0..c as usize {
+                    self.check_child(self.pool(b as usize + i), first, node)?;
+                }
+            }
+            tag::NOT => self.check_child(a, first, node)?,
+            tag::RULE_REF => {
+                if a as usize >= rule {
+                    return Err(invalid(format!(
+                        "rule ref {a} violates topological order (current rule index {rule})"
+                    )));
+                }
+            }
+            tag::COMPARE => self.check_value_index(b)?,
+            tag::IN | tag::NOT_IN => {
+                self.check_block(b as usize, c as usize)?;
+                for i in 0..c as usize {
+                    self.check_bound(self.pool(b as usize + i))?;
+                }
+            }
+            tag::BETWEEN => {
+                self.check_bound(b)?;
+                self.check_bound(c)?;
+            }
+            tag::LIKE | tag::NOT_LIKE => self.check_string(b, c)?,
+            tag::IS_NULL | tag::IS_NOT_NULL | tag::COMPARE_FIELDS => {}
+            tag::CONTAINS | tag::SET_COMPARE | tag::LEN | tag::HAS_KEY => self.check_bound(b)?,
+            tag::ANY | tag::ALL => {
+                self.check_child(b, first, node)?;
+                let mut at = c as usize;
+                self.check_block(at, 1)?;
+                let count = self.pool(at);
+                at += 1;
+                for _ in 0..count {
+                    self.check_block(at, 2)?;
+                    self.check_local(self.pool(at))?;
+                    let segments = self.pool(at + 1) as usize;
+                    at += 2;
+                    self.check_block(at, segments.saturating_mul(2))?;
+                    for _ in 0..segments {
+                        self.check_string(self.pool(at), self.pool(at + 1))?;
+                        at += 2;
+                    }
+                }
+            }
+            tag::LOOKUP => {
+                self.check_child(a, first, node)?;
+                let mut at = b as usize;
+                self.check_block(at, 1)?;
+                let count = self.pool(at);
+                at += 1;
+                for _ in 0..count {
+                    self.check_block(at, 3)?;
+                    self.check_local(self.pool(at))?;
+                    let steps = self.pool(at + 2) as usize;
+                    at += 3;
+                    self.check_block(at, steps.saturating_mul(3))?;
+                    for _ in 0..steps {
+                        match self.pool(at) {
+                            step::KEY => self.check_string(self.pool(at + 1), self.pool(at + 2))?,
+                            step::DYNAMIC | step::INDEX | step::WILDCARD => {}
+                            kind => {
+                                return Err(invalid(format!(
+                                    "node {node} has unknown lookup step kind {kind}"
+                                )))
+                            }
+                        }
+                        at += 3;
+                    }
+                }
+            }
+            _ => return Err(invalid(format!("node {node} has unknown tag {tag}"))),
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: String) -> DeserializeError {
+    DeserializeError::Validation(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode, tag, Layout, COUNTS_SIZE, NODE_SIZE, RULE_SIZE};
+    use crate::serial::DeserializeError;
+    use crate::view::eval;
+    use crate::{RuleSet, Value};
+
+    const SOURCE: &str = r#"
+rule adult:
+    user.age >= 18 AND user.country IN ["US", "CA"]
+
+rule big:
+    ANY(order.items AS line, line.price > limits[user.country].daily)
+
+rule allow (priority 0):
+    adult AND NOT big
+"#;
+
+    fn payload() -> Vec<u8> {
+        encode(&RuleSet::from_dsl(SOURCE).unwrap())
+    }
+
+    fn set_word(payload: &mut [u8], at: usize, word: u32) {
+        payload[at..at + 4].copy_from_slice(&word.to_le_bytes());
+    }
+
+    fn rejected(payload: &[u8]) -> bool {
+        matches!(Layout::parse(payload), Err(DeserializeError::Validation(_)))
+    }
+
+    #[test]
+    fn encoded_payloads_parse() {
+        let payload = payload();
+        let layout = Layout::parse(&payload).unwrap();
+        assert_eq!(layout.rule_count(), 3);
+        assert_eq!(layout.rule_name(2), "allow");
+        assert_eq!(layout.terminal_count(), 1);
+    }
+
+    #[test]
+    fn forward_references_are_rejected() {
+        let first_rule = COUNTS_SIZE;
+        // The first rule's root pointing at a node owned by a later rule.
+        let mut root_ahead = payload();
+        let last_node = u32::from_le_bytes(root_ahead[12..16].try_into().unwrap()) - 1;
+        set_word(&mut root_ahead, first_rule + 12, last_node);
+        assert!(rejected(&root_ahead));
+
+        // The first rule's root made a NOT of itself.
+        let mut payload = payload();
+        let nodes = COUNTS_SIZE
+            + RULE_SIZE * 3
+            + 12
+            + 8 * u32::from_le_bytes(payload[8..12].try_into().unwrap()) as usize;
+        let root = u32::from_le_bytes(
+            payload[first_rule + 12..first_rule + 16]
+                .try_into()
+                .unwrap(),
+        );
+        let at = nodes + root as usize * NODE_SIZE;
+        set_word(&mut payload, at, u32::from(tag::NOT));
+        set_word(&mut payload, at + 4, root);
+        assert!(rejected(&payload));
+    }
+
+    #[test]
+    fn strings_out_of_range_are_rejected() {
+        let mut payload = payload();
+        set_word(&mut payload, COUNTS_SIZE + 4, u32::MAX);
+        assert!(rejected(&payload));
+    }
+
+    #[test]
+    fn counts_past_the_payload_are_rejected() {
+        let mut payload = payload();
+        set_word(&mut payload, 0, u32::MAX);
+        assert!(rejected(&payload));
+        assert!(rejected(&payload[..COUNTS_SIZE - 1]));
+
+        let mut trailing = self::payload();
+        trailing.push(0);
+        assert!(rejected(&trailing));
+    }
+
+    /// Corrupting any byte either fails validation or leaves a payload
+    /// that evaluates without panicking.
+    #[test]
+    fn corrupted_bytes_never_panic() {
+        let payload = payload();
+        for at in 0..payload.len() {
+            for mask in [0x01, 0x80, 0xFF] {
+                let mut corrupted = payload.clone();
+                corrupted[at] ^= mask;
+                let Ok(layout) = Layout::parse(&corrupted) else {
+                    continue;
+                };
+                let thirty = Value::Int(30);
+                let fields = vec![Some(&thirty); layout.field_count().min(64)];
+                if fields.len() != layout.field_count() {
+                    continue;
+                }
+                let _ = eval::evaluate(&layout, &fields, &[]);
+                let _ = eval::evaluate_three_valued(&layout, &fields, &[]);
+                let _ = eval::evaluate_detailed(&layout, &fields, &[]);
+            }
+        }
+    }
+}
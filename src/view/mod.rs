@@ -0,0 +1,168 @@
+//! Zero-copy evaluation of compiled rulesets from a byte buffer.
+
+mod eval;
+pub(crate) mod layout;
+
+use crate::serial::{checked_payload, DeserializeError};
+use crate::types::evaluation_report::EvaluationReport;
+use crate::{Context, RuleOverrides, Value, Verdict};
+
+use layout::Layout;
+
+/// A compiled ruleset evaluated in place from bytes written by
+/// [`RuleSet::to_view_bytes()`](crate::RuleSet::to_view_bytes), typically a
+/// memory-mapped file.
+///
+/// Loading checks the header, checksum and every offset, index and string
+/// once; nothing is decoded or allocated per rule, so startup cost is a
+/// hash of the buffer rather than building the ruleset. Evaluation reads
+/// the fixed-size records directly and gives the same results as the
+/// [`RuleSet`](crate::RuleSet) the bytes were written from.
+///
+/// The view carries what evaluation needs: rule names, conditions, the
+/// `enabled` flag, terminals and field paths. Other metadata and the
+/// schema stay in the [`RuleSet::to_bytes()`](crate::RuleSet::to_bytes)
+/// format. Contexts are matched to fields by path; there is no
+/// [`IndexedContext`](crate::IndexedContext) variant.
+///
+/// # Example
+///
+/// ```
+/// use ooroo::{field, Context, RuleSetBuilder, RuleSetView, Verdict};
+///
+/// let ruleset = RuleSetBuilder::new()
+///     .rule("adult", |r| r.when(field("user.age").gte(18_i64)))
+///     .terminal("adult", 0)
+///     .compile()
+///     .unwrap();
+/// let bytes = ruleset.to_view_bytes();
+///
+/// // With a memory-mapped file, `bytes` would be the mapping.
+/// let view = RuleSetView::from_bytes(&bytes).unwrap();
+/// let ctx = Context::new().set("user.age", 30_i64);
+/// assert_eq!(view.evaluate(&ctx), Some(Verdict::new("adult", true)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RuleSetView<'a> {
+    layout: Layout<'a>,
+}
+
+impl<'a> RuleSetView<'a> {
+    /// Validate `bytes` and view the ruleset they contain.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DeserializeError`] if the header, length or checksum is
+    /// wrong, or if any record refers outside the buffer or breaks the
+    /// ordering evaluation relies on.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, DeserializeError> {
+        let (_, payload) =
+            checked_payload(bytes, layout::MAGIC, layout::VERSION..=layout::VERSION)?;
+        Ok(Self {
+            layout: Layout::parse(payload)?,
+        })
+    }
+
+    /// Returns rule names in execution order.
+    #[must_use]
+    pub fn execution_order(&self) -> Vec<&'a str> {
+        (0..self.layout.rule_count())
+            .map(|rule| self.layout.rule_name(rule))
+            .collect()
+    }
+
+    /// Returns terminal names and priorities, in priority order.
+    #[must_use]
+    pub fn terminal_order(&self) -> Vec<(&'a str, u32)> {
+        (0..self.layout.terminal_count())
+            .map(|t| {
+                let (rule, priority, _) = self.layout.terminal(t);
+                (self.layout.rule_name(rule), priority)
+            })
+            .collect()
+    }
+
+    /// Evaluate against `ctx`, like [`RuleSet::evaluate()`](crate::RuleSet::evaluate).
+    #[must_use]
+    pub fn evaluate(&self, ctx: &Context) -> Option<Verdict> {
+        eval::evaluate(&self.layout, &self.flatten_context(ctx), &[])
+    }
+
+    /// Evaluate with three-valued logic, like
+    /// [`RuleSet::evaluate_three_valued()`](crate::RuleSet::evaluate_three_valued).
+    #[must_use]
+    pub fn evaluate_three_valued(&self, ctx: &Context) -> Option<Verdict> {
+        eval::evaluate_three_valued(&self.layout, &self.flatten_context(ctx), &[])
+    }
+
+    /// Evaluate and report every rule's result, like
+    /// [`RuleSet::evaluate_detailed()`](crate::RuleSet::evaluate_detailed).
+    pub fn evaluate_detailed(&self, ctx: &Context) -> EvaluationReport {
+        eval::evaluate_detailed(&self.layout, &self.flatten_context(ctx), &[])
+    }
+
+    /// Create an empty set of [`RuleOverrides`] for this view.
+    #[must_use]
+    pub fn overrides(&self) -> RuleOverrides {
+        RuleOverrides::from_names(
+            self.execution_order()
+                .into_iter()
+                .map(str::to_owned)
+                .collect(),
+        )
+    }
+
+    /// Evaluate with `overrides` applied, like
+    /// [`RuleSet::evaluate_with_overrides()`](crate::RuleSet::evaluate_with_overrides).
+    #[must_use]
+    pub fn evaluate_with_overrides(
+        &self,
+        ctx: &Context,
+        overrides: &RuleOverrides,
+    ) -> Option<Verdict> {
+        eval::evaluate(
+            &self.layout,
+            &self.flatten_context(ctx),
+            &overrides.states_for(self.rule_indices()),
+        )
+    }
+
+    /// Evaluate with three-valued logic and `overrides` applied.
+    #[must_use]
+    pub fn evaluate_three_valued_with_overrides(
+        &self,
+        ctx: &Context,
+        overrides: &RuleOverrides,
+    ) -> Option<Verdict> {
+        eval::evaluate_three_valued(
+            &self.layout,
+            &self.flatten_context(ctx),
+            &overrides.states_for(self.rule_indices()),
+        )
+    }
+
+    /// Evaluate with `overrides` applied and report every rule's result.
+    pub fn evaluate_detailed_with_overrides(
+        &self,
+        ctx: &Context,
+        overrides: &RuleOverrides,
+    ) -> EvaluationReport {
+        eval::evaluate_detailed(
+            &self.layout,
+            &self.flatten_context(ctx),
+            &overrides.states_for(self.rule_indices()),
+        )
+    }
+
+    /// Each rule's index and name, for matching overrides.
+    fn rule_indices(&self) -> impl ExactSizeIterator<Item = (usize, &'a str)> + Clone + '_ {
+        (0..self.layout.rule_count()).map(|rule| (rule, self.layout.rule_name(rule)))
+    }
+
+    /// Borrow each field the rules use from `ctx`, in field slot order.
+    fn flatten_context<'c>(&self, ctx: &'c Context) -> Vec<Option<&'c Value>> {
+        (0..self.layout.field_count())
+            .map(|slot| ctx.get(self.layout.field(slot)))
+            .collect()
+    }
+}
@@ -1,8 +1,8 @@
 #![cfg(feature = "binary-cache")]
 
 use ooroo::{
-    bound_field, field, rule_ref, Context, DeserializeError, RuleSet, RuleSetBuilder, Value,
-    Verdict,
+    all, any, bound_field, field, inspect, len, rule_ref, Context, DeserializeError, FieldType,
+    RuleSet, RuleSetBuilder, Schema, UnknownPolicy, Value, Verdict,
 };
 
 // ---------------------------------------------------------------------------
@@ -208,7 +208,7 @@ fn version_mismatch() {
             err,
             DeserializeError::IncompatibleVersion {
                 blob: 99,
-                supported: 4
+                supported: 5
             }
         ),
         "expected IncompatibleVersion, got: {err}"
@@ -344,7 +344,7 @@ fn and_or_flattening_round_trip() {
 fn all_value_types_round_trip() {
     let original = RuleSetBuilder::new()
         .rule("int_check", |r| r.when(field("i").eq(42_i64)))
-        .rule("float_check", |r| r.when(field("f").lt(3.14_f64)))
+        .rule("float_check", |r| r.when(field("f").lt(3.25_f64)))
         .rule("bool_check", |r| r.when(field("b").eq(true)))
         .rule("str_check", |r| r.when(field("s").eq("hello")))
         .rule("all", |r| {
@@ -529,3 +529,781 @@ fn not_expression_round_trip() {
     );
     assert_eq!(restored.evaluate(&ctx_banned), None);
 }
+
+// ---------------------------------------------------------------------------
+// Quantifier round-trip
+// ---------------------------------------------------------------------------
+
+#[test]
+fn quantifier_round_trip() {
+    let original = RuleSetBuilder::new()
+        .rule("vip", |r| r.when(field("user.tier").eq("vip")))
+        .rule("r", |r| {
+            r.when(
+                any(
+                    "grid",
+                    "row",
+                    all("row", "cell", field("cell").gt_field("min")),
+                )
+                .and(field("tags").contains("rust"))
+                .or(any("grid", "row", rule_ref("vip"))),
+            )
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let bytes = original.to_bytes(None).unwrap();
+    let restored = RuleSet::from_bytes(&bytes).unwrap();
+
+    let grid = |rows: &[&[i64]]| {
+        Value::List(
+            rows.iter()
+                .map(|row| Value::List(row.iter().copied().map(Value::Int).collect()))
+                .collect(),
+        )
+    };
+    let tags = Value::List(vec![Value::String("rust".into())]);
+
+    let ctx_hit = Context::new()
+        .set("grid", grid(&[&[1, -1], &[5, 6]]))
+        .set("min", 0_i64)
+        .set("tags", tags.clone());
+    assert_eq!(original.evaluate(&ctx_hit), restored.evaluate(&ctx_hit));
+    assert_eq!(restored.evaluate(&ctx_hit), Some(Verdict::new("r", true)));
+
+    let ctx_miss = Context::new()
+        .set("grid", grid(&[&[1, -1]]))
+        .set("min", 0_i64)
+        .set("tags", tags);
+    assert_eq!(original.evaluate(&ctx_miss), restored.evaluate(&ctx_miss));
+    assert_eq!(restored.evaluate(&ctx_miss), None);
+
+    assert_eq!(restored.dependencies_of("r"), Some(vec!["vip"]));
+}
+
+// ---------------------------------------------------------------------------
+// Set operator and len() round-trip
+// ---------------------------------------------------------------------------
+
+#[test]
+fn set_operators_round_trip() {
+    let original = RuleSetBuilder::new()
+        .rule("covers", |r| {
+            r.when(field("perms").superset_of(bound_field("required")))
+        })
+        .rule("clean", |r| {
+            r.when(field("tags").disjoint_from(vec![Value::String("spam".into())]))
+        })
+        .rule("sized", |r| {
+            r.when(len("tags").lte(bound_field("max_tags")))
+        })
+        .rule("r", |r| {
+            r.when(
+                rule_ref("covers")
+                    .and(rule_ref("clean"))
+                    .and(rule_ref("sized"))
+                    .and(field("perms").overlaps(bound_field("required")))
+                    .and(field("required").subset_of(bound_field("perms"))),
+            )
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let bytes = original.to_bytes(None).unwrap();
+    let restored = RuleSet::from_bytes(&bytes).unwrap();
+
+    let list = |items: &[&str]| Value::List(items.iter().map(|s| Value::from(*s)).collect());
+    let ctx_hit = Context::new()
+        .set("perms", list(&["read", "write"]))
+        .set("required", list(&["read"]))
+        .set("tags", list(&["rust"]))
+        .set("max_tags", 2_i64);
+    assert_eq!(original.evaluate(&ctx_hit), restored.evaluate(&ctx_hit));
+    assert_eq!(restored.evaluate(&ctx_hit), Some(Verdict::new("r", true)));
+
+    let ctx_miss = Context::new()
+        .set("perms", list(&["read", "write"]))
+        .set("required", list(&["read"]))
+        .set("tags", list(&["rust", "spam"]))
+        .set("max_tags", 2_i64);
+    assert_eq!(original.evaluate(&ctx_miss), restored.evaluate(&ctx_miss));
+    assert_eq!(restored.evaluate(&ctx_miss), None);
+}
+
+#[test]
+fn map_and_lookup_round_trip() {
+    let original = RuleSetBuilder::new()
+        .rule("under_limit", |r| {
+            r.when(
+                field("order.total")
+                    .lte_field("limits[user.country].daily")
+                    .and(field("flags").has_key(bound_field("user.country")))
+                    .and(any("items", "item", field("item.price").gt(0_i64))),
+            )
+        })
+        .terminal("under_limit", 0)
+        .compile()
+        .unwrap();
+
+    let bytes = original.to_bytes(None).unwrap();
+    let restored = RuleSet::from_bytes(&bytes).unwrap();
+
+    let map = |entries: Vec<(&str, Value)>| {
+        Value::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_owned(), v))
+                .collect(),
+        )
+    };
+    let ctx = Context::new()
+        .set("order.total", 80_i64)
+        .set("user.country", "US")
+        .set(
+            "limits",
+            map(vec![("US", map(vec![("daily", Value::Int(100))]))]),
+        )
+        .set("flags", map(vec![("US", Value::Bool(true))]))
+        .set(
+            "items",
+            Value::List(vec![map(vec![("price", Value::Int(5))])]),
+        );
+    assert_eq!(original.evaluate(&ctx), restored.evaluate(&ctx));
+    assert_eq!(
+        restored.evaluate(&ctx),
+        Some(Verdict::new("under_limit", true))
+    );
+
+    let ctx = ctx.set("order.total", 120_i64);
+    assert_eq!(restored.evaluate(&ctx), None);
+}
+
+#[test]
+fn list_index_and_wildcard_round_trip() {
+    let original = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(
+                field("order.items[0].sku")
+                    .eq("A1")
+                    .and(field("order.items[-1].price").gt(5_i64))
+                    .and(all("order.items[*].price", "p", field("p").gt(0_i64))),
+            )
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let bytes = original.to_bytes(None).unwrap();
+    let restored = RuleSet::from_bytes(&bytes).unwrap();
+
+    let item = |sku: &str, price: i64| {
+        Value::Map(
+            [
+                ("sku".to_owned(), Value::from(sku)),
+                ("price".to_owned(), Value::Int(price)),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    };
+    let ctx = Context::new().set(
+        "order.items",
+        Value::List(vec![item("A1", 3), item("B2", 9)]),
+    );
+    assert_eq!(original.evaluate(&ctx), restored.evaluate(&ctx));
+    assert_eq!(restored.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = Context::new().set(
+        "order.items",
+        Value::List(vec![item("A1", 0), item("B2", 9)]),
+    );
+    assert_eq!(restored.evaluate(&ctx), None);
+}
+
+#[test]
+fn unknown_policy_round_trip() {
+    let original = RuleSetBuilder::new()
+        .rule("deny", |r| r.when(field("user.banned").eq(true)))
+        .rule("allow", |r| r.when(field("user.active").eq(true)))
+        .terminal_on_unknown("deny", 0, UnknownPolicy::Indeterminate)
+        .terminal("allow", 1)
+        .compile()
+        .unwrap();
+
+    let bytes = original.to_bytes(None).unwrap();
+    let restored = RuleSet::from_bytes(&bytes).unwrap();
+
+    let ctx = Context::new().set("user.active", true);
+    assert_eq!(
+        restored.evaluate_three_valued(&ctx),
+        Some(Verdict::indeterminate("deny"))
+    );
+    assert_eq!(restored.evaluate(&ctx), Some(Verdict::new("allow", true)));
+}
+
+#[test]
+fn schema_round_trip() {
+    let schema = Schema::new()
+        .field("user.age", FieldType::Int)
+        .field("limits", FieldType::Map);
+    let original = RuleSetBuilder::new()
+        .schema(schema.clone())
+        .rule("r", |r| r.when(field("user.age").gte(18_i64)))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let bytes = original.to_bytes(None).unwrap();
+    let restored = RuleSet::from_bytes(&bytes).unwrap();
+    assert_eq!(restored.schema(), Some(&schema));
+    assert!(restored
+        .validate_context(&Context::new().set("user.age", "x"))
+        .is_err());
+
+    let unchecked = RuleSet::from_bytes(&simple_ruleset().to_bytes(None).unwrap()).unwrap();
+    assert!(unchecked.schema().is_none());
+}
+
+#[test]
+fn rule_metadata_round_trip() {
+    let original = RuleSetBuilder::new()
+        .rule("kyc", |r| {
+            r.when(field("user.verified").eq(true))
+                .description("user passed KYC")
+                .owner("risk-team")
+                .tag("kyc")
+                .tag("identity")
+                .link("https://tickets.example/RISK-1")
+                .meta("severity", "high")
+        })
+        .rule("legacy", |r| {
+            r.when(field("user.age").gte(18_i64)).enabled(false)
+        })
+        .terminal("legacy", 0)
+        .terminal("kyc", 1)
+        .compile()
+        .unwrap();
+
+    let bytes = original.to_bytes(None).unwrap();
+    let restored = RuleSet::from_bytes(&bytes).unwrap();
+    assert_eq!(restored.metadata("kyc"), original.metadata("kyc"));
+    assert_eq!(restored.rules_with_tag("identity"), ["kyc"]);
+    assert_eq!(restored.disabled_rules(), ["legacy"]);
+
+    let ctx = Context::new()
+        .set("user.verified", true)
+        .set("user.age", 30_i64);
+    assert_eq!(restored.evaluate(&ctx), Some(Verdict::new("kyc", true)));
+}
+
+// ---------------------------------------------------------------------------
+// Golden blobs: every readable format version
+// ---------------------------------------------------------------------------
+//
+// `fixtures/blobs/v<N>.bin` was written from `v<N>.ooroo` by the release
+// that introduced format version N, and must never be regenerated once that
+// version ships. When bumping the format version, add the new pair with
+// `cargo test --features binary-cache --test binary_cache -- --ignored`.
+
+const V4_SOURCE: &str = include_str!("fixtures/blobs/v4.ooroo");
+const V4_BLOB: &[u8] = include_bytes!("fixtures/blobs/v4.bin");
+const V5_SOURCE: &str = include_str!("fixtures/blobs/v5.ooroo");
+const V5_BLOB: &[u8] = include_bytes!("fixtures/blobs/v5.bin");
+
+fn format_version(blob: &[u8]) -> u16 {
+    u16::from_le_bytes([blob[4], blob[5]])
+}
+
+fn map(entries: &[(&str, Value)]) -> Value {
+    Value::Map(
+        entries
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), v.clone()))
+            .collect(),
+    )
+}
+
+fn v4_contexts() -> Vec<Context> {
+    let base = || {
+        Context::new()
+            .set("user.age", 30_i64)
+            .set("user.retirement_age", 67_i64)
+            .set("user.country", "US")
+            .set("user.home_country", "FR")
+            .set("user.email", "ana@example.com")
+            .set("user.profile", "p")
+            .set("user.score", 8.0)
+            .set("user.banned", false)
+            .set("order.total", 50_i64)
+            .set("user.credit_limit", 100_i64)
+    };
+    vec![
+        base(),
+        base().set("user.age", 70_i64),
+        base().set("user.country", "FR"),
+        base().set("user.country", "XX"),
+        base().set("user.email", "test@example.com"),
+        base().set("user.deleted_at", "2026-01-01"),
+        base().set("order.total", 500_i64).set("user.score", 1.0),
+        base().set("user.banned", true),
+        Context::new().set("user.age", 30_i64),
+        Context::new(),
+    ]
+}
+
+fn v5_contexts() -> Vec<Context> {
+    let item = |sku: &str, price: i64, tags: Vec<Value>| {
+        map(&[
+            ("sku", Value::from(sku)),
+            ("price", Value::Int(price)),
+            ("tags", Value::List(tags)),
+        ])
+    };
+    let base = || {
+        Context::new()
+            .set("user.age", 40_i64)
+            .set("user.country", "US")
+            .set("user.banned", false)
+            .set("user.tags", Value::List(vec![Value::from("old")]))
+            .set("order.total", 200_i64)
+            .set("limits", map(&[("US", map(&[("daily", Value::Int(500))]))]))
+            .set(
+                "order.items",
+                Value::List(vec![
+                    item("B1", 150, vec![Value::from("x")]),
+                    item("A2", 10, vec![]),
+                ]),
+            )
+    };
+    vec![
+        base(),
+        base().set("user.age", 12_i64),
+        base().set("user.country", "XX"),
+        base().set("user.country", "FR"),
+        base().set("order.total", 900_i64),
+        base().set("user.tags", Value::List(vec![Value::from("vip")])),
+        base().set("order.items", Value::List(vec![])),
+        Context::new().set("user.age", 40_i64),
+        Context::new(),
+    ]
+}
+
+/// The blob decodes to the same rules, metadata, schema and terminals as
+/// its source compiled by this engine, and evaluates the same.
+fn assert_golden(blob: &[u8], source: &str, contexts: &[Context]) {
+    let loaded = RuleSet::from_bytes(blob).unwrap();
+    let compiled = RuleSet::from_dsl(source).unwrap();
+    assert_eq!(loaded.to_dsl().unwrap(), compiled.to_dsl().unwrap());
+    assert_eq!(loaded.terminal_order(), compiled.terminal_order());
+    for ctx in contexts {
+        assert_eq!(loaded.evaluate(ctx), compiled.evaluate(ctx), "{ctx:?}");
+        assert_eq!(
+            loaded.evaluate_three_valued(ctx),
+            compiled.evaluate_three_valued(ctx),
+            "{ctx:?}"
+        );
+    }
+}
+
+#[test]
+fn golden_v4_blob_is_upgraded() {
+    assert_eq!(format_version(V4_BLOB), 4);
+    assert_golden(V4_BLOB, V4_SOURCE, &v4_contexts());
+    let loaded = RuleSet::from_bytes(V4_BLOB).unwrap();
+    assert_eq!(
+        loaded.evaluate(&v4_contexts()[0]),
+        Some(Verdict::new("allow", true))
+    );
+    assert!(loaded.disabled_rules().is_empty());
+    assert!(loaded.schema().is_none());
+}
+
+#[test]
+fn golden_v5_blob_decodes() {
+    assert_eq!(format_version(V5_BLOB), 5);
+    assert_golden(V5_BLOB, V5_SOURCE, &v5_contexts());
+    let loaded = RuleSet::from_bytes(V5_BLOB).unwrap();
+    assert_eq!(
+        loaded.evaluate(&v5_contexts()[0]),
+        Some(Verdict::new("allow", true))
+    );
+    assert_eq!(loaded.disabled_rules(), ["legacy"]);
+    assert_eq!(
+        loaded.schema().and_then(|s| s.get("user.age")),
+        Some(FieldType::Int)
+    );
+}
+
+#[test]
+fn upgraded_blobs_are_rewritten_in_the_current_version() {
+    let upgraded = RuleSet::from_bytes(V4_BLOB).unwrap();
+    let rewritten = upgraded.to_bytes(None).unwrap();
+    assert_eq!(format_version(&rewritten), 5);
+    let reloaded = RuleSet::from_bytes(&rewritten).unwrap();
+    for ctx in v4_contexts() {
+        assert_eq!(reloaded.evaluate(&ctx), upgraded.evaluate(&ctx));
+    }
+}
+
+#[test]
+fn versions_before_the_oldest_readable_are_rejected() {
+    let mut v3 = V4_BLOB.to_vec();
+    v3[4] = 3;
+    let err = RuleSet::from_bytes(&v3).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            DeserializeError::IncompatibleVersion {
+                blob: 3,
+                supported: 5
+            }
+        ),
+        "expected IncompatibleVersion, got: {err}"
+    );
+}
+
+/// Writes the golden blob for the current format version. Run once when
+/// the version is bumped, then check in the new `.ooroo` and `.bin` pair.
+#[test]
+#[ignore = "writes fixtures/blobs; run only when bumping the format version"]
+fn write_current_golden_blob() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/blobs");
+    let version = format_version(&simple_ruleset().to_bytes(None).unwrap());
+    let source = std::fs::read_to_string(dir.join(format!("v{version}.ooroo"))).unwrap();
+    let blob = RuleSet::from_dsl(&source)
+        .unwrap()
+        .to_bytes(Some(&source))
+        .unwrap();
+    std::fs::write(dir.join(format!("v{version}.bin")), blob).unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// Inspection
+// ---------------------------------------------------------------------------
+
+#[test]
+fn inspect_reads_header_and_metadata() {
+    let source = "rule adult (priority 0): user.age >= 18";
+    let bytes = simple_ruleset().to_bytes(Some(source)).unwrap();
+    let info = inspect(&bytes).unwrap();
+    assert_eq!(info.format_version, 5);
+    assert_eq!(info.engine_version, 1);
+    assert_eq!(info.flags, 0);
+    assert!(!info.is_signed());
+    assert_eq!(info.payload_len as usize, bytes.len() - 32);
+    assert!(info.checksum_valid);
+    assert_eq!(info.rule_count, 3);
+    assert_eq!(info.terminal_count, 1);
+    assert_eq!(info.field_count, 2);
+    assert!(info.matches_source(source));
+    assert!(!info.matches_source("rule adult (priority 0): user.age >= 21"));
+
+    let undigested = inspect(&simple_ruleset().to_bytes(None).unwrap()).unwrap();
+    assert_eq!(undigested.source_digest, None);
+    assert!(!undigested.matches_source(source));
+}
+
+#[test]
+fn inspect_reports_bad_checksums() {
+    let mut bytes = simple_ruleset().to_bytes(None).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    assert!(!inspect(&bytes).unwrap().checksum_valid);
+}
+
+#[test]
+fn inspect_reads_golden_blobs() {
+    let v4 = inspect(V4_BLOB).unwrap();
+    assert_eq!(v4.format_version, 4);
+    assert!(v4.checksum_valid);
+    assert_eq!(v4.rule_count, 10);
+    assert!(v4.matches_source(V4_SOURCE));
+
+    let v5 = inspect(V5_BLOB).unwrap();
+    assert_eq!(v5.format_version, 5);
+    assert!(v5.matches_source(V5_SOURCE));
+}
+
+#[test]
+fn inspect_rejects_what_it_cannot_read() {
+    let bytes = simple_ruleset().to_bytes(None).unwrap();
+    assert!(matches!(
+        inspect(&bytes[..20]),
+        Err(DeserializeError::LengthMismatch { .. })
+    ));
+    assert!(matches!(
+        inspect(&bytes[..40]),
+        Err(DeserializeError::LengthMismatch { .. })
+    ));
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    assert!(matches!(
+        inspect(&bad_magic),
+        Err(DeserializeError::BadMagic)
+    ));
+
+    let mut future = bytes;
+    future[4] = 99;
+    assert!(matches!(
+        inspect(&future),
+        Err(DeserializeError::IncompatibleVersion { blob: 99, .. })
+    ));
+}
+
+// ---------------------------------------------------------------------------
+// Cached loading
+// ---------------------------------------------------------------------------
+
+struct TempDir(std::path::PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("ooroo_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn load_cached_writes_then_reuses_the_cache() {
+    let dir = TempDir::new("load_cached_reuse");
+    let dsl = dir.0.join("rules.ooroo");
+    let cache = dir.0.join("rules.ooroobin");
+    std::fs::write(&dsl, "rule adult (priority 0): user.age >= 18").unwrap();
+
+    let first = RuleSet::load_cached(&dsl, &cache).unwrap();
+    let written = std::fs::read(&cache).unwrap();
+    assert!(inspect(&written)
+        .unwrap()
+        .matches_source("rule adult (priority 0): user.age >= 18"));
+
+    // A fresh cache is used as is: replace it with a different ruleset
+    // carrying the same digest, and that ruleset is what loads.
+    let marker = RuleSet::from_dsl("rule marker (priority 0): x == 1")
+        .unwrap()
+        .to_bytes(Some("rule adult (priority 0): user.age >= 18"))
+        .unwrap();
+    std::fs::write(&cache, &marker).unwrap();
+    let second = RuleSet::load_cached(&dsl, &cache).unwrap();
+    assert_eq!(first.execution_order(), ["adult"]);
+    assert_eq!(second.execution_order(), ["marker"]);
+    assert_eq!(std::fs::read(&cache).unwrap(), marker);
+}
+
+#[test]
+fn load_cached_rebuilds_stale_caches() {
+    let dir = TempDir::new("load_cached_stale");
+    let dsl = dir.0.join("rules.ooroo");
+    let cache = dir.0.join("rules.ooroobin");
+    std::fs::write(&dsl, "rule adult (priority 0): user.age >= 18").unwrap();
+    RuleSet::load_cached(&dsl, &cache).unwrap();
+
+    std::fs::write(&dsl, "rule adult (priority 0): user.age >= 21").unwrap();
+    let ruleset = RuleSet::load_cached(&dsl, &cache).unwrap();
+    let ctx = Context::new().set("user.age", 19_i64);
+    assert_eq!(ruleset.evaluate(&ctx), None);
+    let reloaded = RuleSet::from_binary_file(&cache).unwrap();
+    assert_eq!(reloaded.evaluate(&ctx), None);
+}
+
+#[test]
+fn load_cached_rebuilds_after_an_imported_file_changes() {
+    let dir = TempDir::new("load_cached_imports");
+    let dsl = dir.0.join("rules.ooroo");
+    let cache = dir.0.join("rules.ooroobin");
+    std::fs::create_dir_all(dir.0.join("common")).unwrap();
+    let common = dir.0.join("common/age.ooroo");
+    std::fs::write(&common, "rule adult: user.age >= 18").unwrap();
+    std::fs::write(
+        &dsl,
+        "import \"common/age.ooroo\" as age\nrule allow (priority 0): age.adult",
+    )
+    .unwrap();
+    let ctx = Context::new().set("user.age", 19_i64);
+    let first = RuleSet::load_cached(&dsl, &cache).unwrap();
+    assert_eq!(first.evaluate(&ctx), Some(Verdict::new("allow", true)));
+
+    // Only the imported file changes; the entry file is untouched.
+    std::fs::write(&common, "rule adult: user.age >= 21").unwrap();
+    let second = RuleSet::load_cached(&dsl, &cache).unwrap();
+    assert_eq!(second.evaluate(&ctx), None);
+    let reloaded = RuleSet::from_binary_file(&cache).unwrap();
+    assert_eq!(reloaded.evaluate(&ctx), None);
+}
+
+#[test]
+fn load_cached_rebuilds_damaged_caches() {
+    let dir = TempDir::new("load_cached_damaged");
+    let dsl = dir.0.join("rules.ooroo");
+    let cache = dir.0.join("rules.ooroobin");
+    std::fs::write(&dsl, "rule adult (priority 0): user.age >= 18").unwrap();
+
+    std::fs::write(&cache, b"not a blob").unwrap();
+    RuleSet::load_cached(&dsl, &cache).unwrap();
+    assert!(
+        inspect(&std::fs::read(&cache).unwrap())
+            .unwrap()
+            .checksum_valid
+    );
+
+    let mut bytes = std::fs::read(&cache).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    std::fs::write(&cache, &bytes).unwrap();
+    let ruleset = RuleSet::load_cached(&dsl, &cache).unwrap();
+    assert_eq!(
+        ruleset.evaluate(&Context::new().set("user.age", 30_i64)),
+        Some(Verdict::new("adult", true))
+    );
+    assert!(RuleSet::from_binary_file(&cache).is_ok());
+}
+
+#[test]
+fn load_cached_reports_source_errors() {
+    let dir = TempDir::new("load_cached_errors");
+    let cache = dir.0.join("rules.ooroobin");
+    assert!(RuleSet::load_cached(dir.0.join("missing.ooroo"), &cache).is_err());
+
+    let dsl = dir.0.join("bad.ooroo");
+    std::fs::write(&dsl, "rule :").unwrap();
+    assert!(RuleSet::load_cached(&dsl, &cache).is_err());
+    assert!(!cache.exists());
+}
+
+// ---------------------------------------------------------------------------
+// Payload size: string interning
+// ---------------------------------------------------------------------------
+
+/// `count` rules comparing `region` against a long literal, either the same
+/// one in every rule or a different one per rule.
+fn region_ruleset(count: usize, shared: bool) -> RuleSet {
+    let literal = |i: usize| {
+        let suffix = if shared { 0 } else { i };
+        format!(
+            "{}-{suffix}",
+            "north-atlantic-distribution-centre".repeat(4)
+        )
+    };
+    let mut builder = RuleSetBuilder::new();
+    for i in 0..count {
+        builder = builder.rule(&format!("r{i}"), |r| {
+            r.when(
+                field("region")
+                    .eq(literal(i).as_str())
+                    .and(field("sku").like(literal(i))),
+            )
+        });
+    }
+    builder.terminal("r0", 0).compile().unwrap()
+}
+
+#[test]
+fn repeated_strings_are_stored_once() {
+    let one = region_ruleset(1, true).to_bytes(None).unwrap().len();
+    let shared = region_ruleset(50, true).to_bytes(None).unwrap().len();
+    let distinct = region_ruleset(50, false).to_bytes(None).unwrap().len();
+
+    // Each extra rule adds an index per use, not another copy of the
+    // 138-byte string it compares against and matches.
+    assert!((shared - one) / 49 < 40, "{one} -> {shared}");
+    assert!(distinct > shared + 49 * 130, "{shared} vs {distinct}");
+}
+
+#[test]
+fn interned_strings_round_trip() {
+    for shared in [true, false] {
+        let ruleset = region_ruleset(5, shared);
+        let restored = RuleSet::from_bytes(&ruleset.to_bytes(None).unwrap()).unwrap();
+        assert_eq!(restored.to_dsl().unwrap(), ruleset.to_dsl().unwrap());
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Compression
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "compression")]
+#[test]
+fn compressed_blobs_are_smaller() {
+    let ruleset = RuleSet::from_dsl(
+        &(0..200)
+            .map(|i| {
+                format!("rule r{i} (priority {i}):\n    user.age >= {i} AND user.tier IN [\"t{i}\", \"gold\"]\n")
+            })
+            .collect::<String>(),
+    )
+    .unwrap();
+    let plain = ruleset.to_bytes(None).unwrap();
+    let compressed = ruleset.to_bytes_compressed(None).unwrap();
+    // Interning already removed repeated strings; LZ4 still finds the
+    // rule names and structure that repeat from rule to rule.
+    assert!(
+        compressed.len() < plain.len(),
+        "{} vs {}",
+        compressed.len(),
+        plain.len()
+    );
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn compressed_blobs_round_trip() {
+    let ruleset = complex_ruleset();
+    let blob = ruleset.to_bytes_compressed(Some("source")).unwrap();
+    let restored = RuleSet::from_bytes(&blob).unwrap();
+    assert_eq!(restored.to_dsl().unwrap(), ruleset.to_dsl().unwrap());
+    let ctx = Context::new()
+        .set("age", 30_i64)
+        .set("tier", "premium")
+        .set("verified", true)
+        .set("banned", false);
+    assert_eq!(restored.evaluate(&ctx), ruleset.evaluate(&ctx));
+
+    let info = inspect(&blob).unwrap();
+    assert!(info.is_compressed());
+    assert!(info.checksum_valid);
+    assert!(info.matches_source("source"));
+    assert_eq!(info.rule_count, 8);
+    assert!(!inspect(&ruleset.to_bytes(None).unwrap())
+        .unwrap()
+        .is_compressed());
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn compressed_blobs_are_checksummed() {
+    let mut blob = complex_ruleset().to_bytes_compressed(None).unwrap();
+    let last = blob.len() - 1;
+    blob[last] ^= 0xFF;
+    assert!(matches!(
+        RuleSet::from_bytes(&blob),
+        Err(DeserializeError::ChecksumMismatch)
+    ));
+}
+
+#[cfg(not(feature = "compression"))]
+#[test]
+fn compressed_blobs_need_the_compression_feature() {
+    // The flags are outside the checksum, so setting the COMPRESSED bit is
+    // enough to stand in for a blob written with the feature enabled.
+    let mut blob = simple_ruleset().to_bytes(None).unwrap();
+    blob[8] |= 2;
+    assert!(matches!(
+        RuleSet::from_bytes(&blob),
+        Err(DeserializeError::CompressionUnsupported)
+    ));
+    assert!(matches!(
+        inspect(&blob),
+        Err(DeserializeError::CompressionUnsupported)
+    ));
+}
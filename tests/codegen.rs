@@ -0,0 +1,156 @@
+/// Integration tests for generating Rust source from a ruleset.
+///
+/// Covers: the checked-in module generated from `fixtures/codegen/policy.ooroo`
+/// (which uses every construct) being current, agreeing with the interpreter
+/// on sampled and hand-written contexts, its typed input struct, and
+/// `from_context` rejecting mistyped values; and the generator options.
+use std::collections::{BTreeMap, HashSet};
+
+use ooroo::{Codegen, Context, RuleSet, Value};
+
+mod policy {
+    include!("fixtures/codegen/policy.rs");
+}
+
+const SOURCE: &str = include_str!("fixtures/codegen/policy.ooroo");
+const GENERATED: &str = include_str!("fixtures/codegen/policy.rs");
+
+fn ruleset() -> RuleSet {
+    RuleSet::from_dsl(SOURCE).unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// Generated module
+// ---------------------------------------------------------------------------
+
+#[test]
+fn generated_code_is_current() {
+    assert!(
+        Codegen::new(&ruleset()).generate().unwrap() == GENERATED,
+        "fixtures/codegen/policy.rs is stale; run the ignored `write_generated_code` test"
+    );
+    assert_eq!(policy::SOURCE, ruleset().to_dsl().unwrap());
+}
+
+#[test]
+fn generation_is_deterministic() {
+    let text = ruleset().to_dsl().unwrap();
+    let reparsed = RuleSet::from_dsl(&text).unwrap();
+    assert_eq!(
+        Codegen::new(&reparsed).generate().unwrap(),
+        Codegen::new(&ruleset()).generate().unwrap()
+    );
+}
+
+#[test]
+fn evaluate_matches_interpreter_on_sampled_contexts() {
+    let ruleset = ruleset();
+    let mut seen = HashSet::new();
+    for seed in 0..16 {
+        for ctx in ruleset.sample_contexts(250, seed) {
+            let input = policy::Input::from_context(&ctx).unwrap();
+            let expected = ruleset.evaluate(&ctx);
+            let expected = expected.as_ref().map(|v| v.terminal());
+            assert_eq!(policy::evaluate(&input), expected, "{ctx:?}");
+            seen.insert(expected.map(str::to_owned));
+        }
+    }
+    // The samples reach every outcome, so the comparison is not vacuous.
+    for outcome in [None, Some("deny"), Some("allow"), Some("review")] {
+        assert!(
+            seen.contains(&outcome.map(str::to_owned)),
+            "no sample evaluated to {outcome:?}"
+        );
+    }
+}
+
+#[test]
+fn evaluate_on_hand_written_inputs() {
+    let minor = policy::Input {
+        user_age: Some(15),
+        user_roles: Some(Value::List(vec!["admin".into(), "ops".into()])),
+        ..Default::default()
+    };
+    assert_eq!(policy::evaluate(&minor), Some("deny"));
+
+    let adult = policy::Input {
+        user_age: Some(30),
+        user_country: Some("US".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(policy::evaluate(&adult), Some("allow"));
+
+    let within_limit = policy::Input {
+        user_age: None,
+        order_total: Some(Value::Int(50)),
+        limits: Some(Value::Map(BTreeMap::from([(
+            "US".to_owned(),
+            Value::Int(100),
+        )]))),
+        ..adult
+    };
+    assert_eq!(policy::evaluate(&within_limit), Some("review"));
+    assert_eq!(policy::evaluate(&policy::Input::default()), None);
+}
+
+#[test]
+fn from_context_reads_declared_types() {
+    let ctx = Context::new()
+        .set("user.age", 30_i64)
+        .set("user.score", 3_i64)
+        .set("user.verified", true)
+        .set("order.total", "any");
+    let input = policy::Input::from_context(&ctx).unwrap();
+    assert_eq!(input.user_age, Some(30));
+    assert_eq!(input.user_score, Some(3.0));
+    assert_eq!(input.user_verified, Some(true));
+    assert_eq!(input.order_total, Some(Value::from("any")));
+    assert_eq!(input.user_email, None);
+}
+
+#[test]
+fn from_context_rejects_mistyped_values() {
+    for ctx in [
+        Context::new().set("user.age", "thirty"),
+        Context::new().set("user.score", true),
+        Context::new().set("user.roles", "admin"),
+        Context::new().set("limits", 1_i64),
+    ] {
+        assert_eq!(policy::Input::from_context(&ctx), None, "{ctx:?}");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Options
+// ---------------------------------------------------------------------------
+
+#[test]
+fn options_rename_the_struct_and_crate_path() {
+    let ruleset = RuleSet::from_dsl("rule big (priority 0):\n    x > 1").unwrap();
+    let code = Codegen::new(&ruleset)
+        .struct_name("Facts")
+        .crate_path("crate::engine")
+        .tests(false)
+        .generate()
+        .unwrap();
+    assert!(code
+        .contains("pub struct Facts {\n    /// `x`\n    pub x: Option<crate::engine::Value>,\n}"));
+    assert!(code.contains("pub fn from_context(ctx: &crate::engine::Context)"));
+    assert!(code.contains("pub fn evaluate(input: &Facts) -> Option<&'static str>"));
+    assert!(code.contains("use crate::engine::__codegen as rt;"));
+    assert!(!code.contains("#[cfg(test)]"));
+    assert!(Codegen::new(&ruleset)
+        .generate()
+        .unwrap()
+        .contains("mod generated_tests"));
+}
+
+/// Rewrites the checked-in generated module. Run after changing the
+/// generator or the fixture, then review the diff.
+#[test]
+#[ignore = "writes fixtures/codegen; run only when the generator changes"]
+fn write_generated_code() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/codegen/policy.rs");
+    std::fs::write(path, Codegen::new(&ruleset()).generate().unwrap()).unwrap();
+}
@@ -0,0 +1,195 @@
+/// Integration tests for DSL `const` and `param` declarations.
+///
+/// Covers: scalar and list constants in comparisons, `IN`, `BETWEEN`, set
+/// operators and `len()`, quantifier variables shadowing constants, supplied
+/// parameters versus defaults, one source compiled per tenant, and the
+/// missing, undeclared and duplicate errors, and declared names rejected in
+/// field positions.
+use ooroo::{CompileError, Context, OorooError, Params, RuleSet, Value, Verdict};
+
+fn strings(values: &[&str]) -> Value {
+    Value::List(values.iter().map(|s| Value::from(*s)).collect())
+}
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+#[test]
+fn constants_replace_literals() {
+    let dsl = r#"
+const MIN_AGE = 18
+const BLOCKED = ["XX", "YY"]
+
+rule adult:
+    user.age >= MIN_AGE
+
+rule allowed_country:
+    user.country NOT IN BLOCKED
+
+rule allowed (priority 0):
+    adult AND allowed_country
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    let ctx = Context::new()
+        .set("user.age", 18_i64)
+        .set("user.country", "US");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allowed", true)));
+
+    let ctx = ctx.set("user.country", "XX");
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn constants_in_bounds_and_list_operators() {
+    let dsl = r#"
+const LOW = 10
+const HIGH = 20.5
+const MAX_TAGS = 2
+const SPAM = ["spam", "scam"]
+
+rule r (priority 0):
+    score BETWEEN LOW, HIGH AND tags DISJOINT_FROM SPAM AND len(tags) <= MAX_TAGS
+        AND kind IN [LOW, "other"]
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    let ctx = Context::new()
+        .set("score", 15_i64)
+        .set("tags", strings(&["news"]))
+        .set("kind", 10_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = ctx.set("tags", strings(&["news", "scam"]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn undeclared_names_stay_field_references() {
+    let dsl = "const LIMIT = 100\nrule r (priority 0):\n    amount <= LIMIT AND amount <= limit";
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    let ctx = Context::new().set("amount", 50_i64).set("limit", 60_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+    let ctx = ctx.set("limit", 40_i64);
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn quantifier_variables_shadow_constants() {
+    let dsl = r#"
+const x = 5
+
+rule r (priority 0):
+    ANY(pairs AS x, x == limit) AND value == x
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    let ctx = Context::new()
+        .set("pairs", Value::List(vec![Value::Int(1), Value::Int(7)]))
+        .set("limit", 7_i64)
+        .set("value", 5_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+#[test]
+fn duplicate_declarations_are_rejected() {
+    let err =
+        RuleSet::from_dsl("const A = 1\nparam A\nrule r (priority 0):\n    x == A").unwrap_err();
+    assert!(matches!(err, OorooError::Parse(_)));
+    assert!(err.to_string().contains("'A' is declared more than once"));
+}
+
+#[test]
+fn declared_names_are_rejected_as_fields() {
+    for (dsl, message) in [
+        (
+            "const MIN = 18\nrule r (priority 0):\n    MIN <= user.age",
+            "rule 'r' uses constant 'MIN' as a field",
+        ),
+        (
+            "param TIERS\nrule r (priority 0):\n    x == 1 AND NOT TIERS CONTAINS \"gold\"",
+            "rule 'r' uses parameter 'TIERS' as a field",
+        ),
+        (
+            "const XS = [1]\nrule r (priority 0):\n    ANY(XS AS x, x > 0)",
+            "rule 'r' uses constant 'XS' as a field",
+        ),
+    ] {
+        let err = RuleSet::from_dsl(dsl).unwrap_err();
+        assert!(matches!(err, OorooError::Parse(_)), "{dsl}");
+        assert!(err.to_string().contains(message), "{err}");
+    }
+
+    // An element variable with a declared name is not a field.
+    let dsl = "const x = 5\nrule r (priority 0):\n    ANY(xs AS x, x > 1)";
+    assert!(RuleSet::from_dsl(dsl).is_ok());
+}
+
+// ---------------------------------------------------------------------------
+// Parameters
+// ---------------------------------------------------------------------------
+
+const TENANT_RULES: &str = r#"
+param MIN_AGE = 18
+param MAX_AMOUNT
+param REGIONS = ["US"]
+
+rule allowed (priority 0):
+    user.age >= MIN_AGE AND order.amount <= MAX_AMOUNT AND user.region IN REGIONS
+"#;
+
+fn order(age: i64, amount: i64, region: &str) -> Context {
+    Context::new()
+        .set("user.age", age)
+        .set("order.amount", amount)
+        .set("user.region", region)
+}
+
+#[test]
+fn params_compile_per_tenant() {
+    let small =
+        RuleSet::from_dsl_with_params(TENANT_RULES, &Params::new().set("MAX_AMOUNT", 500_i64))
+            .unwrap();
+    let large = RuleSet::from_dsl_with_params(
+        TENANT_RULES,
+        &Params::new()
+            .set("MAX_AMOUNT", 10_000_i64)
+            .set("MIN_AGE", 21_i64)
+            .set("REGIONS", strings(&["US", "EU"])),
+    )
+    .unwrap();
+
+    let ctx = order(19, 800, "US");
+    assert!(small.evaluate(&ctx).is_none());
+    assert!(large.evaluate(&ctx).is_none());
+
+    let ctx = order(30, 800, "EU");
+    assert!(small.evaluate(&ctx).is_none());
+    assert_eq!(large.evaluate(&ctx), Some(Verdict::new("allowed", true)));
+
+    let ctx = order(19, 400, "US");
+    assert_eq!(small.evaluate(&ctx), Some(Verdict::new("allowed", true)));
+}
+
+#[test]
+fn param_without_value_or_default_is_an_error() {
+    let err = RuleSet::from_dsl(TENANT_RULES).unwrap_err();
+    assert!(matches!(
+        err,
+        OorooError::Compile(CompileError::MissingParam { ref name }) if name == "MAX_AMOUNT"
+    ));
+}
+
+#[test]
+fn undeclared_param_is_an_error() {
+    let params = Params::new()
+        .set("MAX_AMOUNT", 1_i64)
+        .set("MAX_AMOUT", 1_i64);
+    let err = RuleSet::from_dsl_with_params(TENANT_RULES, &params).unwrap_err();
+    assert!(matches!(
+        err,
+        OorooError::Compile(CompileError::UndeclaredParam { ref name }) if name == "MAX_AMOUT"
+    ));
+}
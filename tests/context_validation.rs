@@ -0,0 +1,207 @@
+/// Integration tests for context validation.
+///
+/// Covers: `RuleSet::check_context` reporting unknown, missing and mistyped
+/// fields, type expectations inferred from the rules versus declared in a
+/// schema, paths inside stored maps, strict `ContextBuilder` mode with
+/// `report` and `try_build`, and the report's `Display`.
+use std::collections::BTreeMap;
+
+use ooroo::{
+    any, field, Context, FieldType, RuleSet, RuleSetBuilder, Schema, SchemaViolation, Value,
+    Verdict,
+};
+
+fn ruleset() -> RuleSet {
+    RuleSetBuilder::new()
+        .rule("adult", |r| r.when(field("user.age").gte(18_i64)))
+        .rule("named", |r| r.when(field("user.name").like("a%")))
+        .rule("big_order", |r| {
+            r.when(any("order.amounts", "a", field("a").gt(100_i64)))
+        })
+        .rule("gold", |r| {
+            r.when(field("user.tier").is_in(["gold", "platinum"]))
+        })
+        .terminal("adult", 0)
+        .terminal("named", 1)
+        .terminal("big_order", 2)
+        .terminal("gold", 3)
+        .compile()
+        .unwrap()
+}
+
+fn full_context() -> Context {
+    Context::new()
+        .set("user.age", 30_i64)
+        .set("user.name", "alice")
+        .set("user.tier", "gold")
+        .set("order.amounts", Value::List(vec![Value::Int(50)]))
+}
+
+fn violation(path: &str, expected: FieldType, found: FieldType) -> SchemaViolation {
+    SchemaViolation {
+        path: path.into(),
+        expected,
+        found,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// check_context
+// ---------------------------------------------------------------------------
+
+#[test]
+fn valid_context_has_empty_report() {
+    let report = ruleset().check_context(&full_context());
+    assert!(report.is_empty(), "{report}");
+}
+
+#[test]
+fn reports_unknown_paths() {
+    let ctx = full_context().set("user.agee", 30_i64).set("debug", true);
+    let report = ruleset().check_context(&ctx);
+    assert_eq!(report.unknown(), ["debug", "user.agee"]);
+    assert!(report.has_errors());
+}
+
+#[test]
+fn reports_missing_referenced_fields() {
+    let ctx = Context::new().set("user.age", 30_i64);
+    let report = ruleset().check_context(&ctx);
+    assert_eq!(
+        report.missing(),
+        ["order.amounts", "user.name", "user.tier"]
+    );
+    assert!(!report.has_errors());
+}
+
+#[test]
+fn reports_values_the_rules_cannot_compare() {
+    let ctx = full_context()
+        .set("user.age", "30")
+        .set("user.name", 7_i64)
+        .set("user.tier", true)
+        .set("order.amounts", 50_i64);
+    let report = ruleset().check_context(&ctx);
+    assert_eq!(
+        report.mistyped(),
+        [
+            violation("order.amounts", FieldType::List, FieldType::Int),
+            violation("user.age", FieldType::Int, FieldType::String),
+            violation("user.name", FieldType::String, FieldType::Int),
+            violation("user.tier", FieldType::String, FieldType::Bool),
+        ]
+    );
+}
+
+#[test]
+fn inferred_numbers_accept_int_and_float() {
+    let ctx = full_context().set("user.age", 30.5);
+    assert!(ruleset().check_context(&ctx).is_empty());
+}
+
+#[test]
+fn schema_declarations_take_precedence() {
+    let ruleset = RuleSetBuilder::new()
+        .schema(
+            Schema::new()
+                .field("user.age", FieldType::Int)
+                .field("user.email", FieldType::String),
+        )
+        .rule("adult", |r| r.when(field("user.age").gte(18.0)))
+        .terminal("adult", 0)
+        .compile()
+        .unwrap();
+
+    // Declared `int`, so a float is mistyped even though the rule compares
+    // against a float literal.
+    let ctx = Context::new()
+        .set("user.age", 30.5)
+        .set("user.email", "a@b");
+    let report = ruleset.check_context(&ctx);
+    assert_eq!(
+        report.mistyped(),
+        [violation("user.age", FieldType::Int, FieldType::Float)]
+    );
+    // Declared but unreferenced fields are not unknown.
+    assert!(report.unknown().is_empty());
+}
+
+#[test]
+fn map_values_cover_the_paths_inside_them() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(field("limits.US").gte(100_i64)))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let limits = Value::Map(BTreeMap::from([("US".to_owned(), Value::Int(100))]));
+    let ctx = Context::new().set("limits", limits);
+    assert!(ruleset.check_context(&ctx).is_empty());
+}
+
+// ---------------------------------------------------------------------------
+// Strict ContextBuilder
+// ---------------------------------------------------------------------------
+
+#[test]
+fn strict_builder_rejects_unknown_paths() {
+    let ruleset = ruleset();
+    let report = ruleset
+        .context_builder()
+        .strict()
+        .set("user.agee", 30_i64)
+        .try_build()
+        .unwrap_err();
+    assert_eq!(report.unknown(), ["user.agee"]);
+    assert_eq!(
+        report.missing(),
+        ["order.amounts", "user.age", "user.name", "user.tier"]
+    );
+}
+
+#[test]
+fn lenient_builder_ignores_unknown_paths_but_not_types() {
+    let ruleset = ruleset();
+    let builder = ruleset.context_builder().set("user.agee", 30_i64);
+    assert!(builder.report().unknown().is_empty());
+    assert!(builder.try_build().is_ok());
+
+    let report = ruleset
+        .context_builder()
+        .set("user.age", "thirty")
+        .try_build()
+        .unwrap_err();
+    assert_eq!(
+        report.mistyped(),
+        [violation("user.age", FieldType::Int, FieldType::String)]
+    );
+}
+
+#[test]
+fn strict_builder_builds_valid_context() {
+    let ruleset = ruleset();
+    let ctx = ruleset
+        .context_builder()
+        .strict()
+        .set("user.age", 30_i64)
+        .try_build()
+        .unwrap();
+    assert_eq!(
+        ruleset.evaluate_indexed(&ctx),
+        Some(Verdict::new("adult", true))
+    );
+}
+
+#[test]
+fn report_display() {
+    let ctx = Context::new()
+        .set("user.agee", 1_i64)
+        .set("user.age", "x")
+        .set("user.name", "a")
+        .set("user.tier", "gold");
+    assert_eq!(
+        ruleset().check_context(&ctx).to_string(),
+        "unknown field 'user.agee'; missing field 'order.amounts'; \
+         field 'user.age' should be int but is string"
+    );
+}
@@ -0,0 +1,190 @@
+/// Integration tests for decompiling compiled rulesets back to DSL.
+///
+/// Covers: rules in dependency order with terminals, priorities and
+/// `on_unknown`; annotations; schema declarations; constants and
+/// templates appearing inlined; lookups and quantifiers; parsing the output
+/// back to an equivalent ruleset; names written between backticks; values
+/// with no DSL literal; and decompiling rulesets loaded from binary blobs.
+use std::collections::BTreeMap;
+
+use ooroo::{field, rule_ref, Context, RuleSet, RuleSetBuilder, ToDslError, Value, Verdict};
+
+const SOURCE: &str = r#"
+const BLOCKED = ["XX", "YY"]
+
+template gate(limit): user.age >= limit
+
+# Comments are not kept.
+@description("Grown-ups")
+@owner("risk")
+@tag("kyc")
+expand gate(18) as adult
+
+rule blocked (priority 0, on_unknown indeterminate):
+    user.country IN BLOCKED OR user.banned == true
+
+@disabled
+rule legacy (priority 1):
+    user.legacy == true
+
+rule big_order:
+    ANY(order.items AS line, line.price > 100)
+
+rule within_limit:
+    order.total <= limits[user.country].daily
+
+rule allow (priority 2):
+    adult AND NOT blocked AND (within_limit OR big_order)
+"#;
+
+fn ruleset() -> RuleSet {
+    RuleSet::from_dsl(SOURCE).unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// Output
+// ---------------------------------------------------------------------------
+
+#[test]
+fn rules_are_written_dependencies_first() {
+    let dsl = ruleset().to_dsl().unwrap();
+    let names: Vec<&str> = dsl
+        .lines()
+        .filter_map(|line| line.strip_prefix("rule "))
+        .map(|rest| rest.split([' ', ':']).next().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "adult",
+            "big_order",
+            "blocked",
+            "legacy",
+            "within_limit",
+            "allow"
+        ]
+    );
+    assert!(!dsl.contains("Comments"));
+}
+
+#[test]
+fn terminals_and_annotations_are_written() {
+    let dsl = ruleset().to_dsl().unwrap();
+    assert!(dsl.contains(
+        "rule blocked (priority 0, on_unknown indeterminate):\n    \
+         user.country IN [\"XX\", \"YY\"] OR user.banned == true\n"
+    ));
+    assert!(dsl.contains("@disabled\nrule legacy (priority 1):\n"));
+    assert!(dsl.contains(
+        "@description(\"Grown-ups\")\n@owner(\"risk\")\n@tag(\"kyc\")\nrule adult:\n    \
+         user.age >= 18\n"
+    ));
+    assert!(dsl.contains("rule allow (priority 2):\n"));
+}
+
+#[test]
+fn lookups_and_quantifiers_are_rebuilt() {
+    let dsl = ruleset().to_dsl().unwrap();
+    assert!(dsl.contains("    order.total <= limits[user.country].daily\n"));
+    assert!(dsl.contains("    ANY(order.items AS item, item.price > 100)\n"));
+}
+
+#[test]
+fn schema_declarations_come_first() {
+    let ruleset = RuleSet::from_dsl(
+        "field user.name: string\nfield user.age: int\nrule adult (priority 0): user.age >= 18",
+    )
+    .unwrap();
+    assert_eq!(
+        ruleset.to_dsl().unwrap(),
+        "field user.age: int\nfield user.name: string\n\nrule adult (priority 0):\n    user.age >= 18\n"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Round trip
+// ---------------------------------------------------------------------------
+
+#[test]
+fn output_compiles_to_an_equivalent_ruleset() {
+    let original = ruleset();
+    let dsl = original.to_dsl().unwrap();
+    let rebuilt = RuleSet::from_dsl(&dsl).unwrap();
+    assert_eq!(rebuilt.to_dsl().unwrap(), dsl);
+    assert_eq!(rebuilt.terminal_order(), original.terminal_order());
+
+    let daily = Value::Map(BTreeMap::from([("daily".to_owned(), Value::Int(80))]));
+    let limits = Value::Map(BTreeMap::from([("US".to_owned(), daily)]));
+    let ctx = Context::new()
+        .set("user.age", 30_i64)
+        .set("user.country", "US")
+        .set("user.banned", false)
+        .set("order.total", 50_i64)
+        .set("limits", limits);
+    assert_eq!(original.evaluate(&ctx), Some(Verdict::new("allow", true)));
+    for ctx in [
+        ctx.clone(),
+        ctx.clone().set("user.country", "XX"),
+        ctx.clone().set("order.total", 90_i64),
+        ctx.set("user.age", 10_i64),
+    ] {
+        assert_eq!(rebuilt.evaluate(&ctx), original.evaluate(&ctx));
+    }
+}
+
+#[test]
+fn awkward_names_are_quoted() {
+    let original = RuleSetBuilder::new()
+        .rule("my rule", |r| r.when(field("true").eq(true)))
+        .rule("allow", |r| {
+            r.when(rule_ref("my rule").and(field("AND").gt(1_i64)))
+        })
+        .terminal("allow", 0)
+        .compile()
+        .unwrap();
+    let dsl = original.to_dsl().unwrap();
+    assert_eq!(
+        dsl,
+        "rule `my rule`:\n    `true` == true\n\n\
+         rule allow (priority 0):\n    `my rule` AND `AND` > 1\n"
+    );
+
+    let rebuilt = RuleSet::from_dsl(&dsl).unwrap();
+    let ctx = Context::new().set("true", true).set("AND", 2_i64);
+    assert_eq!(rebuilt.evaluate(&ctx), Some(Verdict::new("allow", true)));
+    assert_eq!(rebuilt.evaluate(&ctx.set("true", false)), None);
+}
+
+#[test]
+fn values_without_literals_are_errors() {
+    let map = Value::Map(BTreeMap::from([("a".to_owned(), Value::Int(1))]));
+    for value in [map, Value::Float(f64::NAN), Value::Float(f64::INFINITY)] {
+        let text = value.to_string();
+        let ruleset = RuleSetBuilder::new()
+            .rule("r", |r| r.when(field("x").eq(value)))
+            .terminal("r", 0)
+            .compile()
+            .unwrap();
+        assert_eq!(ruleset.to_dsl(), Err(ToDslError::Value(text.clone())));
+        assert_eq!(
+            ruleset.to_dsl().unwrap_err().to_string(),
+            format!("cannot write {text} as a DSL literal")
+        );
+    }
+
+    let ruleset = RuleSetBuilder::new()
+        .rule("a`b", |r| r.when(field("x").eq(1_i64)))
+        .terminal("a`b", 0)
+        .compile()
+        .unwrap();
+    assert_eq!(ruleset.to_dsl(), Err(ToDslError::Name("a`b".to_owned())));
+}
+
+#[cfg(feature = "binary-cache")]
+#[test]
+fn blobs_decompile_like_their_source() {
+    let original = ruleset();
+    let bytes = original.to_bytes(Some(SOURCE)).unwrap();
+    let loaded = RuleSet::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded.to_dsl().unwrap(), original.to_dsl().unwrap());
+}
@@ -148,6 +148,7 @@ rule r (priority 0):
 }
 
 #[test]
+#[allow(clippy::approx_constant)] // 3.14 is just a float literal here
 fn dsl_all_value_types() {
     let dsl = r#"
 rule int_check:
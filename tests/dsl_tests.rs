@@ -0,0 +1,229 @@
+/// Integration tests for `test` blocks in the DSL and `RuleSet::run_tests`.
+///
+/// Covers: running the tests of a rules file as a Rust test, failures
+/// carrying their evaluation report, `assert_passed`, compile errors for
+/// bad expectations, tests in imported files, and writing test blocks back
+/// out with `to_dsl` and the formatter.
+use ooroo::{
+    field, format_dsl, is_formatted, CompileError, MemoryLoader, OorooError, RuleSet,
+    RuleSetBuilder, RuleTest, Value,
+};
+
+const POLICY: &str = "tests/fixtures/dsl_tests/policy.ooroo";
+
+fn compile_error(source: &str) -> CompileError {
+    match RuleSet::from_dsl(source) {
+        Err(OorooError::Compile(e)) => e,
+        other => panic!("expected a compile error, got {other:?}"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Running tests
+// ---------------------------------------------------------------------------
+
+/// The entry point a project uses to check its rules in CI.
+#[test]
+fn policy_file_tests_pass() {
+    let ruleset = RuleSet::from_file(POLICY).unwrap();
+    let report = ruleset.run_tests();
+    report.assert_passed();
+    assert_eq!(report.outcomes().len(), 6);
+    assert!(report.to_string().ends_with("6 passed; 0 failed"));
+}
+
+#[test]
+fn tests_are_kept_in_declaration_order() {
+    let ruleset = RuleSet::from_file(POLICY).unwrap();
+    let minor = &ruleset.tests()[0];
+    assert_eq!(minor.name, "minor is denied");
+    assert_eq!(
+        minor.given,
+        [
+            ("user.age".to_owned(), Value::Int(15)),
+            ("user.status".to_owned(), Value::from("active")),
+        ]
+    );
+    assert_eq!(minor.expect.as_deref(), Some("banned"));
+    assert_eq!(ruleset.tests()[4].expect, None);
+}
+
+#[test]
+fn failures_carry_the_evaluation_report() {
+    let ruleset = RuleSet::from_dsl(
+        r#"
+rule adult:
+    user.age >= 18
+rule allow (priority 0):
+    adult AND user.status == "active"
+
+test "adult is allowed" { given user.age = 30; expect allow }
+test "minor is not" { given user.age = 12; expect none }
+"#,
+    )
+    .unwrap();
+    let report = ruleset.run_tests();
+    assert!(!report.passed());
+
+    let failures: Vec<_> = report.failures().collect();
+    assert_eq!(failures.len(), 1);
+    let failure = failures[0];
+    assert_eq!(failure.name(), "adult is allowed");
+    assert_eq!(failure.expected(), Some("allow"));
+    assert_eq!(failure.actual(), None);
+    let evaluation = failure.report().unwrap();
+    assert_eq!(evaluation.evaluated(), ["adult"]);
+    assert!(evaluation.verdict().is_none());
+
+    let passed = &report.outcomes()[1];
+    assert!(passed.passed());
+    assert!(passed.report().is_none());
+
+    let text = report.to_string();
+    assert!(text.contains(
+        "test \"adult is allowed\" ... FAILED: expected allow, got none (verdict: none, evaluated: [adult]"
+    ));
+    assert!(text.contains("test \"minor is not\" ... ok"));
+    assert!(text.ends_with("1 passed; 1 failed"));
+}
+
+#[test]
+#[should_panic(expected = "test \"minor is allowed\" ... FAILED: expected allow, got none")]
+fn assert_passed_panics_with_the_report() {
+    RuleSet::from_dsl(
+        "rule allow (priority 0):\n    age >= 18\n\
+         test \"minor is allowed\" { given age = 12; expect allow }",
+    )
+    .unwrap()
+    .run_tests()
+    .assert_passed();
+}
+
+#[test]
+fn rulesets_without_tests_pass() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("big", |r| r.when(field("x").gt(1_i64)))
+        .terminal("big", 0)
+        .compile()
+        .unwrap();
+    assert!(ruleset.tests().is_empty());
+    let report = ruleset.run_tests();
+    assert!(report.passed());
+    assert_eq!(report.to_string(), "0 passed; 0 failed");
+}
+
+// ---------------------------------------------------------------------------
+// Compile errors
+// ---------------------------------------------------------------------------
+
+#[test]
+fn expectations_must_name_terminals() {
+    let rules = "rule adult:\n    age >= 18\nrule allow (priority 0):\n    adult\n";
+    assert!(matches!(
+        compile_error(&format!("{rules}test \"t\" {{ expect deny }}")),
+        CompileError::UndefinedTestTerminal { test, terminal } if test == "t" && terminal == "deny"
+    ));
+    assert!(matches!(
+        compile_error(&format!("{rules}test \"t\" {{ expect adult }}")),
+        CompileError::UndefinedTestTerminal { terminal, .. } if terminal == "adult"
+    ));
+}
+
+#[test]
+fn test_names_must_be_unique() {
+    let err = compile_error(
+        "rule allow (priority 0):\n    age >= 18\n\
+         test \"t\" { expect none }\ntest \"t\" { expect allow }",
+    );
+    assert!(matches!(err, CompileError::DuplicateTest { name } if name == "t"));
+    assert_eq!(
+        CompileError::DuplicateTest { name: "t".into() }.to_string(),
+        "duplicate test name 't'"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Imports
+// ---------------------------------------------------------------------------
+
+#[test]
+fn only_the_entry_files_tests_run() {
+    let loader = MemoryLoader::new()
+        .file(
+            "base.ooroo",
+            "rule adult (priority 5):\n    age >= 18\n\
+             test \"adult\" { given age = 30; expect adult }",
+        )
+        .file(
+            "main.ooroo",
+            "import \"base.ooroo\" as base\n\
+             rule allow (priority 0):\n    base.adult AND member == true\n\
+             test \"member\" { given age = 30, member = true; expect allow }\n\
+             test \"non-member\" { given age = 30; expect base.adult }",
+        );
+    let ruleset = RuleSet::from_loader(&loader, "main.ooroo").unwrap();
+    let names: Vec<&str> = ruleset.tests().iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["member", "non-member"]);
+    ruleset.run_tests().assert_passed();
+}
+
+// ---------------------------------------------------------------------------
+// Writing tests back out
+// ---------------------------------------------------------------------------
+
+#[test]
+fn policy_file_is_formatted() {
+    let source = std::fs::read_to_string(POLICY).unwrap();
+    assert!(is_formatted(&source).unwrap());
+}
+
+#[test]
+fn to_dsl_round_trips_tests() {
+    let ruleset = RuleSet::from_file(POLICY).unwrap();
+    let text = ruleset.to_dsl().unwrap();
+    assert!(text.ends_with("test \"unknown age is denied\" {\n    expect banned\n}\n"));
+    let reparsed = RuleSet::from_dsl(&text).unwrap();
+    assert_eq!(reparsed.tests(), ruleset.tests());
+    reparsed.run_tests().assert_passed();
+}
+
+#[test]
+fn formatter_lays_out_test_blocks() {
+    let source = "rule allow (priority 0): x == 1\n\
+                  test  \"one\"{given x=1,y=[ \"a\" ,\"b\"];expect allow}\n\
+                  test \"two\" { expect none }";
+    let formatted = format_dsl(source).unwrap();
+    assert_eq!(
+        formatted,
+        "rule allow (priority 0):\n    x == 1\n\n\
+         test \"one\" {\n    given x = 1, y = [\"a\", \"b\"];\n    expect allow\n}\n\n\
+         test \"two\" {\n    expect none\n}\n"
+    );
+    assert!(is_formatted(&formatted).unwrap());
+}
+
+#[test]
+fn long_given_lists_are_split() {
+    let test = RuleTest {
+        name: "long".into(),
+        given: (0..6)
+            .map(|i| (format!("customer.field_{i}"), Value::from("some value")))
+            .collect(),
+        expect: None,
+    };
+    let source = format!(
+        "rule allow (priority 0):\n    x == 1\n\ntest \"long\" {{ given {}; expect none }}",
+        test.given
+            .iter()
+            .map(|(path, _)| format!("{path} = \"some value\""))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let formatted = format_dsl(&source).unwrap();
+    assert!(formatted.contains(
+        "    given\n        customer.field_0 = \"some value\",\n        customer.field_1"
+    ));
+    assert!(formatted.contains("customer.field_5 = \"some value\";\n    expect none\n}"));
+    assert!(is_formatted(&formatted).unwrap());
+    assert_eq!(RuleSet::from_dsl(&formatted).unwrap().tests(), [test]);
+}
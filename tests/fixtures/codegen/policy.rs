@@ -0,0 +1,119 @@
+// @generated by ooroo::Codegen. Do not edit.
+
+/// The ruleset this module was generated from.
+pub const SOURCE: &str = "field limits: map\nfield order: map\nfield user.age: int\nfield user.country: string\nfield user.email: string\nfield user.nickname: string\nfield user.roles: list\nfield user.score: float\nfield user.verified: bool\n\nrule adult:\n    user.age >= 18 AND user.age < 130\n\nrule busy:\n    AT_LEAST(2, user.roles CONTAINS \"admin\", len(user.roles) >= 2, user.roles OVERLAPS [\"ops\", \"dev\"])\n\nrule cheap_tail:\n    order.items[-1].price < 5 OR order.items[*].sku CONTAINS \"FREE\"\n\nrule clean:\n    ALL(order.items AS item, ALL(item.tags AS item1, item1 != \"banned\"))\n\nrule in_range:\n    order.total BETWEEN 10, order.max AND user.age BETWEEN 21.5, 65\n\nrule known:\n    user.nickname IS NOT NULL OR user.email IS NULL\n\n@disabled\nrule legacy:\n    user.age < 0\n\nrule deny (priority 0):\n    legacy OR NOT adult AND busy\n\nrule local:\n    user.country IN [\"US\", \"CA\"] AND user.country NOT IN user.nickname\n\nrule scored:\n    user.score > 0.5 OR user.score == 2\n\nrule scores_match:\n    user.score >= order.total AND user.nickname != user.country\n\nrule spender:\n    ANY(order.items AS item, item.price > 100 AND item.sku LIKE \"A%\")\n\nrule trusted:\n    user.verified == true\n    AND user.email NOT LIKE \"%@spam.test\"\n    AND user.email LIKE \"%@%\"\n\nrule allow (priority 1):\n    adult AND (local OR trusted OR clean) AND (scored OR in_range OR known)\n\nrule within_limit:\n    limits HAS_KEY user.country AND order.total <= limits[user.country]\n\nrule review (priority 2):\n    spender OR cheap_tail OR within_limit OR scores_match OR clean\n";
+
+/// The context fields read by the ruleset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Input {
+    /// `limits`
+    pub limits: Option<::ooroo::Value>,
+    /// `order.items`
+    pub order_items: Option<::ooroo::Value>,
+    /// `order.max`
+    pub order_max: Option<::ooroo::Value>,
+    /// `order.total`
+    pub order_total: Option<::ooroo::Value>,
+    /// `user.age`
+    pub user_age: Option<i64>,
+    /// `user.country`
+    pub user_country: Option<String>,
+    /// `user.email`
+    pub user_email: Option<String>,
+    /// `user.nickname`
+    pub user_nickname: Option<String>,
+    /// `user.roles`
+    pub user_roles: Option<::ooroo::Value>,
+    /// `user.score`
+    pub user_score: Option<f64>,
+    /// `user.verified`
+    pub user_verified: Option<bool>,
+}
+
+impl Input {
+    /// Read every field from `ctx`. Returns `None` if a field declared in
+    /// the schema holds a value of another type.
+    #[must_use]
+    pub fn from_context(ctx: &::ooroo::Context) -> Option<Self> {
+        Some(Self {
+            limits: ::ooroo::__codegen::value(ctx.get("limits"), Some(::ooroo::FieldType::Map))?,
+            order_items: ::ooroo::__codegen::value(ctx.get("order.items"), None)?,
+            order_max: ::ooroo::__codegen::value(ctx.get("order.max"), None)?,
+            order_total: ::ooroo::__codegen::value(ctx.get("order.total"), None)?,
+            user_age: ::ooroo::__codegen::int(ctx.get("user.age"))?,
+            user_country: ::ooroo::__codegen::string(ctx.get("user.country"))?,
+            user_email: ::ooroo::__codegen::string(ctx.get("user.email"))?,
+            user_nickname: ::ooroo::__codegen::string(ctx.get("user.nickname"))?,
+            user_roles: ::ooroo::__codegen::value(ctx.get("user.roles"), Some(::ooroo::FieldType::List))?,
+            user_score: ::ooroo::__codegen::float(ctx.get("user.score"))?,
+            user_verified: ::ooroo::__codegen::bool(ctx.get("user.verified"))?,
+        })
+    }
+}
+
+/// Evaluate the ruleset, returning the name of the highest-priority
+/// terminal that holds.
+#[must_use]
+#[allow(clippy::all, clippy::pedantic, unused_imports, unused_parens, unused_variables)]
+pub fn evaluate(input: &Input) -> Option<&'static str> {
+    use ::ooroo::__codegen as rt;
+    use ::ooroo::{CompareOp, SetOp, Value};
+    static LITERALS: ::std::sync::LazyLock<[Value; 6]> = ::std::sync::LazyLock::new(|| {
+        [
+            Value::String("admin".to_owned()),
+            Value::List(vec![Value::String("ops".to_owned()), Value::String("dev".to_owned())]),
+            Value::String("FREE".to_owned()),
+            Value::String("banned".to_owned()),
+            Value::String("US".to_owned()),
+            Value::String("CA".to_owned()),
+        ]
+    });
+    let f_user_age = input.user_age.map(Value::Int);
+    let f_user_country = input.user_country.as_deref();
+    let f_user_nickname = input.user_nickname.as_deref();
+    let f_user_score = input.user_score.map(Value::Float);
+    let r_adult = (matches!(input.user_age, Some(v) if v >= 18) && matches!(input.user_age, Some(v) if v < 130));
+    let r_busy = (usize::from(rt::contains(input.user_roles.as_ref(), Some(&LITERALS[0]))) + usize::from(rt::len(input.user_roles.as_ref(), CompareOp::Gte, Some(&Value::Int(2)))) + usize::from(rt::set_compare(input.user_roles.as_ref(), SetOp::Overlaps, Some(&LITERALS[1]))) >= 2);
+    let r_cheap_tail = ({ let l0 = rt::lookup(input.order_items.as_ref(), &[rt::Step::Index(-1), rt::Step::Key("price")]); let s0 = l0.as_deref(); rt::compare(s0, CompareOp::Lt, Some(&Value::Int(5))) } || { let l1 = rt::lookup(input.order_items.as_ref(), &[rt::Step::Wildcard, rt::Step::Key("sku")]); let s1 = l1.as_deref(); rt::contains(s1, Some(&LITERALS[2])) });
+    let r_clean = rt::list(input.order_items.as_ref()).is_some_and(|items| items.iter().all(|item| { let s0 = rt::element(item, &["tags"]); rt::list(s0).is_some_and(|items| items.iter().all(|item| { let s1 = rt::element(item, &[]); rt::compare(s1, CompareOp::Neq, Some(&LITERALS[3])) })) }));
+    let r_in_range = (rt::between(input.order_total.as_ref(), Some(&Value::Int(10)), input.order_max.as_ref()) && rt::between(f_user_age.as_ref(), Some(&Value::Float(21.5_f64)), Some(&Value::Int(65))));
+    let r_known = (input.user_nickname.is_some() || input.user_email.is_none());
+    let r_legacy = false;
+    let r_deny = (r_legacy || (!(r_adult) && r_busy));
+    let r_local = (rt::is_in(f_user_country, &[Some(&LITERALS[4]).into(), Some(&LITERALS[5]).into()]) && rt::not_in(f_user_country, &[f_user_nickname.into()]));
+    let r_scored = (matches!(input.user_score, Some(v) if rt::holds(v.partial_cmp(&0.5_f64), CompareOp::Gt)) || matches!(input.user_score, Some(v) if rt::holds(v.partial_cmp(&2.0_f64), CompareOp::Eq)));
+    let r_scores_match = (rt::compare(f_user_score.as_ref(), CompareOp::Gte, input.order_total.as_ref()) && rt::compare(f_user_nickname, CompareOp::Neq, f_user_country));
+    let r_spender = rt::list(input.order_items.as_ref()).is_some_and(|items| items.iter().any(|item| { let s0 = rt::element(item, &["price"]); let s1 = rt::element(item, &["sku"]); (rt::compare(s0, CompareOp::Gt, Some(&Value::Int(100))) && rt::like(s1, "A%")) }));
+    let r_trusted = (matches!(input.user_verified, Some(v) if v == true) && matches!(&input.user_email, Some(v) if !rt::like_str(v, "%@spam.test")) && matches!(&input.user_email, Some(v) if rt::like_str(v, "%@%")));
+    let r_allow = (r_adult && (r_local || r_trusted || r_clean) && (r_scored || r_in_range || r_known));
+    let r_within_limit = (rt::has_key(input.limits.as_ref(), f_user_country) && { let l0 = rt::lookup(input.limits.as_ref(), &[rt::dynamic(f_user_country)]); let s0 = l0.as_deref(); rt::compare(input.order_total.as_ref(), CompareOp::Lte, s0) });
+    let r_review = (r_spender || r_cheap_tail || r_within_limit || r_scores_match || r_clean);
+    if r_deny {
+        return Some("deny");
+    }
+    if r_allow {
+        return Some("allow");
+    }
+    if r_review {
+        return Some("review");
+    }
+    None
+}
+
+#[cfg(test)]
+mod generated_tests {
+    /// `evaluate` agrees with the interpreter on sampled contexts.
+    #[test]
+    fn evaluate_matches_interpreter() {
+        let ruleset = ::ooroo::RuleSet::from_dsl(super::SOURCE).unwrap();
+        for ctx in ruleset.sample_contexts(512, 0) {
+            let input = super::Input::from_context(&ctx).unwrap();
+            let expected = ruleset.evaluate(&ctx);
+            assert_eq!(
+                super::evaluate(&input),
+                expected.as_ref().map(|v| v.terminal()),
+                "{ctx:?}"
+            );
+        }
+    }
+}
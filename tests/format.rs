@@ -0,0 +1,181 @@
+/// Integration tests for the DSL formatter.
+///
+/// Covers: canonical layout of rules, terminals, annotations, declarations,
+/// templates and imports; minimal parentheses; splitting long bodies;
+/// keeping comments and blank lines; idempotence and `is_formatted`; and
+/// `Expr::to_dsl` / `Expr::from_dsl`.
+use ooroo::{field, format_dsl, is_formatted, rule_ref, Context, Expr, RuleSet};
+
+fn formatted(source: &str) -> String {
+    let out = format_dsl(source).unwrap();
+    assert_eq!(format_dsl(&out).unwrap(), out, "not idempotent");
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Layout
+// ---------------------------------------------------------------------------
+
+#[test]
+fn rules_are_laid_out_canonically() {
+    let source = "rule adult:user.age>=18\nrule  allow(priority 10,on_unknown indeterminate) :\n\
+                  ((adult) and (user.status == \"active\"))\nrule deny (priority 0, on_unknown false): NOT (adult)";
+    assert_eq!(
+        formatted(source),
+        "rule adult:\n    user.age >= 18\n\n\
+         rule allow (priority 10, on_unknown indeterminate):\n    adult AND user.status == \"active\"\n\n\
+         rule deny (priority 0):\n    NOT adult\n"
+    );
+}
+
+#[test]
+fn annotations_are_one_per_line_in_a_fixed_order() {
+    let source = r#"@ticket("RISK-1") @tag("kyc") @enabled(false) @owner("risk") @tag("kyc")
+@description("say \"hi\"") @enabled(true)
+rule r: x == 1"#;
+    assert_eq!(
+        formatted(source),
+        r#"@description("say \"hi\"")
+@owner("risk")
+@tag("kyc")
+@ticket("RISK-1")
+rule r:
+    x == 1
+"#
+    );
+}
+
+#[test]
+fn declarations_templates_and_imports_are_kept_as_written() {
+    let source = r#"import "common.ooroo"   as common
+field user.age :int
+const   TIERS=["gold","platinum"]
+param LIMIT=  1.5
+param NAME
+template gate(prod,tiers): product.prod.enabled==true and user.tier in tiers
+@tag("gate") expand gate(books,TIERS) as books_gate (priority 1)
+expand gate(music, ["x"])
+"#;
+    assert_eq!(
+        formatted(source),
+        r#"import "common.ooroo" as common
+field user.age: int
+const TIERS = ["gold", "platinum"]
+param LIMIT = 1.5
+param NAME
+
+template gate(prod, tiers):
+    product.prod.enabled == true AND user.tier IN tiers
+
+@tag("gate")
+expand gate(books, TIERS) as books_gate (priority 1)
+
+expand gate(music, ["x"])
+"#
+    );
+}
+
+#[test]
+fn long_bodies_split_at_top_level_operators() {
+    let source = "rule r (priority 0):\n    user.email LIKE \"%@example.com\" AND user.verified == true \
+                  OR user.role IN [\"admin\", \"owner\"] AND user.mfa_enabled == true AND user.age >= 18";
+    assert_eq!(
+        formatted(source),
+        "rule r (priority 0):\n    user.email LIKE \"%@example.com\" AND user.verified == true\n    \
+         OR user.role IN [\"admin\", \"owner\"]\n        AND user.mfa_enabled == true\n        \
+         AND user.age >= 18\n"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Comments and blank lines
+// ---------------------------------------------------------------------------
+
+const COMMENTED: &str = r#"# Access policy.
+# Owned by the risk team.
+
+const MIN_AGE = 18   # legal age
+const BLOCKED = ["XX"]
+
+
+# Age gate
+rule adult:
+    # hoisted above the rule
+    user.age >= MIN_AGE
+rule allow (priority 1):   adult AND user.country NOT IN BLOCKED  # the verdict
+# end of file
+"#;
+
+#[test]
+fn comments_and_blank_lines_are_kept() {
+    assert_eq!(
+        formatted(COMMENTED),
+        r#"# Access policy.
+# Owned by the risk team.
+
+const MIN_AGE = 18 # legal age
+const BLOCKED = ["XX"]
+
+# Age gate
+# hoisted above the rule
+rule adult:
+    user.age >= MIN_AGE
+
+rule allow (priority 1):
+    adult AND user.country NOT IN BLOCKED # the verdict
+# end of file
+"#
+    );
+}
+
+#[test]
+fn hash_inside_strings_is_not_a_comment() {
+    let source = "rule r (priority 0):\n    tag LIKE \"#%\" # real\n";
+    assert_eq!(formatted(source), source);
+}
+
+#[test]
+fn formatted_source_compiles_to_the_same_rules() {
+    let ruleset = RuleSet::from_dsl(COMMENTED).unwrap();
+    let reformatted = RuleSet::from_dsl(&formatted(COMMENTED)).unwrap();
+    for age in [10_i64, 30] {
+        let ctx = Context::new()
+            .set("user.age", age)
+            .set("user.country", "US");
+        assert_eq!(ruleset.evaluate(&ctx), reformatted.evaluate(&ctx));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Checking
+// ---------------------------------------------------------------------------
+
+#[test]
+fn is_formatted_checks_canonical_form() {
+    assert!(is_formatted("rule r (priority 0):\n    x == 1\n").unwrap());
+    assert!(!is_formatted("rule r (priority 0): x == 1").unwrap());
+    assert!(!is_formatted("rule r (priority 0):\n    (x == 1)\n").unwrap());
+    assert!(is_formatted("").unwrap());
+    assert!(is_formatted("# only a comment\n").unwrap());
+}
+
+#[test]
+fn invalid_source_is_a_parse_error() {
+    assert!(format_dsl("rule r (priority 0):").is_err());
+    assert!(is_formatted("rule :\n    x == 1").is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Expressions
+// ---------------------------------------------------------------------------
+
+#[test]
+fn expr_to_dsl_round_trips() {
+    let expr = (!rule_ref("a").or(rule_ref("b")))
+        .and(field("limits").key("US").gt(1.0))
+        .and(rule_ref("c").and(rule_ref("d")));
+    let dsl = expr.to_dsl().unwrap();
+    assert_eq!(dsl, "NOT (a OR b) AND limits[\"US\"] > 1.0 AND (c AND d)");
+    assert_eq!(Expr::from_dsl(&dsl).unwrap(), expr);
+    assert!(Expr::from_dsl("x == 1 extra").is_err());
+}
@@ -0,0 +1,157 @@
+/// Integration tests for rendering the dependency graph as DOT and Mermaid.
+///
+/// Covers: rules and dependency edges in a stable order, terminals with
+/// their priorities, disabled rules, the fields each rule reads (including
+/// lookup keys, but not quantifier element paths), highlighting the rules
+/// an `EvaluationReport` found true, and escaping of names in labels.
+use ooroo::{field, Context, GraphOptions, RuleSet, RuleSetBuilder};
+
+const SOURCE: &str = r#"
+rule adult:
+    user.age >= 18
+
+rule verified:
+    user.email IS NOT NULL AND limits[user.country] > 0
+
+rule big_order:
+    ANY(order.items AS item, item.price > 100)
+
+@disabled
+rule legacy:
+    user.age < 0
+
+rule allow (priority 1):
+    adult AND verified AND NOT legacy
+
+rule review (priority 0):
+    big_order AND adult
+"#;
+
+fn ruleset() -> RuleSet {
+    RuleSet::from_dsl(SOURCE).unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// DOT
+// ---------------------------------------------------------------------------
+
+#[test]
+fn dot_draws_rules_terminals_and_dependencies() {
+    assert_eq!(
+        ruleset().to_dot(),
+        "digraph ruleset {
+    rankdir=LR;
+    node [shape=box];
+    r0 [label=\"adult\"];
+    r1 [label=\"big_order\"];
+    r2 [label=\"legacy\", style=dashed];
+    r3 [label=\"review\\npriority 0\", peripheries=2];
+    r4 [label=\"verified\"];
+    r5 [label=\"allow\\npriority 1\", peripheries=2];
+    r0 -> r3;
+    r0 -> r5;
+    r1 -> r3;
+    r2 -> r5;
+    r4 -> r5;
+}
+"
+    );
+}
+
+#[test]
+fn dot_draws_fields_when_asked() {
+    let dot = ruleset().to_dot_with_options(&GraphOptions::new().fields(true));
+    for line in [
+        "f0 [label=\"limits\", shape=ellipse, style=dashed];",
+        "f1 [label=\"order.items\", shape=ellipse, style=dashed];",
+        "f2 [label=\"user.age\", shape=ellipse, style=dashed];",
+        "f3 [label=\"user.country\", shape=ellipse, style=dashed];",
+        "f4 [label=\"user.email\", shape=ellipse, style=dashed];",
+        "f0 -> r4 [style=dashed];",
+        "f1 -> r1 [style=dashed];",
+        "f2 -> r0 [style=dashed];",
+        "f2 -> r2 [style=dashed];",
+        "f3 -> r4 [style=dashed];",
+        "f4 -> r4 [style=dashed];",
+    ] {
+        assert!(dot.contains(line), "missing {line} in\n{dot}");
+    }
+    assert!(!dot.contains("item.price"));
+    assert!(!ruleset().to_dot().contains("ellipse"));
+}
+
+#[test]
+fn dot_highlights_true_rules() {
+    let ruleset = ruleset();
+    let ctx = Context::new()
+        .set("user.age", 30_i64)
+        .set("user.email", "a@b.test")
+        .set("user.country", "US");
+    let report = ruleset.evaluate_detailed(&ctx);
+    let dot = ruleset.to_dot_with_options(&GraphOptions::new().highlight(&report));
+    assert!(dot.contains("r0 [label=\"adult\", style=filled, fillcolor=\"#c8f7c5\"];"));
+    assert!(dot.contains("r1 [label=\"big_order\"];"));
+    assert!(dot.contains("r3 [label=\"review\\npriority 0\", peripheries=2];"));
+}
+
+#[test]
+fn dot_escapes_names() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("say \"hi\"", |r| r.when(field("x").eq(1_i64)))
+        .terminal("say \"hi\"", 0)
+        .compile()
+        .unwrap();
+    assert!(ruleset
+        .to_dot()
+        .contains(r#"r0 [label="say \"hi\"\npriority 0", peripheries=2];"#));
+}
+
+// ---------------------------------------------------------------------------
+// Mermaid
+// ---------------------------------------------------------------------------
+
+#[test]
+fn mermaid_draws_rules_terminals_and_dependencies() {
+    assert_eq!(
+        ruleset().to_mermaid(),
+        "flowchart LR
+    r0[\"adult\"]
+    r1[\"big_order\"]
+    r2[\"legacy\"]
+    r3[[\"review<br/>priority 0\"]]
+    r4[\"verified\"]
+    r5[[\"allow<br/>priority 1\"]]
+    r0 --> r3
+    r0 --> r5
+    r1 --> r3
+    r2 --> r5
+    r4 --> r5
+    classDef disabled stroke-dasharray:4 4
+    class r2 disabled
+"
+    );
+}
+
+#[test]
+fn mermaid_draws_fields_and_highlights() {
+    let ruleset = ruleset();
+    let ctx = Context::new().set("user.age", 30_i64);
+    let report = ruleset.evaluate_detailed(&ctx);
+    let mermaid =
+        ruleset.to_mermaid_with_options(&GraphOptions::new().fields(true).highlight(&report));
+    assert!(mermaid.contains("    f2([\"user.age\"])\n"));
+    assert!(mermaid.contains("    f2 -.-> r0\n"));
+    assert!(mermaid.contains("    classDef matched fill:#c8f7c5\n    class r0 matched\n"));
+}
+
+#[test]
+fn mermaid_escapes_quotes() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("say \"hi\"", |r| r.when(field("x").eq(1_i64)))
+        .terminal("say \"hi\"", 0)
+        .compile()
+        .unwrap();
+    assert!(ruleset
+        .to_mermaid()
+        .contains("r0[[\"say #quot;hi#quot;<br/>priority 0\"]]"));
+}
@@ -0,0 +1,280 @@
+/// Integration tests for DSL imports.
+///
+/// Covers: `import "path" as alias` with namespaced rule references, nested
+/// imports, loading through `FileLoader` and `RuleSet::from_file`, `MemoryLoader`,
+/// paths relative to the importing file's directory, path normalization, import cycles, rule-name and alias collisions naming
+/// their files, missing and malformed imported files, conflicting field
+/// declarations, namespaced parameters, and imports without a loader.
+use ooroo::{Context, FieldType, ImportError, MemoryLoader, OorooError, Params, RuleSet, Verdict};
+
+const IDENTITY: &str = "rule verified:\n    user.email_verified == true";
+
+fn verified_user() -> Context {
+    Context::new().set("user.email_verified", true)
+}
+
+// ---------------------------------------------------------------------------
+// Resolution
+// ---------------------------------------------------------------------------
+
+#[test]
+fn imported_rules_are_namespaced() {
+    let loader = MemoryLoader::new()
+        .file("common/identity.ooroo", IDENTITY)
+        .file(
+            "main.ooroo",
+            r#"
+import "common/identity.ooroo" as identity
+
+rule verified:
+    user.vip == true
+
+rule allow (priority 0):
+    identity.verified AND NOT verified
+"#,
+        );
+    let ruleset = RuleSet::from_loader(&loader, "main.ooroo").unwrap();
+    let mut deps = ruleset.dependencies_of("allow").unwrap();
+    deps.sort_unstable();
+    assert_eq!(deps, ["identity.verified", "verified"]);
+
+    let ctx = verified_user().set("user.vip", false);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allow", true)));
+}
+
+#[test]
+fn nested_and_diamond_imports() {
+    let loader = MemoryLoader::new()
+        .file("base.ooroo", "rule adult:\n    user.age >= 18")
+        .file(
+            "identity.ooroo",
+            "import \"base.ooroo\" as base\nrule verified:\n    base.adult",
+        )
+        .file(
+            "payments.ooroo",
+            "import \"./base.ooroo\" as base\nrule payer:\n    base.adult",
+        )
+        .file(
+            "main.ooroo",
+            "import \"identity.ooroo\" as id\nimport \"payments.ooroo\" as pay\n\
+             rule allow (priority 0):\n    id.verified AND pay.payer AND id.base.adult",
+        );
+    let ruleset = RuleSet::from_loader(&loader, "main.ooroo").unwrap();
+    assert_eq!(ruleset.dependencies_of("allow").unwrap().len(), 3);
+
+    let ctx = Context::new().set("user.age", 30_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allow", true)));
+}
+
+#[test]
+fn imported_terminals_stay_terminals() {
+    let loader = MemoryLoader::new()
+        .file(
+            "deny.ooroo",
+            "rule banned (priority 0):\n    user.banned == true",
+        )
+        .file(
+            "main.ooroo",
+            "import \"deny.ooroo\" as deny\nrule allow (priority 10):\n    user.age >= 18",
+        );
+    let ruleset = RuleSet::from_loader(&loader, "main.ooroo").unwrap();
+    assert_eq!(
+        ruleset.terminal_order(),
+        [("deny.banned", 0), ("allow", 10)]
+    );
+}
+
+#[test]
+fn from_file_resolves_imports_against_its_directory() {
+    let ruleset = RuleSet::from_file("tests/fixtures/imports/main.ooroo").unwrap();
+    let ctx = Context::new()
+        .set("user.email_verified", true)
+        .set("user.age", 30_i64)
+        .set("order.amount", 500_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allow", true)));
+}
+
+#[test]
+fn imports_resolve_against_the_importing_files_directory() {
+    let ruleset = RuleSet::from_file("tests/fixtures/imports/nested/main.ooroo").unwrap();
+    let mut names = ruleset.execution_order();
+    names.sort_unstable();
+    assert_eq!(
+        names,
+        [
+            "allow",
+            "kyc.age.adult",
+            "kyc.country.allowed",
+            "kyc.passed"
+        ]
+    );
+
+    let ctx = Context::new()
+        .set("user.age", 30_i64)
+        .set("user.country", "FR");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allow", true)));
+    assert!(ruleset.evaluate(&ctx.set("user.country", "XX")).is_none());
+
+    // The same layout through a `MemoryLoader`.
+    let loader = MemoryLoader::new()
+        .file(
+            "sub/a.ooroo",
+            "import \"x.ooroo\" as x\nrule a (priority 0):\n    x.big",
+        )
+        .file("sub/x.ooroo", "rule big:\n    n > 1")
+        .file("x.ooroo", "rule big:\n    n > 100");
+    let ruleset = RuleSet::from_loader(&loader, "sub/a.ooroo").unwrap();
+    assert_eq!(
+        ruleset.evaluate(&Context::new().set("n", 5_i64)),
+        Some(Verdict::new("a", true))
+    );
+}
+
+#[test]
+fn imported_params_are_qualified_by_alias() {
+    let loader = MemoryLoader::new()
+        .file(
+            "limits.ooroo",
+            "param MAX = 100\nrule within:\n    order.amount <= MAX",
+        )
+        .file(
+            "main.ooroo",
+            "import \"limits.ooroo\" as limits\nrule allow (priority 0):\n    limits.within",
+        );
+    let ctx = Context::new().set("order.amount", 500_i64);
+
+    let ruleset = RuleSet::from_loader(&loader, "main.ooroo").unwrap();
+    assert!(ruleset.evaluate(&ctx).is_none());
+
+    let params = Params::new().set("limits.MAX", 1000_i64);
+    let ruleset = RuleSet::from_loader_with_params(&loader, "main.ooroo", &params).unwrap();
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allow", true)));
+}
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+fn import_error(loader: &MemoryLoader) -> ImportError {
+    match RuleSet::from_loader(loader, "main.ooroo").unwrap_err() {
+        OorooError::Import(err) => err,
+        other => panic!("expected import error, got {other:?}"),
+    }
+}
+
+#[test]
+fn import_cycle_is_detected() {
+    let loader = MemoryLoader::new()
+        .file(
+            "main.ooroo",
+            "import \"a.ooroo\" as a\nrule r (priority 0):\n    a.x",
+        )
+        .file("a.ooroo", "import \"b.ooroo\" as b\nrule x:\n    v == 1")
+        .file("b.ooroo", "import \"./a.ooroo\" as a\nrule y:\n    v == 2");
+    match import_error(&loader) {
+        ImportError::Cycle { chain } => assert_eq!(chain, ["a.ooroo", "b.ooroo", "a.ooroo"]),
+        other => panic!("expected cycle, got {other:?}"),
+    }
+}
+
+#[test]
+fn self_import_is_a_cycle() {
+    let loader = MemoryLoader::new().file(
+        "main.ooroo",
+        "import \"main.ooroo\" as me\nrule r (priority 0):\n    v == 1",
+    );
+    assert!(matches!(
+        import_error(&loader),
+        ImportError::Cycle { chain } if chain == ["main.ooroo", "main.ooroo"]
+    ));
+}
+
+#[test]
+fn rule_collision_names_both_files() {
+    let loader = MemoryLoader::new().file("identity.ooroo", IDENTITY).file(
+        "main.ooroo",
+        "import \"identity.ooroo\" as identity\n\
+             rule identity.verified (priority 0):\n    v == 1",
+    );
+    let err = import_error(&loader);
+    assert_eq!(
+        err.to_string(),
+        "rule 'identity.verified' in 'main.ooroo' is already defined in 'identity.ooroo'"
+    );
+}
+
+#[test]
+fn duplicate_alias_is_rejected() {
+    let loader = MemoryLoader::new()
+        .file("a.ooroo", IDENTITY)
+        .file("b.ooroo", IDENTITY)
+        .file(
+            "main.ooroo",
+            "import \"a.ooroo\" as x\nimport \"b.ooroo\" as x\nrule r (priority 0):\n    x.verified",
+        );
+    assert!(matches!(
+        import_error(&loader),
+        ImportError::DuplicateAlias { file, alias } if file == "main.ooroo" && alias == "x"
+    ));
+}
+
+#[test]
+fn missing_and_malformed_imports_name_the_file() {
+    let loader = MemoryLoader::new().file(
+        "main.ooroo",
+        "import \"nope.ooroo\" as nope\nrule r (priority 0):\n    nope.x",
+    );
+    assert!(matches!(
+        import_error(&loader),
+        ImportError::Load { path, from, .. } if path == "nope.ooroo" && from == "main.ooroo"
+    ));
+
+    let loader = loader.file("nope.ooroo", "rule x\n    v ==");
+    let err = import_error(&loader);
+    assert!(matches!(err, ImportError::Parse { ref file, .. } if file == "nope.ooroo"));
+    assert!(err.to_string().starts_with("in 'nope.ooroo': parse error"));
+}
+
+#[test]
+fn entry_errors_are_reported_directly() {
+    let loader = MemoryLoader::new().file("main.ooroo", "rule r\n    v ==");
+    assert!(matches!(
+        RuleSet::from_loader(&loader, "main.ooroo"),
+        Err(OorooError::Parse(_))
+    ));
+    assert!(matches!(
+        RuleSet::from_loader(&loader, "other.ooroo"),
+        Err(OorooError::Io(_))
+    ));
+}
+
+#[test]
+fn conflicting_field_declarations_are_rejected() {
+    let loader = MemoryLoader::new()
+        .file(
+            "a.ooroo",
+            "field user.age: int\nrule adult:\n    user.age >= 18",
+        )
+        .file(
+            "main.ooroo",
+            "import \"a.ooroo\" as a\nfield user.age: string\nrule r (priority 0):\n    a.adult",
+        );
+    assert!(matches!(
+        import_error(&loader),
+        ImportError::ConflictingField {
+            field_type: FieldType::String,
+            previous_type: FieldType::Int,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn from_dsl_rejects_imports() {
+    let err =
+        RuleSet::from_dsl("import \"a.ooroo\" as a\nrule r (priority 0):\n    a.x").unwrap_err();
+    assert!(matches!(
+        err,
+        OorooError::Import(ImportError::NoLoader { ref path }) if path == "a.ooroo"
+    ));
+}
@@ -0,0 +1,395 @@
+#![cfg(feature = "json")]
+/// Integration tests for JsonLogic import and export.
+///
+/// Covers: importing each supported operator (comparisons, null checks,
+/// three-way ranges, `and`/`or`/`!`, both forms of `in`, `var` paths and the
+/// `some`/`all`/`none` quantifiers); exporting each expression kind;
+/// structural round trips; evaluating imported rules against the results
+/// JsonLogic gives on contexts with every field present; and the errors for
+/// constructs either side cannot express.
+use ooroo::{
+    all, any, at_least, bound_field, field, len, rule_ref, Bound, CompareOp, Context, Expr,
+    JsonLogicError, RuleSetBuilder,
+};
+use serde_json::json;
+
+fn import(logic: serde_json::Value) -> Expr {
+    Expr::from_json_logic(&logic).unwrap()
+}
+
+fn export(expr: &Expr) -> serde_json::Value {
+    expr.to_json_logic().unwrap()
+}
+
+fn evaluate(expr: &Expr, ctx: &serde_json::Value) -> bool {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(expr.clone()))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+    ruleset
+        .evaluate(&Context::from_json(ctx).unwrap())
+        .is_some()
+}
+
+// ---------------------------------------------------------------------------
+// Import
+// ---------------------------------------------------------------------------
+
+#[test]
+fn comparisons_import() {
+    assert_eq!(
+        import(json!({"==": [{"var": "user.country"}, "US"]})),
+        field("user.country").eq("US")
+    );
+    assert_eq!(
+        import(json!({"!==": [{"var": "user.age"}, 18]})),
+        field("user.age").neq(18_i64)
+    );
+    assert_eq!(
+        import(json!({"<": [{"var": "amount"}, 10.5]})),
+        field("amount").lt(10.5_f64)
+    );
+    assert_eq!(
+        import(json!({"<=": [{"var": "amount"}, {"var": "limit"}]})),
+        field("amount").lte_field("limit")
+    );
+}
+
+#[test]
+fn literal_on_the_left_flips_the_operator() {
+    assert_eq!(
+        import(json!({"<": [18, {"var": "user.age"}]})),
+        field("user.age").gt(18_i64)
+    );
+    assert_eq!(
+        import(json!({">=": [100, {"var": "amount"}]})),
+        field("amount").lte(100_i64)
+    );
+}
+
+#[test]
+fn null_comparisons_become_null_checks() {
+    assert_eq!(
+        import(json!({"==": [{"var": "user.id"}, null]})),
+        field("user.id").is_null()
+    );
+    assert_eq!(
+        import(json!({"!=": [null, {"var": "user.id"}]})),
+        field("user.id").is_not_null()
+    );
+}
+
+#[test]
+fn three_way_comparisons_become_ranges() {
+    assert_eq!(
+        import(json!({"<=": [1, {"var": "score"}, {"var": "max"}]})),
+        field("score").between(1_i64, bound_field("max"))
+    );
+    assert_eq!(
+        import(json!({"<": [1, {"var": "score"}, 10]})),
+        field("score").gt(1_i64).and(field("score").lt(10_i64))
+    );
+}
+
+#[test]
+fn logic_imports() {
+    assert_eq!(
+        import(json!({"and": [
+            {">": [{"var": "a"}, 1]},
+            {">": [{"var": "b"}, 2]},
+            {"or": [{"!": {"var": "c"}}]}
+        ]})),
+        field("a")
+            .gt(1_i64)
+            .and(field("b").gt(2_i64))
+            .and(!field("c").eq(true))
+    );
+    assert_eq!(
+        import(json!({"!": [{"==": [{"var": "a"}, 1]}]})),
+        !field("a").eq(1_i64)
+    );
+}
+
+#[test]
+fn membership_imports() {
+    assert_eq!(
+        import(json!({"in": [{"var": "country"}, ["US", {"var": "home"}]]})),
+        field("country").is_in(["US".into(), bound_field("home")])
+    );
+    assert_eq!(
+        import(json!({"in": ["admin", {"var": "roles"}]})),
+        field("roles").contains("admin")
+    );
+    assert_eq!(
+        import(json!({"in": [{"var": "role"}, {"var": "roles"}]})),
+        field("roles").contains(bound_field("role"))
+    );
+}
+
+#[test]
+fn numeric_var_segments_become_indices() {
+    assert_eq!(
+        import(json!({"==": [{"var": "items.0.sku"}, "A1"]})),
+        field("items[0].sku").eq("A1")
+    );
+}
+
+#[test]
+fn quantifiers_import() {
+    let predicate = json!({">": [{"var": "price"}, 100]});
+    assert_eq!(
+        import(json!({"some": [{"var": "order.items"}, predicate]})),
+        any("order.items", "item0", field("item0.price").gt(100_i64))
+    );
+    assert_eq!(
+        import(json!({"none": [{"var": "order.items"}, predicate]})),
+        !any("order.items", "item0", field("item0.price").gt(100_i64))
+    );
+    assert_eq!(
+        import(json!({"all": [{"var": "order.items"}, predicate]})),
+        len("order.items").gte(1_i64).and(all(
+            "order.items",
+            "item0",
+            field("item0.price").gt(100_i64)
+        ))
+    );
+    assert_eq!(
+        import(json!({"some": [{"var": "groups"},
+            {"some": [{"var": "members"}, {"==": [{"var": ""}, "root"]}]}]})),
+        any(
+            "groups",
+            "item0",
+            any("item0.members", "item1", field("item1").eq("root"))
+        )
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Export
+// ---------------------------------------------------------------------------
+
+#[test]
+fn comparisons_export() {
+    assert_eq!(
+        export(&field("user.country").eq("US")),
+        json!({"===": [{"var": "user.country"}, "US"]})
+    );
+    assert_eq!(
+        export(&field("amount").gt_field("limit")),
+        json!({">": [{"var": "amount"}, {"var": "limit"}]})
+    );
+    assert_eq!(
+        export(&field("user.id").is_null()),
+        json!({"==": [{"var": "user.id"}, null]})
+    );
+    assert_eq!(
+        export(&field("score").between(1_i64, 10_i64)),
+        json!({"<=": [1, {"var": "score"}, 10]})
+    );
+}
+
+#[test]
+fn logic_chains_export_flat() {
+    let expr = field("a")
+        .eq(1_i64)
+        .and(field("b").eq(2_i64))
+        .and(field("c").eq(3_i64).or(field("d").eq(4_i64)));
+    assert_eq!(
+        export(&expr),
+        json!({"and": [
+            {"===": [{"var": "a"}, 1]},
+            {"===": [{"var": "b"}, 2]},
+            {"or": [{"===": [{"var": "c"}, 3]}, {"===": [{"var": "d"}, 4]}]}
+        ]})
+    );
+}
+
+#[test]
+fn membership_exports() {
+    assert_eq!(
+        export(&field("country").not_in(["US", "CA"])),
+        json!({"!": [{"in": [{"var": "country"}, ["US", "CA"]]}]})
+    );
+    assert_eq!(
+        export(&field("roles").contains("admin")),
+        json!({"in": ["admin", {"var": "roles"}]})
+    );
+}
+
+#[test]
+fn quantifiers_export() {
+    assert_eq!(
+        export(&any("items", "line", field("line.price").gt(100_i64))),
+        json!({"some": [{"var": "items"}, {">": [{"var": "price"}, 100]}]})
+    );
+    assert_eq!(
+        export(&all("items", "line", field("line.price").gt(100_i64))),
+        json!({"none": [{"var": "items"}, {"!": [{">": [{"var": "price"}, 100]}]}]})
+    );
+    assert_eq!(
+        export(&field("items[0].sku").eq("A1")),
+        json!({"===": [{"var": "items.0.sku"}, "A1"]})
+    );
+}
+
+#[test]
+fn imported_documents_export_back() {
+    for logic in [
+        json!({"and": [
+            {">=": [{"var": "user.age"}, 18]},
+            {"!": [{"in": [{"var": "user.country"}, ["XX", "YY"]]}]},
+            {"all": [{"var": "order.items"}, {"<=": [0, {"var": "qty"}, 99]}]}
+        ]}),
+        json!({"or": [
+            {"===": [{"var": "a"}, true]},
+            {"some": [{"var": "tags"}, {"===": [{"var": ""}, "vip"]}]},
+            {"!=": [{"var": "b"}, null]}
+        ]}),
+    ] {
+        assert_eq!(export(&import(logic.clone())), logic);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Evaluation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn imported_rules_agree_with_json_logic() {
+    let policy = import(json!({"and": [
+        {">=": [{"var": "user.age"}, 18]},
+        {"in": [{"var": "user.country"}, ["US", "CA"]]},
+        {"all": [{"var": "order.items"}, {">": [{"var": "qty"}, 0]}]},
+        {"!": [{"some": [{"var": "order.items"}, {"in": ["blocked", {"var": "tags"}]}]}]}
+    ]}));
+    let item = |qty: i64, tags: &[&str]| json!({"qty": qty, "tags": tags});
+    // Results as JsonLogic computes them for each context.
+    let cases = [
+        (
+            json!({"user": {"age": 30, "country": "US"}, "order": {"items": [item(1, &[])]}}),
+            true,
+        ),
+        (
+            json!({"user": {"age": 17, "country": "US"}, "order": {"items": [item(1, &[])]}}),
+            false,
+        ),
+        (
+            json!({"user": {"age": 30, "country": "FR"}, "order": {"items": [item(1, &[])]}}),
+            false,
+        ),
+        (
+            json!({"user": {"age": 30, "country": "CA"}, "order": {"items": []}}),
+            false,
+        ),
+        (
+            json!({"user": {"age": 30, "country": "CA"},
+                   "order": {"items": [item(2, &["gift"]), item(0, &[])]}}),
+            false,
+        ),
+        (
+            json!({"user": {"age": 30, "country": "CA"},
+                   "order": {"items": [item(2, &["gift"]), item(1, &["blocked"])]}}),
+            false,
+        ),
+        (
+            json!({"user": {"age": 30, "country": "CA"},
+                   "order": {"items": [item(2, &["gift"]), item(5, &[])]}}),
+            true,
+        ),
+    ];
+    let round_tripped = import(export(&policy));
+    for (ctx, expected) in cases {
+        assert_eq!(evaluate(&policy, &ctx), expected, "{ctx}");
+        assert_eq!(evaluate(&round_tripped, &ctx), expected, "{ctx}");
+    }
+}
+
+#[test]
+fn exported_all_keeps_vacuous_truth() {
+    let expr = all("xs", "x", field("x").gt(0_i64));
+    let back = import(export(&expr));
+    for ctx in [
+        json!({"xs": []}),
+        json!({"xs": [1, 2]}),
+        json!({"xs": [1, 0]}),
+    ] {
+        assert_eq!(evaluate(&back, &ctx), evaluate(&expr, &ctx), "{ctx}");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Unsupported constructs
+// ---------------------------------------------------------------------------
+
+#[test]
+fn unsupported_operators_are_rejected_on_import() {
+    for logic in [
+        json!({"+": [1, 2]}),
+        json!({"==": [{"var": "a"}, {"+": [1, 2]}]}),
+        json!({"cat": ["a", {"var": "b"}]}),
+        json!({"==": [{"var": ["a", 0]}, 1]}),
+        json!({"in": ["Spring", "Springfield"]}),
+        json!({"if": [true, true, false]}),
+    ] {
+        assert!(
+            matches!(
+                Expr::from_json_logic(&logic),
+                Err(JsonLogicError::UnsupportedOperator(_))
+            ),
+            "{logic}"
+        );
+    }
+}
+
+#[test]
+fn malformed_documents_are_rejected_on_import() {
+    for logic in [
+        json!(true),
+        json!({"==": [1, 2]}),
+        json!({"==": [{"var": "a"}]}),
+        json!({"and": []}),
+        json!({"<": [{"var": "a"}, null]}),
+        json!({"==": [{"var": "a"}, 1], "!=": [{"var": "b"}, 2]}),
+        json!({"some": [[1, 2], {"var": ""}]}),
+    ] {
+        assert!(
+            matches!(
+                Expr::from_json_logic(&logic),
+                Err(JsonLogicError::Invalid(_))
+            ),
+            "{logic}"
+        );
+    }
+}
+
+#[test]
+fn inexpressible_constructs_are_rejected_on_export() {
+    for expr in [
+        field("sku").like("A%"),
+        at_least(1, vec![field("a").eq(1_i64)]),
+        field("roles").overlaps(bound_field("required")),
+        len("roles").gt(2_i64),
+        field("limits").has_key("US"),
+        rule_ref("adult"),
+        field("limits[user.country]").gt(1_i64),
+        field("items[*].sku").eq("A1"),
+        field("items[-1].sku").eq("A1"),
+        any("items", "line", field("line.price").gt_field("limit")),
+        field("flags").eq(ooroo::Value::List(vec![])),
+        Expr::In {
+            field: "x".to_owned(),
+            members: vec![Bound::Literal(ooroo::Value::List(vec![]))],
+        },
+        field("a").eq(1_i64).and(Expr::CompareFields {
+            left: "b".to_owned(),
+            op: CompareOp::Eq,
+            right: "c[*]".to_owned(),
+        }),
+    ] {
+        assert!(
+            matches!(expr.to_json_logic(), Err(JsonLogicError::Unsupported(_))),
+            "{expr:?}"
+        );
+    }
+}
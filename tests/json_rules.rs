@@ -0,0 +1,290 @@
+#![cfg(feature = "serde")]
+/// Integration tests for serializing the rule AST and compiling JSON rules.
+///
+/// Covers: the documented JSON shape of every expression kind, values,
+/// bounds, operators, rules and terminals; defaults for omitted metadata
+/// and `on_unknown`; compiling with `RuleSet::from_json_rules` to the same
+/// behaviour as the DSL; rejecting malformed documents; and a property test
+/// round-tripping arbitrary expressions through JSON.
+mod strategies;
+
+use std::collections::BTreeMap;
+
+use ooroo::{
+    all, any, at_least, bound_field, field, len, rule_ref, CompareOp, CompileError, Context, Expr,
+    OorooError, Rule, RuleMetadata, RuleSet, SetOp, Terminal, UnknownPolicy, Value, Verdict,
+};
+use proptest::prelude::*;
+use serde_json::json;
+use strategies::arb_dsl_expr;
+
+fn to_json<T: serde::Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// JSON shape
+// ---------------------------------------------------------------------------
+
+#[test]
+fn values_are_plain_json() {
+    let value = Value::Map(BTreeMap::from([
+        ("n".to_owned(), Value::Int(1)),
+        ("x".to_owned(), Value::Float(2.0)),
+        (
+            "l".to_owned(),
+            Value::List(vec![Value::Bool(true), Value::from("s")]),
+        ),
+    ]));
+    let json = to_json(&value);
+    assert_eq!(json, json!({"n": 1, "x": 2.0, "l": [true, "s"]}));
+    assert_eq!(serde_json::from_value::<Value>(json).unwrap(), value);
+    assert_eq!(
+        serde_json::from_str::<Value>("1e3").unwrap(),
+        Value::Float(1000.0)
+    );
+}
+
+#[test]
+fn comparisons_and_logic_have_a_stable_shape() {
+    let expr = field("user.age")
+        .gte(18_i64)
+        .and(!rule_ref("banned"))
+        .or(Expr::IsNull("user.id".to_owned()));
+    assert_eq!(
+        to_json(&expr),
+        json!({"or": [
+            {"and": [
+                {"compare": {"field": "user.age", "op": "gte", "value": 18}},
+                {"not": {"rule_ref": "banned"}}
+            ]},
+            {"is_null": "user.id"}
+        ]})
+    );
+    assert_eq!(
+        to_json(&Expr::CompareFields {
+            left: "amount".to_owned(),
+            op: CompareOp::Lte,
+            right: "limit".to_owned(),
+        }),
+        json!({"compare_fields": {"left": "amount", "op": "lte", "right": "limit"}})
+    );
+    assert_eq!(
+        to_json(&at_least(1, vec![rule_ref("a"), rule_ref("b")])),
+        json!({"at_least": {"n": 1, "exprs": [{"rule_ref": "a"}, {"rule_ref": "b"}]}})
+    );
+}
+
+#[test]
+fn bounds_and_patterns_have_a_stable_shape() {
+    assert_eq!(
+        to_json(&field("country").is_in(["US".into(), bound_field("home")])),
+        json!({"in": {"field": "country", "members": [{"literal": "US"}, {"field": "home"}]}})
+    );
+    assert_eq!(
+        to_json(&field("score").between(10_i64, bound_field("tier.max"))),
+        json!({"between": {"field": "score", "low": {"literal": 10}, "high": {"field": "tier.max"}}})
+    );
+    assert_eq!(
+        to_json(&field("sku").not_like("B%")),
+        json!({"not_like": {"field": "sku", "pattern": "B%"}})
+    );
+}
+
+#[test]
+fn collection_expressions_have_a_stable_shape() {
+    assert_eq!(
+        to_json(&any("order.items", "line", field("line.price").gt(100_i64))),
+        json!({"any": {"list": "order.items", "var": "line", "predicate":
+            {"compare": {"field": "line.price", "op": "gt", "value": 100}}}})
+    );
+    assert!(to_json(&all("xs", "x", rule_ref("r"))).get("all").is_some());
+    assert_eq!(
+        to_json(&field("roles").contains("admin")),
+        json!({"contains": {"field": "roles", "value": {"literal": "admin"}}})
+    );
+    assert_eq!(
+        to_json(&Expr::SetCompare {
+            field: "perms".to_owned(),
+            op: SetOp::SupersetOf,
+            value: bound_field("required"),
+        }),
+        json!({"set_compare": {"field": "perms", "op": "superset_of", "value": {"field": "required"}}})
+    );
+    assert_eq!(
+        to_json(&len("roles").gte(2_i64)),
+        json!({"len": {"field": "roles", "op": "gte", "value": {"literal": 2}}})
+    );
+    assert_eq!(
+        to_json(&field("limits").has_key(bound_field("user.country"))),
+        json!({"has_key": {"field": "limits", "key": {"field": "user.country"}}})
+    );
+}
+
+#[test]
+fn rules_and_terminals_omit_defaults() {
+    let rule = Rule {
+        name: "adult".to_owned(),
+        condition: Some(rule_ref("x")),
+        metadata: RuleMetadata::default(),
+    };
+    assert_eq!(
+        to_json(&rule),
+        json!({"name": "adult", "condition": {"rule_ref": "x"}, "metadata": {"enabled": true}})
+    );
+    let terminal = Terminal {
+        rule_name: "adult".to_owned(),
+        priority: 3,
+        on_unknown: UnknownPolicy::Indeterminate,
+    };
+    assert_eq!(
+        to_json(&terminal),
+        json!({"rule_name": "adult", "priority": 3, "on_unknown": "indeterminate"})
+    );
+
+    let parsed: Rule = serde_json::from_value(json!({"name": "r", "condition": null})).unwrap();
+    assert_eq!(parsed.metadata, RuleMetadata::default());
+    let parsed: Terminal =
+        serde_json::from_value(json!({"rule_name": "r", "priority": 0})).unwrap();
+    assert_eq!(parsed.on_unknown, UnknownPolicy::AsFalse);
+}
+
+// ---------------------------------------------------------------------------
+// Compiling JSON rules
+// ---------------------------------------------------------------------------
+
+const RULES: &str = r#"{
+    "rules": [
+        {"name": "adult", "condition": {"compare": {"field": "user.age", "op": "gte", "value": 18}}},
+        {"name": "banned", "condition": {"in": {"field": "user.country", "members": [{"literal": "XX"}]}},
+         "metadata": {"owner": "risk", "tags": ["geo"]}},
+        {"name": "allow", "condition": {"and": [{"rule_ref": "adult"}, {"not": {"rule_ref": "banned"}}]}}
+    ],
+    "terminals": [
+        {"rule_name": "banned", "priority": 0, "on_unknown": "indeterminate"},
+        {"rule_name": "allow", "priority": 1}
+    ]
+}"#;
+
+const DSL: &str = r#"
+rule adult:
+    user.age >= 18
+
+rule banned (priority 0, on_unknown indeterminate):
+    user.country IN ["XX"]
+
+rule allow (priority 1):
+    adult AND NOT banned
+"#;
+
+#[test]
+fn json_rules_behave_like_the_dsl() {
+    let from_json = RuleSet::from_json_rules(RULES).unwrap();
+    let from_dsl = RuleSet::from_dsl(DSL).unwrap();
+    for ctx in [
+        Context::new()
+            .set("user.age", 30_i64)
+            .set("user.country", "US"),
+        Context::new()
+            .set("user.age", 30_i64)
+            .set("user.country", "XX"),
+        Context::new().set("user.age", 12_i64),
+        Context::new(),
+    ] {
+        assert_eq!(from_json.evaluate(&ctx), from_dsl.evaluate(&ctx));
+        assert_eq!(
+            from_json.evaluate_three_valued(&ctx),
+            from_dsl.evaluate_three_valued(&ctx)
+        );
+    }
+    assert_eq!(
+        from_json.evaluate(
+            &Context::new()
+                .set("user.age", 30_i64)
+                .set("user.country", "US")
+        ),
+        Some(Verdict::new("allow", true))
+    );
+    let metadata = from_json.metadata("banned").unwrap();
+    assert_eq!(metadata.owner.as_deref(), Some("risk"));
+    assert!(metadata.has_tag("geo"));
+}
+
+#[test]
+fn serialized_rules_compile_back() {
+    let rules = vec![
+        Rule {
+            name: "adult".to_owned(),
+            condition: Some(field("age").gte(18_i64)),
+            metadata: RuleMetadata::default(),
+        },
+        Rule {
+            name: "senior".to_owned(),
+            condition: Some(rule_ref("adult").and(field("age").gte(65.5_f64))),
+            metadata: RuleMetadata {
+                enabled: false,
+                ..RuleMetadata::default()
+            },
+        },
+    ];
+    let terminals = vec![Terminal {
+        rule_name: "senior".to_owned(),
+        priority: 0,
+        on_unknown: UnknownPolicy::AsFalse,
+    }];
+    let doc = json!({"rules": rules, "terminals": terminals}).to_string();
+    let ruleset = RuleSet::from_json_rules(&doc).unwrap();
+    assert_eq!(ruleset.evaluate(&Context::new().set("age", 70_i64)), None);
+    assert!(!ruleset.metadata("senior").unwrap().enabled);
+}
+
+// ---------------------------------------------------------------------------
+// Rejections
+// ---------------------------------------------------------------------------
+
+#[test]
+fn malformed_documents_are_rejected() {
+    for doc in [
+        "not json",
+        r#"{"terminals": []}"#,
+        r#"{"rules": [], "extra": 1}"#,
+        r#"{"rules": [{"name": "r", "condition": {"compare": {"field": "x", "op": "~", "value": 1}}}]}"#,
+        r#"{"rules": [{"name": "r", "condition": {"xor": [{"rule_ref": "a"}, {"rule_ref": "b"}]}}]}"#,
+        r#"{"rules": [{"name": "r", "condition": {"in": {"field": "x", "members": ["US"]}}}]}"#,
+    ] {
+        assert!(
+            matches!(RuleSet::from_json_rules(doc), Err(OorooError::Json(_))),
+            "{doc}"
+        );
+    }
+}
+
+#[test]
+fn compile_errors_are_reported() {
+    let missing = r#"{"rules": [{"name": "r", "condition": null}],
+                      "terminals": [{"rule_name": "r", "priority": 0}]}"#;
+    assert!(matches!(
+        RuleSet::from_json_rules(missing),
+        Err(OorooError::Compile(CompileError::MissingCondition { .. }))
+    ));
+    let no_terminals = r#"{"rules": [{"name": "r", "condition": {"rule_ref": "r"}}]}"#;
+    assert!(matches!(
+        RuleSet::from_json_rules(no_terminals),
+        Err(OorooError::Compile(_))
+    ));
+}
+
+// ---------------------------------------------------------------------------
+// Properties
+// ---------------------------------------------------------------------------
+
+proptest! {
+    /// Every expression survives a trip through JSON text unchanged,
+    /// including float literals.
+    #[test]
+    fn expressions_round_trip_through_json(expr in arb_dsl_expr()) {
+        let text = serde_json::to_string(&expr).unwrap();
+        let back: Expr = serde_json::from_str(&text).unwrap();
+        prop_assert_eq!(back, expr);
+    }
+}
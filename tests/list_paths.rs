@@ -0,0 +1,320 @@
+/// Integration tests for list index (`[0]`, `[-1]`) and wildcard (`[*]`)
+/// field paths.
+///
+/// Covers: positional access with dotted segments after the index, indices
+/// read from another field, wildcard paths collected into lists and used with
+/// `IN`, quantifiers and set operators, nested wildcards, wildcards over map
+/// values, paths inside stored maps, `IndexedContext` evaluation,
+/// `Context::get` and `Context::lookup` on the same paths, and the DSL
+/// syntax.
+use ooroo::{
+    any, bound_field, field, len, rule_ref, Context, RuleSet, RuleSetBuilder, Value, Verdict,
+};
+
+fn map(entries: &[(&str, Value)]) -> Value {
+    Value::Map(
+        entries
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), v.clone()))
+            .collect(),
+    )
+}
+
+fn item(sku: &str, price: i64) -> Value {
+    map(&[("sku", Value::from(sku)), ("price", Value::Int(price))])
+}
+
+fn items() -> Value {
+    Value::List(vec![item("A1", 10), item("B2", 25), item("C3", 40)])
+}
+
+fn strings(values: &[&str]) -> Value {
+    Value::List(values.iter().map(|s| Value::from(*s)).collect())
+}
+
+// ---------------------------------------------------------------------------
+// Builder API: indices
+// ---------------------------------------------------------------------------
+
+#[test]
+fn positional_index_with_trailing_segment() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("first", |r| r.when(field("order.items[0].sku").eq("A1")))
+        .rule("last", |r| {
+            r.when(field("order.items").index(-1).key("price").gt(30_i64))
+        })
+        .rule("both", |r| r.when(rule_ref("first").and(rule_ref("last"))))
+        .terminal("both", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("order.items", items());
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("both", true)));
+}
+
+#[test]
+fn out_of_range_index_is_missing() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(field("order.items[3]").is_null()))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("order.items", items());
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = Context::new().set("order.items", Value::List(vec![items(); 4]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn index_read_from_another_field() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(field("order.items[order.selected].price").gte(25_i64))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new()
+        .set("order.items", items())
+        .set("order.selected", 1_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = ctx.set("order.selected", 0_i64);
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+// ---------------------------------------------------------------------------
+// Builder API: wildcards
+// ---------------------------------------------------------------------------
+
+#[test]
+fn wildcard_collects_list_for_in() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(field("wanted").is_in(vec![bound_field("order.items[*].sku")]))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new()
+        .set("order.items", items())
+        .set("wanted", "B2");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = ctx.set("wanted", "Z9");
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn wildcard_with_quantifier_and_len() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("cheap", |r| {
+            r.when(any("order.items[*].price", "p", field("p").lt(15_i64)))
+        })
+        .rule("three", |r| r.when(len("order.items[*].sku").eq(3_i64)))
+        .terminal("cheap", 0)
+        .terminal("three", 1)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("order.items", items());
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("cheap", true)));
+
+    let ctx = Context::new().set(
+        "order.items",
+        Value::List(vec![item("A1", 20), item("B2", 20), item("C3", 20)]),
+    );
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("three", true)));
+}
+
+#[test]
+fn wildcard_with_set_operators() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(
+                field("order.items")
+                    .wildcard()
+                    .key("sku")
+                    .subset_of(bound_field("catalog")),
+            )
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new()
+        .set("order.items", items())
+        .set("catalog", strings(&["A1", "B2", "C3", "D4"]));
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = ctx.set("catalog", strings(&["A1", "B2"]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn nested_wildcards_flatten_and_skip_misses() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(field("orders[*].items[*].sku").eq(Value::List(vec![
+                Value::from("A1"),
+                Value::from("B2"),
+                Value::from("C3"),
+            ])))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let orders = Value::List(vec![
+        map(&[("items", Value::List(vec![item("A1", 1), item("B2", 2)]))]),
+        // No `items` key: contributes nothing.
+        map(&[("id", Value::Int(7))]),
+        map(&[("items", Value::List(vec![item("C3", 3)]))]),
+    ]);
+    let ctx = Context::new().set("orders", orders);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+#[test]
+fn wildcard_over_map_values() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(any("limits[*].daily", "d", field("d").gt(75_i64)))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let limits = map(&[
+        ("US", map(&[("daily", Value::Int(100))])),
+        ("DE", map(&[("daily", Value::Int(50))])),
+    ]);
+    let ctx = Context::new().set("limits", limits);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+#[test]
+fn wildcard_missing_root_is_missing_but_scalar_root_is_empty() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("missing", |r| r.when(field("xs[*]").is_null()))
+        .rule("empty", |r| r.when(len("xs[*]").eq(0_i64)))
+        .terminal("missing", 0)
+        .terminal("empty", 1)
+        .compile()
+        .unwrap();
+
+    assert_eq!(
+        ruleset.evaluate(&Context::new()),
+        Some(Verdict::new("missing", true))
+    );
+    assert_eq!(
+        ruleset.evaluate(&Context::new().set("xs", 5_i64)),
+        Some(Verdict::new("empty", true))
+    );
+}
+
+#[test]
+fn paths_resolve_inside_stored_maps() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(field("order.items[1].sku").eq("B2")))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let order = map(&[("items", items())]);
+    let ctx = Context::new().set("order", order.clone());
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let indexed = ruleset.context_builder().set("order", order).build();
+    assert_eq!(
+        ruleset.evaluate_indexed(&indexed),
+        Some(Verdict::new("r", true))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Context accessors
+// ---------------------------------------------------------------------------
+
+#[test]
+fn context_get_resolves_indexed_paths_like_rules() {
+    let ctx = Context::new()
+        .set("order.items", items())
+        .set("pick", 2_i64)
+        .set("limits", map(&[("US", Value::Int(500))]))
+        .set("user.country", "US");
+
+    assert_eq!(ctx.get("order.items[0].sku"), Some(&Value::from("A1")));
+    assert_eq!(ctx.get("order.items[-1].price"), Some(&Value::Int(40)));
+    assert_eq!(ctx.get("order.items[pick].sku"), Some(&Value::from("C3")));
+    assert_eq!(ctx.get("limits[user.country]"), Some(&Value::Int(500)));
+    assert_eq!(ctx.get("limits[\"US\"]"), Some(&Value::Int(500)));
+    assert_eq!(ctx.get("order.items[9].sku"), None);
+
+    // A wildcard builds a new list, so only `lookup` can return it.
+    assert_eq!(ctx.get("order.items[*].sku"), None);
+    assert_eq!(
+        ctx.lookup("order.items[*].sku").as_deref(),
+        Some(&strings(&["A1", "B2", "C3"]))
+    );
+
+    // Every accessor agrees with the evaluator.
+    for path in [
+        "order.items[0].sku",
+        "order.items[pick].sku",
+        "order.items[*].sku",
+    ] {
+        let ruleset = RuleSetBuilder::new()
+            .rule("r", |r| {
+                r.when(field(path).eq(ctx.lookup(path).unwrap().into_owned()))
+            })
+            .terminal("r", 0)
+            .compile()
+            .unwrap();
+        assert_eq!(
+            ruleset.evaluate(&ctx),
+            Some(Verdict::new("r", true)),
+            "{path}"
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DSL
+// ---------------------------------------------------------------------------
+
+#[test]
+fn dsl_index_and_wildcard_paths() {
+    let dsl = r#"
+rule starts_with_a:
+    order.items[0].sku LIKE "A%"
+
+rule wanted_present:
+    wanted IN [order.items[*].sku]
+
+rule all_priced:
+    ALL(order.items[*].price AS p, p > 0) AND order.items[-1].price <= 40
+
+rule ok (priority 0):
+    starts_with_a AND wanted_present AND all_priced
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    let ctx = Context::new()
+        .set("order.items", items())
+        .set("wanted", "C3");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("ok", true)));
+
+    let ctx = ctx.set("wanted", "D4");
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn dsl_rejects_malformed_index() {
+    assert!(RuleSet::from_dsl("rule r (priority 0):\n    items[1x] == 1").is_err());
+    assert!(RuleSet::from_dsl("rule r (priority 0):\n    items[-] == 1").is_err());
+}
@@ -0,0 +1,274 @@
+/// Integration tests for map values, indexed key lookup and `HAS_KEY`.
+///
+/// Covers: literal and field-sourced `[key]` indexes, dotted access after an
+/// index, `Context::get` resolving into stored maps, map elements inside
+/// quantifiers, `IndexedContext` evaluation, `len` over maps, malformed path
+/// errors, and the DSL syntax. JSON conversion is covered when the `json`
+/// feature is enabled.
+use std::collections::BTreeMap;
+
+use ooroo::{
+    any, bound_field, field, len, CompileError, Context, RuleSet, RuleSetBuilder, Value, Verdict,
+};
+
+fn map(entries: &[(&str, Value)]) -> Value {
+    Value::Map(
+        entries
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), v.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn limits() -> Value {
+    map(&[
+        ("US", map(&[("daily", Value::Int(100))])),
+        ("DE", map(&[("daily", Value::Int(50))])),
+    ])
+}
+
+// ---------------------------------------------------------------------------
+// Builder API: lookup
+// ---------------------------------------------------------------------------
+
+#[test]
+fn literal_key_lookup() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(field("limits").key("US").key("daily").gte(100_i64))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("limits", limits());
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+#[test]
+fn field_sourced_key_lookup() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(field("order.total").lte_field("limits[user.country].daily"))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new()
+        .set("limits", limits())
+        .set("user.country", "DE")
+        .set("order.total", 60_i64);
+    assert!(ruleset.evaluate(&ctx).is_none());
+
+    let ctx = ctx.set("user.country", "US");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+#[test]
+fn missing_key_or_non_map_resolves_to_missing() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(field("limits").key_field("country").is_null())
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("limits", limits()).set("country", "FR");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = Context::new().set("limits", 5_i64).set("country", "US");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    // A non-string key never matches.
+    let ctx = Context::new().set("limits", limits()).set("country", 1_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+#[test]
+fn lookup_inside_quantifier_uses_element_key() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(any(
+                "orders",
+                "o",
+                field("o.amount").gt_field("limits[o.country].daily"),
+            ))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let order = |country: &str, amount: i64| {
+        map(&[
+            ("country", Value::from(country)),
+            ("amount", Value::Int(amount)),
+        ])
+    };
+    let ctx = Context::new().set("limits", limits()).set(
+        "orders",
+        Value::List(vec![order("US", 80), order("DE", 60)]),
+    );
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = Context::new().set("limits", limits()).set(
+        "orders",
+        Value::List(vec![order("US", 80), order("DE", 40)]),
+    );
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn indexed_context_evaluates_lookups() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(field("order.total").lte_field("limits[user.country].daily"))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = ruleset
+        .context_builder()
+        .set("limits", limits())
+        .set("user.country", "US")
+        .set("order.total", 90_i64)
+        .build();
+    assert_eq!(
+        ruleset.evaluate_indexed(&ctx),
+        Some(Verdict::new("r", true))
+    );
+}
+
+#[test]
+fn invalid_field_path_is_a_compile_error() {
+    let err = RuleSetBuilder::new()
+        .rule("r", |r| r.when(field("limits[").eq(1_i64)))
+        .terminal("r", 0)
+        .compile()
+        .unwrap_err();
+    assert!(matches!(err, CompileError::InvalidFieldPath { .. }));
+}
+
+// ---------------------------------------------------------------------------
+// Builder API: HAS_KEY, len and Context::get
+// ---------------------------------------------------------------------------
+
+#[test]
+fn has_key_literal_and_field() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("has_us", |r| r.when(field("limits").has_key("US")))
+        .rule("has_own", |r| {
+            r.when(field("limits").has_key(bound_field("user.country")))
+        })
+        .terminal("has_own", 0)
+        .terminal("has_us", 1)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new()
+        .set("limits", limits())
+        .set("user.country", "FR");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("has_us", true)));
+
+    let ctx = ctx.set("user.country", "DE");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("has_own", true)));
+
+    // Non-map fields never have keys.
+    let ctx = Context::new().set("limits", "US");
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn len_counts_map_entries() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(len("limits").eq(2_i64)))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("limits", limits());
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+#[test]
+fn context_get_resolves_into_maps() {
+    let ctx = Context::new().set("config.limits", limits());
+    assert_eq!(ctx.get("config.limits.US.daily"), Some(&Value::Int(100)));
+    assert!(ctx.get("config.limits.FR").is_none());
+    assert!(ctx.get("config.limits.US.daily.x").is_none());
+}
+
+#[test]
+fn map_display() {
+    let value = map(&[("a", Value::from("x")), ("b", Value::Int(2))]);
+    assert_eq!(value.to_string(), r#"{"a": "x", "b": 2}"#);
+}
+
+// ---------------------------------------------------------------------------
+// DSL
+// ---------------------------------------------------------------------------
+
+#[test]
+fn dsl_indexing_and_has_key() {
+    let dsl = r#"
+rule known_country:
+    limits HAS_KEY user.country
+
+rule us_open:
+    limits["US"].daily > 0
+
+rule within_limit (priority 0):
+    known_country AND us_open AND order.total <= limits[user.country].daily
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    let ctx = Context::new()
+        .set("limits", limits())
+        .set("user.country", "DE")
+        .set("order.total", 40_i64);
+    assert_eq!(
+        ruleset.evaluate(&ctx),
+        Some(Verdict::new("within_limit", true))
+    );
+
+    let ctx = ctx.set("user.country", "FR");
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn dsl_has_key_literal() {
+    let ruleset = RuleSet::from_dsl("rule r (priority 0):\n    limits has_key \"US\"").unwrap();
+    let ctx = Context::new().set("limits", limits());
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+#[test]
+fn dsl_unclosed_index_rejected() {
+    let result = RuleSet::from_dsl("rule r (priority 0):\n    limits[user.country > 1");
+    assert!(result.is_err());
+}
+
+// ---------------------------------------------------------------------------
+// JSON
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "json")]
+#[test]
+fn json_context_drives_lookups() {
+    let json = serde_json::json!({
+        "limits": {"US": {"daily": 100}, "DE": {"daily": 50}},
+        "user": {"country": "US", "nickname": null},
+        "order": {"total": 90}
+    });
+    let ctx = Context::from_json(&json).unwrap();
+    assert!(ctx.get("user.nickname").is_none());
+
+    let ruleset = RuleSet::from_dsl(
+        "rule r (priority 0):\n    order.total <= limits[user.country].daily AND user.nickname IS NULL",
+    )
+    .unwrap();
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+    assert_eq!(Value::from_json(&limits().to_json()), Some(limits()));
+}
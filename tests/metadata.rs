@@ -0,0 +1,158 @@
+/// Integration tests for rule metadata.
+///
+/// Covers: builder metadata setters, DSL `@` annotations on rules and template
+/// expansions, metadata surviving imports, querying rules by tag, owner and
+/// predicate, and disabled rules evaluating to `false` under two- and
+/// three-valued evaluation.
+use ooroo::{
+    field, rule_ref, Context, MemoryLoader, RuleMetadata, RuleSet, RuleSetBuilder, Truth,
+    UnknownPolicy, Verdict,
+};
+
+// ---------------------------------------------------------------------------
+// Builder
+// ---------------------------------------------------------------------------
+
+#[test]
+fn builder_sets_metadata() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("kyc", |r| {
+            r.when(field("user.verified").eq(true))
+                .description("user passed KYC")
+                .owner("risk-team")
+                .tag("kyc")
+                .tag("identity")
+                .tag("kyc")
+                .link("https://tickets.example/RISK-1")
+                .meta("severity", "high")
+        })
+        .rule("plain", |r| r.when(field("x").eq(1_i64)))
+        .terminal("kyc", 0)
+        .terminal("plain", 1)
+        .compile()
+        .unwrap();
+
+    let metadata = ruleset.metadata("kyc").unwrap();
+    assert_eq!(metadata.description.as_deref(), Some("user passed KYC"));
+    assert_eq!(metadata.owner.as_deref(), Some("risk-team"));
+    assert_eq!(metadata.tags, ["kyc", "identity"]);
+    assert_eq!(
+        metadata.link.as_deref(),
+        Some("https://tickets.example/RISK-1")
+    );
+    assert_eq!(metadata.extra["severity"], "high");
+    assert!(metadata.enabled);
+
+    assert_eq!(ruleset.metadata("plain"), Some(&RuleMetadata::default()));
+    assert!(ruleset.metadata("missing").is_none());
+}
+
+// ---------------------------------------------------------------------------
+// DSL annotations
+// ---------------------------------------------------------------------------
+
+const ANNOTATED: &str = r#"
+@owner("risk-team") @tag("kyc")
+@description("email and age verified")
+rule verified:
+    user.email_verified == true AND user.age >= 18
+
+@tag("kyc") @tag("legacy") @disabled
+rule old_check:
+    user.legacy_flag == true
+
+@owner("payments") @ticket("PAY-42")
+rule allow (priority 10):
+    verified OR old_check
+"#;
+
+#[test]
+fn annotations_set_metadata() {
+    let ruleset = RuleSet::from_dsl(ANNOTATED).unwrap();
+    let mut kyc = ruleset.rules_with_tag("kyc");
+    kyc.sort_unstable();
+    assert_eq!(kyc, ["old_check", "verified"]);
+    assert_eq!(ruleset.rules_owned_by("risk-team"), ["verified"]);
+    assert_eq!(ruleset.disabled_rules(), ["old_check"]);
+    assert_eq!(
+        ruleset.rules_where(|m| m.extra.contains_key("ticket")),
+        ["allow"]
+    );
+    assert_eq!(
+        ruleset.metadata("verified").unwrap().description.as_deref(),
+        Some("email and age verified")
+    );
+}
+
+#[test]
+fn annotations_on_expansions_and_imports() {
+    let loader = MemoryLoader::new()
+        .file(
+            "gates.ooroo",
+            "template gate(prod):\n    product.prod.enabled == true\n\
+             @tag(\"gate\") @enabled(false)\nexpand gate(books)",
+        )
+        .file(
+            "main.ooroo",
+            "import \"gates.ooroo\" as gates\n@tag(\"gate\")\nrule allow (priority 0):\n    gates.gate_books",
+        );
+    let ruleset = RuleSet::from_loader(&loader, "main.ooroo").unwrap();
+    let mut gates = ruleset.rules_with_tag("gate");
+    gates.sort_unstable();
+    assert_eq!(gates, ["allow", "gates.gate_books"]);
+    assert_eq!(ruleset.disabled_rules(), ["gates.gate_books"]);
+}
+
+#[test]
+fn malformed_annotations_are_parse_errors() {
+    for dsl in [
+        "@owner rule r (priority 0):\n    x == 1",
+        "@owner(risk) rule r (priority 0):\n    x == 1",
+        "@enabled(\"no\") rule r (priority 0):\n    x == 1",
+        "@ rule r (priority 0):\n    x == 1",
+    ] {
+        assert!(RuleSet::from_dsl(dsl).is_err(), "{dsl}");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Disabled rules
+// ---------------------------------------------------------------------------
+
+#[test]
+fn disabled_rules_evaluate_to_false() {
+    let ruleset = RuleSet::from_dsl(ANNOTATED).unwrap();
+    let ctx = Context::new()
+        .set("user.email_verified", false)
+        .set("user.age", 30_i64)
+        .set("user.legacy_flag", true);
+    assert!(ruleset.evaluate(&ctx).is_none());
+    assert!(!ruleset
+        .evaluate_detailed(&ctx)
+        .evaluated()
+        .contains(&"old_check".to_owned()));
+
+    let ctx = ctx.set("user.email_verified", true);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allow", true)));
+}
+
+#[test]
+fn disabled_terminal_is_skipped_under_three_valued_logic() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("deny", |r| {
+            r.when(field("user.banned").eq(true)).enabled(false)
+        })
+        .rule("allow", |r| r.when(field("user.age").gte(18_i64)))
+        .rule("not_deny", |r| r.when(!rule_ref("deny")))
+        .terminal_on_unknown("deny", 0, UnknownPolicy::Indeterminate)
+        .terminal("allow", 1)
+        .terminal("not_deny", 2)
+        .compile()
+        .unwrap();
+
+    // `user.banned` is missing, which would make `deny` unknown if enabled.
+    let ctx = Context::new().set("user.age", 30_i64);
+    let verdict = ruleset.evaluate_three_valued(&ctx).unwrap();
+    assert_eq!(verdict.terminal(), "allow");
+    assert_eq!(verdict.truth(), Truth::True);
+}
@@ -0,0 +1,243 @@
+/// Integration tests for runtime rule overrides.
+///
+/// Covers: forcing rules true or false, disabling terminals so lower-priority
+/// terminals win, overrides propagating to dependent rules, re-enabling rules
+/// disabled by metadata, the indexed and three-valued paths, and overrides
+/// listed in the `EvaluationReport`, and overrides applied to a recompiled
+/// ruleset whose rules are declared in a different order.
+use ooroo::{
+    field, rule_ref, Context, RuleOverride, RuleSet, RuleSetBuilder, Truth, UnknownPolicy, Verdict,
+};
+
+fn ruleset() -> RuleSet {
+    RuleSetBuilder::new()
+        .rule("banned", |r| r.when(field("user.banned").eq(true)))
+        .rule("adult", |r| r.when(field("user.age").gte(18_i64)))
+        .rule("allow", |r| {
+            r.when(rule_ref("adult").and(!rule_ref("banned")))
+        })
+        .rule("old_check", |r| {
+            r.when(field("user.legacy").eq(true)).enabled(false)
+        })
+        .terminal("banned", 0)
+        .terminal("old_check", 1)
+        .terminal("allow", 2)
+        .compile()
+        .unwrap()
+}
+
+fn adult() -> Context {
+    Context::new()
+        .set("user.banned", false)
+        .set("user.age", 30_i64)
+        .set("user.legacy", true)
+}
+
+// ---------------------------------------------------------------------------
+// Two-valued evaluation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn empty_overrides_match_plain_evaluation() {
+    let ruleset = ruleset();
+    let overrides = ruleset.overrides();
+    for ctx in [adult(), adult().set("user.banned", true), Context::new()] {
+        assert_eq!(
+            ruleset.evaluate_with_overrides(&ctx, &overrides),
+            ruleset.evaluate(&ctx)
+        );
+    }
+}
+
+#[test]
+fn disabled_terminal_lets_the_next_one_win() {
+    let ruleset = ruleset();
+    let ctx = adult().set("user.banned", true).set("user.age", 10_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("banned", true)));
+
+    let mut overrides = ruleset.overrides();
+    overrides.set("banned", RuleOverride::Disable).unwrap();
+    // `allow` now sees `banned` as false, but `adult` still fails.
+    assert!(ruleset.evaluate_with_overrides(&ctx, &overrides).is_none());
+
+    let ctx = ctx.set("user.age", 30_i64);
+    assert_eq!(
+        ruleset.evaluate_with_overrides(&ctx, &overrides),
+        Some(Verdict::new("allow", true))
+    );
+}
+
+#[test]
+fn forced_values_propagate_to_dependents() {
+    let ruleset = ruleset();
+    let mut overrides = ruleset.overrides();
+    overrides.set("adult", RuleOverride::ForceFalse).unwrap();
+    assert!(ruleset
+        .evaluate_with_overrides(&adult(), &overrides)
+        .is_none());
+
+    overrides.set("adult", RuleOverride::ForceTrue).unwrap();
+    assert_eq!(
+        ruleset.evaluate_with_overrides(&Context::new(), &overrides),
+        Some(Verdict::new("allow", true))
+    );
+
+    overrides.set("banned", RuleOverride::ForceTrue).unwrap();
+    assert_eq!(
+        ruleset.evaluate_with_overrides(&adult(), &overrides),
+        Some(Verdict::new("banned", true))
+    );
+}
+
+#[test]
+fn enable_overrides_disabled_metadata() {
+    let ruleset = ruleset();
+    assert_eq!(
+        ruleset.evaluate(&adult()),
+        Some(Verdict::new("allow", true))
+    );
+
+    let mut overrides = ruleset.overrides();
+    overrides.set("old_check", RuleOverride::Enable).unwrap();
+    assert_eq!(
+        ruleset.evaluate_with_overrides(&adult(), &overrides),
+        Some(Verdict::new("old_check", true))
+    );
+}
+
+#[test]
+fn indexed_context_honours_overrides() {
+    let ruleset = ruleset();
+    let ctx = ruleset
+        .context_builder()
+        .set("user.banned", true)
+        .set("user.age", 30_i64)
+        .build();
+    let mut overrides = ruleset.overrides();
+    overrides.set("banned", RuleOverride::Disable).unwrap();
+    assert_eq!(
+        ruleset.evaluate_indexed_with_overrides(&ctx, &overrides),
+        Some(Verdict::new("allow", true))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Three-valued evaluation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn three_valued_overrides_replace_unknown() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("deny", |r| r.when(field("user.banned").eq(true)))
+        .rule("allow", |r| r.when(field("user.age").gte(18_i64)))
+        .terminal_on_unknown("deny", 0, UnknownPolicy::Indeterminate)
+        .terminal("allow", 1)
+        .compile()
+        .unwrap();
+
+    // `user.banned` is missing, so `deny` is unknown.
+    let ctx = Context::new().set("user.age", 30_i64);
+    assert!(ruleset
+        .evaluate_three_valued(&ctx)
+        .unwrap()
+        .is_indeterminate());
+
+    let mut overrides = ruleset.overrides();
+    overrides.set("deny", RuleOverride::Disable).unwrap();
+    let verdict = ruleset
+        .evaluate_three_valued_with_overrides(&ctx, &overrides)
+        .unwrap();
+    assert_eq!(verdict.terminal(), "allow");
+    assert_eq!(verdict.truth(), Truth::True);
+
+    overrides.set("deny", RuleOverride::ForceTrue).unwrap();
+    let verdict = ruleset
+        .evaluate_three_valued_with_overrides(&ctx, &overrides)
+        .unwrap();
+    assert_eq!(verdict.terminal(), "deny");
+    assert_eq!(verdict.truth(), Truth::True);
+}
+
+// ---------------------------------------------------------------------------
+// Reporting
+// ---------------------------------------------------------------------------
+
+#[test]
+fn report_lists_applied_overrides() {
+    let ruleset = ruleset();
+    let mut overrides = ruleset.overrides();
+    overrides.set("banned", RuleOverride::ForceTrue).unwrap();
+    overrides.set("old_check", RuleOverride::Disable).unwrap();
+
+    let report = ruleset.evaluate_detailed_with_overrides(&adult(), &overrides);
+    assert_eq!(report.verdict(), Some(&Verdict::new("banned", true)));
+    assert!(report.evaluated().contains(&"banned".to_owned()));
+    let mut overridden = report.overridden().to_vec();
+    overridden.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        overridden,
+        [
+            ("banned".to_owned(), RuleOverride::ForceTrue),
+            ("old_check".to_owned(), RuleOverride::Disable),
+        ]
+    );
+    assert!(report.to_string().contains("banned=force_true"));
+
+    assert!(ruleset.evaluate_detailed(&adult()).overridden().is_empty());
+}
+
+#[test]
+fn overrides_reject_unknown_rules() {
+    let ruleset = ruleset();
+    let mut overrides = ruleset.overrides();
+    let err = overrides.set("missing", RuleOverride::Disable).unwrap_err();
+    assert_eq!(err.to_string(), "cannot override unknown rule 'missing'");
+    assert!(overrides.is_empty());
+}
+
+// ---------------------------------------------------------------------------
+// Recompiled rulesets
+// ---------------------------------------------------------------------------
+
+#[test]
+fn overrides_follow_rule_names_into_a_recompiled_ruleset() {
+    let mut overrides = ruleset().overrides();
+    overrides.set("banned", RuleOverride::Disable).unwrap();
+    overrides.set("old_check", RuleOverride::ForceTrue).unwrap();
+
+    // Same rule names, declared in a different order and without
+    // `old_check`, so every compiled index differs.
+    let recompiled = RuleSetBuilder::new()
+        .rule("allow", |r| {
+            r.when(rule_ref("adult").and(!rule_ref("banned")))
+        })
+        .rule("adult", |r| r.when(field("user.age").gte(18_i64)))
+        .rule("banned", |r| r.when(field("user.banned").eq(true)))
+        .terminal("banned", 0)
+        .terminal("allow", 1)
+        .compile()
+        .unwrap();
+
+    let ctx = adult().set("user.banned", true);
+    assert_eq!(
+        recompiled.evaluate(&ctx),
+        Some(Verdict::new("banned", true))
+    );
+    // `banned` is disabled wherever it now sits; `old_check` is ignored.
+    assert_eq!(
+        recompiled.evaluate_with_overrides(&ctx, &overrides),
+        Some(Verdict::new("allow", true))
+    );
+
+    overrides.set("banned", RuleOverride::ForceTrue).unwrap();
+    let ctx = adult();
+    assert_eq!(
+        recompiled.evaluate_with_overrides(&ctx, &overrides),
+        Some(Verdict::new("banned", true))
+    );
+    let report = recompiled.evaluate_detailed_with_overrides(&ctx, &overrides);
+    assert_eq!(
+        report.overridden(),
+        [("banned".to_owned(), RuleOverride::ForceTrue)]
+    );
+}
@@ -0,0 +1,85 @@
+mod strategies;
+
+use ooroo::{format_dsl, is_formatted, Context, Expr, RuleSet};
+use proptest::prelude::*;
+use strategies::{
+    arb_chained_ruleset, arb_context, arb_dsl_expr, arb_flat_ruleset, arb_named_ruleset,
+};
+
+proptest! {
+    /// Every expression the DSL can express parses back from its canonical
+    /// form unchanged.
+    #[test]
+    fn expr_round_trips(expr in arb_dsl_expr()) {
+        let dsl = expr.to_dsl().unwrap();
+        let parsed = Expr::from_dsl(&dsl);
+        prop_assert!(parsed.is_ok(), "{dsl}: {}", parsed.unwrap_err());
+        prop_assert_eq!(parsed.unwrap(), expr, "{}", dsl);
+    }
+
+    /// Formatting a rule body parses back to the same expression, including
+    /// bodies split across lines.
+    #[test]
+    fn formatted_rule_round_trips(expr in arb_dsl_expr()) {
+        let formatted = format_dsl(&format!("rule r (priority 0): {}", expr.to_dsl().unwrap())).unwrap();
+        let body = formatted.split_once(":\n").unwrap().1;
+        prop_assert_eq!(Expr::from_dsl(body).unwrap(), expr);
+        prop_assert!(is_formatted(&formatted).unwrap());
+    }
+
+    /// Formatting is idempotent and preserves evaluation.
+    #[test]
+    fn formatting_preserves_rulesets(
+        generated in prop_oneof![arb_flat_ruleset(), arb_chained_ruleset()],
+        ctx in arb_context(),
+    ) {
+        let source = generated.to_dsl();
+        let formatted = format_dsl(&source).unwrap();
+        prop_assert_eq!(&format_dsl(&formatted).unwrap(), &formatted);
+
+        let original = RuleSet::from_dsl(&source).unwrap();
+        let reformatted = RuleSet::from_dsl(&formatted).unwrap();
+        prop_assert_eq!(original.evaluate(&ctx), reformatted.evaluate(&ctx));
+        prop_assert_eq!(original.terminal_order(), reformatted.terminal_order());
+    }
+
+    /// Decompiling a compiled ruleset gives source that compiles to a
+    /// ruleset with the same behaviour, and decompiles to the same text.
+    #[test]
+    fn decompiled_rulesets_evaluate_the_same(
+        generated in prop_oneof![arb_flat_ruleset(), arb_chained_ruleset()],
+        ctx in arb_context(),
+    ) {
+        let original = generated.compile();
+        let dsl = original.to_dsl().unwrap();
+        let rebuilt = RuleSet::from_dsl(&dsl);
+        prop_assert!(rebuilt.is_ok(), "{dsl}: {}", rebuilt.unwrap_err());
+        let rebuilt = rebuilt.unwrap();
+        prop_assert_eq!(original.evaluate(&ctx), rebuilt.evaluate(&ctx));
+        prop_assert_eq!(original.terminal_order(), rebuilt.terminal_order());
+        prop_assert_eq!(rebuilt.to_dsl().unwrap(), dsl);
+    }
+
+    /// Rule names and field paths that are keywords or contain spaces are
+    /// written between backticks, and the source compiles back to a ruleset
+    /// with the same behaviour.
+    #[test]
+    fn quoted_names_round_trip(
+        (original, [x, y]) in arb_named_ruleset(),
+        value in -2_i64..3,
+        flag in prop::option::of(any::<bool>()),
+    ) {
+        let dsl = original.to_dsl().unwrap();
+        let rebuilt = RuleSet::from_dsl(&dsl);
+        prop_assert!(rebuilt.is_ok(), "{dsl}: {}", rebuilt.unwrap_err());
+        let rebuilt = rebuilt.unwrap();
+        let mut ctx = Context::new().set(&x, value);
+        if let Some(flag) = flag {
+            ctx = ctx.set(&y, flag);
+        }
+        prop_assert_eq!(original.evaluate(&ctx), rebuilt.evaluate(&ctx));
+        prop_assert_eq!(original.terminal_order(), rebuilt.terminal_order());
+        prop_assert_eq!(rebuilt.to_dsl().unwrap(), dsl.clone());
+        prop_assert!(is_formatted(&dsl).unwrap(), "{}", dsl);
+    }
+}
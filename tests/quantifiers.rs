@@ -0,0 +1,333 @@
+/// Integration tests for list quantifiers (`ANY`, `ALL`) and `CONTAINS`.
+///
+/// Covers: element binding in predicates, mixing element paths with context
+/// fields and rule references, vacuous truth over empty lists, graceful
+/// handling of missing or non-list fields, nested quantifiers, and the DSL
+/// syntax for all three operators, with explicit (`AS var`) and inferred
+/// element bindings.
+use ooroo::{
+    all, any, bound_field, field, rule_ref, Context, RuleSet, RuleSetBuilder, Value, Verdict,
+};
+
+fn ints(values: &[i64]) -> Value {
+    Value::List(values.iter().copied().map(Value::Int).collect())
+}
+
+fn strings(values: &[&str]) -> Value {
+    Value::List(values.iter().map(|s| Value::from(*s)).collect())
+}
+
+// ---------------------------------------------------------------------------
+// Builder API: ANY
+// ---------------------------------------------------------------------------
+
+#[test]
+fn any_true_when_one_element_matches() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(any("order.amounts", "amount", field("amount").gt(100_i64)))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("order.amounts", ints(&[5, 250, 10]));
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = Context::new().set("order.amounts", ints(&[5, 10]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn any_over_empty_list_is_false() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(any("xs", "x", field("x").gt(0_i64))))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("xs", ints(&[]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn any_compares_element_against_context_field() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(any(
+                "order.amounts",
+                "amount",
+                field("amount").gt_field("limits.max"),
+            ))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new()
+        .set("order.amounts", ints(&[50, 80]))
+        .set("limits.max", 60_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = Context::new()
+        .set("order.amounts", ints(&[50, 80]))
+        .set("limits.max", 100_i64);
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn element_var_does_not_register_a_context_field() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(any("xs", "x", field("x").eq(1_i64))))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    // Only the list field itself is a context field.
+    let ctx = ruleset.context_builder().set("xs", ints(&[1])).build();
+    assert_eq!(
+        ruleset.evaluate_indexed(&ctx),
+        Some(Verdict::new("r", true))
+    );
+    assert_eq!(
+        ruleset.to_string(),
+        "RuleSet(1 rules, 1 terminals, 1 fields)"
+    );
+}
+
+#[test]
+fn predicate_can_reference_rules() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("vip", |r| r.when(field("user.tier").eq("vip")))
+        .rule("r", |r| {
+            r.when(any("xs", "x", field("x").gt(10_i64).or(rule_ref("vip"))))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("xs", ints(&[1])).set("user.tier", "vip");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+    assert_eq!(ruleset.dependencies_of("r"), Some(vec!["vip"]));
+}
+
+// ---------------------------------------------------------------------------
+// Builder API: ALL
+// ---------------------------------------------------------------------------
+
+#[test]
+fn all_true_only_when_every_element_matches() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(all("user.roles", "role", field("role").neq("guest")))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("user.roles", strings(&["admin", "editor"]));
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = Context::new().set("user.roles", strings(&["admin", "guest"]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn all_over_empty_list_is_vacuously_true() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(all("xs", "x", field("x").gt(0_i64))))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("xs", ints(&[]));
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+#[test]
+fn quantifiers_false_for_missing_or_scalar_field() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("any_r", |r| r.when(any("xs", "x", field("x").gt(0_i64))))
+        .rule("all_r", |r| r.when(all("xs", "x", field("x").gt(0_i64))))
+        .terminal("any_r", 0)
+        .terminal("all_r", 1)
+        .compile()
+        .unwrap();
+
+    assert!(ruleset.evaluate(&Context::new()).is_none());
+    assert!(ruleset.evaluate(&Context::new().set("xs", 5_i64)).is_none());
+}
+
+#[test]
+fn nested_quantifiers_bind_independently() {
+    // Some row has every cell positive.
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(any(
+                "grid",
+                "row",
+                all("row", "cell", field("cell").gt(0_i64)),
+            ))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let grid = Value::List(vec![ints(&[1, -1]), ints(&[2, 3])]);
+    let ctx = Context::new().set("grid", grid);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let grid = Value::List(vec![ints(&[1, -1]), ints(&[-2, 3])]);
+    let ctx = Context::new().set("grid", grid);
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn inner_var_shadows_outer_var() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(any("grid", "x", any("x", "x", field("x").eq(7_i64))))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let grid = Value::List(vec![ints(&[1, 2]), ints(&[7])]);
+    let ctx = Context::new().set("grid", grid);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+}
+
+// ---------------------------------------------------------------------------
+// Builder API: CONTAINS
+// ---------------------------------------------------------------------------
+
+#[test]
+fn contains_literal() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(field("user.roles").contains("admin")))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("user.roles", strings(&["editor", "admin"]));
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    let ctx = Context::new().set("user.roles", strings(&["editor"]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn contains_field_bound_and_cross_type_numbers() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| {
+            r.when(field("codes").contains(bound_field("wanted")))
+        })
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new()
+        .set("codes", Value::List(vec![Value::Float(3.0)]))
+        .set("wanted", 3_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+
+    // Missing bound field
+    let ctx = Context::new().set("codes", ints(&[3]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn contains_on_scalar_is_false() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(field("role").contains("admin")))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+
+    let ctx = Context::new().set("role", "admin");
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+// ---------------------------------------------------------------------------
+// DSL
+// ---------------------------------------------------------------------------
+
+#[test]
+fn dsl_quantifiers_and_contains() {
+    let dsl = r#"
+rule has_large_amount:
+    ANY(order.amounts AS amount, amount > 100)
+
+rule no_guests:
+    ALL(user.roles AS role, role != "guest")
+
+rule is_admin:
+    user.roles CONTAINS "admin"
+
+rule allowed (priority 0):
+    is_admin AND no_guests AND NOT has_large_amount
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    let ctx = Context::new()
+        .set("order.amounts", ints(&[20, 40]))
+        .set("user.roles", strings(&["admin", "editor"]));
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allowed", true)));
+
+    let ctx = Context::new()
+        .set("order.amounts", ints(&[20, 400]))
+        .set("user.roles", strings(&["admin", "editor"]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn dsl_quantifier_binding_is_inferred_from_the_predicate() {
+    let dsl = r#"
+rule big_item:
+    ANY(order.items, item.price > 100)
+
+rule no_guests (priority 0):
+    ALL(user.roles, role != "guest") AND big_item
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+    let item = |price: i64| {
+        Value::Map(
+            [("price".to_owned(), Value::Int(price))]
+                .into_iter()
+                .collect(),
+        )
+    };
+
+    let ctx = Context::new()
+        .set("order.items", Value::List(vec![item(20), item(150)]))
+        .set("user.roles", strings(&["admin", "editor"]));
+    assert_eq!(
+        ruleset.evaluate(&ctx),
+        Some(Verdict::new("no_guests", true))
+    );
+
+    let ctx = Context::new()
+        .set("order.items", Value::List(vec![item(20), item(150)]))
+        .set("user.roles", strings(&["admin", "guest"]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+
+    // Only the lists are context fields; `item` and `role` are bound.
+    assert_eq!(
+        ruleset.to_string(),
+        "RuleSet(2 rules, 1 terminals, 2 fields)"
+    );
+}
+
+#[test]
+fn dsl_inferred_binding_matches_explicit_as() {
+    let inferred = RuleSet::from_dsl("rule r (priority 0):\n    ANY(xs, x > 1 AND x < 9)").unwrap();
+    let explicit =
+        RuleSet::from_dsl("rule r (priority 0):\n    ANY(xs AS x, x > 1 AND x < 9)").unwrap();
+    assert_eq!(inferred.to_dsl().unwrap(), explicit.to_dsl().unwrap());
+}
+
+#[test]
+fn dsl_quantifier_without_a_tested_field_needs_as() {
+    let result = RuleSet::from_dsl("rule a:\n    x > 1\nrule r (priority 0):\n    ANY(xs, a)");
+    assert!(result.is_err());
+}
@@ -0,0 +1,277 @@
+/// Integration tests for field schema declarations.
+///
+/// Covers: compile-time rejection of undeclared fields and type mismatches in
+/// comparisons, `BETWEEN`, `IN`, `LIKE` and field-to-field comparisons,
+/// list and map operators on fields of other types,
+/// numeric widening between `int` and `float`, untyped paths (quantifier
+/// elements, entries of declared maps, indexed lookups), runtime context
+/// validation, and the DSL `field` declarations.
+use ooroo::{
+    all, any, bound_field, field, len, CompileError, Context, Expr, FieldType, RuleSet,
+    RuleSetBuilder, Schema, SchemaViolation, Value, Verdict,
+};
+
+fn schema() -> Schema {
+    Schema::new()
+        .field("user.age", FieldType::Int)
+        .field("user.name", FieldType::String)
+        .field("user.verified", FieldType::Bool)
+        .field("order.total", FieldType::Float)
+        .field("order.limit", FieldType::Int)
+        .field("order.items", FieldType::List)
+        .field("limits", FieldType::Map)
+}
+
+fn compile(expr: Expr) -> Result<RuleSet, CompileError> {
+    RuleSetBuilder::new()
+        .schema(schema())
+        .rule("r", |r| r.when(expr))
+        .terminal("r", 0)
+        .compile()
+}
+
+// ---------------------------------------------------------------------------
+// Compile-time checks
+// ---------------------------------------------------------------------------
+
+#[test]
+fn well_typed_rules_compile() {
+    for expr in [
+        field("user.age").gte(18_i64),
+        field("user.age").lt(17.5),
+        field("order.total").between(1_i64, bound_field("order.limit")),
+        field("user.name").is_in(["alice", "bob"]),
+        field("user.name").is_in(vec![bound_field("order.items")]),
+        field("user.name").like("a%"),
+        field("order.total").lte_field("order.limit"),
+        field("user.verified").eq(true),
+        field("user.age").is_null(),
+    ] {
+        assert!(compile(expr.clone()).is_ok(), "{expr}");
+    }
+}
+
+#[test]
+fn undeclared_field_is_rejected() {
+    let err = compile(field("user.agee").gte(18_i64)).unwrap_err();
+    assert!(matches!(
+        err,
+        CompileError::UnknownField { ref rule, ref path } if rule == "r" && path == "user.agee"
+    ));
+
+    // Field-sourced keys and quantifier lists are references too.
+    assert!(matches!(
+        compile(field("limits[user.country]").gt(0_i64)).unwrap_err(),
+        CompileError::UnknownField { path, .. } if path == "user.country"
+    ));
+    assert!(matches!(
+        compile(any("orders", "o", field("o.total").gt(0_i64))).unwrap_err(),
+        CompileError::UnknownField { path, .. } if path == "orders"
+    ));
+}
+
+#[test]
+fn mismatched_types_are_rejected() {
+    for expr in [
+        field("user.age").eq("eighteen"),
+        field("user.verified").gt(false),
+        field("user.age").between(1_i64, "z"),
+        field("user.age").between(1_i64, bound_field("user.name")),
+        field("user.age").is_in([1_i64.into(), Value::from("x")]),
+        field("user.age").not_in(vec![bound_field("user.name")]),
+        field("user.age").like("1%"),
+        field("user.verified").not_like("t%"),
+        field("user.name").gt_field("user.age"),
+    ] {
+        assert!(
+            matches!(
+                compile(expr.clone()),
+                Err(CompileError::TypeMismatch { .. })
+            ),
+            "{expr}"
+        );
+    }
+}
+
+#[test]
+fn type_mismatch_reports_field_and_reason() {
+    let err = compile(field("user.age").eq("eighteen")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "type mismatch in rule 'r': field 'user.age' is int and cannot be compared with string"
+    );
+    let err = compile(field("user.age").like("1%")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "type mismatch in rule 'r': field 'user.age' is int and cannot be matched with LIKE"
+    );
+}
+
+#[test]
+fn list_and_map_operators_need_collections() {
+    let ints = || Value::List(vec![Value::Int(1)]);
+    for (expr, reason) in [
+        (
+            field("user.age").contains(1_i64),
+            "cannot be searched with CONTAINS",
+        ),
+        (
+            field("user.age").overlaps(ints()),
+            "cannot be compared as a set with OVERLAPS",
+        ),
+        (
+            field("order.items").subset_of(bound_field("user.age")),
+            "cannot be compared as a set with SUBSET_OF",
+        ),
+        (len("user.age").gte(1_i64), "has no len()"),
+        (
+            field("user.age").has_key("x"),
+            "cannot be tested with HAS_KEY",
+        ),
+        (
+            field("order.items").has_key("x"),
+            "cannot be tested with HAS_KEY",
+        ),
+        (
+            any("user.age", "a", field("a").gt(1_i64)),
+            "cannot be quantified over with ANY",
+        ),
+        (
+            all("user.name", "c", field("c").eq("x")),
+            "cannot be quantified over with ALL",
+        ),
+    ] {
+        let err = compile(expr.clone()).unwrap_err();
+        assert!(
+            matches!(err, CompileError::TypeMismatch { .. }),
+            "{expr}: {err}"
+        );
+        assert!(err.to_string().ends_with(reason), "{expr}: {err}");
+    }
+
+    for expr in [
+        field("order.items").contains(1_i64),
+        field("order.items").overlaps(ints()),
+        len("order.items").gte(1_i64),
+        len("limits").gte(1_i64),
+        field("limits").has_key("US"),
+        all("order.items", "i", field("i").is_not_null()),
+        // Element variables are untyped.
+        any("order.items", "i", len("i").gt(0_i64)),
+    ] {
+        assert!(compile(expr.clone()).is_ok(), "{expr}");
+    }
+}
+
+#[test]
+fn untyped_paths_are_not_checked() {
+    for expr in [
+        // Entries of a declared map.
+        field("limits.US").eq("anything"),
+        // Quantifier elements.
+        any("order.items", "i", field("i.sku").like("A%")),
+        // Indexed lookups.
+        field("order.items[0].price").gt(1_i64),
+    ] {
+        assert!(compile(expr.clone()).is_ok(), "{expr}");
+    }
+}
+
+#[test]
+fn rulesets_without_a_schema_are_unchecked() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("r", |r| r.when(field("user.age").eq("eighteen")))
+        .terminal("r", 0)
+        .compile()
+        .unwrap();
+    assert!(ruleset.schema().is_none());
+    assert!(ruleset
+        .validate_context(&Context::new().set("user.age", 1.5))
+        .is_ok());
+}
+
+// ---------------------------------------------------------------------------
+// Runtime validation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn validate_context_reports_violations() {
+    let ruleset = compile(field("user.age").gte(18_i64)).unwrap();
+    assert_eq!(ruleset.schema(), Some(&schema()));
+
+    let ctx = Context::new()
+        .set("user.age", "18")
+        .set("order.total", 10_i64)
+        .set("user.verified", 1_i64);
+    assert_eq!(
+        ruleset.validate_context(&ctx),
+        Err(vec![
+            SchemaViolation {
+                path: "user.age".into(),
+                expected: FieldType::Int,
+                found: FieldType::String,
+            },
+            SchemaViolation {
+                path: "user.verified".into(),
+                expected: FieldType::Bool,
+                found: FieldType::Int,
+            },
+        ])
+    );
+
+    let ctx = Context::new().set("user.age", 18_i64);
+    assert!(ruleset.validate_context(&ctx).is_ok());
+}
+
+// ---------------------------------------------------------------------------
+// DSL
+// ---------------------------------------------------------------------------
+
+#[test]
+fn dsl_field_declarations() {
+    let dsl = r#"
+field user.age: int
+field user.country: string
+
+rule adult:
+    user.age >= 18
+
+field limits: map
+
+rule allowed (priority 0):
+    adult AND limits[user.country] > 0
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+    assert_eq!(
+        ruleset.schema().unwrap().get("user.country"),
+        Some(FieldType::String)
+    );
+
+    let ctx = Context::new()
+        .set("user.age", 30_i64)
+        .set("user.country", "US")
+        .set(
+            "limits",
+            Value::Map([("US".to_owned(), Value::Int(5))].into()),
+        );
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allowed", true)));
+}
+
+#[test]
+fn dsl_type_errors_fail_compilation() {
+    let dsl = "field user.age: int\nrule r (priority 0):\n    user.age == \"eighteen\"";
+    assert!(matches!(
+        RuleSet::from_dsl(dsl),
+        Err(ooroo::OorooError::Compile(
+            CompileError::TypeMismatch { .. }
+        ))
+    ));
+
+    let dsl = "field user.age: int\nrule r (priority 0):\n    user.agee >= 18";
+    assert!(matches!(
+        RuleSet::from_dsl(dsl),
+        Err(ooroo::OorooError::Compile(
+            CompileError::UnknownField { .. }
+        ))
+    ));
+}
@@ -0,0 +1,204 @@
+/// Integration tests for list set operators (`OVERLAPS`, `SUBSET_OF`,
+/// `SUPERSET_OF`, `DISJOINT_FROM`) and `len()` counting.
+///
+/// Covers: list literals and list-valued field references on the right-hand
+/// side, equality semantics shared with `CONTAINS`, missing and non-list
+/// operands, and the DSL syntax.
+use ooroo::{bound_field, field, len, Context, RuleSet, RuleSetBuilder, Value, Verdict};
+
+fn strings(values: &[&str]) -> Value {
+    Value::List(values.iter().map(|s| Value::from(*s)).collect())
+}
+
+fn single(expr: ooroo::Expr) -> RuleSet {
+    RuleSetBuilder::new()
+        .rule("r", |r| r.when(expr))
+        .terminal("r", 0)
+        .compile()
+        .unwrap()
+}
+
+fn matches(ruleset: &RuleSet, ctx: &Context) -> bool {
+    ruleset.evaluate(ctx) == Some(Verdict::new("r", true))
+}
+
+// ---------------------------------------------------------------------------
+// Builder API: set operators
+// ---------------------------------------------------------------------------
+
+#[test]
+fn superset_of_field() {
+    let ruleset = single(field("user.perms").superset_of(bound_field("resource.required")));
+
+    let ctx = Context::new()
+        .set("user.perms", strings(&["read", "write", "admin"]))
+        .set("resource.required", strings(&["read", "write"]));
+    assert!(matches(&ruleset, &ctx));
+
+    let ctx = Context::new()
+        .set("user.perms", strings(&["read"]))
+        .set("resource.required", strings(&["read", "write"]));
+    assert!(!matches(&ruleset, &ctx));
+}
+
+#[test]
+fn subset_of_literal() {
+    let ruleset = single(field("tags").subset_of(vec![Value::from("a"), Value::from("b")]));
+
+    assert!(matches(
+        &ruleset,
+        &Context::new().set("tags", strings(&["a"]))
+    ));
+    assert!(matches(&ruleset, &Context::new().set("tags", strings(&[]))));
+    assert!(!matches(
+        &ruleset,
+        &Context::new().set("tags", strings(&["a", "c"]))
+    ));
+}
+
+#[test]
+fn overlaps_and_disjoint_from() {
+    let overlaps = single(field("a").overlaps(bound_field("b")));
+    let disjoint = single(field("a").disjoint_from(bound_field("b")));
+
+    let shared = Context::new()
+        .set("a", strings(&["x", "y"]))
+        .set("b", strings(&["y", "z"]));
+    assert!(matches(&overlaps, &shared));
+    assert!(!matches(&disjoint, &shared));
+
+    let apart = Context::new()
+        .set("a", strings(&["x"]))
+        .set("b", strings(&["z"]));
+    assert!(!matches(&overlaps, &apart));
+    assert!(matches(&disjoint, &apart));
+}
+
+#[test]
+fn set_operators_use_numeric_equality() {
+    let ruleset = single(field("a").subset_of(bound_field("b")));
+    let ctx = Context::new()
+        .set("a", Value::List(vec![Value::Int(1), Value::Int(2)]))
+        .set("b", Value::List(vec![Value::Float(1.0), Value::Float(2.0)]));
+    assert!(matches(&ruleset, &ctx));
+}
+
+#[test]
+fn set_operators_false_for_missing_or_scalar_operands() {
+    let disjoint = single(field("a").disjoint_from(bound_field("b")));
+
+    // Missing right-hand side
+    let ctx = Context::new().set("a", strings(&["x"]));
+    assert!(!matches(&disjoint, &ctx));
+
+    // Scalar left-hand side
+    let ctx = Context::new().set("a", "x").set("b", strings(&["y"]));
+    assert!(!matches(&disjoint, &ctx));
+}
+
+// ---------------------------------------------------------------------------
+// Builder API: len()
+// ---------------------------------------------------------------------------
+
+#[test]
+fn len_against_literal() {
+    let ruleset = single(len("roles").gte(2_i64));
+
+    assert!(matches(
+        &ruleset,
+        &Context::new().set("roles", strings(&["a", "b"]))
+    ));
+    assert!(!matches(
+        &ruleset,
+        &Context::new().set("roles", strings(&["a"]))
+    ));
+}
+
+#[test]
+fn len_against_field() {
+    let ruleset = single(len("items").lte(bound_field("limits.max_items")));
+
+    let ctx = Context::new()
+        .set("items", strings(&["a", "b"]))
+        .set("limits.max_items", 2_i64);
+    assert!(matches(&ruleset, &ctx));
+
+    let ctx = Context::new()
+        .set("items", strings(&["a", "b", "c"]))
+        .set("limits.max_items", 2_i64);
+    assert!(!matches(&ruleset, &ctx));
+}
+
+#[test]
+fn len_of_empty_list_is_zero() {
+    let ruleset = single(len("items").eq(0_i64));
+    assert!(matches(
+        &ruleset,
+        &Context::new().set("items", strings(&[]))
+    ));
+}
+
+#[test]
+fn len_false_for_missing_or_scalar_field() {
+    let ruleset = single(len("items").gte(0_i64));
+    assert!(!matches(&ruleset, &Context::new()));
+    assert!(!matches(&ruleset, &Context::new().set("items", "abc")));
+}
+
+// ---------------------------------------------------------------------------
+// DSL
+// ---------------------------------------------------------------------------
+
+#[test]
+fn dsl_set_operators_and_len() {
+    let dsl = r#"
+rule has_required:
+    user.perms SUPERSET_OF resource.required
+
+rule no_blocked_tags:
+    post.tags DISJOINT_FROM ["spam", "nsfw"]
+
+rule few_tags:
+    len(post.tags) <= 3
+
+rule publish (priority 0):
+    has_required AND no_blocked_tags AND few_tags
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    let ctx = Context::new()
+        .set("user.perms", strings(&["post", "edit"]))
+        .set("resource.required", strings(&["post"]))
+        .set("post.tags", strings(&["rust", "news"]));
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("publish", true)));
+
+    let ctx = Context::new()
+        .set("user.perms", strings(&["post", "edit"]))
+        .set("resource.required", strings(&["post"]))
+        .set("post.tags", strings(&["rust", "spam"]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+
+    let ctx = Context::new()
+        .set("user.perms", strings(&["post", "edit"]))
+        .set("resource.required", strings(&["post"]))
+        .set("post.tags", strings(&["a", "b", "c", "d"]));
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn dsl_set_operator_inside_quantifier() {
+    let dsl = r#"
+rule any_group_covers:
+    ANY(groups AS g, g SUPERSET_OF needed)
+
+rule allowed (priority 0):
+    any_group_covers
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    let groups = Value::List(vec![strings(&["a"]), strings(&["a", "b"])]);
+    let ctx = Context::new()
+        .set("groups", groups)
+        .set("needed", strings(&["a", "b"]));
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allowed", true)));
+}
@@ -0,0 +1,195 @@
+#![cfg(feature = "signing")]
+/// Integration tests for signed binary-cache blobs.
+///
+/// Covers: signing and verifying round trips, key rotation with several
+/// trusted keys, rejecting unsigned blobs, wrong keys, tampered payloads,
+/// cleared flags and stripped signatures, loading signed blobs without
+/// verification, inspecting them, and signed blobs that carry a source
+/// digest or are compressed.
+use ooroo::{
+    field, inspect, rule_ref, Context, DeserializeError, RuleSet, RuleSetBuilder, SigningKey,
+    Verdict,
+};
+
+fn ruleset() -> RuleSet {
+    RuleSetBuilder::new()
+        .rule("adult", |r| r.when(field("user.age").gte(18_i64)))
+        .rule("banned", |r| r.when(field("user.banned").eq(true)))
+        .rule("allow", |r| {
+            r.when(rule_ref("adult").and(!rule_ref("banned")))
+        })
+        .terminal("banned", 0)
+        .terminal("allow", 1)
+        .compile()
+        .unwrap()
+}
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn ctx() -> Context {
+    Context::new()
+        .set("user.age", 30_i64)
+        .set("user.banned", false)
+}
+
+// ---------------------------------------------------------------------------
+// Round trips
+// ---------------------------------------------------------------------------
+
+#[test]
+fn signed_blobs_verify() {
+    let blob = ruleset().to_bytes_signed(None, &key(1)).unwrap();
+    let loaded = RuleSet::from_bytes_verified(&blob, &[key(1).verifying_key()]).unwrap();
+    assert_eq!(loaded.evaluate(&ctx()), Some(Verdict::new("allow", true)));
+}
+
+#[test]
+fn any_trusted_key_verifies() {
+    let blob = ruleset().to_bytes_signed(None, &key(2)).unwrap();
+    let keys = [key(1).verifying_key(), key(2).verifying_key()];
+    assert!(RuleSet::from_bytes_verified(&blob, &keys).is_ok());
+}
+
+#[test]
+fn inspect_reports_signed_blobs() {
+    let signed = ruleset().to_bytes_signed(None, &key(1)).unwrap();
+    let info = inspect(&signed).unwrap();
+    assert!(info.is_signed());
+    assert!(info.checksum_valid);
+    assert!(!inspect(&ruleset().to_bytes(None).unwrap())
+        .unwrap()
+        .is_signed());
+}
+
+#[test]
+fn signed_blobs_load_without_verification() {
+    let blob = ruleset().to_bytes_signed(None, &key(1)).unwrap();
+    let loaded = RuleSet::from_bytes(&blob).unwrap();
+    assert_eq!(loaded.evaluate(&ctx()), Some(Verdict::new("allow", true)));
+}
+
+#[test]
+fn signed_blobs_keep_the_source_digest() {
+    let blob = ruleset().to_bytes_signed(Some("source"), &key(1)).unwrap();
+    let info = inspect(&blob).unwrap();
+    assert!(info.is_signed());
+    assert!(info.matches_source("source"));
+    assert!(RuleSet::from_bytes_verified(&blob, &[key(1).verifying_key()]).is_ok());
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn signed_blobs_can_be_compressed() {
+    let blob = ruleset()
+        .to_bytes_compressed_signed(Some("source"), &key(1))
+        .unwrap();
+    let info = inspect(&blob).unwrap();
+    assert!(info.is_signed());
+    assert!(info.is_compressed());
+    assert!(info.matches_source("source"));
+
+    let loaded = RuleSet::from_bytes_verified(&blob, &[key(1).verifying_key()]).unwrap();
+    assert_eq!(loaded.evaluate(&ctx()), Some(Verdict::new("allow", true)));
+    assert!(RuleSet::from_bytes(&blob).is_ok());
+
+    // Clearing the compression bit is caught by the signature.
+    let mut tampered = blob.clone();
+    tampered[8] &= !2;
+    assert!(matches!(
+        RuleSet::from_bytes_verified(&tampered, &[key(1).verifying_key()]),
+        Err(DeserializeError::BadSignature)
+    ));
+}
+
+// ---------------------------------------------------------------------------
+// Rejections
+// ---------------------------------------------------------------------------
+
+#[test]
+fn unsigned_blobs_are_rejected() {
+    let blob = ruleset().to_bytes(None).unwrap();
+    assert!(matches!(
+        RuleSet::from_bytes_verified(&blob, &[key(1).verifying_key()]),
+        Err(DeserializeError::MissingSignature)
+    ));
+}
+
+#[test]
+fn untrusted_keys_are_rejected() {
+    let blob = ruleset().to_bytes_signed(None, &key(1)).unwrap();
+    assert!(matches!(
+        RuleSet::from_bytes_verified(&blob, &[key(2).verifying_key()]),
+        Err(DeserializeError::BadSignature)
+    ));
+    assert!(matches!(
+        RuleSet::from_bytes_verified(&blob, &[]),
+        Err(DeserializeError::BadSignature)
+    ));
+}
+
+#[test]
+fn payloads_resigned_without_the_key_are_rejected() {
+    // An attacker swaps in their own policy, with a valid checksum, and
+    // signs it with their own key.
+    let theirs = RuleSetBuilder::new()
+        .rule("allow", |r| r.when(field("user.age").gte(0_i64)))
+        .terminal("allow", 0)
+        .compile()
+        .unwrap();
+    let forged = theirs.to_bytes_signed(None, &key(9)).unwrap();
+    assert!(RuleSet::from_bytes(&forged).is_ok());
+    assert!(matches!(
+        RuleSet::from_bytes_verified(&forged, &[key(1).verifying_key()]),
+        Err(DeserializeError::BadSignature)
+    ));
+}
+
+#[test]
+fn tampered_signatures_are_rejected() {
+    let blob = ruleset().to_bytes_signed(None, &key(1)).unwrap();
+    let trusted = [key(1).verifying_key()];
+
+    let mut flipped = blob.clone();
+    let last = flipped.len() - 1;
+    flipped[last] ^= 0x01;
+    assert!(matches!(
+        RuleSet::from_bytes_verified(&flipped, &trusted),
+        Err(DeserializeError::BadSignature)
+    ));
+
+    let stripped = &blob[..blob.len() - 64];
+    assert!(matches!(
+        RuleSet::from_bytes_verified(stripped, &trusted),
+        Err(DeserializeError::BadSignature)
+    ));
+
+    let mut extended = blob.clone();
+    extended.push(0);
+    assert!(matches!(
+        RuleSet::from_bytes_verified(&extended, &trusted),
+        Err(DeserializeError::BadSignature)
+    ));
+}
+
+#[test]
+fn cleared_flags_are_rejected() {
+    let mut blob = ruleset().to_bytes_signed(None, &key(1)).unwrap();
+    blob[8] = 0;
+    assert!(matches!(
+        RuleSet::from_bytes_verified(&blob, &[key(1).verifying_key()]),
+        Err(DeserializeError::MissingSignature)
+    ));
+}
+
+#[test]
+fn tampered_headers_are_rejected() {
+    let mut blob = ruleset().to_bytes_signed(None, &key(1)).unwrap();
+    // The engine version is informational, but still signed.
+    blob[6] ^= 0xFF;
+    assert!(matches!(
+        RuleSet::from_bytes_verified(&blob, &[key(1).verifying_key()]),
+        Err(DeserializeError::BadSignature)
+    ));
+}
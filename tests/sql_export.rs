@@ -0,0 +1,292 @@
+/// Integration tests for exporting rules as SQL `WHERE` clauses.
+///
+/// Covers: each supported construct (comparisons, `IN` with literal, list
+/// and field members, `BETWEEN`, `LIKE`, `IS NULL`, field-to-field
+/// comparisons, `AT_LEAST`, `NOT` and boolean chains with the parentheses
+/// SQL needs); `NULL` columns matching three-valued evaluation; inlining rule references, constants and disabled rules;
+/// dialect differences in quoting, booleans and `LIKE` escaping; and the
+/// errors for unknown rules, unmapped fields and unsupported constructs.
+use ooroo::{
+    all, any, at_least, bound_field, field, len, rule_ref, Context, Expr, RuleSet, SqlDialect,
+    SqlError, SqlExporter, Value,
+};
+
+const SOURCE: &str = r#"
+const BLOCKED = ["XX", "YY"]
+
+rule adult:
+    user.age >= 18
+
+rule blocked:
+    user.country IN BLOCKED OR user.email LIKE "%@spam.test"
+
+@disabled
+rule legacy:
+    user.age < 0
+
+rule allow (priority 0):
+    adult AND NOT blocked AND (user.tier == "gold" OR legacy)
+"#;
+
+fn exporter(dialect: SqlDialect) -> SqlExporter {
+    SqlExporter::new(dialect).columns([
+        ("user.age", "age"),
+        ("user.country", "country"),
+        ("user.email", "email"),
+        ("user.tier", "tier"),
+        ("user.id", "id"),
+        ("score", "s.score"),
+        ("limit", "s.limit"),
+        ("flags.beta", "beta"),
+    ])
+}
+
+fn ansi(expr: &Expr) -> String {
+    expr.to_sql_where(&exporter(SqlDialect::Ansi)).unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// Rule references
+// ---------------------------------------------------------------------------
+
+#[test]
+fn references_are_inlined() {
+    let ruleset = RuleSet::from_dsl(SOURCE).unwrap();
+    assert_eq!(
+        ruleset
+            .to_sql_where("allow", &exporter(SqlDialect::Ansi))
+            .unwrap(),
+        r#""age" >= 18 AND NOT ("country" IN ('XX', 'YY') OR "email" LIKE '%@spam.test') AND ("tier" = 'gold' OR FALSE)"#
+    );
+}
+
+#[test]
+fn the_exported_rule_is_written_even_when_disabled() {
+    let ruleset = RuleSet::from_dsl(SOURCE).unwrap();
+    assert_eq!(
+        ruleset
+            .to_sql_where("legacy", &exporter(SqlDialect::Ansi))
+            .unwrap(),
+        r#""age" < 0"#
+    );
+}
+
+#[test]
+fn unknown_rules_are_reported() {
+    let ruleset = RuleSet::from_dsl(SOURCE).unwrap();
+    assert_eq!(
+        ruleset.to_sql_where("nope", &exporter(SqlDialect::Ansi)),
+        Err(SqlError::UnknownRule("nope".to_owned()))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Constructs
+// ---------------------------------------------------------------------------
+
+#[test]
+fn comparisons() {
+    assert_eq!(ansi(&field("user.age").neq(18_i64)), r#""age" <> 18"#);
+    assert_eq!(ansi(&field("score").gt(0.5_f64)), r#""s"."score" > 0.5"#);
+    assert_eq!(ansi(&field("flags.beta").eq(true)), r#""beta" = TRUE"#);
+    assert_eq!(
+        ansi(&field("score").lte_field("limit")),
+        r#""s"."score" <= "s"."limit""#
+    );
+}
+
+#[test]
+fn membership_and_ranges() {
+    assert_eq!(
+        ansi(&field("user.country").is_in(["US".into(), bound_field("user.tier")])),
+        r#""country" IN ('US', "tier")"#
+    );
+    assert_eq!(
+        ansi(&field("user.age").not_in([1_i64, 2_i64])),
+        r#""age" NOT IN (1, 2)"#
+    );
+    assert_eq!(
+        ansi(&field("score").between(0_i64, bound_field("limit"))),
+        r#""s"."score" BETWEEN 0 AND "s"."limit""#
+    );
+    let empty = Expr::In {
+        field: "user.age".to_owned(),
+        members: vec![],
+    };
+    assert_eq!(ansi(&empty), "FALSE");
+}
+
+#[test]
+fn patterns_and_null_checks() {
+    assert_eq!(
+        ansi(&field("user.email").not_like("%@corp.test")),
+        r#""email" NOT LIKE '%@corp.test'"#
+    );
+    assert_eq!(ansi(&field("user.id").is_null()), r#""id" IS NULL"#);
+    assert_eq!(ansi(&field("user.id").is_not_null()), r#""id" IS NOT NULL"#);
+    assert_eq!(
+        ansi(&field("user.tier").eq("o'brien")),
+        r#""tier" = 'o''brien'"#
+    );
+}
+
+#[test]
+fn at_least_counts_matches() {
+    let expr = at_least(
+        2,
+        vec![
+            field("user.age").gte(18_i64),
+            field("user.tier").eq("gold"),
+            field("user.id").is_not_null(),
+        ],
+    );
+    assert_eq!(
+        ansi(&expr),
+        concat!(
+            r#"CASE WHEN (CASE WHEN "age" >= 18 THEN 1 ELSE 0 END + "#,
+            r#"CASE WHEN "tier" = 'gold' THEN 1 ELSE 0 END + "#,
+            r#"CASE WHEN "id" IS NOT NULL THEN 1 ELSE 0 END) >= 2 THEN 1 "#,
+            r#"WHEN (CASE WHEN NOT ("age" >= 18) THEN 0 ELSE 1 END + "#,
+            r#"CASE WHEN NOT ("tier" = 'gold') THEN 0 ELSE 1 END + "#,
+            r#"CASE WHEN NOT ("id" IS NOT NULL) THEN 0 ELSE 1 END) >= 2 THEN NULL "#,
+            r#"ELSE 0 END = 1"#
+        )
+    );
+    assert_eq!(ansi(&at_least(0, vec![])), "TRUE");
+    assert_eq!(
+        ansi(&at_least(2, vec![field("user.id").is_null()])),
+        "FALSE"
+    );
+}
+
+/// With `a = 5` and `b` NULL, `AT_LEAST(2, a > 1, b > 1)` is unknown, so
+/// its negation must not match either: the count comparison has to be
+/// `NULL` rather than false.
+#[test]
+fn negated_at_least_is_unknown_when_operands_are() {
+    let ruleset =
+        RuleSet::from_dsl("rule few (priority 0):\n    NOT AT_LEAST(2, score > 1, limit > 1)")
+            .unwrap();
+    let row = Context::new().set("score", 5_i64);
+    assert_eq!(ruleset.evaluate_three_valued(&row), None);
+    assert_eq!(
+        ruleset
+            .to_sql_where("few", &exporter(SqlDialect::Ansi))
+            .unwrap(),
+        concat!(
+            r#"NOT (CASE WHEN (CASE WHEN "s"."score" > 1 THEN 1 ELSE 0 END + "#,
+            r#"CASE WHEN "s"."limit" > 1 THEN 1 ELSE 0 END) >= 2 THEN 1 "#,
+            r#"WHEN (CASE WHEN NOT ("s"."score" > 1) THEN 0 ELSE 1 END + "#,
+            r#"CASE WHEN NOT ("s"."limit" > 1) THEN 0 ELSE 1 END) >= 2 THEN NULL "#,
+            r#"ELSE 0 END = 1)"#
+        )
+    );
+
+    // Once the known operands decide it, the result is known either way.
+    let decided = Context::new().set("score", 0_i64);
+    assert!(ruleset.evaluate_three_valued(&decided).is_some());
+}
+
+#[test]
+fn chains_are_flat_and_parenthesized_where_needed() {
+    let a = || field("user.age").gt(1_i64);
+    let b = || field("user.age").lt(9_i64);
+    let c = || field("user.id").is_null();
+    assert_eq!(
+        ansi(&a().and(b()).and(c())),
+        r#""age" > 1 AND "age" < 9 AND "id" IS NULL"#
+    );
+    assert_eq!(
+        ansi(&a().and(b()).or(c())),
+        r#""age" > 1 AND "age" < 9 OR "id" IS NULL"#
+    );
+    assert_eq!(
+        ansi(&a().and(b().or(c()))),
+        r#""age" > 1 AND ("age" < 9 OR "id" IS NULL)"#
+    );
+    assert_eq!(ansi(&!a()), r#"NOT ("age" > 1)"#);
+}
+
+// ---------------------------------------------------------------------------
+// Dialects
+// ---------------------------------------------------------------------------
+
+#[test]
+fn dialects_quote_identifiers() {
+    let expr = field("score").gt(1_i64);
+    let sql = |dialect| expr.to_sql_where(&exporter(dialect)).unwrap();
+    assert_eq!(sql(SqlDialect::Ansi), r#""s"."score" > 1"#);
+    assert_eq!(sql(SqlDialect::Postgres), r#""s"."score" > 1"#);
+    assert_eq!(sql(SqlDialect::Sqlite), r#""s"."score" > 1"#);
+    assert_eq!(sql(SqlDialect::MySql), "`s`.`score` > 1");
+    assert_eq!(sql(SqlDialect::SqlServer), "[s].[score] > 1");
+}
+
+#[test]
+fn sql_server_has_no_boolean_literals() {
+    let ex = exporter(SqlDialect::SqlServer);
+    assert_eq!(
+        field("flags.beta").eq(false).to_sql_where(&ex).unwrap(),
+        "[beta] = 0"
+    );
+    let ruleset = RuleSet::from_dsl(SOURCE).unwrap();
+    assert!(ruleset
+        .to_sql_where("allow", &ex)
+        .unwrap()
+        .ends_with("([tier] = 'gold' OR 1 = 0)"));
+}
+
+#[test]
+fn like_patterns_are_escaped_per_dialect() {
+    let expr = field("user.email").like(r"a\b[c]%");
+    let sql = |dialect| expr.to_sql_where(&exporter(dialect)).unwrap();
+    assert_eq!(sql(SqlDialect::Ansi), r#""email" LIKE 'a\b[c]%'"#);
+    assert_eq!(sql(SqlDialect::Postgres), r#""email" LIKE 'a\\b[c]%'"#);
+    assert_eq!(sql(SqlDialect::MySql), r"`email` LIKE 'a\\\\b[c]%'");
+    assert_eq!(sql(SqlDialect::SqlServer), r"[email] LIKE 'a\b[[]c]%'");
+}
+
+// ---------------------------------------------------------------------------
+// Unsupported constructs
+// ---------------------------------------------------------------------------
+
+#[test]
+fn unmapped_fields_are_reported() {
+    assert_eq!(
+        field("user.name")
+            .eq("x")
+            .to_sql_where(&exporter(SqlDialect::Ansi)),
+        Err(SqlError::UnmappedField("user.name".to_owned()))
+    );
+    let ruleset = RuleSet::from_dsl(SOURCE).unwrap();
+    assert_eq!(
+        ruleset.to_sql_where(
+            "allow",
+            &SqlExporter::new(SqlDialect::Ansi).column("user.age", "age")
+        ),
+        Err(SqlError::UnmappedField("user.country".to_owned()))
+    );
+}
+
+#[test]
+fn unsupported_constructs_are_reported() {
+    for expr in [
+        any("user.tags", "t", field("t").eq("a")),
+        all("user.tags", "t", field("t").eq("a")),
+        field("user.tags").contains("a"),
+        field("user.tags").overlaps(bound_field("user.roles")),
+        len("user.tags").gt(1_i64),
+        field("user.limits").has_key("US"),
+        field("user.age").eq(Value::List(vec![])),
+        field("score").gt(f64::INFINITY),
+        rule_ref("adult"),
+    ] {
+        assert!(
+            matches!(
+                expr.to_sql_where(&exporter(SqlDialect::Ansi)),
+                Err(SqlError::Unsupported(_))
+            ),
+            "{expr:?}"
+        );
+    }
+}
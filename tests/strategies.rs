@@ -1,4 +1,10 @@
-use ooroo::{field, rule_ref, Context, Expr, RuleSet, RuleSetBuilder};
+// Shared by several test crates, each of which uses only some strategies.
+#![allow(dead_code)]
+
+use ooroo::{
+    all, any as any_of, at_least, field, rule_ref, Bound, CompareOp, Context, Expr, RuleSet,
+    RuleSetBuilder, SetOp, Value,
+};
 use proptest::prelude::*;
 
 // --- Fixed field schema ---
@@ -99,6 +105,21 @@ pub struct GenRuleSet {
 }
 
 impl GenRuleSet {
+    /// Write as DSL source, using `Display` for the rule bodies, so every
+    /// operation is parenthesized.
+    #[must_use]
+    pub fn to_dsl(&self) -> String {
+        let mut dsl = String::new();
+        for rule in &self.rules {
+            dsl.push_str(&format!("rule {}", rule.name));
+            if let Some(t) = self.terminals.iter().find(|t| t.rule_name == rule.name) {
+                dsl.push_str(&format!(" (priority {})", t.priority));
+            }
+            dsl.push_str(&format!(":\n    {}\n", rule.expr));
+        }
+        dsl
+    }
+
     /// Compile into an actual `RuleSet`.
     ///
     /// # Panics
@@ -274,3 +295,158 @@ pub fn arb_chained_ruleset() -> impl Strategy<Value = GenRuleSet> {
         })
     })
 }
+
+// --- Expressions covering the whole DSL grammar ---
+
+const PATHS: &[&str] = &[
+    "user.age",
+    "user.tags",
+    "limits[\"US\"].daily",
+    "limits[user.country]",
+    "order.items[0].sku",
+    "order.items[-1]",
+    "order.items[*].sku",
+];
+
+fn arb_path() -> impl Strategy<Value = String> {
+    prop::sample::select(PATHS).prop_map(str::to_owned)
+}
+
+fn arb_scalar() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        any::<i64>().prop_map(Value::Int),
+        any::<f64>()
+            .prop_filter("must be finite", |f| f.is_finite())
+            .prop_map(Value::Float),
+        any::<bool>().prop_map(Value::Bool),
+        "[a-z #%_\"\\\\\n\t]{0,8}".prop_map(Value::String),
+    ]
+}
+
+fn arb_literal() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        4 => arb_scalar(),
+        1 => prop::collection::vec(arb_scalar(), 0..3).prop_map(Value::List),
+    ]
+}
+
+fn arb_bound() -> impl Strategy<Value = Bound> {
+    prop_oneof![
+        arb_path().prop_map(Bound::Field),
+        arb_literal().prop_map(Bound::Literal),
+    ]
+}
+
+fn arb_compare_op() -> impl Strategy<Value = CompareOp> {
+    prop::sample::select(
+        &[
+            CompareOp::Eq,
+            CompareOp::Neq,
+            CompareOp::Gt,
+            CompareOp::Gte,
+            CompareOp::Lt,
+            CompareOp::Lte,
+        ][..],
+    )
+}
+
+fn arb_dsl_leaf() -> impl Strategy<Value = Expr> {
+    let set_ops = &[
+        SetOp::Overlaps,
+        SetOp::SubsetOf,
+        SetOp::SupersetOf,
+        SetOp::DisjointFrom,
+    ][..];
+    prop_oneof![
+        (arb_path(), arb_compare_op(), arb_literal())
+            .prop_map(|(field, op, value)| Expr::Compare { field, op, value }),
+        (arb_path(), arb_compare_op(), arb_path())
+            .prop_map(|(left, op, right)| Expr::CompareFields { left, op, right }),
+        prop::sample::select(&["adult", "kyc_passed", "rule_2"][..]).prop_map(rule_ref),
+        (
+            arb_path(),
+            prop::collection::vec(arb_bound(), 1..4),
+            any::<bool>()
+        )
+            .prop_map(|(field, members, negate)| if negate {
+                Expr::NotIn { field, members }
+            } else {
+                Expr::In { field, members }
+            }),
+        (arb_path(), arb_bound(), arb_bound()).prop_map(|(field, low, high)| Expr::Between {
+            field,
+            low,
+            high
+        }),
+        (arb_path(), "[a-z%_\"\\\\]{0,6}", any::<bool>()).prop_map(|(field, pattern, negate)| {
+            if negate {
+                Expr::NotLike { field, pattern }
+            } else {
+                Expr::Like { field, pattern }
+            }
+        }),
+        (arb_path(), any::<bool>()).prop_map(|(path, negate)| if negate {
+            Expr::IsNotNull(path)
+        } else {
+            Expr::IsNull(path)
+        }),
+        (arb_path(), arb_bound()).prop_map(|(f, value)| Expr::Contains { field: f, value }),
+        (arb_path(), prop::sample::select(set_ops), arb_bound())
+            .prop_map(|(field, op, value)| Expr::SetCompare { field, op, value }),
+        (arb_path(), arb_compare_op(), arb_bound()).prop_map(|(field, op, value)| Expr::Len {
+            field,
+            op,
+            value
+        }),
+        (arb_path(), arb_bound()).prop_map(|(f, key)| Expr::HasKey { field: f, key }),
+    ]
+}
+
+/// Generate an expression from every construct the DSL can express, nested
+/// with AND, OR, NOT, `AT_LEAST` and quantifiers.
+pub fn arb_dsl_expr() -> impl Strategy<Value = Expr> {
+    arb_dsl_leaf().prop_recursive(4, 24, 3, |inner| {
+        prop_oneof![
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| a.and(b)),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| a.or(b)),
+            inner.clone().prop_map(|e| !e),
+            (0_usize..4, prop::collection::vec(inner.clone(), 0..3))
+                .prop_map(|(n, exprs)| at_least(n, exprs)),
+            (arb_path(), inner.clone()).prop_map(|(list, p)| any_of(&list, "e", p)),
+            (arb_path(), inner).prop_map(|(list, p)| all(&list, "e", p)),
+        ]
+    })
+}
+
+// --- Names the DSL only reads between backticks ---
+
+/// Generate a rule name or field path: a plain identifier, a keyword, or a
+/// word with spaces and punctuation.
+pub fn arb_name() -> impl Strategy<Value = String> {
+    prop_oneof![
+        prop::sample::select(
+            &["true", "false", "AND", "or", "NOT", "not", "null", "any", "len", "my rule"][..]
+        )
+        .prop_map(str::to_owned),
+        "[a-zA-Z_][a-zA-Z0-9_]{0,5}",
+        "[a-z #%-][a-z0-9 #%-]{0,6}",
+    ]
+}
+
+/// Generate a ruleset whose three rules and two fields are named by
+/// [`arb_name`], together with the two field names.
+pub fn arb_named_ruleset() -> impl Strategy<Value = (RuleSet, [String; 2])> {
+    prop::collection::btree_set(arb_name(), 5).prop_map(|names| {
+        let [a, b, c, x, y]: [String; 5] =
+            names.into_iter().collect::<Vec<_>>().try_into().unwrap();
+        let ruleset = RuleSetBuilder::new()
+            .rule(&a, |r| r.when(field(&x).gt(0_i64)))
+            .rule(&b, |r| r.when(field(&y).eq(true).or(field(&x).is_null())))
+            .rule(&c, |r| r.when(rule_ref(&a).and(!rule_ref(&b))))
+            .terminal(&c, 0)
+            .terminal(&b, 1)
+            .compile()
+            .expect("generated ruleset should compile");
+        (ruleset, [x, y])
+    })
+}
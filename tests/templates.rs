@@ -0,0 +1,257 @@
+/// Integration tests for DSL rule templates.
+///
+/// Covers: `template` definitions expanded with `expand`, generated and
+/// explicit rule names, terminal expansions, parameters in field path
+/// segments, `[...]` keys, value positions and rule references, constants as
+/// arguments, parameters used as values keeping the compared field,
+/// quantifier shadowing, and errors naming the invocation line, including
+/// arguments that do not form a valid path or rule reference.
+use ooroo::{Context, RuleSet, Value, Verdict};
+
+fn tiers(values: &[&str]) -> Value {
+    Value::List(values.iter().map(|s| Value::from(*s)).collect())
+}
+
+// ---------------------------------------------------------------------------
+// Expansion
+// ---------------------------------------------------------------------------
+
+const TIER_GATES: &str = r#"
+template tier_gate(prod, tiers):
+    product.prod.enabled == true AND user.tier IN tiers
+
+expand tier_gate(books, ["gold", "platinum"])
+expand tier_gate(music, ["platinum"]) as music_gate
+
+rule allow (priority 0):
+    tier_gate_books OR music_gate
+"#;
+
+#[test]
+fn expansions_become_named_rules() {
+    let ruleset = RuleSet::from_dsl(TIER_GATES).unwrap();
+    let mut deps = ruleset.dependencies_of("allow").unwrap();
+    deps.sort_unstable();
+    assert_eq!(deps, ["music_gate", "tier_gate_books"]);
+
+    let ctx = Context::new()
+        .set("product.books.enabled", true)
+        .set("product.music.enabled", true)
+        .set("user.tier", "gold");
+    let report = ruleset.evaluate_detailed(&ctx);
+    assert!(report.evaluated().contains(&"tier_gate_books".to_owned()));
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allow", true)));
+
+    let ctx = ctx.set("product.books.enabled", false);
+    assert!(ruleset.evaluate(&ctx).is_none());
+    let ctx = ctx.set("user.tier", "platinum");
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("allow", true)));
+}
+
+#[test]
+fn expansions_can_be_terminals() {
+    let dsl = r#"
+template over_limit(order_kind, limit):
+    order.kind == order_kind AND order.amount > limit
+
+expand over_limit("gift", 100) (priority 0)
+expand over_limit("bulk", 10000) as bulk_limit (priority 1)
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+    assert_eq!(
+        ruleset.terminal_order(),
+        [("over_limit_gift_100", 0), ("bulk_limit", 1)]
+    );
+
+    let ctx = Context::new()
+        .set("order.kind", "bulk")
+        .set("order.amount", 20_000_i64);
+    assert_eq!(
+        ruleset.evaluate(&ctx),
+        Some(Verdict::new("bulk_limit", true))
+    );
+}
+
+#[test]
+fn arguments_in_keys_fields_and_rule_refs() {
+    let dsl = r#"
+const GOLD = ["gold", "platinum"]
+
+rule adult:
+    user.age >= 18
+
+template gate(prod, tiers, base, limit):
+    base AND catalog[prod].enabled == true AND user.tier IN tiers
+        AND order.amount <= limit
+
+expand gate("e-books", GOLD, adult, user.budget) as ebooks (priority 0)
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+    assert_eq!(ruleset.dependencies_of("ebooks").unwrap(), ["adult"]);
+
+    let ctx = Context::new()
+        .set("user.age", 30_i64)
+        .set(
+            "catalog",
+            Value::Map(
+                [("e-books".to_owned(), {
+                    Value::Map([("enabled".to_owned(), Value::Bool(true))].into())
+                })]
+                .into(),
+            ),
+        )
+        .set("user.tier", "gold")
+        .set("order.amount", 50_i64)
+        .set("user.budget", 80_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("ebooks", true)));
+
+    let ctx = ctx.set("user.budget", 20_i64);
+    assert!(ruleset.evaluate(&ctx).is_none());
+}
+
+#[test]
+fn quantifier_variables_shadow_template_parameters() {
+    let dsl = r#"
+template any_above(list, x):
+    ANY(list AS x, x > 10) AND threshold == x
+
+expand any_above(order.amounts, 5) as r (priority 0)
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+    let ctx = Context::new()
+        .set(
+            "order.amounts",
+            Value::List(vec![Value::Int(1), Value::Int(20)]),
+        )
+        .set("threshold", 5_i64);
+    assert_eq!(ruleset.evaluate(&ctx), Some(Verdict::new("r", true)));
+    assert!(ruleset
+        .evaluate(&ctx.set("order.amounts", tiers(&[])))
+        .is_none());
+}
+
+#[test]
+fn value_parameters_keep_the_compared_field() {
+    let dsl = r#"
+template tier_is(tier):
+    user.tier == tier
+
+template min_age(age):
+    user.age >= age AND user.age IN [age, 99]
+
+expand tier_is("gold") (priority 0)
+expand min_age(18) (priority 1)
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+    assert_eq!(
+        ruleset.to_dsl().unwrap(),
+        "rule min_age_18 (priority 1):\n    user.age >= 18 AND user.age IN [18, 99]\n\n\
+         rule tier_is_gold (priority 0):\n    user.tier == \"gold\"\n"
+    );
+    let ctx = Context::new().set("user.tier", "gold");
+    assert_eq!(
+        ruleset.evaluate(&ctx),
+        Some(Verdict::new("tier_is_gold", true))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+fn parse_error(dsl: &str) -> String {
+    RuleSet::from_dsl(dsl).unwrap_err().to_string()
+}
+
+#[test]
+fn unknown_template_names_the_line() {
+    let err = parse_error("rule r (priority 0):\n    x == 1\n\nexpand nope(a)");
+    assert_eq!(
+        err,
+        "parse error: in expansion of 'nope' at line 4: no such template"
+    );
+}
+
+#[test]
+fn wrong_argument_count_names_the_line() {
+    let err = parse_error(&format!("{TIER_GATES}\nexpand tier_gate(books)"));
+    assert!(
+        err.ends_with("in expansion of 'tier_gate' at line 11: expected 2 argument(s), found 1"),
+        "{err}"
+    );
+}
+
+#[test]
+fn list_argument_in_path_is_rejected() {
+    let err = parse_error(
+        "template t(p):\n    product.p.enabled == true\nexpand t([\"a\"]) (priority 0)",
+    );
+    assert!(
+        err.contains("at line 3: parameter 'p' is bound to a list"),
+        "{err}"
+    );
+}
+
+#[test]
+fn arguments_that_are_not_path_segments_are_rejected() {
+    for (arg, message) in [
+        ("\"a b\"", "invalid field path 'a b'"),
+        ("\"\"", "invalid field path ''"),
+        ("1.5", "parameter 'x' is bound to 1.5, which cannot be used"),
+        ("7", "parameter 'x' is bound to 7, which cannot be used"),
+        (
+            "true",
+            "parameter 'x' is bound to true, which cannot be used",
+        ),
+    ] {
+        let err = parse_error(&format!(
+            "template t(x):\n    x > 1\nexpand t({arg}) as r (priority 0)"
+        ));
+        assert!(err.contains("in expansion of 't' at line 3: "), "{err}");
+        assert!(err.contains(message), "{err}");
+    }
+
+    let err =
+        parse_error("template t(p):\n    product.p.enabled == true\nexpand t(\"a.\") (priority 0)");
+    assert!(
+        err.contains("at line 3: invalid field path 'product.a..enabled'"),
+        "{err}"
+    );
+
+    let err = parse_error("template t(base):\n    base AND x == 1\nexpand t(\"a b\") (priority 0)");
+    assert!(
+        err.contains("at line 3: invalid rule reference 'a b'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn generated_name_collisions_are_rejected() {
+    let err = parse_error(&format!(
+        "{TIER_GATES}\nexpand tier_gate(books, [\"silver\"])"
+    ));
+    assert!(
+        err.ends_with("at line 11: rule 'tier_gate_books' is already defined"),
+        "{err}"
+    );
+
+    let err = parse_error(
+        "rule dup (priority 0):\n    x == 1\ntemplate t(v):\n    x == v\nexpand t(1) as dup",
+    );
+    assert!(
+        err.ends_with("at line 5: rule 'dup' is already defined"),
+        "{err}"
+    );
+}
+
+#[test]
+fn duplicate_templates_are_rejected() {
+    let err = parse_error("template t(a):\n    a == 1\ntemplate t(b):\n    b == 1");
+    assert_eq!(err, "parse error: template 't' is defined more than once");
+
+    let err = parse_error("template t(a, a):\n    a == 1");
+    assert_eq!(
+        err,
+        "parse error: template 't' declares parameter 'a' more than once"
+    );
+}
@@ -0,0 +1,277 @@
+/// Integration tests for three-valued (Kleene) evaluation.
+///
+/// Covers: `Unknown` from missing and type-mismatched fields, propagation
+/// through `NOT`/`AND`/`OR`/`AT_LEAST` and quantifiers, rule references,
+/// definite tests (`IS NULL`) staying two-valued, per-terminal
+/// `UnknownPolicy`, indexed evaluation, unchanged two-valued evaluation, and
+/// the DSL `on_unknown` annotation.
+use ooroo::{
+    all, any, at_least, field, rule_ref, Context, RuleSet, RuleSetBuilder, UnknownPolicy, Value,
+    Verdict,
+};
+
+/// A ruleset with a single terminal `r` that surfaces `Unknown`.
+fn single(expr: ooroo::Expr) -> RuleSet {
+    RuleSetBuilder::new()
+        .rule("r", |r| r.when(expr))
+        .terminal_on_unknown("r", 0, UnknownPolicy::Indeterminate)
+        .compile()
+        .unwrap()
+}
+
+fn ints(values: &[i64]) -> Value {
+    Value::List(values.iter().copied().map(Value::Int).collect())
+}
+
+// ---------------------------------------------------------------------------
+// Leaves and negation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn not_of_missing_field_is_unknown() {
+    let ruleset = single(!field("user.age").lt(18_i64));
+
+    // Two-valued evaluation keeps its existing behavior.
+    assert_eq!(
+        ruleset.evaluate(&Context::new()),
+        Some(Verdict::new("r", true))
+    );
+    assert_eq!(
+        ruleset.evaluate_three_valued(&Context::new()),
+        Some(Verdict::indeterminate("r"))
+    );
+
+    let ctx = Context::new().set("user.age", 30_i64);
+    assert_eq!(
+        ruleset.evaluate_three_valued(&ctx),
+        Some(Verdict::new("r", true))
+    );
+    let ctx = Context::new().set("user.age", 12_i64);
+    assert_eq!(ruleset.evaluate_three_valued(&ctx), None);
+}
+
+#[test]
+fn type_mismatch_is_unknown() {
+    let ruleset = single(!field("user.age").lt(18_i64));
+    let ctx = Context::new().set("user.age", "old");
+    assert_eq!(
+        ruleset.evaluate_three_valued(&ctx),
+        Some(Verdict::indeterminate("r"))
+    );
+}
+
+#[test]
+fn null_checks_are_always_definite() {
+    let ruleset = single(field("user.age").is_null().or(field("user.age").gt(1_i64)));
+    assert_eq!(
+        ruleset.evaluate_three_valued(&Context::new()),
+        Some(Verdict::new("r", true))
+    );
+}
+
+#[test]
+fn other_leaf_tests_are_unknown_when_missing() {
+    for expr in [
+        field("x").is_in([1_i64, 2]),
+        field("x").not_in([1_i64, 2]),
+        field("x").between(1_i64, 2_i64),
+        field("x").not_like("a%"),
+        field("x").contains(1_i64),
+        field("x").has_key("a"),
+        field("x").gt_field("y"),
+    ] {
+        let ruleset = single(!expr.clone());
+        assert_eq!(
+            ruleset.evaluate_three_valued(&Context::new().set("y", 1_i64)),
+            Some(Verdict::indeterminate("r")),
+            "{expr}"
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Combinators
+// ---------------------------------------------------------------------------
+
+#[test]
+fn and_or_follow_kleene_logic() {
+    let missing = || field("missing").eq(1_i64);
+    let yes = || field("x").eq(1_i64);
+    let no = || field("x").eq(2_i64);
+    let ctx = Context::new().set("x", 1_i64);
+
+    let cases = [
+        (missing().and(no()), None),
+        (missing().and(yes()), Some(Verdict::indeterminate("r"))),
+        (missing().or(yes()), Some(Verdict::new("r", true))),
+        (missing().or(no()), Some(Verdict::indeterminate("r"))),
+    ];
+    for (expr, expected) in cases {
+        let ruleset = single(expr.clone());
+        assert_eq!(ruleset.evaluate_three_valued(&ctx), expected, "{expr}");
+    }
+}
+
+#[test]
+fn at_least_counts_unknowns_as_possible() {
+    let ctx = Context::new().set("a", 1_i64).set("b", 0_i64);
+    let exprs = || {
+        vec![
+            field("a").eq(1_i64),
+            field("b").eq(1_i64),
+            field("c").eq(1_i64),
+        ]
+    };
+
+    // One true, one unknown: two might still be reached.
+    let ruleset = single(at_least(2, exprs()));
+    assert_eq!(
+        ruleset.evaluate_three_valued(&ctx),
+        Some(Verdict::indeterminate("r"))
+    );
+
+    // Three can no longer be reached.
+    let ruleset = single(at_least(3, exprs()));
+    assert_eq!(ruleset.evaluate_three_valued(&ctx), None);
+
+    let ruleset = single(at_least(1, exprs()));
+    assert_eq!(
+        ruleset.evaluate_three_valued(&ctx),
+        Some(Verdict::new("r", true))
+    );
+}
+
+#[test]
+fn quantifiers_propagate_unknown_elements() {
+    let ctx = Context::new().set("xs", ints(&[1, 5])).set(
+        "limits",
+        Value::List(vec![Value::Int(3), Value::from("n/a")]),
+    );
+
+    // 3 < 2 is false and "n/a" cannot be compared, so ANY is unknown.
+    let ruleset = single(any("limits", "l", field("l").lt(2_i64)));
+    assert_eq!(
+        ruleset.evaluate_three_valued(&ctx),
+        Some(Verdict::indeterminate("r"))
+    );
+
+    // A definite false element decides ALL regardless of unknowns.
+    let ruleset = single(all("limits", "l", field("l").gt(5_i64)));
+    assert_eq!(ruleset.evaluate_three_valued(&ctx), None);
+
+    // A definite true element decides ANY.
+    let ruleset = single(any("xs", "x", field("x").gt(4_i64)));
+    assert_eq!(
+        ruleset.evaluate_three_valued(&ctx),
+        Some(Verdict::new("r", true))
+    );
+
+    // A missing list is unknown, an empty one is decided.
+    let ruleset = single(!any("ys", "y", field("y").gt(0_i64)));
+    assert_eq!(
+        ruleset.evaluate_three_valued(&ctx),
+        Some(Verdict::indeterminate("r"))
+    );
+    let ruleset = single(all("ys", "y", field("y").gt(0_i64)));
+    assert_eq!(
+        ruleset.evaluate_three_valued(&Context::new().set("ys", ints(&[]))),
+        Some(Verdict::new("r", true))
+    );
+}
+
+#[test]
+fn rule_refs_carry_unknown() {
+    let ruleset = RuleSetBuilder::new()
+        .rule("minor", |r| r.when(field("user.age").lt(18_i64)))
+        .rule("adult", |r| r.when(!rule_ref("minor")))
+        .terminal_on_unknown("adult", 0, UnknownPolicy::Indeterminate)
+        .compile()
+        .unwrap();
+
+    assert_eq!(
+        ruleset.evaluate_three_valued(&Context::new()),
+        Some(Verdict::indeterminate("adult"))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Terminal policies
+// ---------------------------------------------------------------------------
+
+fn deny_allow(deny_policy: UnknownPolicy) -> RuleSet {
+    RuleSetBuilder::new()
+        .rule("deny", |r| r.when(field("user.banned").eq(true)))
+        .rule("allow", |r| r.when(field("user.active").eq(true)))
+        .terminal_on_unknown("deny", 0, deny_policy)
+        .terminal("allow", 1)
+        .compile()
+        .unwrap()
+}
+
+#[test]
+fn as_false_policy_falls_through() {
+    let ruleset = deny_allow(UnknownPolicy::AsFalse);
+    let ctx = Context::new().set("user.active", true);
+    assert_eq!(
+        ruleset.evaluate_three_valued(&ctx),
+        Some(Verdict::new("allow", true))
+    );
+}
+
+#[test]
+fn indeterminate_policy_blocks_lower_priority_terminals() {
+    let ruleset = deny_allow(UnknownPolicy::Indeterminate);
+    let ctx = Context::new().set("user.active", true);
+    let verdict = ruleset.evaluate_three_valued(&ctx).unwrap();
+    assert_eq!(verdict.terminal(), "deny");
+    assert!(verdict.is_indeterminate());
+    assert!(!verdict.result());
+
+    let ctx = ctx.set("user.banned", false);
+    assert_eq!(
+        ruleset.evaluate_three_valued(&ctx),
+        Some(Verdict::new("allow", true))
+    );
+}
+
+#[test]
+fn indexed_three_valued_matches_context() {
+    let ruleset = deny_allow(UnknownPolicy::Indeterminate);
+    let ctx = ruleset.context_builder().set("user.active", true).build();
+    assert_eq!(
+        ruleset.evaluate_three_valued_indexed(&ctx),
+        Some(Verdict::indeterminate("deny"))
+    );
+}
+
+// ---------------------------------------------------------------------------
+// DSL
+// ---------------------------------------------------------------------------
+
+#[test]
+fn dsl_on_unknown_annotation() {
+    let dsl = r#"
+rule minor:
+    user.age < 18
+
+rule deny (priority 0, on_unknown indeterminate):
+    minor
+
+rule allow (priority 1):
+    NOT minor
+"#;
+    let ruleset = RuleSet::from_dsl(dsl).unwrap();
+
+    assert_eq!(
+        ruleset.evaluate_three_valued(&Context::new()),
+        Some(Verdict::indeterminate("deny"))
+    );
+    assert_eq!(
+        ruleset.evaluate(&Context::new()),
+        Some(Verdict::new("allow", true))
+    );
+    assert_eq!(
+        ruleset.evaluate_three_valued(&Context::new().set("user.age", 40_i64)),
+        Some(Verdict::new("allow", true))
+    );
+}
@@ -0,0 +1,356 @@
+#![cfg(feature = "binary-cache")]
+/// Integration tests for evaluating rulesets in place with `RuleSetView`.
+///
+/// Covers: matching `RuleSet` results across every expression kind,
+/// including lookups, quantifiers, set operators and inlined constants;
+/// three-valued evaluation, overrides and detailed reports; execution and
+/// terminal order; writing view files; rejecting truncated, corrupted and
+/// foreign buffers; and a property test comparing the view with the
+/// ruleset it was written from.
+mod strategies;
+
+use std::collections::BTreeMap;
+
+use ooroo::{
+    field, rule_ref, Context, DeserializeError, Expr, RuleOverride, RuleSet, RuleSetBuilder,
+    RuleSetView, Value, Verdict,
+};
+use proptest::prelude::*;
+use strategies::arb_dsl_expr;
+
+const SOURCE: &str = r#"
+const BLOCKED = ["XX", "YY"]
+
+rule adult:
+    user.age >= 18
+
+rule blocked (priority 0, on_unknown indeterminate):
+    user.country IN BLOCKED OR user.banned == true
+
+@disabled
+rule legacy (priority 1):
+    user.legacy == true
+
+rule big_order:
+    ANY(order.items AS line, line.price > 100 AND line.sku LIKE "B%")
+
+rule all_tagged:
+    ALL(order.items AS line, len(line.tags) >= 1)
+
+rule within_limit:
+    order.total <= limits[user.country].daily
+
+rule trusted:
+    user.tags OVERLAPS ["staff", "vip"] OR AT_LEAST(2, user.verified == true, user.age > 30, user.tags CONTAINS "old")
+
+rule allow (priority 2):
+    adult AND NOT blocked AND (within_limit OR big_order) AND (trusted OR all_tagged)
+"#;
+
+fn ruleset() -> RuleSet {
+    RuleSet::from_dsl(SOURCE).unwrap()
+}
+
+fn item(sku: &str, price: i64, tags: &[&str]) -> Value {
+    Value::Map(BTreeMap::from([
+        ("sku".to_owned(), Value::from(sku)),
+        ("price".to_owned(), Value::Int(price)),
+        (
+            "tags".to_owned(),
+            Value::List(tags.iter().map(|&t| Value::from(t)).collect()),
+        ),
+    ]))
+}
+
+fn limits() -> Value {
+    Value::Map(BTreeMap::from([(
+        "US".to_owned(),
+        Value::Map(BTreeMap::from([("daily".to_owned(), Value::Int(500))])),
+    )]))
+}
+
+fn contexts() -> Vec<Context> {
+    let base = || {
+        Context::new()
+            .set("user.age", 40_i64)
+            .set("user.country", "US")
+            .set("user.banned", false)
+            .set("user.verified", true)
+            .set("user.tags", Value::List(vec![Value::from("old")]))
+            .set("order.total", 200_i64)
+            .set("limits", limits())
+            .set(
+                "order.items",
+                Value::List(vec![item("B1", 150, &["x"]), item("A2", 10, &[])]),
+            )
+    };
+    vec![
+        base(),
+        base().set("user.age", 12_i64),
+        base().set("user.country", "XX"),
+        base().set("user.country", "FR"),
+        base().set("order.total", 900_i64),
+        base().set("user.tags", Value::List(vec![Value::from("vip")])),
+        base().set("order.items", Value::List(vec![])),
+        Context::new().set("user.age", 40_i64),
+        Context::new()
+            .set("user.age", 40_i64)
+            .set("user.banned", false),
+        Context::new(),
+    ]
+}
+
+fn view_bytes() -> Vec<u8> {
+    ruleset().to_view_bytes()
+}
+
+// ---------------------------------------------------------------------------
+// Parity with RuleSet
+// ---------------------------------------------------------------------------
+
+#[test]
+fn evaluation_matches_the_ruleset() {
+    let ruleset = ruleset();
+    let bytes = ruleset.to_view_bytes();
+    let view = RuleSetView::from_bytes(&bytes).unwrap();
+    for ctx in contexts() {
+        assert_eq!(view.evaluate(&ctx), ruleset.evaluate(&ctx), "{ctx:?}");
+        assert_eq!(
+            view.evaluate_three_valued(&ctx),
+            ruleset.evaluate_three_valued(&ctx),
+            "{ctx:?}"
+        );
+    }
+    assert_eq!(
+        view.evaluate(&contexts()[0]),
+        Some(Verdict::new("allow", true))
+    );
+}
+
+#[test]
+fn detailed_reports_match_the_ruleset() {
+    let ruleset = ruleset();
+    let bytes = ruleset.to_view_bytes();
+    let view = RuleSetView::from_bytes(&bytes).unwrap();
+    for ctx in contexts() {
+        let expected = ruleset.evaluate_detailed(&ctx);
+        let report = view.evaluate_detailed(&ctx);
+        assert_eq!(report.verdict(), expected.verdict());
+        assert_eq!(report.evaluated(), expected.evaluated());
+        assert_eq!(report.evaluation_order(), expected.evaluation_order());
+    }
+}
+
+#[test]
+fn overrides_apply_like_the_ruleset() {
+    let ruleset = ruleset();
+    let bytes = ruleset.to_view_bytes();
+    let view = RuleSetView::from_bytes(&bytes).unwrap();
+
+    let mut overrides = view.overrides();
+    overrides.set("blocked", RuleOverride::Disable).unwrap();
+    overrides.set("legacy", RuleOverride::Enable).unwrap();
+    overrides.set("trusted", RuleOverride::ForceTrue).unwrap();
+    let mut expected = ruleset.overrides();
+    expected.set("blocked", RuleOverride::Disable).unwrap();
+    expected.set("legacy", RuleOverride::Enable).unwrap();
+    expected.set("trusted", RuleOverride::ForceTrue).unwrap();
+
+    for ctx in contexts() {
+        let ctx = ctx.set("user.legacy", true);
+        assert_eq!(
+            view.evaluate_with_overrides(&ctx, &overrides),
+            ruleset.evaluate_with_overrides(&ctx, &expected)
+        );
+        assert_eq!(
+            view.evaluate_three_valued_with_overrides(&ctx, &overrides),
+            ruleset.evaluate_three_valued_with_overrides(&ctx, &expected)
+        );
+        assert_eq!(
+            view.evaluate_detailed_with_overrides(&ctx, &overrides)
+                .overridden(),
+            ruleset
+                .evaluate_detailed_with_overrides(&ctx, &expected)
+                .overridden()
+        );
+    }
+    assert!(overrides.set("missing", RuleOverride::Disable).is_err());
+}
+
+#[test]
+fn order_matches_the_ruleset() {
+    let ruleset = ruleset();
+    let bytes = ruleset.to_view_bytes();
+    let view = RuleSetView::from_bytes(&bytes).unwrap();
+    assert_eq!(view.execution_order(), ruleset.execution_order());
+    assert_eq!(
+        view.terminal_order(),
+        [("blocked", 0), ("legacy", 1), ("allow", 2)]
+    );
+}
+
+#[test]
+fn deep_chains_evaluate() {
+    let mut builder = RuleSetBuilder::new().rule("r0", |r| r.when(field("x").eq(1_i64)));
+    let mut expr = field("x").eq(1_i64);
+    for i in 0..200 {
+        expr = expr.or(field("y").eq(i64::from(i)));
+    }
+    builder = builder.rule("wide", |r| r.when(expr));
+    for i in 1..100 {
+        let prev = format!("r{}", i - 1);
+        builder = builder.rule(&format!("r{i}"), |r| r.when(rule_ref(&prev)));
+    }
+    let ruleset = builder
+        .terminal("r99", 0)
+        .terminal("wide", 1)
+        .compile()
+        .unwrap();
+    let bytes = ruleset.to_view_bytes();
+    let view = RuleSetView::from_bytes(&bytes).unwrap();
+    for ctx in [
+        Context::new().set("x", 1_i64),
+        Context::new().set("y", 150_i64),
+        Context::new(),
+    ] {
+        assert_eq!(view.evaluate(&ctx), ruleset.evaluate(&ctx));
+    }
+}
+
+#[test]
+fn view_files_can_be_read_back() {
+    let dir = std::env::temp_dir().join(format!("ooroo-view-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("rules.oorv");
+    ruleset().to_view_file(&path).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    let view = RuleSetView::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        view.evaluate(&contexts()[0]),
+        Some(Verdict::new("allow", true))
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// Rejecting bad input
+// ---------------------------------------------------------------------------
+
+#[test]
+fn truncated_buffers_are_rejected() {
+    let bytes = view_bytes();
+    for len in [0, 4, 10, bytes.len() / 2, bytes.len() - 1] {
+        assert!(RuleSetView::from_bytes(&bytes[..len]).is_err(), "{len}");
+    }
+}
+
+#[test]
+fn corrupted_payloads_fail_the_checksum() {
+    let mut bytes = view_bytes();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    assert!(matches!(
+        RuleSetView::from_bytes(&bytes),
+        Err(DeserializeError::ChecksumMismatch)
+    ));
+}
+
+#[test]
+fn ruleset_blobs_are_not_views() {
+    let blob = ruleset().to_bytes(None).unwrap();
+    assert!(matches!(
+        RuleSetView::from_bytes(&blob),
+        Err(DeserializeError::BadMagic)
+    ));
+    assert!(matches!(
+        RuleSet::from_bytes(&view_bytes()),
+        Err(DeserializeError::BadMagic)
+    ));
+}
+
+#[test]
+fn other_versions_are_rejected() {
+    let mut bytes = view_bytes();
+    bytes[4] = bytes[4].wrapping_add(1);
+    assert!(matches!(
+        RuleSetView::from_bytes(&bytes),
+        Err(DeserializeError::IncompatibleVersion { .. })
+    ));
+}
+
+// ---------------------------------------------------------------------------
+// Properties
+// ---------------------------------------------------------------------------
+
+fn arb_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        (-3_i64..3).prop_map(Value::Int),
+        prop::sample::select(&[-1.5_f64, 0.0, 2.0][..]).prop_map(Value::Float),
+        any::<bool>().prop_map(Value::Bool),
+        prop::sample::select(&["", "a", "US", "b%"][..]).prop_map(Value::from),
+    ];
+    leaf.prop_recursive(2, 12, 3, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..3).prop_map(Value::List),
+            prop::collection::btree_map(
+                prop::sample::select(&["US", "daily", "sku", "a"][..]).prop_map(str::to_owned),
+                inner,
+                0..3
+            )
+            .prop_map(Value::Map),
+        ]
+    })
+}
+
+fn arb_view_context() -> impl Strategy<Value = Context> {
+    let roots = &[
+        "user.age",
+        "user.tags",
+        "user.country",
+        "limits",
+        "order.items",
+    ][..];
+    prop::collection::vec((prop::sample::select(roots), arb_value()), 0..5).prop_map(|fields| {
+        fields
+            .into_iter()
+            .fold(Context::new(), |ctx, (path, value)| ctx.set(path, value))
+    })
+}
+
+fn compile(expr: &Expr) -> Option<RuleSet> {
+    let expr = expr.clone();
+    RuleSetBuilder::new()
+        .rule("adult", |r| r.when(field("user.age").gte(18_i64)))
+        .rule("kyc_passed", |r| r.when(field("user.tags").contains("kyc")))
+        .rule("rule_2", |r| r.when(field("user.country").eq("US")))
+        .rule("r", move |r| r.when(expr))
+        .terminal("r", 0)
+        .terminal("adult", 1)
+        .compile()
+        .ok()
+}
+
+proptest! {
+    /// A view evaluates every construct exactly like the ruleset it was
+    /// written from.
+    #[test]
+    fn views_evaluate_like_their_ruleset(
+        expr in arb_dsl_expr(),
+        contexts in prop::collection::vec(arb_view_context(), 1..4),
+    ) {
+        let Some(ruleset) = compile(&expr) else {
+            return Ok(());
+        };
+        let bytes = ruleset.to_view_bytes();
+        let view = RuleSetView::from_bytes(&bytes);
+        prop_assert!(view.is_ok(), "{}", view.unwrap_err());
+        let view = view.unwrap();
+        for ctx in &contexts {
+            prop_assert_eq!(view.evaluate(ctx), ruleset.evaluate(ctx));
+            prop_assert_eq!(
+                view.evaluate_three_valued(ctx),
+                ruleset.evaluate_three_valued(ctx)
+            );
+        }
+    }
+}